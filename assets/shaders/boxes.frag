@@ -0,0 +1,43 @@
+#version 330 core
+
+uniform sampler2DArray blockTextures;
+
+// See render::SkyUniforms.
+layout (std140) uniform Sky {
+    vec4 skyColor;
+    vec4 fogColor;
+    vec4 sunDirection;
+    vec4 moonDirection;
+    float skyLight;
+    float fogStart;
+    float fogEnd;
+    float moonPhase;
+};
+
+// Texels with a lower alpha are discarded.
+uniform float alphaCutoff = 0.5;
+
+// Overlays such as cracks are drawn unlit, to be multiplied with what is behind them.
+uniform bool overlay = false;
+
+in vec2 vUv;
+flat in float vLayer;
+in float vShade;
+in float vFog;
+
+out vec4 FragColor;
+
+void main() {
+    vec4 color = texture(blockTextures, vec3(vUv, vLayer));
+
+    if (color.a < alphaCutoff) {
+        discard;
+    }
+
+    if (overlay) {
+        FragColor = color;
+    }
+    else {
+        FragColor = vec4(mix(color.rgb * vShade, fogColor.rgb, vFog), color.a);
+    }
+}
@@ -0,0 +1,47 @@
+#version 330 core
+
+// Textured box vertex in world space, see render::TexturedBoxes.
+layout (location = 0) in vec3 aPosition;
+layout (location = 1) in vec2 aUv;
+layout (location = 2) in float aLayer;
+layout (location = 3) in float aFace;
+layout (location = 4) in vec2 aLight;
+
+layout (std140) uniform Camera {
+    mat4 view;
+    mat4 projection;
+    mat4 viewProjection;
+    vec4 cameraPosition;
+};
+
+// See render::SkyUniforms.
+layout (std140) uniform Sky {
+    vec4 skyColor;
+    vec4 fogColor;
+    vec4 sunDirection;
+    vec4 moonDirection;
+    float skyLight;
+    float fogStart;
+    float fogEnd;
+    float moonPhase;
+};
+
+out vec2 vUv;
+flat out float vLayer;
+out float vShade;
+out float vFog;
+
+// Directional shading of Down, Up, North, South, West and East faces, as for chunks.
+const float FACE_SHADE[6] = float[6](0.5, 1.0, 0.8, 0.8, 0.6, 0.6);
+
+void main() {
+    // Sky light dims with the time of day, block light does not.
+    float light = max(aLight.x * skyLight, aLight.y);
+
+    vUv = aUv;
+    vLayer = aLayer;
+    vShade = FACE_SHADE[int(aFace)] * mix(0.05, 1.0, light);
+    vFog = smoothstep(fogStart, fogEnd, length(aPosition - cameraPosition.xyz));
+
+    gl_Position = viewProjection * vec4(aPosition, 1.0);
+}
@@ -0,0 +1,37 @@
+#version 330 core
+
+uniform sampler2DArray blockTextures;
+
+// See render::SkyUniforms.
+layout (std140) uniform Sky {
+    vec4 skyColor;
+    vec4 fogColor;
+    vec4 sunDirection;
+    vec4 moonDirection;
+    float skyLight;
+    float fogStart;
+    float fogEnd;
+    float moonPhase;
+};
+
+// Texels with a lower alpha are discarded: 0.5 for the cutout layer, 0 otherwise.
+uniform float alphaCutoff;
+
+in vec2 vUv;
+flat in float vLayer;
+in vec3 vShade;
+in float vFog;
+
+out vec4 FragColor;
+
+void main() {
+    // Gradients come from the unwrapped coordinates, so wrapping with fract() does not pick
+    // the smallest mipmap at every block edge.
+    vec4 color = textureGrad(blockTextures, vec3(fract(vUv), vLayer), dFdx(vUv), dFdy(vUv));
+
+    if (color.a < alphaCutoff) {
+        discard;
+    }
+
+    FragColor = vec4(mix(color.rgb * vShade, fogColor.rgb, vFog), color.a);
+}
@@ -0,0 +1,79 @@
+#version 330 core
+
+// Packed block vertex, see mesh::BlockVertex.
+layout (location = 0) in uvec3 aPacked;
+
+layout (std140) uniform Camera {
+    mat4 view;
+    mat4 projection;
+    mat4 viewProjection;
+    vec4 cameraPosition;
+};
+
+// See render::SkyUniforms.
+layout (std140) uniform Sky {
+    vec4 skyColor;
+    vec4 fogColor;
+    vec4 sunDirection;
+    vec4 moonDirection;
+    float skyLight;
+    float fogStart;
+    float fogEnd;
+    float moonPhase;
+};
+
+uniform vec3 sectionOrigin;
+
+// How dark ambient occlusion makes corners, from 0 to 1. See render::RenderSettings.
+uniform float aoStrength = 1.0;
+
+out vec2 vUv;
+flat out float vLayer;
+out vec3 vShade;
+out float vFog;
+
+// Directional shading of Down, Up, North, South, West and East faces.
+const float FACE_SHADE[6] = float[6](0.5, 1.0, 0.8, 0.8, 0.6, 0.6);
+
+void main() {
+    uint position = aPacked.x;
+    uint data = aPacked.y;
+    uint color = aPacked.z;
+
+    vec3 local = vec3(position & 511u, (position >> 9) & 511u, (position >> 18) & 511u) / 16.0;
+    uint face = (position >> 27) & 7u;
+    uint ao = position >> 30;
+
+    vec3 world = sectionOrigin + local;
+
+    // Texture coordinates follow the face plane so textures tile across merged faces and stay
+    // upright on the sides.
+    if (face == 0u) vUv = vec2(world.x, world.z);
+    else if (face == 1u) vUv = vec2(world.x, world.z);
+    else if (face == 2u) vUv = vec2(-world.x, -world.y);
+    else if (face == 3u) vUv = vec2(world.x, -world.y);
+    else if (face == 4u) vUv = vec2(world.z, -world.y);
+    else vUv = vec2(-world.z, -world.y);
+
+    // Flowing fluid surfaces turn their texture to run along the flow.
+    if (face <= 1u && (data >> 31) == 1u) {
+        float angle = float((data >> 24) & 127u) / 128.0 * 6.28318531;
+        vec2 flow = vec2(cos(angle), sin(angle));
+        vUv = vec2(dot(world.xz, vec2(-flow.y, flow.x)), dot(world.xz, flow));
+    }
+
+    // Sky light dims with the time of day, block light does not.
+    float skyLevel = float((data >> 16) & 15u) / 15.0 * skyLight;
+    float blockLevel = float((data >> 20) & 15u) / 15.0;
+    float light = max(skyLevel, blockLevel);
+    float occlusion = mix(1.0, 0.4 + float(ao) * 0.2, aoStrength);
+
+    vec3 tint = vec3(color & 255u, (color >> 8) & 255u, (color >> 16) & 255u) / 255.0;
+
+    vLayer = float(data & 65535u);
+    vShade = tint * (FACE_SHADE[face] * occlusion * mix(0.05, 1.0, light));
+
+    vFog = smoothstep(fogStart, fogEnd, length(world - cameraPosition.xyz));
+
+    gl_Position = viewProjection * vec4(world, 1.0);
+}
@@ -0,0 +1,9 @@
+#version 330 core
+
+uniform vec4 outlineColor = vec4(0.0, 0.0, 0.0, 0.4);
+
+out vec4 FragColor;
+
+void main() {
+    FragColor = outlineColor;
+}
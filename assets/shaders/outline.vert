@@ -0,0 +1,15 @@
+#version 330 core
+
+// Line ends in world space, see render::SelectionOutline.
+layout (location = 0) in vec3 aPosition;
+
+layout (std140) uniform Camera {
+    mat4 view;
+    mat4 projection;
+    mat4 viewProjection;
+    vec4 cameraPosition;
+};
+
+void main() {
+    gl_Position = viewProjection * vec4(aPosition, 1.0);
+}
@@ -3,9 +3,16 @@
 layout (location = 0) in vec3 aPos;
 layout (location = 1) in vec3 aColor;
 
+layout (std140) uniform Camera {
+    mat4 view;
+    mat4 projection;
+    mat4 viewProjection;
+    vec4 cameraPosition;
+};
+
 out vec3 ourColor;
 
 void main() {
-    gl_Position = vec4(aPos, 1.0);
+    gl_Position = viewProjection * vec4(aPos, 1.0);
     ourColor = aColor;
 }
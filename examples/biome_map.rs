@@ -0,0 +1,60 @@
+//! Draws a map of the biomes and terrain of a world to a PNG file, for tuning the data files
+//! in `assets/biomes/` without launching the game.
+//!
+//! Run with `cargo run --release --example biome_map -- [layer] [seed] [x] [z] [size] [scale] [output]`,
+//! where `layer` is `biomes`, `height`, `temperature` or `humidity`. The map is centered on
+//! world `x` and `z`, `size` pixels wide with one pixel every `scale` blocks.
+
+use rustedcraft::block::BlockRegistryBuilder;
+use rustedcraft::worldgen::{biome_map, BiomeRegistry, MapLayer, TerrainGenerator};
+
+use std::sync::Arc;
+use std::time::Instant;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arg = |index: usize, default: &str| args.get(index).cloned().unwrap_or_else(|| default.to_string());
+
+    let layer = match arg(0, "biomes").as_str() {
+        "biomes" => MapLayer::Biomes,
+        "height" => MapLayer::Height,
+        "temperature" => MapLayer::Temperature,
+        "humidity" => MapLayer::Humidity,
+        other => {
+            eprintln!("Unknown layer {other}, expected biomes, height, temperature or humidity");
+            std::process::exit(1);
+        }
+    };
+
+    let number = |index: usize, default: &str| {
+        arg(index, default).parse::<i64>().unwrap_or_else(|e| {
+            eprintln!("Invalid argument {}: {e}", index + 1);
+            std::process::exit(1);
+        })
+    };
+
+    let seed = number(1, "0") as u64;
+    let (x, z) = (number(2, "0") as i32, number(3, "0") as i32);
+    let size = number(4, "512").clamp(1, 8192) as u32;
+    let scale = number(5, "8").max(1) as u32;
+    let output = arg(6, "biome_map.png");
+
+    let source = std::fs::read_to_string("assets/blocks/rustedcraft.toml").expect("run from the repository root");
+    let mut builder = BlockRegistryBuilder::new();
+    builder.load_str("rustedcraft", &source).unwrap();
+    let blocks = builder.build().unwrap();
+
+    let source = std::fs::read_to_string("assets/biomes/rustedcraft.toml").expect("run from the repository root");
+    let mut biomes = BiomeRegistry::new();
+    biomes.load_str("rustedcraft", &source, &blocks).unwrap();
+
+    let generator = TerrainGenerator::new(seed, &blocks, Arc::new(biomes)).unwrap();
+    let half = (size * scale / 2) as i32;
+
+    let start = Instant::now();
+    let image = biome_map(&generator, layer, x - half, z - half, size, size, scale);
+    println!("Drew {size}x{size} pixels in {:.2?}", start.elapsed());
+
+    std::fs::write(&output, image.encode_png().unwrap()).unwrap();
+    println!("Saved {output}");
+}
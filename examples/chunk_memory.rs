@@ -0,0 +1,90 @@
+//! Measures memory usage and access speed of chunk storage for a few typical layouts.
+//!
+//! Run with `cargo run --release --example chunk_memory`.
+
+use rustedcraft::world::{BlockState, Chunk, ChunkPos, CHUNK_HEIGHT, SECTION_SIZE};
+use std::time::Instant;
+
+/// Small deterministic generator so runs are comparable.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        self.0 >> 33
+    }
+}
+
+fn main() {
+    let stone = BlockState::new(1);
+    let dirt = BlockState::new(2);
+    let grass = BlockState::new(3);
+
+    report("empty", Chunk::new(ChunkPos::new(0, 0)));
+
+    let mut flat = Chunk::new(ChunkPos::new(0, 0));
+    flat.fill((0, 0, 0), (15, 59, 15), stone);
+    flat.fill((0, 60, 0), (15, 62, 15), dirt);
+    flat.fill((0, 63, 0), (15, 63, 15), grass);
+    report("flat terrain", flat);
+
+    let mut rng = Lcg(0x5eed);
+    let mut ores = Chunk::new(ChunkPos::new(0, 0));
+    ores.fill((0, 0, 0), (15, 63, 15), stone);
+    for _ in 0..400 {
+        let (x, y, z) = (rng.next() as usize % 16, rng.next() as usize % 64, rng.next() as usize % 16);
+        ores.set(x, y, z, BlockState::new(10 + (rng.next() % 6) as u16));
+    }
+    report("stone with ores", ores);
+
+    for unique in [16, 256, 4096] {
+        let mut noisy = Chunk::new(ChunkPos::new(0, 0));
+        for (i, y) in (0..CHUNK_HEIGHT).enumerate() {
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    let id = (rng.next() as usize + i) % unique;
+                    noisy.set(x, y, z, BlockState::new(id as u16 + 1));
+                }
+            }
+        }
+        report(&format!("random, {unique} states"), noisy.clone());
+        noisy.compact();
+        report("  after compact", noisy);
+    }
+
+    let mut chunk = Chunk::new(ChunkPos::new(0, 0));
+    chunk.fill((0, 0, 0), (15, 63, 15), stone);
+
+    let start = Instant::now();
+    let mut sum = 0u64;
+    for _ in 0..100 {
+        for y in 0..CHUNK_HEIGHT {
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    sum += chunk.get(x, y, z).id() as u64;
+                }
+            }
+        }
+    }
+    let reads = 100 * CHUNK_HEIGHT * SECTION_SIZE * SECTION_SIZE;
+    println!("get: {:.2} ns/block (checksum {sum})", start.elapsed().as_nanos() as f64 / reads as f64);
+
+    let start = Instant::now();
+    for _ in 0..1_000_000 {
+        let (x, y, z) = (rng.next() as usize % 16, rng.next() as usize % 256, rng.next() as usize % 16);
+        chunk.set(x, y, z, BlockState::new((rng.next() % 64) as u16));
+    }
+    println!("set: {:.2} ns/block", start.elapsed().as_nanos() as f64 / 1_000_000.0);
+
+    let start = Instant::now();
+    let count = chunk.iter().filter(|(_, _, _, state)| !state.is_air()).count();
+    println!("iter: {:.2?} ({count} non-air blocks)", start.elapsed());
+}
+
+fn report(name: &str, chunk: Chunk) {
+    let raw = CHUNK_HEIGHT * SECTION_SIZE * SECTION_SIZE * size_of::<BlockState>();
+    let used = chunk.memory_usage();
+    let bits: Vec<u8> = chunk.sections().iter().map(|s| s.blocks().bits()).collect();
+
+    println!("{name:>20}: {used:>7} bytes ({:>5.1}% of {raw}), bits per section {bits:?}", used as f64 * 100.0 / raw as f64);
+}
@@ -0,0 +1,110 @@
+//! Compares naive and greedy meshing on generated terrain, in vertex count and build time.
+//!
+//! Run with `cargo run --release --example meshing_benchmark`.
+
+use rustedcraft::block::{BlockRegistry, BlockRegistryBuilder};
+use rustedcraft::mesh::{mesh_section, BlockMeshTable, BlockVertex, ChunkNeighbors, MeshSettings, MeshingMode, SectionNeighborhood};
+use rustedcraft::world::{Chunk, ChunkPos, SECTION_COUNT};
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Side of the square of chunks meshed, in chunks.
+const RADIUS: i32 = 6;
+
+const SEA_LEVEL: usize = 62;
+
+fn main() {
+    let source = std::fs::read_to_string("assets/blocks/rustedcraft.toml").expect("run from the repository root");
+    let mut builder = BlockRegistryBuilder::new();
+    builder.load_str("rustedcraft", &source).unwrap();
+    let registry = builder.build().unwrap();
+
+    // Layers only matter for merging, so give every texture its own.
+    let mut layers = HashMap::new();
+    let table = BlockMeshTable::new(&registry, |name| {
+        let next = layers.len() as u16;
+        *layers.entry(name.to_string()).or_insert(next)
+    });
+
+    for (name, height) in [("plains", plains as fn(i32, i32) -> usize), ("hills", hills)] {
+        let chunks = generate(&registry, height);
+        println!("{name}: {} chunks", chunks.len());
+
+        for (label, mode) in [("naive", MeshingMode::Naive), ("greedy", MeshingMode::Greedy)] {
+            let settings = MeshSettings { opaque: mode, cutout: mode, translucent: mode, ..MeshSettings::default() };
+            let (vertices, time) = mesh_all(&chunks, &table, &settings);
+            let sections = chunks.len() * SECTION_COUNT;
+
+            println!(
+                "  {label:>6}: {vertices:>9} vertices ({:>6.2} MiB), {:>8.2?} total, {:>6.1?} per section",
+                (vertices * size_of::<BlockVertex>()) as f64 / (1024.0 * 1024.0),
+                time,
+                time / sections as u32,
+            );
+        }
+    }
+}
+
+fn plains(x: i32, z: i32) -> usize {
+    64 + ((x as f32 * 0.05).sin() + (z as f32 * 0.04).cos()).round() as usize
+}
+
+fn hills(x: i32, z: i32) -> usize {
+    let wave = (x as f32 * 0.09).sin() * 9.0 + (z as f32 * 0.07).cos() * 7.0 + ((x + z) as f32 * 0.21).sin() * 3.0;
+    (64.0 + wave) as usize
+}
+
+fn generate(registry: &BlockRegistry, height: fn(i32, i32) -> usize) -> HashMap<ChunkPos, Chunk> {
+    let state = |name| registry.default_state(name).unwrap();
+    let (stone, dirt, grass, sand, water) = (state("stone"), state("dirt"), state("grass_block"), state("sand"), state("water"));
+    let mut chunks = HashMap::new();
+
+    for cz in -RADIUS..RADIUS {
+        for cx in -RADIUS..RADIUS {
+            let pos = ChunkPos::new(cx, cz);
+            let mut chunk = Chunk::new(pos);
+
+            for z in 0..16 {
+                for x in 0..16 {
+                    let world = pos.block(x, 0, z);
+                    let top = height(world.x, world.z);
+
+                    chunk.fill((x, 0, z), (x, top - 4, z), stone);
+                    chunk.fill((x, top - 3, z), (x, top - 1, z), if top <= SEA_LEVEL + 1 { sand } else { dirt });
+                    chunk.set(x, top, z, if top <= SEA_LEVEL + 1 { sand } else { grass });
+
+                    if top < SEA_LEVEL {
+                        chunk.fill((x, top + 1, z), (x, SEA_LEVEL, z), water);
+                    }
+                }
+            }
+
+            chunk.compact();
+            chunks.insert(pos, chunk);
+        }
+    }
+
+    chunks
+}
+
+fn mesh_all(chunks: &HashMap<ChunkPos, Chunk>, table: &BlockMeshTable, settings: &MeshSettings) -> (usize, Duration) {
+    let mut vertices = 0;
+    let mut time = Duration::ZERO;
+
+    for chunk in chunks.values() {
+        let neighbors = ChunkNeighbors::gather(chunk, |pos| chunks.get(&pos));
+
+        for section in 0..SECTION_COUNT {
+            let neighborhood = SectionNeighborhood::from_chunks(&neighbors, section);
+
+            let start = Instant::now();
+            let mesh = mesh_section(&neighborhood, table, settings);
+            time += start.elapsed();
+
+            vertices += mesh.quad_count() * 4;
+        }
+    }
+
+    (vertices, time)
+}
@@ -1,92 +1,818 @@
 #![cfg_attr(debug_assertions, allow(dead_code, unused))]
-use glfw::{Context, OpenGlProfileHint};
-use glfw::{Key, Action, Modifiers};
-use glfw::{WindowHint, Window};
+use glfw::{Context, JoystickId};
 
-use rustedcraft::opengl::{BufferTarget, BufferUsage, BufferObject};
 use rustedcraft::opengl::{ShaderType, Shader};
-use rustedcraft::opengl::VertexAttribPointer;
-use rustedcraft::opengl::VertexArray;
 use rustedcraft::opengl::Program;
+use rustedcraft::resource::ResourceManager;
+use rustedcraft::block::{BlockRegistry, RenderLayer};
+use rustedcraft::mesh::BlockMeshTable;
+use rustedcraft::render::{BlockTextures, ChunkMeshes, SelectionOutline, SkyState, SkyUniforms, TexturedBoxes};
+use rustedcraft::jobs::ChunkStreamer;
+use rustedcraft::worldgen::{BiomeRegistry, FeatureRegistry, StructureRegistry, TerrainGenerator, SEA_LEVEL};
+use rustedcraft::light::{LightEngine, LightTable};
+use rustedcraft::command::{time_command, CommandRegistry, Console};
+use rustedcraft::save::{LevelData, WorldStorage, LEVEL_FILE};
+use rustedcraft::import::{import_world, BlockMapping};
+use rustedcraft::physics::{raycast, Body, PhysicsTable, PLAYER_EYE_HEIGHT, PLAYER_REACH};
+use rustedcraft::item::{Inventory, Item, ItemRegistry, ItemStack};
+use rustedcraft::interaction::{place_target, BlockRules, BlockUpdates, Mining, PlaceContext, BREAK_STAGES};
+use rustedcraft::entity::{Entities, DROPPED_ITEM_SIZE, FALLING_BLOCK_SIZE};
+use rustedcraft::world::{BlockPos, BlockState, SECTION_SIZE};
+use rustedcraft::input::{action, Bindings, InputMap};
+use rustedcraft::camera::{Camera, CameraUniforms, FlyController, Projection, WalkController};
+use rustedcraft::camera::{set_cursor_captured, is_cursor_captured};
+use rustedcraft::game_loop::GameLoop;
+use rustedcraft::window::GameWindow;
+use rustedcraft::settings::Settings;
+use rustedcraft::math::{Aabb, Vec3};
 
-use std::ffi::CString;
-use std::ptr::null;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Directory of the world played.
+const WORLD_DIR: &str = "saves/world";
+
+/// Seed of newly created worlds.
+const WORLD_SEED: u64 = 0x5eed_0fc0_ffee;
+
+/// Distance in chunks around the player within which blocks get random ticks.
+const SIMULATION_DISTANCE: i32 = 8;
+
+/// How to run the import subcommand.
+const IMPORT_USAGE: &str = "Usage: rustedcraft import <minecraft world> [destination] [--mapping <file>] [--force]";
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.first().is_some_and(|command| command == "import") {
+        std::process::exit(import(&args[1..]));
+    }
+
     let mut glfw = glfw::init(glfw::fail_on_errors).unwrap();
 
-    glfw.window_hint(WindowHint::OpenGlProfile(OpenGlProfileHint::Core));
-    glfw.window_hint(WindowHint::ContextVersion(3, 3));
-    glfw.window_hint(WindowHint::Resizable(false));
+    let settings = Settings::load("config/settings.toml").unwrap_or_else(|e| {
+        eprintln!("Failed to load settings, using defaults: {e}");
+        Settings::default()
+    });
+
+    let mut game_window = match GameWindow::create(&mut glfw, settings.window, "RustedCraft") {
+        Ok(game_window) => game_window,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
 
-    let (mut window, _) = glfw
-        .create_window(640, 480, "RustedCraft", glfw::WindowMode::Windowed)
-        .expect("Failed to create glfw window");
+    let window = game_window.window_mut();
 
-    gl::load_with(|s| window.get_proc_address(s));
+    window.set_key_polling(true);
+    window.set_mouse_button_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+    window.set_focus_polling(true);
 
-    window.set_key_callback(handle_input);
-    window.make_current();
+    let mut resources = ResourceManager::new("assets", "resourcepacks", "config/resourcepacks.toml")
+        .expect("Failed to load resource packs");
 
-    let vert_source = std::fs::read_to_string("assets/shaders/triangle.vert").unwrap();
-    let triangle_vert = Shader::from_source(&vert_source, ShaderType::Vertex).unwrap();
+    let blocks = match BlockRegistry::load(&resources) {
+        Ok(blocks) => Arc::new(blocks),
+        Err(e) => {
+            eprintln!("Failed to load blocks: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    let frag_source = std::fs::read_to_string("assets/shaders/triangle.frag").unwrap();
-    let triangle_frag = Shader::from_source(&frag_source, ShaderType::Fragment).unwrap();
+    let items = match ItemRegistry::load(&resources, &blocks) {
+        Ok(items) => items,
+        Err(e) => {
+            eprintln!("Failed to load items: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    let shader_program = Program::new();
+    let rules = match BlockRules::new(&blocks, &items) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Failed to load blocks: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    shader_program.attach_shader(&triangle_vert);
-    shader_program.attach_shader(&triangle_frag);
-    shader_program.link();
+    let crack_textures: Vec<String> = (0..BREAK_STAGES).map(|stage| format!("block/destroy_stage_{stage}")).collect();
+    let item_textures = items.items().iter().filter_map(Item::texture).map(String::from);
+    let extra_textures: Vec<String> = crack_textures.iter().cloned().chain(item_textures).collect();
+    let (mut textures, mut table, mut crack_layers) = load_textures(&resources, &blocks, &extra_textures, &crack_textures);
 
-    let uniform_name = CString::new("ourColor").unwrap();
-    let uniform = unsafe { gl::GetUniformLocation(shader_program.id(), uniform_name.as_ptr()) };
+    let biomes = match BiomeRegistry::load(&resources, &blocks) {
+        Ok(biomes) => Arc::new(biomes),
+        Err(e) => {
+            eprintln!("Failed to load biomes: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    #[rustfmt::skip]
-    let triangle: [f32; 18] = [
-         0.5, -0.5, 0.0, 1.0, 0.0, 0.0,
-        -0.5, -0.5, 0.0, 0.0, 1.0, 0.0,
-         0.0,  0.5, 0.0, 0.0, 0.0, 1.0,
-    ];
+    let features = match FeatureRegistry::load(&resources, &blocks) {
+        Ok(features) => Arc::new(features),
+        Err(e) => {
+            eprintln!("Failed to load features: {e}");
+            std::process::exit(1);
+        }
+    };
 
-    #[rustfmt::skip]
-    let indices: [u32; 3] = [
-        0, 1, 2
-    ];
+    let structures = match StructureRegistry::load(&resources, &blocks, &biomes) {
+        Ok(structures) => Arc::new(structures),
+        Err(e) => {
+            eprintln!("Failed to load structures: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let mut level = match LevelData::load(WORLD_DIR) {
+        Ok(level) => level.unwrap_or_else(|| LevelData::new(WORLD_SEED)),
+        Err(e) => {
+            eprintln!("Failed to load the world: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let generator = TerrainGenerator::new(level.seed, &blocks, biomes.clone()).and_then(|generator| generator.with_features(features));
+
+    let generator = match generator {
+        Ok(generator) => Arc::new(generator.with_structures(structures)),
+        Err(e) => {
+            eprintln!("Failed to create the world generator: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    let spawn = *level.spawn.get_or_insert_with(|| [8, generator.height_at(8, 8).max(SEA_LEVEL) + 1, 8]);
+    let spawn_eye = Vec3::new(spawn[0] as f32, spawn[1] as f32 + PLAYER_EYE_HEIGHT, spawn[2] as f32);
+    let storage = Arc::new(WorldStorage::new(WORLD_DIR, blocks.clone(), settings.save.compression));
+
+    let lighting = Arc::new(LightEngine::new(LightTable::new(&blocks)));
+    let physics = PhysicsTable::new(&blocks);
+    let mut streamer = ChunkStreamer::new(generator.clone(), table.clone(), lighting, &settings.chunks, settings.meshing);
+    streamer.set_biome_tints(Arc::new(biomes.tints()));
+    streamer.set_storage(storage.clone());
+    let mut chunk_meshes = ChunkMeshes::new();
+
+    let bindings = Bindings::load("config/controls.toml").unwrap_or_else(|e| {
+        eprintln!("Failed to load controls, using defaults: {e}");
+        Bindings::default()
+    });
+
+    let mut input = InputMap::new(bindings);
+
+    let camera_settings = settings.camera;
+    let (width, height) = game_window.framebuffer_size();
+
+    let mut projection = Projection::new(camera_settings.fov, 1.0);
+    projection.set_viewport(width, height);
+    projection.reverse_z = camera_settings.reverse_z;
+    projection.apply_depth_state();
+
+    let mut camera = Camera::new(spawn_eye, projection);
+    let mut controller = FlyController::new(camera.position);
+    let mut walker = WalkController::new(camera.position - Vec3::Y * PLAYER_EYE_HEIGHT);
+    let mut flying = false;
+    let camera_uniforms = CameraUniforms::new();
+    let sky_uniforms = SkyUniforms::new();
+
+    let mut inventory = starter_inventory(&items);
+    let mut mining = Mining::new();
+    let mut updates = BlockUpdates::new(level.seed);
+    updates.set_random_tick_speed(level.game_rules.random_tick_speed as usize);
+
+    if let Err(e) = updates.scheduler_mut().load(&blocks, &level.scheduled_ticks) {
+        eprintln!("Failed to load scheduled ticks: {e}");
+    }
+
+    let mut entities = Entities::new(level.seed);
+
+    set_cursor_captured(game_window.window_mut(), true);
+
+    let mut shader_program = load_program(&resources, "chunk").unwrap();
+    let mut outline_program = load_program(&resources, "outline").unwrap();
+    let mut boxes_program = load_program(&resources, "boxes").unwrap();
+    let mut resource_generation = resources.generation();
+    camera_uniforms.attach(&shader_program);
+    camera_uniforms.attach(&outline_program);
+    camera_uniforms.attach(&boxes_program);
+    sky_uniforms.attach(&shader_program);
+    sky_uniforms.attach(&boxes_program);
+
+    let mut outline = SelectionOutline::new();
+    let mut item_boxes = TexturedBoxes::new();
+    let mut crack_boxes = TexturedBoxes::new();
+
+    let mut commands = CommandRegistry::new();
+
+    commands
+        .register("time", "time <set|add|query> [value]", "Changes or tells the time of day", |level: &mut LevelData, arguments: &[&str]| {
+            time_command(&mut level.time, arguments)
+        })
+        .expect("commands have unique names");
+
+    let console = Console::spawn().inspect_err(|e| eprintln!("{e}")).ok();
+
+    unsafe {
+        gl::Enable(gl::CULL_FACE);
+        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+    }
+
+    let mut sky_target = biome_sky(&generator, camera.position);
+    let mut sky = sky_target;
+
+    let mut game_loop = GameLoop::new(20);
+    let mut last_report = 0;
+    let autosave_ticks = settings.save.autosave_interval as u64 * 20;
+    let mut last_autosave = 0;
+
+    while !game_window.window().should_close() {
+        let gamepad = glfw.get_joystick(JoystickId::Joystick1).get_gamepad_state();
+        input.update_gamepad(gamepad.as_ref());
+
+        let tick_seconds = game_loop.tick_duration().as_secs_f32();
+
+        let frame = game_loop.frame(|_| {
+            input.update();
+            if input.pressed(action::TOGGLE_FLY) {
+                flying = !flying;
 
-    let vao = VertexArray::new();
-    vao.bind();
+                if flying {
+                    controller.teleport(walker.eye_position());
+                }
+                else {
+                    walker.teleport(controller.position() - Vec3::Y * PLAYER_EYE_HEIGHT);
+                }
+            }
 
-    let vbo = BufferObject::new(BufferTarget::ArrayBuffer, BufferUsage::StaticDraw);
-    let ebo = BufferObject::new(BufferTarget::ElementArrayBuffer, BufferUsage::StaticDraw);
+            if flying {
+                controller.tick(&camera, &input, &camera_settings, tick_seconds);
+            }
+            else {
+                walker.tick(&camera, &input, &physics, &streamer);
+            }
 
-    vbo.data(&triangle);
-    ebo.data(&indices);
+            let eye = match flying {
+                true => controller.position(),
+                false => walker.eye_position(),
+            };
 
-    let vap = VertexAttribPointer::new::<f32>(0, 3, false, 6, 0);
-    let cap = VertexAttribPointer::new::<f32>(1, 3, false, 6, 3);
+            let captured = is_cursor_captured(game_window.window());
+            let held = inventory.selected().map(|stack| items.item(stack.item));
+            let tool = held.and_then(Item::tool);
+            let target = raycast(&physics, &streamer, eye, camera.forward(), PLAYER_REACH);
 
-    vap.enable();
-    cap.enable();
+            match target.as_ref().filter(|_| captured && input.held(action::ATTACK)) {
+                Some(hit) => {
+                    // Flying players break blocks as fast as if they stood on the ground.
+                    let body = walker.body();
+                    let in_fluid = !flying && body.fluid().is_some();
+                    let on_ground = flying || body.on_ground();
+                    let time = rules.break_time(hit.state, tool, in_fluid, on_ground);
+
+                    if mining.tick(hit.pos, hit.state, time, tick_seconds) {
+                        updates.break_block(&rules, &mut streamer, hit.pos, tool);
+                    }
+                }
+                None => mining.stop(),
+            }
+
+            if captured
+                && input.pressed(action::USE)
+                && let Some(hit) = &target
+                && let Some(block) = held.and_then(Item::block).map(|id| blocks.block(id))
+            {
+                let bodies = match flying {
+                    true => Vec::new(),
+                    false => vec![walker.body().aabb()],
+                };
+
+                let context = PlaceContext { hit, look: camera.forward(), bodies: &bodies };
+
+                if let Some((pos, state)) = place_target(&blocks, &rules, &physics, &streamer, block, &context)
+                    && updates.set_block(&mut streamer, pos, state).is_some()
+                {
+                    inventory.take_selected(1);
+                }
+            }
+
+            if captured
+                && input.pressed(action::PICK_BLOCK)
+                && let Some(hit) = &target
+                && let Some(item) = items.block_item(blocks.block_of(hit.state).id())
+            {
+                inventory.pick(item.id());
+            }
+
+            if input.pressed(action::HOTBAR_NEXT) {
+                inventory.scroll(1);
+            }
+
+            if input.pressed(action::HOTBAR_PREVIOUS) {
+                inventory.scroll(-1);
+            }
+
+            if input.pressed(action::DROP_ITEM)
+                && let Some(stack) = inventory.take_selected(1)
+            {
+                entities.throw(stack, eye, camera.forward());
+            }
+
+            let center = BlockPos::new(eye.x.floor() as i32, 0, eye.z.floor() as i32).chunk();
+            updates.queue_random_ticks(&rules, streamer.chunks().filter(|chunk| chunk.pos().distance(center) <= SIMULATION_DISTANCE));
+            updates.tick(&rules, &mut streamer);
+            entities.tick(&physics, &rules, &mut updates, &mut streamer);
+            updates.process(&rules, &mut streamer);
+
+            for (pos, stack) in updates.take_drops() {
+                entities.drop_from_block(pos, stack);
+            }
+
+            for (pos, state) in updates.take_falling() {
+                entities.start_falling(pos, state);
+            }
+
+            let player = Body::player(eye - Vec3::Y * PLAYER_EYE_HEIGHT).aabb();
+            entities.pick_up(player, &mut inventory, &items);
+
+            sky_target = biome_sky(&generator, camera.position);
+            if level.game_rules.do_daylight_cycle {
+                level.time.tick();
+            }
+
+            if input.pressed(action::ATTACK) && !is_cursor_captured(game_window.window()) {
+                set_cursor_captured(game_window.window_mut(), true);
+            }
+
+            if input.pressed(action::PAUSE) {
+                game_window.window_mut().set_should_close(true);
+            }
+
+            if input.pressed(action::TOGGLE_FULLSCREEN) {
+                game_window.toggle_fullscreen();
+            }
+
+            if input.pressed(action::RELOAD_RESOURCES)
+                && let Err(e) = resources.reload()
+            {
+                eprintln!("Failed to apply resource packs: {e}");
+            }
+        });
+
+        // Refresh the timings in the window title about once per second.
+        if game_loop.tick() / 20 != last_report {
+            last_report = game_loop.tick() / 20;
+
+            let timings = game_loop.timings();
+            let held = match inventory.selected() {
+                Some(stack) => format!("{} x{}", items.item(stack.item).name(), stack.count),
+                None => String::from("empty hand"),
+            };
+
+            let title = format!(
+                "RustedCraft - {:.0} FPS, {:.1} TPS, {:.2} ms/frame, {} chunks, {} jobs - slot {}: {held}",
+                timings.fps(),
+                timings.tps(),
+                timings.frame.average().as_secs_f64() * 1000.0,
+                chunk_meshes.chunk_count(),
+                streamer.pending_jobs(),
+                inventory.selected_slot() + 1,
+            );
+
+            game_window.window_mut().set_title(&title);
+        }
+
+        if autosave_ticks != 0 && game_loop.tick() / autosave_ticks != last_autosave {
+            last_autosave = game_loop.tick() / autosave_ticks;
+            save_world(&mut level, &updates, &blocks, &mut streamer, &storage);
+        }
+
+        if let Err(e) = resources.poll_config() {
+            eprintln!("Failed to apply resource packs: {e}");
+        }
+
+        if resources.generation() != resource_generation {
+            resource_generation = resources.generation();
+
+            // Chunks are meshed again with the texture layers of the new atlas.
+            (textures, table, crack_layers) = load_textures(&resources, &blocks, &extra_textures, &crack_textures);
+            streamer.set_mesh_table(table.clone());
+
+            match load_program(&resources, "chunk") {
+                Ok(program) => shader_program = program,
+                Err(e) => eprintln!("Failed to reload shaders: {e}"),
+            }
+
+            match load_program(&resources, "outline") {
+                Ok(program) => outline_program = program,
+                Err(e) => eprintln!("Failed to reload shaders: {e}"),
+            }
+
+            match load_program(&resources, "boxes") {
+                Ok(program) => boxes_program = program,
+                Err(e) => eprintln!("Failed to reload shaders: {e}"),
+            }
+
+            camera_uniforms.attach(&shader_program);
+            camera_uniforms.attach(&outline_program);
+            camera_uniforms.attach(&boxes_program);
+            sky_uniforms.attach(&shader_program);
+            sky_uniforms.attach(&boxes_program);
+        }
+
+        while let Some(line) = console.as_ref().and_then(Console::poll) {
+            match commands.execute(&mut level, &line) {
+                Ok(message) => println!("{message}"),
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+
+        if let Some((width, height)) = game_window.take_resize() {
+            camera.projection.set_viewport(width, height);
+        }
+
+        if !is_cursor_captured(game_window.window()) {
+            input.take_frame_motion();
+        }
+        else if flying {
+            controller.look(&mut camera, &mut input, &camera_settings, frame.delta.as_secs_f32());
+        }
+        else {
+            walker.look(&mut camera, &mut input, &camera_settings, frame.delta.as_secs_f32());
+        }
+
+        if flying {
+            controller.apply(&mut camera, frame.alpha);
+        }
+        else {
+            walker.apply(&mut camera, frame.alpha);
+        }
+
+        // Ease towards the sky of the biomes around, over about a second.
+        let fade = (frame.delta.as_secs_f32() * 2.0).min(1.0);
+        sky = std::array::from_fn(|i| sky[i] + (sky_target[i] - sky[i]) * fade);
+
+        camera_uniforms.update(&camera);
+
+        let sky_state = SkyState::new(level.time, frame.alpha, sky, camera.forward());
+        sky_uniforms.update(&sky_state, (streamer.view_distance() as usize * SECTION_SIZE) as f32);
+
+        streamer.update(camera.position, camera.forward());
+        chunk_meshes.sync(&mut streamer, settings.chunks.upload_budget);
+
+        let target = raycast(&physics, &streamer, camera.position, camera.forward(), PLAYER_REACH);
+        outline.set_boxes(target.as_ref().map_or(&[], |hit| hit.shape.as_slice()));
+
+        item_boxes.clear();
+
+        for item in entities.items() {
+            push_dropped_item(&mut item_boxes, &blocks, &table, &textures, &items, &streamer, item.stack, item.position(frame.alpha), item.age());
+        }
+
+        for block in entities.falling_blocks() {
+            let position = block.position(frame.alpha);
+            let half = FALLING_BLOCK_SIZE / 2.0;
+            let min = Vec3::new(position.x - half, position.y, position.z - half);
+            let light = streamer.light(block.block_pos()).unwrap_or((15, 0));
+
+            push_block(&mut item_boxes, &table, block.state, Aabb::new(min, min + Vec3::splat(FALLING_BLOCK_SIZE)), light);
+        }
+
+        crack_boxes.clear();
+
+        if let Some((pos, stage)) = mining.stage()
+            && let Some(state) = streamer.block(pos)
+        {
+            let corner = Vec3::new(pos.x as f32, pos.y as f32, pos.z as f32);
+            let block = Aabb::UNIT.offset(corner);
+
+            for b in physics.selection(state) {
+                crack_boxes.push(b.offset(corner).inflate(0.002), block, [crack_layers[stage as usize]; 6], (15, 15));
+            }
+        }
 
-    while !window.should_close() {
         shader_program.use_program();
+        textures.bind(0);
+
+        let origin = shader_program.uniform_location("sectionOrigin");
+        let alpha_cutoff = shader_program.uniform_location("alphaCutoff");
 
         unsafe {
-            gl::Clear(gl::COLOR_BUFFER_BIT);
-            gl::ClearColor(0.2, 0.2, 0.4, 1.0);
-            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, null());
-        };
+            let [r, g, b] = sky_state.fog_color;
+            gl::ClearColor(r, g, b, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            gl::Uniform1i(shader_program.uniform_location("blockTextures"), 0);
+            gl::Uniform1f(shader_program.uniform_location("aoStrength"), settings.render.ao_strength());
+            gl::Uniform1f(alpha_cutoff, 0.0);
+        }
+
+        chunk_meshes.draw(RenderLayer::Opaque, origin);
+
+        unsafe { gl::Uniform1f(alpha_cutoff, 0.5) };
+        chunk_meshes.draw(RenderLayer::Cutout, origin);
+
+        boxes_program.use_program();
+
+        unsafe {
+            gl::Uniform1i(boxes_program.uniform_location("blockTextures"), 0);
+            gl::Uniform1f(boxes_program.uniform_location("alphaCutoff"), 0.5);
+            gl::Uniform1i(boxes_program.uniform_location("overlay"), 0);
+        }
+
+        item_boxes.draw();
+
+        // Cracks darken the block under them by multiplying its color.
+        if !crack_boxes.is_empty() {
+            unsafe {
+                gl::Uniform1f(boxes_program.uniform_location("alphaCutoff"), 0.1);
+                gl::Uniform1i(boxes_program.uniform_location("overlay"), 1);
+                gl::Enable(gl::BLEND);
+                gl::BlendFunc(gl::DST_COLOR, gl::SRC_COLOR);
+                gl::DepthMask(gl::FALSE);
+            }
+
+            crack_boxes.draw();
+
+            unsafe {
+                gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+                gl::DepthMask(gl::TRUE);
+                gl::Disable(gl::BLEND);
+            }
+        }
+
+        shader_program.use_program();
 
-        window.swap_buffers();
+        unsafe {
+            gl::Uniform1f(alpha_cutoff, 0.0);
+            gl::Enable(gl::BLEND);
+            gl::DepthMask(gl::FALSE);
+        }
+
+        chunk_meshes.draw(RenderLayer::Translucent, origin);
+
+        outline_program.use_program();
+        outline.draw();
+
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+            gl::Disable(gl::BLEND);
+        }
+
+        game_window.window_mut().swap_buffers();
         glfw.poll_events();
+
+        let events: Vec<_> = glfw::flush_messages(game_window.events()).collect();
+
+        for (_, event) in events {
+            game_window.handle_event(&event);
+            input.handle_event(&event);
+        }
+
+        game_loop.pace();
+    }
+
+    save_world(&mut level, &updates, &blocks, &mut streamer, &storage);
+    storage.flush();
+
+    let settings = Settings { window: *game_window.settings(), ..settings };
+
+    if let Err(e) = settings.save("config/settings.toml") {
+        eprintln!("Failed to save settings: {e}");
     }
 }
 
-fn handle_input(window: &mut Window, key: Key, scancode: i32, action: Action, mods: Modifiers) {
-    match (key, action) {
-        (Key::Escape, Action::Press) => window.set_should_close(true),
-        _ => {}
+/// Queues the chunks changed and the level, with the ticks still scheduled, to be saved in
+/// the background.
+fn save_world(level: &mut LevelData, updates: &BlockUpdates, blocks: &BlockRegistry, streamer: &mut ChunkStreamer, storage: &WorldStorage) {
+    level.scheduled_ticks = updates.scheduler().save(blocks);
+    streamer.save_modified();
+    storage.save_level(level.clone());
+}
+
+/// Imports the Minecraft Java world given in `args` into ours, [`WORLD_DIR`] unless another
+/// destination is given, then reports what could not be imported. Returns the exit code.
+fn import(args: &[String]) -> i32 {
+    let mut paths = Vec::new();
+    let mut mapping_path = None;
+    let mut force = false;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--force" => force = true,
+            "--mapping" => match args.next() {
+                Some(path) => mapping_path = Some(path),
+                None => {
+                    eprintln!("{IMPORT_USAGE}");
+                    return 1;
+                }
+            },
+            option if option.starts_with("--") => {
+                eprintln!("Unknown option {option}\n{IMPORT_USAGE}");
+                return 1;
+            }
+            path => paths.push(path),
+        }
+    }
+
+    let (source, destination) = match paths[..] {
+        [source] => (source, WORLD_DIR),
+        [source, destination] => (source, destination),
+        _ => {
+            eprintln!("{IMPORT_USAGE}");
+            return 1;
+        }
     };
+
+    if Path::new(destination).join(LEVEL_FILE).exists() && !force {
+        eprintln!("{destination} already holds a world, pass --force to import over it");
+        return 1;
+    }
+
+    let settings = Settings::load("config/settings.toml").unwrap_or_else(|e| {
+        eprintln!("Failed to load settings, using defaults: {e}");
+        Settings::default()
+    });
+
+    let loaded = ResourceManager::new("assets", "resourcepacks", "config/resourcepacks.toml").and_then(|resources| {
+        let blocks = BlockRegistry::load(&resources)?;
+
+        let mapping = match mapping_path {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| BlockMapping::parse(&source, &blocks))
+                .map_err(|e| format!("{path}: {e}"))?,
+            None => BlockMapping::load(&resources, &blocks)?,
+        };
+
+        Ok((blocks, mapping))
+    });
+
+    let (blocks, mapping) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Failed to load the blocks to import: {e}");
+            return 1;
+        }
+    };
+
+    let report = import_world(Path::new(source), Path::new(destination), &blocks, &mapping, settings.save.compression, |done, total| {
+        eprint!("\rImporting region {done} of {total}");
+    });
+
+    eprintln!();
+
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to import {source}: {e}");
+            return 1;
+        }
+    };
+
+    println!("Imported {} chunks from {} regions into {destination}", report.chunks, report.regions);
+
+    if report.incomplete > 0 {
+        println!("Left out {} chunks Minecraft had not finished generating", report.incomplete);
+    }
+
+    if report.clipped_sections > 0 {
+        println!("Left out {} sections below y 0 or from y 256 up", report.clipped_sections);
+    }
+
+    if !report.failures.is_empty() {
+        println!("Failed to import {} chunks or regions:", report.failures.len());
+
+        for failure in &report.failures {
+            println!("  {failure}");
+        }
+    }
+
+    let unmapped = report.unmapped_by_count();
+
+    if !unmapped.is_empty() {
+        println!("Unmapped blocks, replaced with {}:", blocks.state_name(mapping.fallback()));
+
+        for (name, count) in unmapped {
+            println!("  {count:>10}  {name}");
+        }
+    }
+
+    0
+}
+
+/// Compiles and links `shaders/<name>.vert` and `shaders/<name>.frag`.
+/// Builds the block atlas from the active resource packs, with the mesh table and the layers
+/// of the cracks of blocks being broken that go with it.
+fn load_textures(resources: &ResourceManager, blocks: &BlockRegistry, extra: &[String], cracks: &[String]) -> (BlockTextures, Arc<BlockMeshTable>, Vec<u16>) {
+    let textures = BlockTextures::load(resources, blocks, extra);
+
+    if !textures.missing().is_empty() {
+        eprintln!("Missing block textures: {}", textures.missing().join(", "));
+    }
+
+    let table = Arc::new(BlockMeshTable::new(blocks, |name| textures.layer(name)));
+    let crack_layers = cracks.iter().map(|name| textures.layer(name)).collect();
+
+    (textures, table, crack_layers)
+}
+
+fn load_program(resources: &ResourceManager, name: &str) -> Result<Program, String> {
+    let vert_source = resources.read_to_string(&format!("shaders/{name}.vert"))?;
+    let vert = Shader::from_source(&vert_source, ShaderType::Vertex)?;
+
+    let frag_source = resources.read_to_string(&format!("shaders/{name}.frag"))?;
+    let frag = Shader::from_source(&frag_source, ShaderType::Fragment)?;
+
+    let shader_program = Program::new();
+
+    shader_program.attach_shader(&vert);
+    shader_program.attach_shader(&frag);
+    shader_program.link()?;
+
+    Ok(shader_program)
+}
+
+/// Returns the inventory a new player starts with: tools, and blocks to build with.
+fn starter_inventory(items: &ItemRegistry) -> Inventory {
+    let mut inventory = Inventory::new();
+
+    let starter = [
+        "iron_pickaxe", "iron_shovel", "iron_axe", "cobblestone", "oak_planks", "torch", "oak_slab", "ladder", "snow", "oak_stairs", "oak_log",
+        "glass", "sand", "gravel", "furnace", "dirt", "wheat", "water", "lava",
+    ];
+
+    for item in starter.iter().filter_map(|name| items.get(name)) {
+        inventory.add(items, ItemStack::new(item.id(), item.max_stack()));
+    }
+
+    inventory
+}
+
+/// Adds the boxes of a dropped item to `boxes`, lying with its bottom at `position` and
+/// bobbing up and down with its `age`. Blocks are drawn as small copies of their shape, other
+/// items as thin tiles of their texture.
+#[allow(clippy::too_many_arguments)]
+fn push_dropped_item(
+    boxes: &mut TexturedBoxes,
+    blocks: &BlockRegistry,
+    table: &BlockMeshTable,
+    textures: &BlockTextures,
+    items: &ItemRegistry,
+    streamer: &ChunkStreamer,
+    stack: ItemStack,
+    position: Vec3,
+    age: u32,
+) {
+    let bob = ((age as f32 / 10.0).sin() + 1.0) * 0.05;
+    let feet = position.floor();
+    let light = streamer.light(BlockPos::new(feet.x as i32, feet.y as i32, feet.z as i32)).unwrap_or((15, 0));
+    let item = items.item(stack.item);
+
+    let half = DROPPED_ITEM_SIZE / 2.0;
+    let min = Vec3::new(position.x - half, position.y + bob, position.z - half);
+    let bounds = Aabb::new(min, min + Vec3::splat(DROPPED_ITEM_SIZE));
+
+    match item.block() {
+        Some(block) => push_block(boxes, table, blocks.block(block).default_state(), bounds, light),
+        None => {
+            let layer = textures.layer(item.texture().unwrap_or_default());
+            let thin = Vec3::new(0.0, 0.0, DROPPED_ITEM_SIZE * 15.0 / 32.0);
+            let tile = Aabb::new(bounds.min + thin, bounds.max - thin);
+
+            boxes.push(tile, bounds, [layer; 6], light);
+        }
+    }
+}
+
+/// Adds the boxes of the model of `state` to `boxes`, scaled from a block to `bounds`.
+fn push_block(boxes: &mut TexturedBoxes, table: &BlockMeshTable, state: BlockState, bounds: Aabb, light: (u8, u8)) {
+    let info = table.get(state);
+    let scale = (bounds.max - bounds.min) / 16.0;
+
+    for shape in &info.boxes {
+        let [min, max] = [[shape[0], shape[1], shape[2]], [shape[3], shape[4], shape[5]]]
+            .map(|corner| bounds.min + Vec3::new(corner[0] as f32 * scale.x, corner[1] as f32 * scale.y, corner[2] as f32 * scale.z));
+
+        boxes.push(Aabb::new(min, max), bounds, info.textures, light);
+    }
+}
+
+/// Returns the sky color at `position`, averaged over the biomes around so that it changes
+/// gradually when crossing biome borders.
+fn biome_sky(generator: &TerrainGenerator, position: Vec3) -> [f32; 3] {
+    let biomes = generator.biome_source().registry();
+    let (x, z) = (position.x.floor() as i32, position.z.floor() as i32);
+    let mut sum = [0.0; 3];
+
+    for dz in -2..=2 {
+        for dx in -2..=2 {
+            let sky = biomes.get(generator.biome_at(x + dx * 8, z + dz * 8)).colors.sky;
+            sum = std::array::from_fn(|i| sum[i] + sky[i] as f32 / 255.0);
+        }
+    }
+
+    sum.map(|c| c / 25.0)
 }
@@ -0,0 +1,262 @@
+use crate::math::Aabb;
+use crate::world::Direction;
+
+use serde::Deserialize;
+
+/// The render pass a block state is drawn in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderLayer {
+    /// Not drawn at all, like air.
+    Invisible,
+
+    /// Fully opaque faces, drawn first with depth writes.
+    #[default]
+    Opaque,
+
+    /// Texels are either opaque or discarded, like leaves and flowers.
+    Cutout,
+
+    /// Blended with what is behind, like water and stained glass. Drawn last, back to front.
+    Translucent,
+}
+
+/// The biome color a face is multiplied by, for textures drawn in shades of gray.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tint {
+    /// Drawn with the colors of its texture.
+    #[default]
+    None,
+
+    /// Tinted with the grass color of the biome, like grass blocks and tall grass.
+    Grass,
+
+    /// Tinted with the foliage color of the biome, like leaves.
+    Foliage,
+
+    /// Tinted with the water color of the biome.
+    Water,
+}
+
+/// A fluid a block state is made of, which bodies inside it swim or wade through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fluid {
+    Water,
+    Lava,
+}
+
+impl Fluid {
+    /// Every fluid, in declaration order.
+    pub const ALL: [Self; 2] = [Self::Water, Self::Lava];
+
+    /// Returns the game ticks between two steps of the fluid flowing.
+    pub fn tick_delay(self) -> u32 {
+        match self {
+            Self::Water => 5,
+            Self::Lava => 30,
+        }
+    }
+
+    /// Returns the levels the fluid loses for every block it flows sideways.
+    pub fn drop_off(self) -> u8 {
+        match self {
+            Self::Water => 1,
+            Self::Lava => 2,
+        }
+    }
+
+    /// Returns how many blocks away the fluid looks for a way down before spreading evenly.
+    pub fn slope_distance(self) -> u32 {
+        match self {
+            Self::Water => 4,
+            Self::Lava => 2,
+        }
+    }
+
+    /// Returns whether a block between two sources of the fluid, above solid ground, becomes
+    /// a source too.
+    pub fn is_infinite(self) -> bool {
+        self == Self::Water
+    }
+}
+
+/// The kind of tool that breaks a block faster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolKind {
+    Pickaxe,
+    Shovel,
+    Axe,
+}
+
+/// Where a block rests on another one, breaking when that one is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Support {
+    /// On the top face of the block below, like torches and flowers.
+    Below,
+
+    /// On the face of the block behind, opposite the `facing` property, like ladders.
+    Behind,
+}
+
+/// How the `facing` property of a block is chosen when a player places it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Placement {
+    /// Towards the player, like the front of a furnace.
+    #[default]
+    Player,
+
+    /// The way the player looks, like stairs rising away from them.
+    Away,
+
+    /// Out of the face clicked, like ladders hanging on the wall they are placed against.
+    Face,
+}
+
+/// A block shape made of boxes, such as the solid part of a block state or what is outlined
+/// when aiming at it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CollisionShape {
+    /// Nothing to collide with, like air, flowers and fluids.
+    Empty,
+
+    /// The whole block.
+    #[default]
+    Full,
+
+    /// A union of boxes in block coordinates, each within the unit cube.
+    Boxes(Vec<Aabb>),
+}
+
+impl CollisionShape {
+    /// Returns the boxes making up the shape, in block coordinates.
+    pub fn boxes(&self) -> &[Aabb] {
+        match self {
+            Self::Empty => &[],
+            Self::Full => std::slice::from_ref(&Aabb::UNIT),
+            Self::Boxes(boxes) => boxes,
+        }
+    }
+
+    /// Returns whether the shape is the whole block.
+    pub fn is_full(&self) -> bool {
+        matches!(self, Self::Full)
+    }
+
+    /// Returns whether there is nothing to collide with.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    /// Returns whether a single box of the shape covers the whole face of the block pointing
+    /// in `direction`, so that something can rest on it.
+    pub fn covers(&self, direction: Direction) -> bool {
+        let axis = direction.axis();
+
+        self.boxes().iter().any(|b| {
+            let (min, max) = (b.min.to_array(), b.max.to_array());
+            let flush = match direction.is_positive() {
+                true => max[axis] >= 1.0,
+                false => min[axis] <= 0.0,
+            };
+
+            flush && (0..3).filter(|&a| a != axis).all(|a| min[a] <= 0.0 && max[a] >= 1.0)
+        })
+    }
+}
+
+/// Everything the engine needs to know about a single block state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockAttributes {
+    /// How much light is lost passing through the block, from 0 (transparent) to 15.
+    pub opacity: u8,
+
+    /// Light level emitted by the block, from 0 to 15.
+    pub light: u8,
+
+    /// Base time in seconds to break the block by hand, negative if unbreakable.
+    pub hardness: f32,
+
+    /// Shape bodies collide with.
+    pub collision: CollisionShape,
+
+    /// Shape hit and outlined when aiming at the block, empty if it cannot be targeted.
+    pub selection: CollisionShape,
+
+    /// Whether bodies touching the block can climb it, like ladders and vines.
+    pub climbable: bool,
+
+    /// Fluid the block is made of, if any.
+    pub fluid: Option<Fluid>,
+
+    /// Whether placing a block into this one replaces it, like air, fluids and short grass.
+    pub replaceable: bool,
+
+    /// Tool breaking the block faster, if any.
+    pub tool: Option<ToolKind>,
+
+    /// Whether the block is slow to break and drops nothing without its tool, like stone.
+    pub requires_tool: bool,
+
+    /// Names of the items dropped when the block is broken, once per item dropped.
+    pub drops: Vec<String>,
+
+    /// Block this one rests on, if it cannot stand on its own.
+    pub support: Option<Support>,
+
+    /// Whether the block falls when there is nothing below it, like sand and gravel.
+    pub falls: bool,
+
+    /// Whether the block ages on random ticks while lit, through its `age` property, like crops.
+    pub grows: bool,
+
+    /// Name of the block this one spreads onto while lit, and turns back into when covered,
+    /// like grass onto dirt.
+    pub spreads: Option<String>,
+
+    /// Whether the block breaks on random ticks when it is cut off from blocks that hold it,
+    /// like leaves, unless its `persistent` property is set.
+    pub decays: bool,
+
+    /// Whether the block keeps leaves around it from decaying, like logs.
+    pub holds_leaves: bool,
+
+    /// How the `facing` property is chosen when the block is placed.
+    pub placement: Placement,
+
+    /// Render pass the block is drawn in.
+    pub render_layer: RenderLayer,
+
+    /// Texture names of each face, indexed by [`Direction`].
+    pub textures: [String; 6],
+
+    /// Biome tint of each face, indexed by [`Direction`].
+    pub tints: [Tint; 6],
+}
+
+impl BlockAttributes {
+    /// Returns the texture name of the face pointing in `direction`.
+    pub fn texture(&self, direction: Direction) -> &str {
+        &self.textures[direction as usize]
+    }
+
+    /// Returns the biome tint of the face pointing in `direction`.
+    pub fn tint(&self, direction: Direction) -> Tint {
+        self.tints[direction as usize]
+    }
+
+    /// Returns whether the block is a full opaque cube, which hides the faces of its
+    /// neighbors and fully blocks light.
+    pub fn is_opaque_cube(&self) -> bool {
+        self.render_layer == RenderLayer::Opaque && self.collision.is_full()
+    }
+
+    /// Returns whether the block can never be broken.
+    pub fn is_unbreakable(&self) -> bool {
+        self.hardness < 0.0
+    }
+}
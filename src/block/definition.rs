@@ -0,0 +1,366 @@
+use super::{BlockAttributes, CollisionShape, Fluid, Placement, Property, RenderLayer, Support, Tint, ToolKind};
+use crate::math::{Aabb, Vec3};
+use crate::world::Direction;
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A block type as written in a `blocks/<namespace>.toml` data file, before registration.
+///
+/// Attributes set at the top level apply to every state; entries of `states` override them
+/// for the states whose properties match `when`, later entries winning.
+///
+/// # Example
+///
+/// ```toml
+/// [oak_log]
+/// hardness = 2.0
+/// properties = { axis = "axis" }
+/// default = { axis = "y" }
+/// textures = { end = "block/oak_log_top", side = "block/oak_log" }
+///
+/// [[oak_log.states]]
+/// when = { axis = "x" }
+/// textures = { west = "block/oak_log_top", east = "block/oak_log_top", up = "block/oak_log", down = "block/oak_log" }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BlockDefinition {
+    /// Properties of the block, by name. States are ordered by property name.
+    #[serde(default)]
+    pub properties: BTreeMap<String, PropertyDefinition>,
+
+    /// Property values of the default state. Unlisted properties use their first value.
+    #[serde(default)]
+    pub default: BTreeMap<String, toml::Value>,
+
+    /// Attribute overrides for specific states.
+    #[serde(default)]
+    pub states: Vec<StateOverride>,
+
+    /// Attributes shared by every state.
+    #[serde(flatten)]
+    pub attributes: AttributeDefinition,
+}
+
+/// The values a property can take, in one of several shorthands.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum PropertyDefinition {
+    /// An explicit list, such as `["lower", "upper"]` or `[1, 2, 3]`.
+    Values(Vec<toml::Value>),
+
+    /// An integer range, such as `{ min = 0, max = 15 }`.
+    Range { min: i32, max: i32 },
+
+    /// A named kind: `"boolean"`, `"axis"` (x, y, z), `"facing"` (all six directions) or
+    /// `"horizontal"` (the four horizontal directions).
+    Kind(String),
+}
+
+/// Attributes overridden for the states matching `when`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StateOverride {
+    /// Property values a state must have for the override to apply.
+    pub when: BTreeMap<String, toml::Value>,
+
+    #[serde(flatten)]
+    pub attributes: AttributeDefinition,
+}
+
+/// Block attributes as written in data files, where anything can be left out.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AttributeDefinition {
+    /// Light lost passing through, 0 to 15. Defaults to 15 for full opaque cubes, 0 otherwise.
+    pub opacity: Option<u8>,
+
+    /// Emitted light level, 0 to 15. Defaults to 0.
+    pub light: Option<u8>,
+
+    /// Time to break by hand in seconds, negative if unbreakable. Defaults to 1.
+    pub hardness: Option<f32>,
+
+    /// Collision shape. Defaults to a full block.
+    pub collision: Option<CollisionDefinition>,
+
+    /// Shape targeted by the cursor. Defaults to the collision shape.
+    pub selection: Option<CollisionDefinition>,
+
+    /// Whether bodies can climb the block. Defaults to false.
+    pub climbable: Option<bool>,
+
+    /// Fluid the block is made of. Defaults to none.
+    pub fluid: Option<Fluid>,
+
+    /// Whether placed blocks replace it. Defaults to false.
+    pub replaceable: Option<bool>,
+
+    /// Tool breaking it faster. Defaults to none.
+    pub tool: Option<ToolKind>,
+
+    /// Whether it drops nothing without its tool. Defaults to false.
+    pub requires_tool: Option<bool>,
+
+    /// Items dropped when broken. Defaults to the block itself.
+    pub drops: Option<Vec<String>>,
+
+    /// Block it rests on. Defaults to none.
+    pub support: Option<Support>,
+
+    /// Whether it falls when nothing is below. Defaults to false.
+    pub falls: Option<bool>,
+
+    /// Whether it ages on random ticks while lit. Defaults to false.
+    pub grows: Option<bool>,
+
+    /// Block it spreads onto and turns back into. Defaults to none.
+    pub spreads: Option<String>,
+
+    /// Whether it decays away from blocks holding it. Defaults to false.
+    pub decays: Option<bool>,
+
+    /// Whether it keeps leaves from decaying. Defaults to false.
+    pub holds_leaves: Option<bool>,
+
+    /// How its `facing` property is chosen when placed. Defaults to towards the player.
+    pub placement: Option<Placement>,
+
+    /// Render pass. Defaults to opaque.
+    pub render: Option<RenderLayer>,
+
+    /// Face textures. Faces left out use `block/<name>`.
+    pub textures: Option<TextureDefinition>,
+
+    /// Biome tint of the faces. Defaults to none.
+    pub tint: Option<TintDefinition>,
+}
+
+/// A collision or selection shape as written in data files.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CollisionDefinition {
+    /// `"full"` or `"empty"`.
+    Named(String),
+
+    /// Boxes as `[min_x, min_y, min_z, max_x, max_y, max_z]`, in sixteenths of a block.
+    Boxes(Vec<[f32; 6]>),
+}
+
+/// Face textures as written in data files.
+///
+/// Faces are resolved from the most specific key: the face itself (`north`, `up`...), then
+/// `top` and `bottom`, then `end` for both vertical faces or `side` for the horizontal ones,
+/// and finally `all`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TextureDefinition {
+    /// The same texture on every face.
+    All(String),
+
+    /// Textures per face or group of faces.
+    Faces(BTreeMap<String, String>),
+}
+
+/// Face tints as written in data files, with the same face keys as textures.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TintDefinition {
+    /// The same tint on every face.
+    All(Tint),
+
+    /// Tints per face or group of faces, such as `{ top = "grass" }`.
+    Faces(BTreeMap<String, Tint>),
+}
+
+impl PropertyDefinition {
+    /// Builds the property called `name`.
+    pub fn build(&self, name: &str) -> Result<Property, String> {
+        match self {
+            Self::Values(values) => {
+                let values = values.iter().map(property_value).collect::<Result<_, _>>();
+                Property::new(name, values.map_err(|e| format!("property {name}: {e}"))?)
+            }
+            Self::Range { min, max } => Property::range(name, *min, *max),
+            Self::Kind(kind) => match kind.as_str() {
+                "boolean" => Ok(Property::boolean(name)),
+                "axis" => Property::new(name, ["x", "y", "z"].map(String::from).to_vec()),
+                "facing" => Property::new(name, Direction::ALL.map(|d| d.name().to_string()).to_vec()),
+                "horizontal" => Property::new(name, Direction::HORIZONTAL.map(|d| d.name().to_string()).to_vec()),
+                _ => Err(format!("property {name}: unknown kind {kind}")),
+            },
+        }
+    }
+}
+
+impl CollisionDefinition {
+    fn build(&self) -> Result<CollisionShape, String> {
+        match self {
+            Self::Named(name) => match name.as_str() {
+                "full" => Ok(CollisionShape::Full),
+                "empty" => Ok(CollisionShape::Empty),
+                _ => Err(format!("unknown shape: {name}")),
+            },
+            Self::Boxes(boxes) if boxes.is_empty() => Ok(CollisionShape::Empty),
+            Self::Boxes(boxes) => boxes
+                .iter()
+                .map(|b| {
+                    let min = Vec3::new(b[0], b[1], b[2]) / 16.0;
+                    let max = Vec3::new(b[3], b[4], b[5]) / 16.0;
+                    let inside = |v: Vec3| (0.0..=1.0).contains(&v.x) && (0.0..=1.0).contains(&v.y) && (0.0..=1.0).contains(&v.z);
+
+                    if inside(min) && inside(max) && min.x < max.x && min.y < max.y && min.z < max.z {
+                        Ok(Aabb::new(min, max))
+                    } else {
+                        Err(format!("invalid box: {b:?}"))
+                    }
+                })
+                .collect::<Result<_, _>>()
+                .map(CollisionShape::Boxes),
+        }
+    }
+}
+
+impl TextureDefinition {
+    /// Writes the faces this definition specifies into `faces`, leaving the others untouched.
+    fn apply(&self, faces: &mut [Option<String>; 6]) -> Result<(), String> {
+        match self {
+            Self::All(texture) => {
+                faces.fill(Some(texture.clone()));
+                Ok(())
+            }
+            Self::Faces(keys) => apply_faces(keys, faces).map_err(|key| format!("unknown texture key: {key}")),
+        }
+    }
+}
+
+impl TintDefinition {
+    /// Writes the faces this definition specifies into `faces`, leaving the others untouched.
+    fn apply(&self, faces: &mut [Option<Tint>; 6]) -> Result<(), String> {
+        match self {
+            Self::All(tint) => {
+                faces.fill(Some(*tint));
+                Ok(())
+            }
+            Self::Faces(keys) => apply_faces(keys, faces).map_err(|key| format!("unknown tint key: {key}")),
+        }
+    }
+}
+
+/// Writes the value of each face found in `keys` into `faces`, from the most specific key.
+/// Returns the first unknown key as an error.
+fn apply_faces<T: Clone>(keys: &BTreeMap<String, T>, faces: &mut [Option<T>; 6]) -> Result<(), String> {
+    if let Some(key) = keys.keys().find(|key| !TEXTURE_KEYS.contains(&key.as_str())) {
+        return Err(key.clone());
+    }
+
+    for direction in Direction::ALL {
+        let group = match direction {
+            Direction::Up => ["up", "top", "end"],
+            Direction::Down => ["down", "bottom", "end"],
+            _ => [direction.name(), "side", "side"],
+        };
+
+        if let Some(value) = group.iter().chain(&["all"]).find_map(|key| keys.get(*key)) {
+            faces[direction as usize] = Some(value.clone());
+        }
+    }
+
+    Ok(())
+}
+
+const TEXTURE_KEYS: [&str; 11] = ["all", "top", "bottom", "side", "end", "up", "down", "north", "south", "west", "east"];
+
+/// Resolves attribute layers into the attributes of a state. Later layers take precedence.
+///
+/// Faces without a texture in any layer use `default_texture`, and the block drops
+/// `default_drop` unless a layer says otherwise.
+pub(super) fn resolve(layers: &[&AttributeDefinition], default_texture: &str, default_drop: &str) -> Result<BlockAttributes, String> {
+    let collision = match layers.iter().rev().find_map(|layer| layer.collision.as_ref()) {
+        Some(collision) => collision.build()?,
+        None => CollisionShape::Full,
+    };
+
+    let selection = match layers.iter().rev().find_map(|layer| layer.selection.as_ref()) {
+        Some(selection) => selection.build()?,
+        None => collision.clone(),
+    };
+
+    let render_layer = layers.iter().rev().find_map(|layer| layer.render).unwrap_or_default();
+    let full_opaque = render_layer == RenderLayer::Opaque && collision.is_full();
+    let opacity = layers.iter().rev().find_map(|layer| layer.opacity).unwrap_or(if full_opaque { 15 } else { 0 });
+    let light = layers.iter().rev().find_map(|layer| layer.light).unwrap_or(0);
+    let hardness = layers.iter().rev().find_map(|layer| layer.hardness).unwrap_or(1.0);
+    let climbable = layers.iter().rev().find_map(|layer| layer.climbable).unwrap_or(false);
+    let fluid = layers.iter().rev().find_map(|layer| layer.fluid);
+    let replaceable = layers.iter().rev().find_map(|layer| layer.replaceable).unwrap_or(false);
+    let tool = layers.iter().rev().find_map(|layer| layer.tool);
+    let requires_tool = layers.iter().rev().find_map(|layer| layer.requires_tool).unwrap_or(false);
+    let support = layers.iter().rev().find_map(|layer| layer.support);
+    let falls = layers.iter().rev().find_map(|layer| layer.falls).unwrap_or(false);
+    let grows = layers.iter().rev().find_map(|layer| layer.grows).unwrap_or(false);
+    let spreads = layers.iter().rev().find_map(|layer| layer.spreads.clone());
+    let decays = layers.iter().rev().find_map(|layer| layer.decays).unwrap_or(false);
+    let holds_leaves = layers.iter().rev().find_map(|layer| layer.holds_leaves).unwrap_or(false);
+    let placement = layers.iter().rev().find_map(|layer| layer.placement).unwrap_or_default();
+
+    let drops = match layers.iter().rev().find_map(|layer| layer.drops.as_ref()) {
+        Some(drops) => drops.clone(),
+        None => vec![default_drop.to_string()],
+    };
+
+    if opacity > 15 || light > 15 {
+        return Err(String::from("opacity and light must be between 0 and 15"));
+    }
+
+    let mut faces: [Option<String>; 6] = Default::default();
+
+    for layer in layers {
+        if let Some(textures) = &layer.textures {
+            textures.apply(&mut faces)?;
+        }
+    }
+
+    let textures = faces.map(|face| face.unwrap_or_else(|| default_texture.to_string()));
+    let mut tints = [None; 6];
+
+    for layer in layers {
+        if let Some(tint) = &layer.tint {
+            tint.apply(&mut tints)?;
+        }
+    }
+
+    let tints = tints.map(Option::unwrap_or_default);
+
+    Ok(BlockAttributes {
+        opacity,
+        light,
+        hardness,
+        collision,
+        selection,
+        climbable,
+        fluid,
+        replaceable,
+        tool,
+        requires_tool,
+        drops,
+        support,
+        falls,
+        grows,
+        spreads,
+        decays,
+        holds_leaves,
+        placement,
+        render_layer,
+        textures,
+        tints,
+    })
+}
+
+/// Converts a property value written as a string, boolean or integer to its string form.
+pub(super) fn property_value(value: &toml::Value) -> Result<String, String> {
+    match value {
+        toml::Value::String(s) => Ok(s.clone()),
+        toml::Value::Boolean(b) => Ok(b.to_string()),
+        toml::Value::Integer(i) => Ok(i.to_string()),
+        other => Err(format!("invalid property value: {other}")),
+    }
+}
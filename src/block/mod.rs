@@ -0,0 +1,9 @@
+mod property;
+mod attributes;
+mod definition;
+mod registry;
+
+pub use property::Property;
+pub use attributes::{BlockAttributes, CollisionShape, Fluid, Placement, RenderLayer, Support, Tint, ToolKind};
+pub use definition::{BlockDefinition, PropertyDefinition, StateOverride, AttributeDefinition, CollisionDefinition, TextureDefinition, TintDefinition};
+pub use registry::{Block, BlockId, BlockRegistry, BlockRegistryBuilder, DEFAULT_NAMESPACE, AIR};
@@ -0,0 +1,53 @@
+/// A named property of a block with a fixed list of values, such as `facing` or `waterlogged`.
+///
+/// Every combination of property values is a distinct block state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Property {
+    name: String,
+    values: Vec<String>,
+}
+
+impl Property {
+    /// Creates a property from its name and possible values.
+    ///
+    /// # Returns
+    /// * `Err(String)` if there are no values, duplicate values or more than 256 of them.
+    pub fn new(name: impl Into<String>, values: Vec<String>) -> Result<Self, String> {
+        let name = name.into();
+
+        if values.is_empty() || values.len() > 256 {
+            return Err(format!("property {name} must have between 1 and 256 values"));
+        }
+
+        if let Some(value) = values.iter().enumerate().find_map(|(i, v)| values[..i].contains(v).then_some(v)) {
+            return Err(format!("property {name} lists {value} twice"));
+        }
+
+        Ok(Self { name, values })
+    }
+
+    /// Creates a `false`/`true` property.
+    pub fn boolean(name: impl Into<String>) -> Self {
+        Self { name: name.into(), values: vec![String::from("false"), String::from("true")] }
+    }
+
+    /// Creates a property taking every integer from `min` to `max` inclusive.
+    pub fn range(name: impl Into<String>, min: i32, max: i32) -> Result<Self, String> {
+        Self::new(name, (min..=max).map(|value| value.to_string()).collect())
+    }
+
+    /// Returns the name of the property.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the possible values, in declaration order.
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    /// Returns the position of `value` in [`values`](Self::values).
+    pub fn index_of(&self, value: &str) -> Option<usize> {
+        self.values.iter().position(|v| v == value)
+    }
+}
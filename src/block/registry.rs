@@ -0,0 +1,450 @@
+use super::definition::{self, AttributeDefinition, BlockDefinition};
+use super::{BlockAttributes, CollisionDefinition, Property, RenderLayer};
+use crate::resource::ResourceManager;
+use crate::world::BlockState;
+
+use std::collections::HashMap;
+
+/// Namespace assumed for block names written without one, and used by the built-in blocks.
+pub const DEFAULT_NAMESPACE: &str = "rustedcraft";
+
+/// Name of the air block, always registered first so that its only state is [`BlockState::AIR`].
+pub const AIR: &str = "rustedcraft:air";
+
+/// Directory of `assets/` holding block data files, one per namespace.
+const BLOCKS_DIR: &str = "blocks/";
+
+const BLOCK_KEYS: [&str; 24] = [
+    "properties", "default", "states", "opacity", "light", "hardness", "collision", "selection", "climbable", "fluid", "replaceable", "tool",
+    "requires_tool", "drops", "support", "falls", "grows", "spreads", "decays", "holds_leaves", "placement", "render", "textures", "tint",
+];
+const STATE_KEYS: [&str; 22] = [
+    "when", "opacity", "light", "hardness", "collision", "selection", "climbable", "fluid", "replaceable", "tool", "requires_tool", "drops",
+    "support", "falls", "grows", "spreads", "decays", "holds_leaves", "placement", "render", "textures", "tint",
+];
+
+/// Index of a block type in a [`BlockRegistry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockId(u16);
+
+impl BlockId {
+    /// Returns the numeric index of the block type.
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A registered block type, such as `rustedcraft:oak_log`.
+#[derive(Debug, Clone)]
+pub struct Block {
+    id: BlockId,
+    name: String,
+    properties: Vec<Property>,
+    first_state: u16,
+    state_count: usize,
+    default_state: BlockState,
+}
+
+impl Block {
+    /// Returns the index of the block type in its registry.
+    pub fn id(&self) -> BlockId {
+        self.id
+    }
+
+    /// Returns the namespaced name, such as `rustedcraft:stone`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the properties of the block, ordered by name.
+    pub fn properties(&self) -> &[Property] {
+        &self.properties
+    }
+
+    /// Returns the property called `name`, if the block has one.
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|property| property.name() == name)
+    }
+
+    /// Returns the state the block is placed or generated in by default.
+    pub fn default_state(&self) -> BlockState {
+        self.default_state
+    }
+
+    /// Iterates over every state of the block.
+    pub fn states(&self) -> impl Iterator<Item = BlockState> {
+        let first = self.first_state as usize;
+        (first..first + self.state_count).map(|id| BlockState::new(id as u16))
+    }
+
+    /// Returns whether `state` is one of the states of this block.
+    pub fn contains(&self, state: BlockState) -> bool {
+        (self.first_state as usize..self.first_state as usize + self.state_count).contains(&(state.id() as usize))
+    }
+
+    /// Returns the state with the given value index for every property.
+    fn state_of(&self, values: &[u8]) -> BlockState {
+        let offset = self.properties.iter().zip(values).fold(0, |acc, (property, &value)| {
+            acc * property.values().len() + value as usize
+        });
+
+        BlockState::new(self.first_state + offset as u16)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct StateEntry {
+    block: BlockId,
+    values: Box<[u8]>,
+    attributes: BlockAttributes,
+}
+
+/// Every block type and block state known to the game, frozen once built.
+///
+/// States get dense numeric identifiers, which is what chunks store. Identifiers follow the
+/// registration order, so they can change when blocks are added: anything written to disk
+/// should refer to states by name, see [`BlockRegistry::state_name`].
+#[derive(Debug, Clone)]
+pub struct BlockRegistry {
+    blocks: Vec<Block>,
+    states: Vec<StateEntry>,
+    names: HashMap<String, BlockId>,
+}
+
+impl BlockRegistry {
+    /// Loads every block from the data files of `resources`, see
+    /// [`BlockRegistryBuilder::load`].
+    pub fn load(resources: &ResourceManager) -> Result<Self, String> {
+        let mut builder = BlockRegistryBuilder::new();
+        builder.load(resources)?;
+        builder.build()
+    }
+
+    /// Returns every block type, in identifier order.
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    /// Returns the number of block states.
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns the block type with the given identifier.
+    pub fn block(&self, id: BlockId) -> &Block {
+        &self.blocks[id.index()]
+    }
+
+    /// Returns the block type called `name`. The namespace may be left out for built-in blocks.
+    pub fn get(&self, name: &str) -> Option<&Block> {
+        let id = match name.contains(':') {
+            true => self.names.get(name),
+            false => self.names.get(&format!("{DEFAULT_NAMESPACE}:{name}")),
+        };
+
+        id.map(|id| self.block(*id))
+    }
+
+    /// Returns the default state of the block called `name`.
+    pub fn default_state(&self, name: &str) -> Option<BlockState> {
+        self.get(name).map(Block::default_state)
+    }
+
+    /// Returns the block type a state belongs to.
+    ///
+    /// # Panics
+    /// Panics if `state` was not assigned by this registry.
+    pub fn block_of(&self, state: BlockState) -> &Block {
+        self.block(self.states[state.id() as usize].block)
+    }
+
+    /// Returns the attributes of a state.
+    ///
+    /// # Panics
+    /// Panics if `state` was not assigned by this registry.
+    #[inline]
+    pub fn attributes(&self, state: BlockState) -> &BlockAttributes {
+        &self.states[state.id() as usize].attributes
+    }
+
+    /// Returns the value of `property` in `state`, if its block has that property.
+    pub fn value(&self, state: BlockState, property: &str) -> Option<&str> {
+        let entry = &self.states[state.id() as usize];
+        let block = self.block(entry.block);
+        let index = block.properties.iter().position(|p| p.name() == property)?;
+
+        Some(&block.properties[index].values()[entry.values[index] as usize])
+    }
+
+    /// Returns `state` with `property` set to `value`, if its block has that property and
+    /// value.
+    pub fn with_value(&self, state: BlockState, property: &str, value: &str) -> Option<BlockState> {
+        let entry = &self.states[state.id() as usize];
+        let block = self.block(entry.block);
+        let index = block.properties.iter().position(|p| p.name() == property)?;
+        let mut values = entry.values.clone();
+
+        values[index] = block.properties[index].index_of(value)? as u8;
+        Some(block.state_of(&values))
+    }
+
+    /// Returns the full name of a state, such as `rustedcraft:oak_log[axis=x]`.
+    pub fn state_name(&self, state: BlockState) -> String {
+        let entry = &self.states[state.id() as usize];
+        let block = self.block(entry.block);
+
+        if block.properties.is_empty() {
+            return block.name.clone();
+        }
+
+        let values: Vec<String> = block
+            .properties
+            .iter()
+            .zip(entry.values.iter())
+            .map(|(property, &value)| format!("{}={}", property.name(), property.values()[value as usize]))
+            .collect();
+
+        format!("{}[{}]", block.name, values.join(","))
+    }
+
+    /// Parses a state name such as `rustedcraft:oak_log[axis=x]` or `stone`.
+    ///
+    /// Properties left out keep the values of the block's default state.
+    pub fn parse_state(&self, name: &str) -> Result<BlockState, String> {
+        let (block_name, properties) = match name.split_once('[') {
+            Some((block, rest)) => {
+                let properties = rest.strip_suffix(']').ok_or_else(|| format!("missing ] in {name}"))?;
+                (block, properties)
+            }
+            None => (name, ""),
+        };
+
+        let block = self.get(block_name).ok_or_else(|| format!("unknown block: {block_name}"))?;
+        let mut state = block.default_state;
+
+        for pair in properties.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (property, value) = pair.split_once('=').ok_or_else(|| format!("invalid property in {name}: {pair}"))?;
+            state = self
+                .with_value(state, property.trim(), value.trim())
+                .ok_or_else(|| format!("{} has no {pair}", block.name))?;
+        }
+
+        Ok(state)
+    }
+
+    /// Iterates over every block state.
+    pub fn states(&self) -> impl Iterator<Item = BlockState> {
+        (0..self.states.len()).map(|id| BlockState::new(id as u16))
+    }
+}
+
+/// Collects block definitions at startup, then freezes them into a [`BlockRegistry`].
+#[derive(Debug, Clone)]
+pub struct BlockRegistryBuilder {
+    definitions: Vec<(String, BlockDefinition)>,
+}
+
+impl BlockRegistryBuilder {
+    /// Creates a builder holding only air.
+    pub fn new() -> Self {
+        let air = BlockDefinition {
+            attributes: AttributeDefinition {
+                opacity: Some(0),
+                hardness: Some(0.0),
+                collision: Some(CollisionDefinition::Named(String::from("empty"))),
+                replaceable: Some(true),
+                drops: Some(Vec::new()),
+                render: Some(RenderLayer::Invisible),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        Self { definitions: vec![(String::from(AIR), air)] }
+    }
+
+    /// Adds a block type.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the name is not of the form `namespace:path` or is already taken.
+    pub fn register(&mut self, name: &str, definition: BlockDefinition) -> Result<(), String> {
+        validate_name(name)?;
+
+        if self.definitions.iter().any(|(existing, _)| existing == name) {
+            return Err(format!("block {name} is registered twice"));
+        }
+
+        self.definitions.push((name.to_string(), definition));
+        Ok(())
+    }
+
+    /// Registers every block of a data file whose blocks belong to `namespace`.
+    ///
+    /// Each top-level table of the file is a block named after its key, see
+    /// [`BlockDefinition`] for the format.
+    pub fn load_str(&mut self, namespace: &str, source: &str) -> Result<(), String> {
+        let table: toml::Table = toml::from_str(source).map_err(|e| e.to_string())?;
+
+        for (path, value) in table {
+            let name = format!("{namespace}:{path}");
+            check_keys(&value, &BLOCK_KEYS).map_err(|e| format!("{name}: {e}"))?;
+
+            if let Some(states) = value.get("states").and_then(toml::Value::as_array) {
+                for state in states {
+                    check_keys(state, &STATE_KEYS).map_err(|e| format!("{name}: {e}"))?;
+                }
+            }
+
+            let definition = value.try_into().map_err(|e| format!("{name}: {e}"))?;
+            self.register(&name, definition)?;
+        }
+
+        Ok(())
+    }
+
+    /// Registers the blocks of every `blocks/<namespace>.toml` file provided by `resources`,
+    /// in file name order.
+    pub fn load(&mut self, resources: &ResourceManager) -> Result<(), String> {
+        for path in resources.list(BLOCKS_DIR) {
+            let Some(namespace) = path.strip_prefix(BLOCKS_DIR).and_then(|file| file.strip_suffix(".toml")) else {
+                continue;
+            };
+
+            if namespace.contains('/') {
+                continue;
+            }
+
+            let source = resources.read_to_string(&path)?;
+            self.load_str(namespace, &source).map_err(|e| format!("{path}: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Assigns state identifiers and resolves the attributes of every state.
+    ///
+    /// # Returns
+    /// * `Err(String)` if a definition is invalid or there are more than 65536 states.
+    pub fn build(self) -> Result<BlockRegistry, String> {
+        let mut registry = BlockRegistry { blocks: Vec::new(), states: Vec::new(), names: HashMap::new() };
+
+        for (index, (name, definition)) in self.definitions.iter().enumerate() {
+            let block = build_block(&mut registry, BlockId(index as u16), name, definition)
+                .map_err(|e| format!("{name}: {e}"))?;
+
+            registry.names.insert(name.clone(), block.id);
+            registry.blocks.push(block);
+        }
+
+        Ok(registry)
+    }
+}
+
+impl Default for BlockRegistryBuilder {
+    fn default() -> Self { Self::new() }
+}
+
+/// Builds one block type, appending its states to the registry.
+fn build_block(registry: &mut BlockRegistry, id: BlockId, name: &str, definition: &BlockDefinition) -> Result<Block, String> {
+    let properties: Vec<Property> = definition
+        .properties
+        .iter()
+        .map(|(name, property)| property.build(name))
+        .collect::<Result<_, _>>()?;
+
+    let state_count = properties.iter().try_fold(1usize, |acc, p| acc.checked_mul(p.values().len()));
+    let first_state = registry.states.len();
+
+    let state_count = match state_count {
+        Some(count) if first_state + count <= u16::MAX as usize + 1 => count,
+        _ => return Err(String::from("too many block states, at most 65536 are supported")),
+    };
+
+    let lookup = |values: &std::collections::BTreeMap<String, toml::Value>| -> Result<Vec<(usize, u8)>, String> {
+        values
+            .iter()
+            .map(|(property, value)| {
+                let index = properties.iter().position(|p| p.name() == property);
+                let index = index.ok_or_else(|| format!("unknown property {property}"))?;
+                let value = definition::property_value(value)?;
+                let value = properties[index].index_of(&value).ok_or_else(|| format!("{property} cannot be {value}"))?;
+                Ok((index, value as u8))
+            })
+            .collect()
+    };
+
+    let mut default_values = vec![0u8; properties.len()];
+
+    for (index, value) in lookup(&definition.default)? {
+        default_values[index] = value;
+    }
+
+    let overrides: Vec<(Vec<(usize, u8)>, &AttributeDefinition)> = definition
+        .states
+        .iter()
+        .map(|state| Ok((lookup(&state.when)?, &state.attributes)))
+        .collect::<Result<_, String>>()?;
+
+    let path = name.split_once(':').map_or(name, |(_, path)| path);
+    let default_texture = format!("block/{path}");
+    let mut values = vec![0u8; properties.len()];
+
+    for _ in 0..state_count {
+        let mut layers = vec![&definition.attributes];
+        layers.extend(
+            overrides
+                .iter()
+                .filter(|(when, _)| when.iter().all(|&(index, value)| values[index] == value))
+                .map(|(_, attributes)| *attributes),
+        );
+
+        let attributes = definition::resolve(&layers, &default_texture, name)?;
+        registry.states.push(StateEntry { block: id, values: values.clone().into_boxed_slice(), attributes });
+
+        // Advance to the next combination, the last property changing fastest.
+        for (value, property) in values.iter_mut().zip(&properties).rev() {
+            *value += 1;
+
+            if (*value as usize) < property.values().len() {
+                break;
+            }
+
+            *value = 0;
+        }
+    }
+
+    let mut block = Block {
+        id,
+        name: name.to_string(),
+        properties,
+        first_state: first_state as u16,
+        state_count,
+        default_state: BlockState::AIR,
+    };
+
+    block.default_state = block.state_of(&default_values);
+    Ok(block)
+}
+
+/// Rejects names that are not of the form `namespace:path` with lowercase ASCII letters,
+/// digits, `_`, `-`, `.` and, in the path, `/`.
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = |part: &str, extra: &[char]| {
+        !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-.".contains(c) || extra.contains(&c))
+    };
+
+    match name.split_once(':') {
+        Some((namespace, path)) if valid(namespace, &[]) && valid(path, &['/']) => Ok(()),
+        _ => Err(format!("invalid block name: {name}")),
+    }
+}
+
+/// Rejects tables with keys outside of `allowed`, to catch typos in data files.
+fn check_keys(value: &toml::Value, allowed: &[&str]) -> Result<(), String> {
+    let table = value.as_table().ok_or("expected a table")?;
+
+    match table.keys().find(|key| !allowed.contains(&key.as_str())) {
+        Some(key) => Err(format!("unknown key {key}")),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,22 @@
+use glfw::{CursorMode, Window};
+
+/// Captures or releases the mouse cursor for mouse look.
+///
+/// While captured the cursor is hidden and locked to the window, so it can move indefinitely
+/// without leaving it. Raw mouse motion is used when the platform supports it, which avoids
+/// the acceleration applied to the desktop cursor.
+pub fn set_cursor_captured(window: &mut Window, captured: bool) {
+    match captured {
+        true => window.set_cursor_mode(CursorMode::Disabled),
+        false => window.set_cursor_mode(CursorMode::Normal),
+    }
+
+    if window.glfw.supports_raw_motion() {
+        window.set_raw_mouse_motion(captured);
+    }
+}
+
+/// Returns whether the mouse cursor is currently captured by the window.
+pub fn is_cursor_captured(window: &Window) -> bool {
+    window.get_cursor_mode() == CursorMode::Disabled
+}
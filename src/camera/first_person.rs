@@ -0,0 +1,86 @@
+use super::Projection;
+use crate::math::{Mat4, Vec3};
+
+/// Pitch is kept just short of straight up or down so the view direction never becomes
+/// parallel to the up vector.
+const MAX_PITCH: f32 = 89.9;
+
+/// A first-person camera described by a position and yaw/pitch angles.
+///
+/// The world is right-handed with Y pointing up. A yaw of zero looks towards negative Z and
+/// increases when turning right; a positive pitch looks up. Angles are in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// Position of the eye in world space.
+    pub position: Vec3,
+
+    /// Horizontal angle in degrees, wrapped to `[0, 360)`.
+    yaw: f32,
+
+    /// Vertical angle in degrees, clamped to about `[-90, 90]`.
+    pitch: f32,
+
+    /// Projection parameters.
+    pub projection: Projection,
+}
+
+impl Camera {
+    /// Creates a camera at `position` looking towards negative Z.
+    pub fn new(position: Vec3, projection: Projection) -> Self {
+        Self { position, yaw: 0.0, pitch: 0.0, projection }
+    }
+
+    /// Returns the horizontal angle in degrees.
+    pub fn yaw(&self) -> f32 {
+        self.yaw
+    }
+
+    /// Returns the vertical angle in degrees.
+    pub fn pitch(&self) -> f32 {
+        self.pitch
+    }
+
+    /// Sets the view angles in degrees, wrapping yaw and clamping pitch.
+    pub fn set_rotation(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw.rem_euclid(360.0);
+        self.pitch = pitch.clamp(-MAX_PITCH, MAX_PITCH);
+    }
+
+    /// Turns the camera by the given angles in degrees.
+    pub fn rotate(&mut self, yaw: f32, pitch: f32) {
+        self.set_rotation(self.yaw + yaw, self.pitch + pitch);
+    }
+
+    /// Returns the unit vector the camera is looking along.
+    pub fn forward(&self) -> Vec3 {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        Vec3::new(yaw.sin() * pitch.cos(), pitch.sin(), -yaw.cos() * pitch.cos())
+    }
+
+    /// Returns the unit vector pointing to the right of the camera, parallel to the ground.
+    pub fn right(&self) -> Vec3 {
+        let yaw = self.yaw.to_radians();
+        Vec3::new(yaw.cos(), 0.0, yaw.sin())
+    }
+
+    /// Returns the unit vector the camera would walk along, ignoring pitch.
+    pub fn horizontal_forward(&self) -> Vec3 {
+        let yaw = self.yaw.to_radians();
+        Vec3::new(yaw.sin(), 0.0, -yaw.cos())
+    }
+
+    /// Returns the matrix transforming world space to view space.
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_to(self.position, self.forward(), Vec3::Y)
+    }
+
+    /// Returns the projection matrix.
+    pub fn projection_matrix(&self) -> Mat4 {
+        self.projection.matrix()
+    }
+
+    /// Returns the matrix transforming world space to clip space.
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection_matrix() * self.view_matrix()
+    }
+}
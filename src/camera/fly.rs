@@ -0,0 +1,84 @@
+use super::{Camera, CameraSettings};
+
+use crate::input::{action, axis, InputMap};
+use crate::math::Vec3;
+
+/// Moves a camera freely through the world, ignoring collisions, like a spectator.
+///
+/// Movement is simulated once per tick while looking around happens every frame, so the
+/// controller keeps the positions of the last two ticks and places the camera in between
+/// them using the game loop's interpolation factor.
+#[derive(Debug, Clone, Copy)]
+pub struct FlyController {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl FlyController {
+    /// Creates a controller starting at `position`.
+    pub fn new(position: Vec3) -> Self {
+        Self { previous: position, current: position }
+    }
+
+    /// Returns the simulated position after the last tick.
+    pub fn position(&self) -> Vec3 {
+        self.current
+    }
+
+    /// Moves to `position` instantly, without interpolating from the previous position.
+    pub fn teleport(&mut self, position: Vec3) {
+        self.previous = position;
+        self.current = position;
+    }
+
+    /// Advances the simulation by one tick of `seconds` seconds.
+    ///
+    /// Walking axes move along the ground plane, jump and sneak move straight up and down,
+    /// and sprinting doubles the speed.
+    pub fn tick(&mut self, camera: &Camera, input: &InputMap, settings: &CameraSettings, seconds: f32) {
+        self.previous = self.current;
+
+        let vertical = input.held(action::JUMP) as i32 - input.held(action::SNEAK) as i32;
+
+        let direction = camera.right() * input.axis(axis::MOVE_X)
+            + camera.horizontal_forward() * input.axis(axis::MOVE_Z)
+            + Vec3::Y * vertical as f32;
+
+        let speed = match input.held(action::SPRINT) {
+            true => settings.fly_speed * 2.0,
+            false => settings.fly_speed,
+        };
+
+        // Only shrink the direction, so analog sticks can still move slowly.
+        let direction = match direction.length() > 1.0 {
+            true => direction.normalize_or_zero(),
+            false => direction,
+        };
+
+        self.current += direction * speed * seconds;
+    }
+
+    /// Turns the camera from mouse and stick input. Call once per frame.
+    ///
+    /// # Arguments
+    /// * `seconds` - Time elapsed since the previous frame, used to scale stick input.
+    pub fn look(&self, camera: &mut Camera, input: &mut InputMap, settings: &CameraSettings, seconds: f32) {
+        look(camera, input, settings, seconds);
+    }
+
+    /// Places the camera between the last two ticks. `alpha` comes from the game loop.
+    pub fn apply(&self, camera: &mut Camera, alpha: f32) {
+        camera.position = self.previous.lerp(self.current, alpha);
+    }
+}
+
+/// Turns `camera` from the mouse motion of the frame and the look stick.
+pub(super) fn look(camera: &mut Camera, input: &mut InputMap, settings: &CameraSettings, seconds: f32) {
+    let (dx, dy) = input.take_frame_motion();
+    let (yaw, pitch) = settings.mouse_look(dx, dy);
+    camera.rotate(yaw, pitch);
+
+    let (x, y) = (input.axis(axis::LOOK_X), input.axis(axis::LOOK_Y));
+    let (yaw, pitch) = settings.stick_look(x, y, seconds);
+    camera.rotate(yaw, pitch);
+}
@@ -0,0 +1,15 @@
+mod first_person;
+mod projection;
+mod uniforms;
+mod settings;
+mod cursor;
+mod fly;
+mod walk;
+
+pub use cursor::{set_cursor_captured, is_cursor_captured};
+pub use uniforms::CameraUniforms;
+pub use settings::CameraSettings;
+pub use first_person::Camera;
+pub use projection::Projection;
+pub use fly::FlyController;
+pub use walk::WalkController;
@@ -0,0 +1,75 @@
+use crate::math::Mat4;
+
+/// Perspective projection parameters of a camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Projection {
+    /// Vertical field of view, in degrees.
+    pub fov: f32,
+
+    /// Width divided by height of the viewport.
+    pub aspect: f32,
+
+    /// Distance to the near clipping plane.
+    pub near: f32,
+
+    /// Distance to the far clipping plane. Ignored with reverse-Z, whose far plane is at infinity.
+    pub far: f32,
+
+    /// Whether to use a reverse-Z projection, see [`Mat4::perspective_reverse_z`].
+    pub reverse_z: bool,
+}
+
+impl Projection {
+    /// Creates a projection with the given vertical field of view in degrees and aspect ratio.
+    pub fn new(fov: f32, aspect: f32) -> Self {
+        Self { fov, aspect, near: 0.05, far: 1024.0, reverse_z: false }
+    }
+
+    /// Updates the aspect ratio from a viewport size in pixels. Empty viewports are ignored.
+    pub fn set_viewport(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.aspect = width as f32 / height as f32;
+        }
+    }
+
+    /// Returns the projection matrix.
+    pub fn matrix(&self) -> Mat4 {
+        let fov = self.fov.to_radians();
+
+        match self.reverse_z {
+            true => Mat4::perspective_reverse_z(fov, self.aspect, self.near),
+            false => Mat4::perspective(fov, self.aspect, self.near, self.far),
+        }
+    }
+
+    /// Configures the OpenGL depth test for this projection.
+    ///
+    /// Reverse-Z needs a `[0, 1]` clip depth range (`glClipControl`, OpenGL 4.5), a depth buffer
+    /// cleared to `0` and a `GREATER` depth function; the standard projection uses the defaults.
+    ///
+    /// # Returns
+    /// * `true` if the requested depth mode is active.
+    /// * `false` if reverse-Z is not supported by the context, in which case it is turned off.
+    pub fn apply_depth_state(&mut self) -> bool {
+        let supported = !self.reverse_z || gl::ClipControl::is_loaded();
+        self.reverse_z &= supported;
+
+        let (depth_range, clear_depth, depth_func) = match self.reverse_z {
+            true => (gl::ZERO_TO_ONE, 0.0, gl::GREATER),
+            false => (gl::NEGATIVE_ONE_TO_ONE, 1.0, gl::LESS),
+        };
+
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+
+            if gl::ClipControl::is_loaded() {
+                gl::ClipControl(gl::LOWER_LEFT, depth_range);
+            }
+
+            gl::ClearDepth(clear_depth);
+            gl::DepthFunc(depth_func);
+        }
+
+        supported
+    }
+}
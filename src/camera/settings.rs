@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// User preferences for the camera and mouse look.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    /// Vertical field of view, in degrees.
+    pub fov: f32,
+
+    /// Degrees turned per pixel of mouse motion.
+    pub sensitivity: f32,
+
+    /// Degrees turned per second with a gamepad stick fully pushed.
+    pub stick_speed: f32,
+
+    /// Whether moving the mouse up looks down.
+    pub invert_y: bool,
+
+    /// Whether to use a reverse-Z depth buffer, if the OpenGL context supports it.
+    pub reverse_z: bool,
+
+    /// Speed of the free-fly spectator camera, in blocks per second.
+    pub fly_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            fov: 70.0,
+            sensitivity: 0.15,
+            stick_speed: 180.0,
+            invert_y: false,
+            reverse_z: false,
+            fly_speed: 10.0,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Converts a mouse motion in pixels to yaw and pitch deltas in degrees.
+    pub fn mouse_look(&self, dx: f32, dy: f32) -> (f32, f32) {
+        let invert = if self.invert_y { -1.0 } else { 1.0 };
+        (dx * self.sensitivity, -dy * self.sensitivity * invert)
+    }
+
+    /// Converts gamepad stick deflection held for `seconds` to yaw and pitch deltas in degrees.
+    pub fn stick_look(&self, x: f32, y: f32, seconds: f32) -> (f32, f32) {
+        let invert = if self.invert_y { -1.0 } else { 1.0 };
+        let speed = self.stick_speed * seconds;
+        (x * speed, -y * speed * invert)
+    }
+}
@@ -0,0 +1,85 @@
+use super::Camera;
+
+use crate::opengl::{BufferObject, BufferTarget, BufferUsage, Program};
+use crate::math::Mat4;
+
+/// Data of the `Camera` uniform block, laid out with the std140 rules.
+///
+/// Shaders declare it as:
+///
+/// ```glsl
+/// layout (std140) uniform Camera {
+///     mat4 view;
+///     mat4 projection;
+///     mat4 viewProjection;
+///     vec4 cameraPosition;
+/// };
+/// ```
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct CameraBlock {
+    view: Mat4,
+    projection: Mat4,
+    view_projection: Mat4,
+    position: [f32; 4],
+}
+
+/// A uniform buffer holding the camera matrices, shared by every shader.
+///
+/// The buffer stays bound to [`CameraUniforms::BINDING`]; programs only need to be connected
+/// to it once with [`CameraUniforms::attach`], and then see every update.
+pub struct CameraUniforms {
+    buffer: BufferObject,
+}
+
+impl CameraUniforms {
+    /// Name of the uniform block in shaders.
+    pub const BLOCK_NAME: &'static str = "Camera";
+
+    /// Uniform buffer binding point reserved for the camera block.
+    pub const BINDING: u32 = 0;
+
+    /// Creates the uniform buffer and binds it to [`CameraUniforms::BINDING`].
+    pub fn new() -> Self {
+        let buffer = BufferObject::new(BufferTarget::UniformBuffer, BufferUsage::DynamicDraw);
+        let block = CameraBlock {
+            view: Mat4::IDENTITY,
+            projection: Mat4::IDENTITY,
+            view_projection: Mat4::IDENTITY,
+            position: [0.0; 4],
+        };
+
+        buffer.data(std::slice::from_ref(&block));
+        buffer.bind_base(Self::BINDING);
+
+        Self { buffer }
+    }
+
+    /// Connects the `Camera` block of `program` to the shared buffer.
+    ///
+    /// # Returns
+    /// `false` if the program does not use the camera block.
+    pub fn attach(&self, program: &Program) -> bool {
+        program.bind_uniform_block(Self::BLOCK_NAME, Self::BINDING)
+    }
+
+    /// Uploads the matrices of `camera`. Call once per frame before drawing.
+    pub fn update(&self, camera: &Camera) {
+        let view = camera.view_matrix();
+        let projection = camera.projection_matrix();
+        let position = camera.position;
+
+        let block = CameraBlock {
+            view,
+            projection,
+            view_projection: projection * view,
+            position: [position.x, position.y, position.z, 1.0],
+        };
+
+        self.buffer.sub_data(0, std::slice::from_ref(&block));
+    }
+}
+
+impl Default for CameraUniforms {
+    fn default() -> Self { Self::new() }
+}
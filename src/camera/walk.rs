@@ -0,0 +1,75 @@
+use super::{Camera, CameraSettings};
+
+use crate::input::{action, axis, InputMap};
+use crate::math::Vec3;
+use crate::physics::{BlockAccess, Body, MoveInput, PhysicsTable, PLAYER_EYE_HEIGHT, PLAYER_SNEAK_EYE_HEIGHT};
+
+/// Moves a camera as the eyes of a player walking through the world, colliding with blocks,
+/// falling, swimming and climbing ladders.
+///
+/// Like [`FlyController`](super::FlyController), the body moves once per tick and the camera
+/// is placed between the eye positions of the last two ticks.
+#[derive(Debug, Clone, Copy)]
+pub struct WalkController {
+    previous: Vec3,
+    body: Body,
+    sneaking: bool,
+}
+
+impl WalkController {
+    /// Creates a controller for a player standing at `feet`.
+    pub fn new(feet: Vec3) -> Self {
+        let body = Body::player(feet);
+        Self { previous: feet + Vec3::Y * PLAYER_EYE_HEIGHT, body, sneaking: false }
+    }
+
+    /// Returns the body of the player.
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    /// Returns the position of the eyes after the last tick.
+    pub fn eye_position(&self) -> Vec3 {
+        let eye_height = match self.sneaking {
+            true => PLAYER_SNEAK_EYE_HEIGHT,
+            false => PLAYER_EYE_HEIGHT,
+        };
+
+        self.body.position + Vec3::Y * eye_height
+    }
+
+    /// Moves the player's feet to `feet` at rest, without interpolating from the previous
+    /// position.
+    pub fn teleport(&mut self, feet: Vec3) {
+        self.body.position = feet;
+        self.body.velocity = Vec3::ZERO;
+        self.previous = self.eye_position();
+    }
+
+    /// Advances the player by one tick, walking the way `camera` faces.
+    pub fn tick(&mut self, camera: &Camera, input: &InputMap, table: &PhysicsTable, world: &impl BlockAccess) {
+        self.previous = self.eye_position();
+        self.sneaking = input.held(action::SNEAK);
+
+        let input = MoveInput {
+            forward: input.axis(axis::MOVE_Z),
+            strafe: input.axis(axis::MOVE_X),
+            yaw: camera.yaw(),
+            jump: input.held(action::JUMP),
+            sneak: self.sneaking,
+            sprint: input.held(action::SPRINT),
+        };
+
+        self.body.tick(table, world, &input);
+    }
+
+    /// Turns the camera from mouse and stick input. Call once per frame.
+    pub fn look(&self, camera: &mut Camera, input: &mut InputMap, settings: &CameraSettings, seconds: f32) {
+        super::fly::look(camera, input, settings, seconds);
+    }
+
+    /// Places the camera between the last two ticks. `alpha` comes from the game loop.
+    pub fn apply(&self, camera: &mut Camera, alpha: f32) {
+        camera.position = self.previous.lerp(self.eye_position(), alpha);
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::BufRead;
+use std::sync::mpsc::{channel, Receiver};
+
+/// Lines typed in the terminal the game was started from, read on a background thread so
+/// the game loop never waits for input.
+///
+/// The thread ends once standard input is closed; it is not joined, as it may be blocked
+/// reading when the game exits.
+pub struct Console {
+    receiver: Receiver<String>,
+}
+
+impl Console {
+    /// Starts reading standard input.
+    pub fn spawn() -> Result<Self, String> {
+        let (sender, receiver) = channel();
+
+        std::thread::Builder::new()
+            .name(String::from("console"))
+            .spawn(move || {
+                for line in std::io::stdin().lock().lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+
+                    if !line.trim().is_empty() && sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            })
+            .map_err(|e| format!("Failed to start the console: {e}"))?;
+
+        Ok(Self { receiver })
+    }
+
+    /// Takes the next line typed, if any.
+    pub fn poll(&self) -> Option<String> {
+        self.receiver.try_recv().ok()
+    }
+}
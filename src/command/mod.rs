@@ -0,0 +1,7 @@
+mod registry;
+mod console;
+mod time;
+
+pub use registry::CommandRegistry;
+pub use console::Console;
+pub use time::{parse_ticks, time_command};
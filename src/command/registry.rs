@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+type Handler<C> = Box<dyn Fn(&mut C, &[&str]) -> Result<String, String>>;
+
+struct Command<C> {
+    usage: String,
+    description: String,
+    handler: Handler<C>,
+}
+
+/// The commands a player can type, each run against a context `C` holding what commands may
+/// change.
+///
+/// A command line is a name followed by arguments separated by whitespace, with an optional
+/// leading `/`. Handlers return the message to show, or an error message. `help` is always
+/// available and lists the registered commands.
+///
+/// # Example
+///
+/// ```
+/// use rustedcraft::command::{time_command, CommandRegistry};
+/// use rustedcraft::world::WorldTime;
+///
+/// let mut commands = CommandRegistry::new();
+/// commands.register("time", "time <set|add|query> [value]", "Changes the time of day", time_command).unwrap();
+///
+/// let mut time = WorldTime::new(0);
+/// commands.execute(&mut time, "/time set noon").unwrap();
+/// assert_eq!(time.time_of_day(), 6000);
+/// ```
+pub struct CommandRegistry<C> {
+    commands: BTreeMap<String, Command<C>>,
+}
+
+impl<C> CommandRegistry<C> {
+    /// Creates a registry with only the `help` command.
+    pub fn new() -> Self {
+        Self { commands: BTreeMap::new() }
+    }
+
+    /// Registers the command `name`, described by its `usage` line and a one line
+    /// `description`, run by `handler` with its arguments.
+    pub fn register(
+        &mut self,
+        name: &str,
+        usage: &str,
+        description: &str,
+        handler: impl Fn(&mut C, &[&str]) -> Result<String, String> + 'static,
+    ) -> Result<(), String> {
+        if name.is_empty() || name.contains(char::is_whitespace) || name.starts_with('/') {
+            return Err(format!("invalid command name {name:?}"));
+        }
+
+        if name == "help" || self.commands.contains_key(name) {
+            return Err(format!("command {name} is already registered"));
+        }
+
+        let command = Command { usage: usage.to_owned(), description: description.to_owned(), handler: Box::new(handler) };
+        self.commands.insert(name.to_owned(), command);
+        Ok(())
+    }
+
+    /// Returns the names of the registered commands, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.commands.keys().map(String::as_str)
+    }
+
+    /// Runs the command `line` against `context`, returning its message.
+    pub fn execute(&self, context: &mut C, line: &str) -> Result<String, String> {
+        let line = line.trim();
+        let line = line.strip_prefix('/').unwrap_or(line);
+        let mut words = line.split_whitespace();
+
+        let Some(name) = words.next() else {
+            return Err(String::from("empty command"));
+        };
+
+        let arguments: Vec<&str> = words.collect();
+
+        if name == "help" {
+            return self.help(arguments.first().copied());
+        }
+
+        let command = self.commands.get(name).ok_or_else(|| format!("unknown command {name}, try help"))?;
+        (command.handler)(context, &arguments).map_err(|e| format!("{e}\nusage: {}", command.usage))
+    }
+
+    /// Describes the command `name`, or lists every command.
+    fn help(&self, name: Option<&str>) -> Result<String, String> {
+        if let Some(name) = name {
+            let command = self.commands.get(name).ok_or_else(|| format!("unknown command {name}"))?;
+            return Ok(format!("{}\n{}", command.usage, command.description));
+        }
+
+        let lines: Vec<String> = self.commands.values().map(|command| format!("{} - {}", command.usage, command.description)).collect();
+        Ok(lines.join("\n"))
+    }
+}
+
+impl<C> Default for CommandRegistry<C> {
+    fn default() -> Self { Self::new() }
+}
@@ -0,0 +1,53 @@
+use crate::world::{WorldTime, TICKS_PER_DAY};
+
+/// Game ticks per second.
+const TICKS_PER_SECOND: u64 = 20;
+
+/// Runs the `time` command on `time`:
+///
+/// - `time set <time>` moves to a time of the current day, given in ticks since sunrise or
+///   by name (`sunrise`, `day`, `noon`, `sunset`, `night`, `midnight`),
+/// - `time add <duration>` moves forward, see [`parse_ticks`] for durations,
+/// - `time query [daytime|gametime|day|moon]` tells the time, the time of day by default.
+pub fn time_command(time: &mut WorldTime, arguments: &[&str]) -> Result<String, String> {
+    match arguments {
+        ["set", value] => {
+            let ticks = match WorldTime::parse_time_of_day(value) {
+                Some(ticks) => ticks,
+                None => parse_ticks(value)?,
+            };
+
+            time.set_time_of_day(ticks);
+            Ok(format!("Set the time to {}", time.time_of_day()))
+        }
+        ["add", value] => {
+            time.add(parse_ticks(value)?);
+            Ok(format!("Set the time to {}", time.time_of_day()))
+        }
+        ["query"] | ["query", "daytime"] => Ok(format!("The time is {}", time.time_of_day())),
+        ["query", "gametime"] => Ok(format!("The game time is {}", time.ticks())),
+        ["query", "day"] => Ok(format!("The day is {}", time.day())),
+        ["query", "moon"] => Ok(format!("The moon phase is {}", time.moon_phase())),
+        ["query", other] => Err(format!("unknown time query {other}")),
+        _ => Err(String::from("expected set, add or query")),
+    }
+}
+
+/// Parses a duration in ticks, followed by `t` (ticks, the default), `s` (seconds) or `d`
+/// (days), such as `1000`, `30s` or `2d`.
+pub fn parse_ticks(value: &str) -> Result<u64, String> {
+    let (number, unit) = match value.char_indices().last() {
+        Some((index, 't')) => (&value[..index], 1),
+        Some((index, 's')) => (&value[..index], TICKS_PER_SECOND),
+        Some((index, 'd')) => (&value[..index], TICKS_PER_DAY),
+        _ => (value, 1),
+    };
+
+    let number: f64 = number.parse().map_err(|_| format!("invalid time {value:?}"))?;
+
+    if !number.is_finite() || number < 0.0 {
+        return Err(format!("invalid time {value:?}"));
+    }
+
+    Ok((number * unit as f64).round() as u64)
+}
@@ -0,0 +1,84 @@
+use crate::item::ItemStack;
+use crate::math::Vec3;
+use crate::physics::{BlockAccess, Body, PhysicsTable};
+
+/// Width and height of the box of a dropped item.
+pub const DROPPED_ITEM_SIZE: f32 = 0.25;
+
+/// Ticks after being dropped before an item can be picked up.
+const PICKUP_DELAY: u32 = 10;
+
+/// Ticks an item lies on the ground before it disappears, five minutes.
+const LIFETIME: u32 = 6000;
+
+const GRAVITY: f32 = 0.04;
+const AIR_DRAG: f32 = 0.98;
+const GROUND_FRICTION: f32 = 0.588;
+const FLUID_DRAG: f32 = 0.8;
+
+/// A stack of items lying in the world, waiting to be picked up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroppedItem {
+    pub stack: ItemStack,
+    body: Body,
+    previous: Vec3,
+    age: u32,
+}
+
+impl DroppedItem {
+    /// Creates an item with the center of its bottom at `position`, thrown with `velocity`.
+    pub fn new(stack: ItemStack, position: Vec3, velocity: Vec3) -> Self {
+        let mut body = Body::new(position, DROPPED_ITEM_SIZE, DROPPED_ITEM_SIZE, 0.0);
+        body.velocity = velocity;
+
+        Self { stack, body, previous: position, age: 0 }
+    }
+
+    /// Returns the body of the item.
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    /// Returns the number of ticks since the item was dropped.
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+
+    /// Returns whether the item has been lying around long enough to be picked up.
+    pub fn can_pick_up(&self) -> bool {
+        self.age >= PICKUP_DELAY
+    }
+
+    /// Returns whether the item has been lying around long enough to disappear.
+    pub fn is_expired(&self) -> bool {
+        self.age >= LIFETIME
+    }
+
+    /// Returns the position between the last two ticks. `alpha` comes from the game loop.
+    pub fn position(&self, alpha: f32) -> Vec3 {
+        self.previous.lerp(self.body.position, alpha)
+    }
+
+    /// Advances the item by one tick of falling and sliding to a stop.
+    pub fn tick(&mut self, table: &PhysicsTable, world: &impl BlockAccess) {
+        self.previous = self.body.position;
+        self.age += 1;
+
+        self.body.update_surroundings(table, world);
+        self.body.velocity.y -= GRAVITY;
+        self.body.move_by(table, world, self.body.velocity, false);
+
+        let drag = match (self.body.fluid(), self.body.on_ground()) {
+            (Some(_), _) => FLUID_DRAG,
+            (None, true) => GROUND_FRICTION,
+            (None, false) => AIR_DRAG,
+        };
+
+        self.body.velocity.x *= drag;
+        self.body.velocity.z *= drag;
+        self.body.velocity.y *= match self.body.fluid() {
+            Some(_) => FLUID_DRAG,
+            None => AIR_DRAG,
+        };
+    }
+}
@@ -0,0 +1,104 @@
+use super::{DroppedItem, FallingBlock};
+use crate::interaction::{BlockRules, BlockUpdates, WorldAccess};
+use crate::item::{Inventory, ItemRegistry, ItemStack};
+use crate::math::{Aabb, Vec3};
+use crate::physics::PhysicsTable;
+use crate::world::{BlockPos, BlockState};
+use crate::worldgen::WorldRandom;
+
+/// How far around the player's box items are picked up, horizontally and vertically.
+const PICKUP_REACH: Vec3 = Vec3::new(1.0, 0.5, 1.0);
+
+/// Speed of items thrown by the player.
+const THROW_SPEED: f32 = 0.3;
+
+/// Everything in the world that is not a block, ticked along with it.
+#[derive(Debug, Clone)]
+pub struct Entities {
+    items: Vec<DroppedItem>,
+    falling_blocks: Vec<FallingBlock>,
+    random: WorldRandom,
+}
+
+impl Entities {
+    /// Creates a world without entities. `seed` drives where dropped items scatter.
+    pub fn new(seed: u64) -> Self {
+        Self { items: Vec::new(), falling_blocks: Vec::new(), random: WorldRandom::new(seed) }
+    }
+
+    /// Returns the items lying in the world.
+    pub fn items(&self) -> &[DroppedItem] {
+        &self.items
+    }
+
+    /// Returns the blocks falling down.
+    pub fn falling_blocks(&self) -> &[FallingBlock] {
+        &self.falling_blocks
+    }
+
+    /// Makes `state` fall from `pos`, which the caller already emptied.
+    pub fn start_falling(&mut self, pos: BlockPos, state: BlockState) {
+        self.falling_blocks.push(FallingBlock::new(state, pos));
+    }
+
+    /// Drops `stack` out of the block at `pos`, popping up in a random direction.
+    pub fn drop_from_block(&mut self, pos: BlockPos, stack: ItemStack) {
+        let mut spread = || (self.random.next_f64() as f32 - 0.5) * 0.5;
+        let position = Vec3::new(pos.x as f32 + 0.5 + spread(), pos.y as f32 + 0.25 + spread(), pos.z as f32 + 0.5 + spread());
+        let velocity = Vec3::new(spread() * 0.4, 0.2, spread() * 0.4);
+
+        self.items.push(DroppedItem::new(stack, position, velocity));
+    }
+
+    /// Throws `stack` from `eye` towards `direction`, as a player dropping what they hold.
+    pub fn throw(&mut self, stack: ItemStack, eye: Vec3, direction: Vec3) {
+        let position = eye - Vec3::Y * 0.3;
+        let velocity = direction.normalize_or_zero() * THROW_SPEED + Vec3::Y * 0.1;
+
+        self.items.push(DroppedItem::new(stack, position, velocity));
+    }
+
+    /// Advances every entity by one tick. Falling blocks that land are placed where they
+    /// are through `updates` if the block there is replaceable and they can stay, and drop
+    /// as items otherwise. Items lying around too long disappear.
+    pub fn tick(&mut self, table: &PhysicsTable, rules: &BlockRules, updates: &mut BlockUpdates, world: &mut impl WorldAccess) {
+        for item in &mut self.items {
+            item.tick(table, world);
+        }
+
+        self.items.retain(|item| !item.is_expired());
+
+        for block in &mut self.falling_blocks {
+            block.tick(table, world);
+        }
+
+        let landed: Vec<_> = self.falling_blocks.extract_if(.., |block| block.has_landed() || block.is_expired()).collect();
+
+        for block in landed {
+            let pos = block.block_pos();
+            let free = world.block(pos).is_some_and(|existing| rules.is_replaceable(existing));
+
+            if !block.is_expired() && free && rules.can_stay(world, pos, block.state) {
+                updates.set_block(world, pos, block.state);
+                continue;
+            }
+
+            for &stack in rules.natural_drops(block.state) {
+                self.drop_from_block(pos, stack);
+            }
+        }
+    }
+
+    /// Moves the items within reach of `player` into `inventory`, as far as it has room.
+    pub fn pick_up(&mut self, player: Aabb, inventory: &mut Inventory, items: &ItemRegistry) {
+        let reach = Aabb::new(player.min - PICKUP_REACH, player.max + PICKUP_REACH);
+
+        for item in &mut self.items {
+            if item.can_pick_up() && reach.intersects(item.body().aabb()) {
+                item.stack.count = inventory.add(items, item.stack).map_or(0, |left| left.count);
+            }
+        }
+
+        self.items.retain(|item| item.stack.count > 0);
+    }
+}
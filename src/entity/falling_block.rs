@@ -0,0 +1,74 @@
+use crate::math::Vec3;
+use crate::physics::{BlockAccess, Body, PhysicsTable};
+use crate::world::{BlockPos, BlockState};
+
+/// Width and height of the box of a falling block, a little smaller than a block so that it
+/// fits down holes.
+pub const FALLING_BLOCK_SIZE: f32 = 0.98;
+
+/// Ticks a block falls before giving up and dropping as items, thirty seconds.
+const LIFETIME: u32 = 600;
+
+const GRAVITY: f32 = 0.04;
+const DRAG: f32 = 0.98;
+
+/// A block falling down, like sand with nothing below it, until it lands and is placed again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FallingBlock {
+    pub state: BlockState,
+    body: Body,
+    previous: Vec3,
+    age: u32,
+}
+
+impl FallingBlock {
+    /// Creates a block starting to fall from `pos`.
+    pub fn new(state: BlockState, pos: BlockPos) -> Self {
+        let position = Vec3::new(pos.x as f32 + 0.5, pos.y as f32, pos.z as f32 + 0.5);
+        let body = Body::new(position, FALLING_BLOCK_SIZE, FALLING_BLOCK_SIZE, 0.0);
+
+        Self { state, body, previous: position, age: 0 }
+    }
+
+    /// Returns the body of the block.
+    pub fn body(&self) -> &Body {
+        &self.body
+    }
+
+    /// Returns the number of ticks since the block started falling.
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+
+    /// Returns whether the block has been falling too long and should drop as items.
+    pub fn is_expired(&self) -> bool {
+        self.age >= LIFETIME
+    }
+
+    /// Returns whether the block came to rest on the ground.
+    pub fn has_landed(&self) -> bool {
+        self.body.on_ground()
+    }
+
+    /// Returns the position between the last two ticks. `alpha` comes from the game loop.
+    pub fn position(&self, alpha: f32) -> Vec3 {
+        self.previous.lerp(self.body.position, alpha)
+    }
+
+    /// Returns the position of the block the falling block is in.
+    pub fn block_pos(&self) -> BlockPos {
+        let position = self.body.position.floor();
+        BlockPos::new(position.x as i32, position.y as i32, position.z as i32)
+    }
+
+    /// Advances the block by one tick of falling.
+    pub fn tick(&mut self, table: &PhysicsTable, world: &impl BlockAccess) {
+        self.previous = self.body.position;
+        self.age += 1;
+
+        self.body.update_surroundings(table, world);
+        self.body.velocity.y -= GRAVITY;
+        self.body.move_by(table, world, self.body.velocity, false);
+        self.body.velocity *= DRAG;
+    }
+}
@@ -0,0 +1,7 @@
+mod dropped_item;
+mod entities;
+mod falling_block;
+
+pub use dropped_item::{DroppedItem, DROPPED_ITEM_SIZE};
+pub use entities::Entities;
+pub use falling_block::{FallingBlock, FALLING_BLOCK_SIZE};
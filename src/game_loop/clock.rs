@@ -0,0 +1,69 @@
+use std::time::{Duration, Instant};
+
+/// A monotonic time source driving the game loop.
+///
+/// Abstracting the clock lets the simulation be driven headlessly, with time advancing
+/// only when told to, which makes tick behavior reproducible.
+pub trait Clock {
+    /// Returns the time elapsed since the clock was created.
+    fn now(&self) -> Duration;
+
+    /// Blocks until `duration` has passed on this clock.
+    fn sleep(&mut self, duration: Duration);
+}
+
+/// A clock backed by the system monotonic timer.
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    /// Creates a clock starting at zero now.
+    pub fn new() -> Self {
+        Self { start: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self { Self::new() }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// A clock that only moves when advanced by hand.
+///
+/// Intended for tests and tools that need to replay a precise sequence of frames.
+#[derive(Debug, Default, Clone)]
+pub struct ManualClock {
+    now: Duration,
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now
+    }
+
+    fn sleep(&mut self, duration: Duration) {
+        self.advance(duration);
+    }
+}
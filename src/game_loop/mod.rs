@@ -0,0 +1,7 @@
+mod timestep;
+mod timings;
+mod clock;
+
+pub use clock::{Clock, SystemClock, ManualClock};
+pub use timings::{Timings, TimingStats};
+pub use timestep::{GameLoop, Frame};
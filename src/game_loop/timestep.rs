@@ -0,0 +1,294 @@
+use super::{Clock, SystemClock, Timings};
+use std::time::Duration;
+
+/// Number of frames and ticks the reported timings are averaged over.
+const TIMING_WINDOW: usize = 120;
+
+/// Outcome of a single call to [`GameLoop::frame`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    /// Wall time elapsed since the previous frame.
+    pub delta: Duration,
+
+    /// Number of simulation ticks run during this frame.
+    pub ticks: u32,
+
+    /// Number of ticks dropped because the catch-up limit was reached.
+    pub skipped: u32,
+
+    /// How far the simulation is between the last tick and the next one, in `[0, 1)`.
+    ///
+    /// Renderers should draw `previous.lerp(current, alpha)` to move smoothly between ticks.
+    pub alpha: f32,
+}
+
+/// A fixed-timestep game loop.
+///
+/// The simulation advances in ticks of constant length (20 per second by default, like
+/// Minecraft), independently of how fast frames are rendered. Each frame runs as many ticks
+/// as real time demands, then reports an interpolation factor for rendering between the two
+/// most recent simulation states.
+///
+/// To avoid the "spiral of death" where slow ticks cause ever more ticks to be queued, at most
+/// [`GameLoop::max_catch_up`] ticks run per frame and any remaining backlog is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use rustedcraft::game_loop::GameLoop;
+///
+/// let mut game_loop = GameLoop::new(20);
+///
+/// loop {
+///     let frame = game_loop.frame(|tick| { /* advance the simulation */ });
+///     // render using frame.alpha
+///     game_loop.pace();
+/// }
+/// ```
+pub struct GameLoop<C: Clock = SystemClock> {
+    clock: C,
+
+    /// Length of a single simulation tick.
+    tick_duration: Duration,
+
+    /// Maximum number of ticks a single frame may run.
+    max_catch_up: u32,
+
+    /// Minimum time between frames when a frame limit is set.
+    min_frame_time: Option<Duration>,
+
+    /// Simulation time owed but not yet ticked.
+    accumulator: Duration,
+
+    /// Clock time at the start of the previous frame.
+    last_frame: Duration,
+
+    /// Number of ticks run since the loop started.
+    tick: u64,
+
+    alpha: f32,
+    timings: Timings,
+}
+
+impl GameLoop<SystemClock> {
+    /// Creates a game loop running `tick_rate` ticks per second on the system clock.
+    pub fn new(tick_rate: u32) -> Self {
+        Self::with_clock(SystemClock::new(), tick_rate)
+    }
+}
+
+impl<C: Clock> GameLoop<C> {
+    /// Creates a game loop running `tick_rate` ticks per second on the given clock.
+    ///
+    /// # Panics
+    /// Panics if `tick_rate` is zero.
+    pub fn with_clock(clock: C, tick_rate: u32) -> Self {
+        assert!(tick_rate > 0, "tick rate must be positive");
+
+        let last_frame = clock.now();
+
+        Self {
+            clock,
+            tick_duration: Duration::from_secs(1) / tick_rate,
+            max_catch_up: 10,
+            min_frame_time: None,
+            accumulator: Duration::ZERO,
+            last_frame,
+            tick: 0,
+            alpha: 0.0,
+            timings: Timings::new(TIMING_WINDOW),
+        }
+    }
+
+    /// Returns the length of a simulation tick.
+    pub fn tick_duration(&self) -> Duration {
+        self.tick_duration
+    }
+
+    /// Returns the maximum number of ticks run by a single frame.
+    pub fn max_catch_up(&self) -> u32 {
+        self.max_catch_up
+    }
+
+    /// Sets the maximum number of ticks run by a single frame. Must be at least one.
+    pub fn set_max_catch_up(&mut self, ticks: u32) {
+        self.max_catch_up = ticks.max(1);
+    }
+
+    /// Limits rendering to `fps` frames per second, or removes the limit with `None`.
+    ///
+    /// The limit is enforced by [`GameLoop::pace`]. With vsync enabled this is usually unneeded.
+    pub fn set_frame_limit(&mut self, fps: Option<u32>) {
+        self.min_frame_time = fps.filter(|&fps| fps > 0).map(|fps| Duration::from_secs(1) / fps);
+    }
+
+    /// Returns the number of ticks run since the loop started.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns the interpolation factor computed by the last frame.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// Returns the frame and tick timings.
+    pub fn timings(&self) -> &Timings {
+        &self.timings
+    }
+
+    /// Returns the clock driving this loop.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Returns the clock driving this loop mutably, e.g. to advance a manual clock.
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Starts a new frame, running every simulation tick that is due.
+    ///
+    /// # Arguments
+    /// * `on_tick` - Called once per tick with the index of the tick being run.
+    ///
+    /// # Returns
+    /// A [`Frame`] describing the ticks run and the interpolation factor for rendering.
+    pub fn frame(&mut self, mut on_tick: impl FnMut(u64)) -> Frame {
+        let now = self.clock.now();
+        let delta = now.saturating_sub(self.last_frame);
+
+        self.last_frame = now;
+        self.accumulator += delta;
+
+        let mut ticks = 0;
+
+        while self.accumulator >= self.tick_duration && ticks < self.max_catch_up {
+            let start = self.clock.now();
+            on_tick(self.tick);
+            self.timings.tick.push(self.clock.now().saturating_sub(start));
+
+            self.accumulator -= self.tick_duration;
+            self.tick += 1;
+            ticks += 1;
+        }
+
+        // Drop whatever is still owed rather than trying to catch up on the next frame,
+        // keeping only the fraction of a tick so interpolation stays continuous.
+        let skipped = (self.accumulator.as_nanos() / self.tick_duration.as_nanos()) as u32;
+
+        if skipped > 0 {
+            self.accumulator -= self.tick_duration * skipped;
+            self.timings.skipped_ticks += skipped as u64;
+        }
+
+        self.alpha = self.accumulator.as_secs_f32() / self.tick_duration.as_secs_f32();
+        self.timings.record_frame(delta, ticks);
+
+        Frame { delta, ticks, skipped, alpha: self.alpha }
+    }
+
+    /// Sleeps until the next frame is due according to the frame limit, if one is set.
+    pub fn pace(&mut self) {
+        let Some(min_frame_time) = self.min_frame_time else { return };
+
+        let elapsed = self.clock.now().saturating_sub(self.last_frame);
+
+        if elapsed < min_frame_time {
+            self.clock.sleep(min_frame_time - elapsed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_loop::ManualClock;
+
+    const TICK: Duration = Duration::from_millis(50);
+
+    fn game_loop() -> GameLoop<ManualClock> {
+        GameLoop::with_clock(ManualClock::new(), 20)
+    }
+
+    #[test]
+    fn runs_the_ticks_that_are_due() {
+        let mut game_loop = game_loop();
+        let mut ran = Vec::new();
+
+        assert_eq!(game_loop.frame(|tick| ran.push(tick)).ticks, 0);
+
+        game_loop.clock_mut().advance(TICK * 3 + TICK / 2);
+        let frame = game_loop.frame(|tick| ran.push(tick));
+
+        assert_eq!((frame.ticks, frame.skipped, frame.delta), (3, 0, TICK * 3 + TICK / 2));
+        assert_eq!(ran, [0, 1, 2]);
+
+        // The half tick left over is owed to the next frame.
+        game_loop.clock_mut().advance(TICK / 2);
+        assert_eq!(game_loop.frame(|tick| ran.push(tick)).ticks, 1);
+        assert_eq!(ran, [0, 1, 2, 3]);
+        assert_eq!(game_loop.tick(), 4);
+    }
+
+    #[test]
+    fn caps_catch_up_and_skips_the_rest() {
+        let mut game_loop = game_loop();
+        game_loop.set_max_catch_up(4);
+
+        game_loop.clock_mut().advance(TICK * 10 + TICK / 4);
+        let frame = game_loop.frame(|_| {});
+
+        assert_eq!((frame.ticks, frame.skipped), (4, 6));
+        assert_eq!(game_loop.timings().skipped_ticks, 6);
+        assert!((frame.alpha - 0.25).abs() < 1e-4);
+
+        // The backlog is dropped rather than run on the next frame.
+        game_loop.clock_mut().advance(TICK);
+        assert_eq!(game_loop.frame(|_| {}).ticks, 1);
+
+        game_loop.set_max_catch_up(0);
+        assert_eq!(game_loop.max_catch_up(), 1);
+    }
+
+    #[test]
+    fn alpha_stays_within_a_tick() {
+        let mut game_loop = game_loop();
+
+        for step in 1..200u32 {
+            game_loop.clock_mut().advance(Duration::from_millis((step * 7 % 130) as u64));
+            let frame = game_loop.frame(|_| {});
+
+            assert!((0.0..1.0).contains(&frame.alpha), "alpha {} at step {step}", frame.alpha);
+            assert_eq!(frame.alpha, game_loop.alpha());
+        }
+    }
+
+    #[test]
+    fn paces_frames_to_the_limit() {
+        let mut game_loop = game_loop();
+
+        // Without a limit, pacing never sleeps.
+        game_loop.frame(|_| {});
+        game_loop.pace();
+        assert_eq!(game_loop.clock().now(), Duration::ZERO);
+
+        game_loop.set_frame_limit(Some(50));
+        game_loop.clock_mut().advance(Duration::from_millis(5));
+        game_loop.frame(|_| {});
+        game_loop.clock_mut().advance(Duration::from_millis(8));
+        game_loop.pace();
+        assert_eq!(game_loop.clock().now(), Duration::from_millis(25));
+
+        // Frames slower than the limit are not slowed down further.
+        game_loop.frame(|_| {});
+        game_loop.clock_mut().advance(Duration::from_millis(30));
+        game_loop.pace();
+        assert_eq!(game_loop.clock().now(), Duration::from_millis(55));
+
+        game_loop.set_frame_limit(None);
+        game_loop.frame(|_| {});
+        game_loop.pace();
+        assert_eq!(game_loop.clock().now(), Duration::from_millis(55));
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Rolling statistics over the most recent duration samples.
+#[derive(Debug, Clone)]
+pub struct TimingStats {
+    samples: VecDeque<Duration>,
+    capacity: usize,
+    total: Duration,
+}
+
+impl TimingStats {
+    /// Creates an empty set of statistics keeping at most `capacity` samples.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self { samples: VecDeque::with_capacity(capacity), capacity, total: Duration::ZERO }
+    }
+
+    /// Records a new sample, discarding the oldest one if the window is full.
+    pub fn push(&mut self, sample: Duration) {
+        if self.samples.len() == self.capacity
+            && let Some(oldest) = self.samples.pop_front()
+        {
+            self.total -= oldest;
+        }
+
+        self.samples.push_back(sample);
+        self.total += sample;
+    }
+
+    /// Returns the mean of the recorded samples, or zero if there are none.
+    pub fn average(&self) -> Duration {
+        match self.samples.len() {
+            0 => Duration::ZERO,
+            len => self.total / len as u32,
+        }
+    }
+
+    /// Returns the shortest recorded sample.
+    pub fn min(&self) -> Duration {
+        self.samples.iter().copied().min().unwrap_or_default()
+    }
+
+    /// Returns the longest recorded sample.
+    pub fn max(&self) -> Duration {
+        self.samples.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Returns the most recent sample.
+    pub fn last(&self) -> Duration {
+        self.samples.back().copied().unwrap_or_default()
+    }
+
+    /// Returns how many samples are in the window.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns whether no sample has been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the sum of the samples in the window.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+}
+
+/// Frame and tick timings reported by the game loop.
+#[derive(Debug, Clone)]
+pub struct Timings {
+    /// Wall time between consecutive frames.
+    pub frame: TimingStats,
+
+    /// Time spent inside each simulation tick.
+    pub tick: TimingStats,
+
+    /// Number of ticks run by each frame.
+    pub ticks_per_frame: VecDeque<u32>,
+
+    /// Total ticks dropped because the simulation fell too far behind.
+    pub skipped_ticks: u64,
+}
+
+impl Timings {
+    /// Creates timings averaging over the last `window` frames and ticks.
+    pub fn new(window: usize) -> Self {
+        Self {
+            frame: TimingStats::new(window),
+            tick: TimingStats::new(window),
+            ticks_per_frame: VecDeque::with_capacity(window),
+            skipped_ticks: 0,
+        }
+    }
+
+    /// Returns the measured frames per second over the sampling window.
+    pub fn fps(&self) -> f64 {
+        per_second(self.frame.len() as f64, self.frame.total())
+    }
+
+    /// Returns the measured ticks per second over the sampling window.
+    pub fn tps(&self) -> f64 {
+        let ticks = self.ticks_per_frame.iter().sum::<u32>();
+        per_second(ticks as f64, self.frame.total())
+    }
+
+    pub(super) fn record_frame(&mut self, delta: Duration, ticks: u32) {
+        if self.ticks_per_frame.len() == self.frame.capacity {
+            self.ticks_per_frame.pop_front();
+        }
+
+        self.ticks_per_frame.push_back(ticks);
+        self.frame.push(delta);
+    }
+}
+
+fn per_second(count: f64, over: Duration) -> f64 {
+    if over.is_zero() { 0.0 } else { count / over.as_secs_f64() }
+}
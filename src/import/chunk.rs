@@ -0,0 +1,255 @@
+use super::BlockMapping;
+use crate::block::BlockRegistry;
+use crate::nbt::{Compound, Tag};
+use crate::world::{BlockState, Chunk, ChunkPos, ChunkSection, SECTION_COUNT, SECTION_VOLUME};
+
+use std::collections::HashMap;
+
+/// Data version of 17w47a, the first to store blocks by name, for Minecraft 1.13.
+const FLATTENING_VERSION: i64 = 1451;
+
+/// Data version of 20w17a, from which packed block states no longer span two longs, for
+/// Minecraft 1.16.
+const PADDED_STATES_VERSION: i64 = 2527;
+
+/// Statuses of chunks that are fully generated, from the various versions.
+const FULL_STATUSES: [&str; 3] = ["full", "fullchunk", "postprocessed"];
+
+/// Names of the Minecraft blocks that are air, left out of the sections counted as clipped.
+const AIR_NAMES: [&str; 3] = ["minecraft:air", "minecraft:cave_air", "minecraft:void_air"];
+
+/// Widest packed palette index, enough for every block state of Minecraft.
+const MAX_BITS: usize = 16;
+
+/// A chunk of a Minecraft world converted to ours.
+#[derive(Debug, Clone)]
+pub struct ConvertedChunk {
+    pub chunk: Chunk,
+
+    /// Number of blocks of each unmapped Minecraft block, which became the fallback block.
+    pub unmapped: HashMap<String, u64>,
+
+    /// Number of sections holding blocks that were left out, being below y 0 or from y 256
+    /// up.
+    pub clipped_sections: usize,
+}
+
+/// Converts the NBT of a chunk of a Minecraft Java world, as stored in its region files,
+/// into our chunk at `pos`, mapping its blocks through `mapping`. Returns `None` for chunks
+/// Minecraft has not finished generating.
+///
+/// Both the layout of Minecraft 1.18 and later, with `sections` at the root, and the older
+/// one under `Level` are read, from Minecraft 1.13 on. Biomes, light, block entities and
+/// entities are left out.
+///
+/// # Returns
+/// * `Err(String)` if the chunk comes from before Minecraft 1.13 or its sections are
+///   malformed.
+pub fn convert_chunk(root: &Compound, pos: ChunkPos, blocks: &BlockRegistry, mapping: &BlockMapping) -> Result<Option<ConvertedChunk>, String> {
+    let version = root.get_i64("DataVersion").unwrap_or(0);
+
+    if version < FLATTENING_VERSION {
+        return Err(String::from("saved before Minecraft 1.13, open and save the world in a newer version first"));
+    }
+
+    let (level, padded) = match root.get("sections") {
+        Some(_) => (root, true),
+        None => (root.get_compound("Level").ok_or("no sections nor Level")?, version >= PADDED_STATES_VERSION),
+    };
+
+    if let Some(status) = level.get_str("Status") {
+        let status = status.strip_prefix("minecraft:").unwrap_or(status);
+
+        if !FULL_STATUSES.contains(&status) {
+            return Ok(None);
+        }
+    }
+
+    let sections = level.get_list("sections").or_else(|| level.get_list("Sections")).unwrap_or_default();
+    let mut converted = ConvertedChunk { chunk: Chunk::new(pos), unmapped: HashMap::new(), clipped_sections: 0 };
+
+    for section in sections.iter().filter_map(Tag::as_compound) {
+        let y = section.get_i64("Y").ok_or("section without a Y")?;
+
+        let (palette, data) = match section.get_compound("block_states") {
+            Some(states) => (states.get_list("palette"), states.get("data")),
+            None => (section.get_list("Palette"), section.get("BlockStates")),
+        };
+
+        let Some(palette) = palette.filter(|palette| !palette.is_empty()) else {
+            continue;
+        };
+
+        let palette = palette.iter().map(read_state).collect::<Result<Vec<_>, _>>().map_err(|e| format!("section {y}: {e}"))?;
+
+        if !(0..SECTION_COUNT as i64).contains(&y) {
+            converted.clipped_sections += palette.iter().any(|(name, _)| !AIR_NAMES.contains(&name.as_str())) as usize;
+            continue;
+        }
+
+        let data = data.map(|data| data.as_long_array().ok_or("block states that are not a long array")).transpose().map_err(|e| format!("section {y}: {e}"))?;
+        let indices = unpack(data.unwrap_or_default(), palette.len(), padded).map_err(|e| format!("section {y}: {e}"))?;
+
+        let states: Vec<Option<BlockState>> = palette.iter().map(|(name, properties)| mapping.get(name, properties, blocks)).collect();
+        let mut counts = vec![0u64; palette.len()];
+        let target = converted.chunk.section_mut(y as usize);
+
+        for (index, &entry) in indices.iter().enumerate() {
+            let (x, y, z) = ChunkSection::coords(index);
+            target.set(x, y, z, states[entry as usize].unwrap_or(mapping.fallback()));
+            counts[entry as usize] += 1;
+        }
+
+        target.compact();
+
+        for (((name, _), state), count) in palette.iter().zip(&states).zip(counts) {
+            if state.is_none() && count > 0 {
+                *converted.unmapped.entry(name.clone()).or_default() += count;
+            }
+        }
+    }
+
+    Ok(Some(converted))
+}
+
+/// Reads a palette entry: the name of a block and its properties.
+fn read_state(tag: &Tag) -> Result<(String, Vec<(String, String)>), String> {
+    let entry = tag.as_compound().ok_or("palette entry that is not a compound")?;
+    let name = entry.get_str("Name").ok_or("palette entry without a name")?;
+
+    let properties = match entry.get_compound("Properties") {
+        Some(properties) => properties
+            .iter()
+            .map(|(property, value)| value.as_str().map(|value| (property.to_string(), value.to_string())).ok_or_else(|| format!("property {property} of {name} is not a string")))
+            .collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+
+    Ok((name.to_string(), properties))
+}
+
+/// Unpacks the palette index of every block of a section from `data`.
+///
+/// Indices take as many bits as the palette needs, and at least 4, as Minecraft writes them,
+/// and are stored from the lowest bits of each long up. Padded data starts each long afresh
+/// with the bits left unused, while older data lets indices span two longs. Sections with a
+/// single palette entry may have no data at all.
+fn unpack(data: &[i64], palette_len: usize, padded: bool) -> Result<Vec<u16>, String> {
+    if data.is_empty() {
+        return Ok(vec![0; SECTION_VOLUME]);
+    }
+
+    // The length of the data alone cannot tell the width, as some widths fill longs alike.
+    let bits = (usize::BITS - (palette_len - 1).leading_zeros()).max(4) as usize;
+
+    let expected = match padded {
+        true => SECTION_VOLUME.div_ceil(64 / bits),
+        false => (SECTION_VOLUME * bits).div_ceil(64),
+    };
+
+    if bits > MAX_BITS || data.len() != expected {
+        return Err(format!("{} packed longs for a palette of {palette_len}", data.len()));
+    }
+
+    let mask = (1u64 << bits) - 1;
+    let mut indices = Vec::with_capacity(SECTION_VOLUME);
+
+    for index in 0..SECTION_VOLUME {
+        let value = match padded {
+            true => {
+                let per_long = 64 / bits;
+                (data[index / per_long] as u64 >> (index % per_long * bits)) & mask
+            }
+            false => {
+                let (long, offset) = (index * bits / 64, index * bits % 64);
+                let mut value = data[long] as u64 >> offset;
+
+                if offset + bits > 64 {
+                    value |= (data[long + 1] as u64) << (64 - offset);
+                }
+
+                value & mask
+            }
+        };
+
+        if value as usize >= palette_len {
+            return Err(format!("palette index {value} out of {palette_len}"));
+        }
+
+        indices.push(value as u16);
+    }
+
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Packs `indices` of `bits` each the way Minecraft does.
+    fn pack(indices: &[u16], bits: usize, padded: bool) -> Vec<i64> {
+        let mut data = Vec::new();
+
+        for (index, &value) in indices.iter().enumerate() {
+            let (long, offset) = match padded {
+                true => (index / (64 / bits), index % (64 / bits) * bits),
+                false => (index * bits / 64, index * bits % 64),
+            };
+
+            data.resize(data.len().max(long + 1 + (offset + bits > 64) as usize), 0u64);
+            data[long] |= (value as u64) << offset;
+
+            if offset + bits > 64 {
+                data[long + 1] |= value as u64 >> (64 - offset);
+            }
+        }
+
+        let expected = match padded {
+            true => SECTION_VOLUME.div_ceil(64 / bits),
+            false => (SECTION_VOLUME * bits).div_ceil(64),
+        };
+
+        data.resize(expected, 0);
+        data.into_iter().map(|long| long as i64).collect()
+    }
+
+    fn indices(palette_len: usize) -> Vec<u16> {
+        (0..SECTION_VOLUME).map(|index| (index * 7 % palette_len) as u16).collect()
+    }
+
+    #[test]
+    fn unpacks_padded_data() {
+        let expected = indices(20);
+        assert_eq!(unpack(&pack(&expected, 5, true), 20, true), Ok(expected));
+    }
+
+    #[test]
+    fn unpacks_data_spanning_longs() {
+        let expected = indices(20);
+        assert_eq!(unpack(&pack(&expected, 5, false), 20, false), Ok(expected));
+    }
+
+    #[test]
+    fn takes_the_width_from_the_palette() {
+        // 11 and 12 bits both fit 5 indices in a long.
+        let expected = indices(2000);
+        assert_eq!(unpack(&pack(&expected, 11, true), 2000, true), Ok(expected));
+
+        // Small palettes still take 4 bits.
+        let expected = indices(2);
+        assert!(unpack(&pack(&expected, 1, true), 2, true).is_err());
+        assert_eq!(unpack(&pack(&expected, 4, true), 2, true), Ok(expected));
+    }
+
+    #[test]
+    fn reads_single_entry_palettes_without_data() {
+        assert_eq!(unpack(&[], 1, true), Ok(vec![0; SECTION_VOLUME]));
+    }
+
+    #[test]
+    fn rejects_indices_past_the_palette() {
+        let mut indices = indices(20);
+        indices[100] = 25;
+        assert!(unpack(&pack(&indices, 5, true), 20, true).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::nbt::{self, Endian, Tag};
+use crate::save::LevelData;
+use crate::world::{WorldTime, CHUNK_HEIGHT};
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Name of the file holding the level data in a Minecraft world directory.
+pub const JAVA_LEVEL_FILE: &str = "level.dat";
+
+/// The root of `level.dat`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LevelRoot {
+    data: JavaLevel,
+}
+
+/// The parts of `level.dat` that carry over to our worlds.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JavaLevel {
+    /// Seed of worlds from before Minecraft 1.16.
+    random_seed: Option<i64>,
+    world_gen_settings: Option<WorldGenSettings>,
+    spawn_x: Option<i32>,
+    spawn_y: Option<i32>,
+    spawn_z: Option<i32>,
+    day_time: Option<i64>,
+
+    /// Every game rule, with its value as text.
+    #[serde(default)]
+    game_rules: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorldGenSettings {
+    seed: i64,
+}
+
+/// Reads the `level.dat` of the Minecraft Java world in `directory` as our level data: its
+/// seed, spawn point, time of day and the game rules we share.
+///
+/// Spawn points outside of our height are dropped, to be picked again on the first start.
+///
+/// # Returns
+/// * `Err(String)` if the file cannot be read or is not the level data of a world.
+pub fn read_java_level(directory: impl AsRef<Path>) -> Result<LevelData, String> {
+    let path = directory.as_ref().join(JAVA_LEVEL_FILE);
+    let bytes = std::fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let (_, tag) = nbt::read_compressed(&bytes, Endian::Big).map_err(|e| format!("{}: {e}", path.display()))?;
+
+    convert_level(tag).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn convert_level(tag: Tag) -> Result<LevelData, String> {
+    let java = nbt::from_tag::<LevelRoot>(tag)?.data;
+    let seed = java.world_gen_settings.map(|settings| settings.seed).or(java.random_seed).ok_or("no seed")?;
+    let mut level = LevelData::new(seed as u64);
+
+    if let (Some(x), Some(y), Some(z)) = (java.spawn_x, java.spawn_y, java.spawn_z)
+        && (0..CHUNK_HEIGHT as i32).contains(&y)
+    {
+        level.spawn = Some([x, y, z]);
+    }
+
+    level.time = WorldTime::new(java.day_time.unwrap_or(0).max(0) as u64);
+
+    if let Some(value) = java.game_rules.get("doDaylightCycle") {
+        level.game_rules.do_daylight_cycle = value != "false";
+    }
+
+    if let Some(speed) = java.game_rules.get("randomTickSpeed").and_then(|value| value.parse().ok()) {
+        level.game_rules.random_tick_speed = speed;
+    }
+
+    Ok(level)
+}
@@ -0,0 +1,235 @@
+use crate::block::BlockRegistry;
+use crate::resource::ResourceManager;
+use crate::world::BlockState;
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Path of the default mapping within the resources.
+pub const MAPPING_FILE: &str = "import/anvil.toml";
+
+/// Namespace of the blocks of Minecraft worlds, assumed for names without one.
+const MINECRAFT_NAMESPACE: &str = "minecraft";
+
+/// A mapping table as written in TOML.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MappingDefinition {
+    fallback: String,
+
+    #[serde(default)]
+    blocks: BTreeMap<String, String>,
+}
+
+/// One entry of the table, for a Minecraft block and the properties it must have.
+#[derive(Debug, Clone)]
+struct Rule {
+    properties: Vec<(String, String)>,
+    target: BlockState,
+
+    /// Properties the target sets itself, which are not copied from the Minecraft state.
+    fixed: Vec<String>,
+}
+
+/// Turns the block states of Minecraft Java worlds into ours, for importing them.
+///
+/// The table maps Minecraft block names, with `minecraft:` assumed, to our states. A name
+/// may list properties in brackets, as in `"tall_grass[half=upper]"`, to only match states
+/// with these values; the entry listing the most properties that all match wins. Blocks
+/// without an entry map to our block of the same name if there is one. Otherwise they are
+/// unmapped and become the fallback block.
+///
+/// Properties of the Minecraft state that the block it maps to also has are copied over,
+/// such as the `axis` of logs or the `type` of slabs, unless the target sets them itself.
+///
+/// # Example
+///
+/// ```toml
+/// fallback = "stone"
+///
+/// [blocks]
+/// cave_air = "air"
+/// deepslate = "stone"
+/// spruce_stairs = "oak_stairs"
+/// "tall_grass[half=lower]" = "short_grass"
+/// "tall_grass[half=upper]" = "air"
+/// ```
+#[derive(Debug, Clone)]
+pub struct BlockMapping {
+    fallback: BlockState,
+    rules: HashMap<String, Vec<Rule>>,
+}
+
+impl BlockMapping {
+    /// Loads the mapping from [`MAPPING_FILE`] in the resources.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the file is missing or invalid, see [`parse`](Self::parse).
+    pub fn load(resources: &ResourceManager, blocks: &BlockRegistry) -> Result<Self, String> {
+        let source = resources.read_to_string(MAPPING_FILE)?;
+        Self::parse(&source, blocks).map_err(|e| format!("{MAPPING_FILE}: {e}"))
+    }
+
+    /// Parses a mapping table written in TOML.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the TOML is invalid, a Minecraft name is malformed or a target is
+    ///   not a state of `blocks`.
+    pub fn parse(source: &str, blocks: &BlockRegistry) -> Result<Self, String> {
+        let definition: MappingDefinition = toml::from_str(source).map_err(|e| e.to_string())?;
+        let fallback = blocks.parse_state(&definition.fallback).map_err(|e| format!("fallback: {e}"))?;
+        let mut rules: HashMap<String, Vec<Rule>> = HashMap::new();
+
+        for (source, target) in &definition.blocks {
+            let (name, properties) = split_state(source).ok_or_else(|| format!("invalid block state: {source}"))?;
+            let state = blocks.parse_state(target).map_err(|e| format!("{source}: {e}"))?;
+            let fixed = split_state(target).map(|(_, properties)| properties.into_iter().map(|(name, _)| name).collect()).unwrap_or_default();
+
+            rules.entry(namespaced(&name)).or_default().push(Rule { properties, target: state, fixed });
+        }
+
+        for rules in rules.values_mut() {
+            rules.sort_by_key(|rule| std::cmp::Reverse(rule.properties.len()));
+        }
+
+        Ok(Self { fallback, rules })
+    }
+
+    /// Returns the state unmapped blocks become.
+    pub fn fallback(&self) -> BlockState {
+        self.fallback
+    }
+
+    /// Returns our state for the Minecraft block called `name` with `properties`, or `None`
+    /// if it is unmapped.
+    pub fn get(&self, name: &str, properties: &[(String, String)], blocks: &BlockRegistry) -> Option<BlockState> {
+        let name = namespaced(name);
+        let matches = |rule: &&Rule| rule.properties.iter().all(|property| properties.contains(property));
+
+        let (target, fixed) = match self.rules.get(&name).and_then(|rules| rules.iter().find(matches)) {
+            Some(rule) => (rule.target, rule.fixed.as_slice()),
+            None => {
+                let path = name.strip_prefix(MINECRAFT_NAMESPACE).and_then(|name| name.strip_prefix(':'))?;
+                (blocks.default_state(path)?, [].as_slice())
+            }
+        };
+
+        let copied = properties.iter().filter(|(property, _)| !fixed.contains(property));
+        Some(copied.fold(target, |state, (property, value)| blocks.with_value(state, property, value).unwrap_or(state)))
+    }
+}
+
+/// Returns `name` with the Minecraft namespace if it has none.
+fn namespaced(name: &str) -> String {
+    match name.contains(':') {
+        true => name.to_string(),
+        false => format!("{MINECRAFT_NAMESPACE}:{name}"),
+    }
+}
+
+/// Splits a state such as `oak_log[axis=x]` into its block name and properties.
+fn split_state(state: &str) -> Option<(String, Vec<(String, String)>)> {
+    let Some((name, properties)) = state.split_once('[') else {
+        return Some((state.trim().to_string(), Vec::new()));
+    };
+
+    let properties = properties
+        .strip_suffix(']')?
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').map(|(property, value)| (property.trim().to_string(), value.trim().to_string())))
+        .collect::<Option<_>>()?;
+
+    Some((name.trim().to_string(), properties))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockRegistryBuilder;
+
+    const BLOCKS: &str = r#"
+        [stone]
+
+        [oak_log]
+        properties = { axis = ["x", "y", "z"] }
+
+        [short_grass]
+        render = "cutout"
+        collision = "empty"
+
+        [oak_slab]
+        properties = { type = ["bottom", "top", "double"], waterlogged = "boolean" }
+        collision = [[0, 0, 0, 16, 8, 16]]
+    "#;
+
+    const MAPPING: &str = r#"
+        fallback = "stone"
+
+        [blocks]
+        deepslate = "stone"
+        spruce_log = "oak_log"
+        tall_grass = "short_grass"
+        "tall_grass[half=upper]" = "air"
+        "petrified_oak_slab[type=double]" = "oak_slab[type=bottom]"
+    "#;
+
+    fn setup() -> (BlockRegistry, BlockMapping) {
+        let mut builder = BlockRegistryBuilder::new();
+        builder.load_str(crate::block::DEFAULT_NAMESPACE, BLOCKS).unwrap();
+        let blocks = builder.build().unwrap();
+        let mapping = BlockMapping::parse(MAPPING, &blocks).unwrap();
+        (blocks, mapping)
+    }
+
+    fn properties(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn maps_blocks_by_entry_then_by_name() {
+        let (blocks, mapping) = setup();
+        let stone = blocks.parse_state("stone").unwrap();
+
+        assert_eq!(mapping.get("minecraft:deepslate", &[], &blocks), Some(stone));
+        assert_eq!(mapping.get("deepslate", &[], &blocks), Some(stone));
+        assert_eq!(mapping.get("minecraft:stone", &[], &blocks), Some(stone));
+        assert_eq!(mapping.get("minecraft:diamond_block", &[], &blocks), None);
+        assert_eq!(mapping.get("othermod:stone", &[], &blocks), None);
+        assert_eq!(mapping.fallback(), stone);
+    }
+
+    #[test]
+    fn prefers_the_entry_with_the_most_properties() {
+        let (blocks, mapping) = setup();
+
+        let upper = properties(&[("half", "upper")]);
+        let lower = properties(&[("half", "lower")]);
+
+        assert_eq!(mapping.get("tall_grass", &upper, &blocks), Some(BlockState::AIR));
+        assert_eq!(mapping.get("tall_grass", &lower, &blocks), blocks.parse_state("short_grass").ok());
+        assert_eq!(mapping.get("petrified_oak_slab", &properties(&[("type", "top")]), &blocks), None);
+    }
+
+    #[test]
+    fn copies_properties_the_target_does_not_set() {
+        let (blocks, mapping) = setup();
+
+        let log = mapping.get("spruce_log", &properties(&[("axis", "x")]), &blocks);
+        assert_eq!(log, blocks.parse_state("oak_log[axis=x]").ok());
+
+        let slab = mapping.get("petrified_oak_slab", &properties(&[("type", "double"), ("waterlogged", "true")]), &blocks);
+        assert_eq!(slab, blocks.parse_state("oak_slab[type=bottom,waterlogged=true]").ok());
+
+        let slab = mapping.get("oak_slab", &properties(&[("type", "top"), ("unknown", "1")]), &blocks);
+        assert_eq!(slab, blocks.parse_state("oak_slab[type=top]").ok());
+    }
+
+    #[test]
+    fn rejects_unknown_targets() {
+        let (blocks, _) = setup();
+        assert!(BlockMapping::parse("fallback = \"stone\"\n[blocks]\nstone = \"marble\"\n", &blocks).is_err());
+        assert!(BlockMapping::parse("fallback = \"marble\"\n", &blocks).is_err());
+    }
+}
@@ -0,0 +1,9 @@
+mod chunk;
+mod level;
+mod mapping;
+mod world;
+
+pub use chunk::{convert_chunk, ConvertedChunk};
+pub use level::{read_java_level, JAVA_LEVEL_FILE};
+pub use mapping::{BlockMapping, MAPPING_FILE};
+pub use world::{import_world, ImportReport};
@@ -0,0 +1,241 @@
+use super::{convert_chunk, read_java_level, BlockMapping};
+use crate::block::BlockRegistry;
+use crate::nbt::{self, Compound, Endian, Tag};
+use crate::save::{encode_chunk, Compression, RegionFile, RegionPos, REGION_DIR};
+use crate::world::ChunkPos;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Flag of the compression byte of chunks too large for their region file, stored in a
+/// `c.<x>.<z>.mcc` file next to it.
+const EXTERNAL_CHUNK: u8 = 0x80;
+
+/// Directory next to the region directory of the destination that regions are imported into,
+/// until all of them are.
+const STAGING_DIR: &str = "region.importing";
+
+/// Name the region directory of the destination is moved to while the imported one takes its
+/// place.
+const REPLACED_DIR: &str = "region.replaced";
+
+/// What importing a world did and left out.
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+    /// Number of region files read.
+    pub regions: usize,
+
+    /// Number of chunks imported.
+    pub chunks: usize,
+
+    /// Number of chunks Minecraft had not finished generating, which were left for our
+    /// generator.
+    pub incomplete: usize,
+
+    /// Why each region or chunk that could not be imported failed.
+    pub failures: Vec<String>,
+
+    /// Number of blocks of each unmapped Minecraft block, which became the fallback block.
+    pub unmapped: HashMap<String, u64>,
+
+    /// Number of sections holding blocks that were left out, being below y 0 or from y 256
+    /// up.
+    pub clipped_sections: usize,
+}
+
+impl ImportReport {
+    /// Returns the unmapped blocks with their number of blocks, the most common first.
+    pub fn unmapped_by_count(&self) -> Vec<(&str, u64)> {
+        let mut unmapped: Vec<(&str, u64)> = self.unmapped.iter().map(|(name, count)| (name.as_str(), *count)).collect();
+        unmapped.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        unmapped
+    }
+}
+
+/// Imports the overworld of the Minecraft Java world in `source` into our world in
+/// `destination`, mapping blocks through `mapping` and compressing chunks with
+/// `compression`.
+///
+/// Each region file of the source becomes one of ours and its `level.dat` becomes our level
+/// data, written last. Regions are written to a directory of their own, which only replaces
+/// the region directory of the destination once every region is imported, so no chunk of a
+/// world imported over is left behind and a failed import leaves it as it was. Chunks that fail to convert are
+/// reported and left out, to be generated again. `progress` is called after each region
+/// with the number of regions done and the total.
+///
+/// # Returns
+/// * `Err(String)` if the source has no readable `level.dat` or region directory, or the
+///   destination cannot be written.
+pub fn import_world(
+    source: &Path,
+    destination: &Path,
+    blocks: &BlockRegistry,
+    mapping: &BlockMapping,
+    compression: Compression,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<ImportReport, String> {
+    let level = read_java_level(source)?;
+    let region_dir = source.join(REGION_DIR);
+    let entries = std::fs::read_dir(&region_dir).map_err(|e| format!("{}: {e}", region_dir.display()))?;
+    let mut regions = Vec::new();
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("{}: {e}", region_dir.display()))?;
+
+        if let Some(pos) = entry.file_name().to_str().and_then(parse_region_name) {
+            regions.push(pos);
+        }
+    }
+
+    regions.sort_by_key(|pos| (pos.x, pos.z));
+
+    // Left behind by an import that was interrupted.
+    let staging = destination.join(STAGING_DIR);
+    remove_dir(&staging)?;
+    std::fs::create_dir_all(&staging).map_err(|e| format!("{}: {e}", staging.display()))?;
+
+    let report = match import_regions(&region_dir, &regions, &staging, blocks, mapping, compression, &mut progress) {
+        Ok(report) => report,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+
+    replace_dir(&staging, &destination.join(REGION_DIR), &destination.join(REPLACED_DIR))?;
+    level.save(destination)?;
+    Ok(report)
+}
+
+/// Imports the Minecraft `regions` of `region_dir` as our region files in `output_dir`.
+fn import_regions(
+    region_dir: &Path,
+    regions: &[RegionPos],
+    output_dir: &Path,
+    blocks: &BlockRegistry,
+    mapping: &BlockMapping,
+    compression: Compression,
+    progress: &mut impl FnMut(usize, usize),
+) -> Result<ImportReport, String> {
+    let mut report = ImportReport::default();
+
+    for (done, &region_pos) in regions.iter().enumerate() {
+        let path = region_dir.join(format!("r.{}.{}.mca", region_pos.x, region_pos.z));
+        let bytes = std::fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+
+        // Minecraft leaves empty region files behind for regions it only looked into.
+        if bytes.is_empty() {
+            progress(done + 1, regions.len());
+            continue;
+        }
+
+        let region = match RegionFile::from_bytes(&bytes) {
+            Ok(region) => region,
+            Err(e) => {
+                report.failures.push(format!("{}: {e}", path.display()));
+                progress(done + 1, regions.len());
+                continue;
+            }
+        };
+
+        let mut output = RegionFile::new();
+        report.regions += 1;
+
+        for (pos, reason) in region.skipped(region_pos) {
+            report.failures.push(format!("{}: chunk {}, {}: {reason}", path.display(), pos.x, pos.z));
+        }
+
+        for pos in region.positions(region_pos) {
+            let converted = read_chunk(&region, pos, region_dir).and_then(|root| convert_chunk(&root, pos, blocks, mapping));
+
+            let converted = match converted {
+                Ok(Some(converted)) => converted,
+                Ok(None) => {
+                    report.incomplete += 1;
+                    continue;
+                }
+                Err(e) => {
+                    report.failures.push(format!("{}: chunk {}, {}: {e}", path.display(), pos.x, pos.z));
+                    continue;
+                }
+            };
+
+            let timestamp = region.timestamp(pos).unwrap_or(0);
+
+            if let Err(e) = output.insert(pos, &encode_chunk(&converted.chunk, blocks), compression, timestamp) {
+                report.failures.push(e);
+                continue;
+            }
+
+            report.chunks += 1;
+            report.clipped_sections += converted.clipped_sections;
+
+            for (name, count) in converted.unmapped {
+                *report.unmapped.entry(name).or_default() += count;
+            }
+        }
+
+        if !output.is_empty() {
+            output.write(output_dir.join(region_pos.file_name()))?;
+        }
+
+        progress(done + 1, regions.len());
+    }
+
+    Ok(report)
+}
+
+/// Puts the directory `new` in place of `target`, moving `target` out of the way to `old`
+/// first as a directory cannot be renamed over one that is not empty.
+fn replace_dir(new: &Path, target: &Path, old: &Path) -> Result<(), String> {
+    remove_dir(old)?;
+
+    if target.exists() {
+        std::fs::rename(target, old).map_err(|e| format!("{}: {e}", target.display()))?;
+    }
+
+    std::fs::rename(new, target).map_err(|e| format!("{}: {e}", target.display()))?;
+    remove_dir(old)
+}
+
+/// Removes the directory at `path` and everything in it, if it exists.
+fn remove_dir(path: &Path) -> Result<(), String> {
+    match std::fs::remove_dir_all(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("{}: {e}", path.display())),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the position of the region in a Minecraft region file called `r.<x>.<z>.mca`.
+fn parse_region_name(name: &str) -> Option<RegionPos> {
+    let (x, z) = name.strip_prefix("r.")?.strip_suffix(".mca")?.split_once('.')?;
+    Some(RegionPos { x: x.parse().ok()?, z: z.parse().ok()? })
+}
+
+/// Reads the NBT of the chunk at `pos` of a Minecraft region, from the region itself or from
+/// the file of its own next to it.
+fn read_chunk(region: &RegionFile, pos: ChunkPos, region_dir: &Path) -> Result<Compound, String> {
+    let (compression, data) = region.get_raw(pos).ok_or("missing from its region")?;
+
+    let external;
+    let data = match compression & EXTERNAL_CHUNK != 0 {
+        true => {
+            let path = region_dir.join(format!("c.{}.{}.mcc", pos.x, pos.z));
+            external = std::fs::read(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+            external.as_slice()
+        }
+        false => data,
+    };
+
+    // NBT reading tells gzip (1), zlib (2) and uncompressed data (3) apart by itself.
+    match compression & !EXTERNAL_CHUNK {
+        1..=3 => {}
+        4 => return Err(String::from("compressed with LZ4, which cannot be imported; set region-file-compression to deflate and optimize the world in Minecraft first")),
+        other => return Err(format!("unknown compression {other}")),
+    }
+
+    match nbt::read_compressed(data, Endian::Big)?.1 {
+        Tag::Compound(root) => Ok(root),
+        tag => Err(format!("root {} instead of a compound", tag.tag_type().name())),
+    }
+}
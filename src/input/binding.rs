@@ -0,0 +1,126 @@
+use super::InputSource;
+
+use serde::{Deserialize, Serialize};
+use glfw::{Key, Modifiers};
+use std::fmt;
+
+/// A combination of inputs that triggers an action.
+///
+/// A binding is active while all of its inputs and modifiers are held. Bindings with several
+/// inputs act as chords, such as `F3+B`. Modifiers are matched against the modifier keys held,
+/// whichever side of the keyboard they are on. Other modifiers may be held as well, so `W`
+/// keeps walking while `Ctrl` sprints, but a binding gives way to an active one needing the
+/// same inputs and more modifiers: holding `Ctrl+Q` does not trigger `Q`.
+///
+/// Bindings are written as their parts joined with `+`, modifiers first:
+/// `W`, `Ctrl+Q`, `Shift+Mouse1`, `F3+G`, `Gamepad.A`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Binding {
+    /// Modifier keys that must be held.
+    modifiers: Modifiers,
+
+    /// Inputs that must all be active, the last one being the trigger.
+    inputs: Vec<InputSource>,
+}
+
+/// Names of the modifiers in binding strings, in the order they are written.
+const MODIFIERS: [(Modifiers, &str); 4] = [
+    (Modifiers::Control, "Ctrl"),
+    (Modifiers::Shift, "Shift"),
+    (Modifiers::Alt, "Alt"),
+    (Modifiers::Super, "Super"),
+];
+
+impl Binding {
+    /// Creates a binding triggered by a single input.
+    pub fn new(input: InputSource) -> Self {
+        Self { modifiers: Modifiers::empty(), inputs: vec![input] }
+    }
+
+    /// Creates a binding triggered by holding all `inputs` along with `modifiers`.
+    ///
+    /// # Panics
+    /// Panics if `inputs` is empty.
+    pub fn chord(modifiers: Modifiers, inputs: Vec<InputSource>) -> Self {
+        assert!(!inputs.is_empty(), "a binding needs at least one input");
+        Self { modifiers, inputs }
+    }
+
+    /// Creates a binding triggered by `key` without modifiers.
+    pub fn key(key: Key) -> Self {
+        Self::new(InputSource::Key(key))
+    }
+
+    /// Returns the modifiers that must be held.
+    pub fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Returns the inputs that must be active.
+    pub fn inputs(&self) -> &[InputSource] {
+        &self.inputs
+    }
+
+    /// Returns whether this binding is satisfied given a predicate telling which inputs are
+    /// active and the modifiers currently held.
+    pub fn is_active(&self, modifiers: Modifiers, is_down: impl Fn(InputSource) -> bool) -> bool {
+        modifiers.contains(self.modifiers) && self.inputs.iter().all(|&input| is_down(input))
+    }
+
+    /// Returns whether `other` gives way to this binding while both are active, this one
+    /// needing every input of `other` and more modifiers, as `Ctrl+Q` does over `Q`.
+    pub fn overrides(&self, other: &Binding) -> bool {
+        self.modifiers != other.modifiers && self.modifiers.contains(other.modifiers) && other.inputs.iter().all(|input| self.inputs.contains(input))
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = MODIFIERS
+            .iter()
+            .filter(|(modifier, _)| self.modifiers.contains(*modifier))
+            .map(|(_, name)| name.to_string());
+
+        let inputs = self.inputs.iter().map(|input| input.to_string());
+        let parts: Vec<_> = modifiers.chain(inputs).collect();
+
+        f.write_str(&parts.join("+"))
+    }
+}
+
+impl std::str::FromStr for Binding {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::empty();
+        let mut inputs = Vec::new();
+
+        for part in text.split('+').map(str::trim) {
+            match MODIFIERS.iter().find(|(_, name)| name.eq_ignore_ascii_case(part)) {
+                Some(&(modifier, _)) => modifiers |= modifier,
+                None => inputs.push(part.parse()?),
+            }
+        }
+
+        if inputs.is_empty() {
+            return Err(format!("binding has no input: {text}"));
+        }
+
+        Ok(Self { modifiers, inputs })
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> Self {
+        binding.to_string()
+    }
+}
@@ -0,0 +1,203 @@
+use super::{action, axis, AnalogSource, Binding, InputSource, ScrollDirection};
+
+use serde::{Deserialize, Serialize};
+use glfw::{GamepadAxis, GamepadButton, Key, MouseButton};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How an axis combines actions and analog inputs into a single value.
+///
+/// The digital part is `1` while the positive action is held, `-1` while the negative one is,
+/// and `0` when both or neither are. Analog inputs are added on top, ignoring gamepad values
+/// within the dead zone, and the sum is multiplied by `scale`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AxisBinding {
+    /// Action pushing the axis towards `1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub positive: Option<String>,
+
+    /// Action pushing the axis towards `-1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub negative: Option<String>,
+
+    /// Continuous inputs added to the axis value.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub analog: Vec<AnalogSource>,
+
+    /// Gamepad axis values with a magnitude below this are treated as zero.
+    #[serde(default = "default_deadzone")]
+    pub deadzone: f32,
+
+    /// Multiplier applied to the final value, e.g. mouse sensitivity.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+}
+
+fn default_deadzone() -> f32 { 0.15 }
+fn default_scale() -> f32 { 1.0 }
+
+impl AxisBinding {
+    /// Creates an axis driven by a pair of actions.
+    pub fn digital(positive: &str, negative: &str) -> Self {
+        Self {
+            positive: Some(positive.to_string()),
+            negative: Some(negative.to_string()),
+            analog: Vec::new(),
+            deadzone: default_deadzone(),
+            scale: default_scale(),
+        }
+    }
+
+    /// Creates an axis driven by continuous inputs only.
+    pub fn analog(sources: Vec<AnalogSource>) -> Self {
+        Self { positive: None, negative: None, analog: sources, ..Self::digital("", "") }
+    }
+
+    /// Adds a continuous input to this axis.
+    pub fn with_analog(mut self, source: AnalogSource) -> Self {
+        self.analog.push(source);
+        self
+    }
+}
+
+/// The mapping from inputs to named actions and axes, stored as TOML.
+///
+/// # Example
+///
+/// ```toml
+/// [actions]
+/// move_forward = ["W", "Up"]
+/// jump = ["Space", "Gamepad.A"]
+/// toggle_hitboxes = ["Ctrl+B"]
+///
+/// [axes.look_x]
+/// analog = ["Gamepad.RightX"]
+/// deadzone = 0.2
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bindings {
+    /// Bindings of each action. Any of them activates the action.
+    #[serde(default)]
+    pub actions: BTreeMap<String, Vec<Binding>>,
+
+    /// Axes, by name.
+    #[serde(default)]
+    pub axes: BTreeMap<String, AxisBinding>,
+}
+
+impl Bindings {
+    /// Loads bindings from `path`, falling back to the defaults if the file does not exist.
+    ///
+    /// Actions and axes missing from the file keep their default bindings, so new actions
+    /// show up in old configuration files.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+
+        let source = match std::fs::read_to_string(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+            Ok(source) => source,
+        };
+
+        let loaded: Self = toml::from_str(&source).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut bindings = Self::default();
+
+        bindings.actions.extend(loaded.actions);
+        bindings.axes.extend(loaded.axes);
+
+        Ok(bindings)
+    }
+
+    /// Writes the bindings to `path`, creating parent directories as needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let source = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("{}: {e}", parent.display()))?;
+        }
+
+        std::fs::write(path, source).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Returns the bindings of `action`.
+    pub fn get(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Adds `binding` to `action`, unless it is already bound to it.
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        let bindings = self.actions.entry(action.to_string()).or_default();
+
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Replaces every binding of `action` with `binding`.
+    pub fn rebind(&mut self, action: &str, binding: Binding) {
+        self.actions.insert(action.to_string(), vec![binding]);
+    }
+
+    /// Removes every binding of `action`.
+    pub fn unbind(&mut self, action: &str) {
+        self.actions.insert(action.to_string(), Vec::new());
+    }
+
+    /// Returns the actions other than `action` that share one of its bindings.
+    pub fn conflicts(&self, action: &str) -> Vec<&str> {
+        let bindings = self.get(action);
+
+        self.actions
+            .iter()
+            .filter(|(name, _)| name.as_str() != action)
+            .filter(|(_, other)| other.iter().any(|binding| bindings.contains(binding)))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use InputSource::{GamepadAxis as Stick, GamepadButton as Button, Mouse, Scroll};
+
+        let key = Binding::key;
+        let input = Binding::new;
+
+        let actions = [
+            (action::MOVE_FORWARD, vec![key(Key::W), input(Stick(GamepadAxis::AxisLeftY, false))]),
+            (action::MOVE_BACKWARD, vec![key(Key::S), input(Stick(GamepadAxis::AxisLeftY, true))]),
+            (action::MOVE_LEFT, vec![key(Key::A), input(Stick(GamepadAxis::AxisLeftX, false))]),
+            (action::MOVE_RIGHT, vec![key(Key::D), input(Stick(GamepadAxis::AxisLeftX, true))]),
+            (action::JUMP, vec![key(Key::Space), input(Button(GamepadButton::ButtonA))]),
+            (action::SNEAK, vec![key(Key::LeftShift), input(Button(GamepadButton::ButtonRightThumb))]),
+            (action::SPRINT, vec![key(Key::LeftControl), input(Button(GamepadButton::ButtonLeftThumb))]),
+            (action::TOGGLE_FLY, vec![key(Key::F)]),
+            (action::ATTACK, vec![input(Mouse(MouseButton::Button1)), input(Stick(GamepadAxis::AxisRightTrigger, true))]),
+            (action::USE, vec![input(Mouse(MouseButton::Button2)), input(Stick(GamepadAxis::AxisLeftTrigger, true))]),
+            (action::PICK_BLOCK, vec![input(Mouse(MouseButton::Button3))]),
+            (action::OPEN_INVENTORY, vec![key(Key::E), input(Button(GamepadButton::ButtonY))]),
+            (action::DROP_ITEM, vec![key(Key::Q), input(Button(GamepadButton::ButtonB))]),
+            (action::HOTBAR_NEXT, vec![input(Scroll(ScrollDirection::Down)), input(Button(GamepadButton::ButtonRightBumper))]),
+            (action::HOTBAR_PREVIOUS, vec![input(Scroll(ScrollDirection::Up)), input(Button(GamepadButton::ButtonLeftBumper))]),
+            (action::PAUSE, vec![key(Key::Escape), input(Button(GamepadButton::ButtonStart))]),
+            (action::TOGGLE_DEBUG, vec![key(Key::F3)]),
+            (action::TOGGLE_FULLSCREEN, vec![key(Key::F11)]),
+            (action::SCREENSHOT, vec![key(Key::F2)]),
+            // Not a chord with F3, which would toggle the debug overlay on its way.
+            (action::RELOAD_RESOURCES, vec![key(Key::F4)]),
+        ];
+
+        let axes = [
+            (axis::MOVE_X, AxisBinding::digital(action::MOVE_RIGHT, action::MOVE_LEFT)),
+            (axis::MOVE_Z, AxisBinding::digital(action::MOVE_FORWARD, action::MOVE_BACKWARD)),
+            (axis::LOOK_X, AxisBinding::analog(vec![AnalogSource::Gamepad(GamepadAxis::AxisRightX)])),
+            (axis::LOOK_Y, AxisBinding::analog(vec![AnalogSource::Gamepad(GamepadAxis::AxisRightY)])),
+        ];
+
+        Self {
+            actions: actions.into_iter().map(|(name, bindings)| (name.to_string(), bindings)).collect(),
+            axes: axes.into_iter().map(|(name, axis)| (name.to_string(), axis)).collect(),
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use super::{AnalogSource, Binding, Bindings, InputSource, ScrollDirection};
+
+use glfw::{Action, GamepadAxis, GamepadButton, GamepadState, Key, Modifiers, WindowEvent};
+use std::collections::{HashMap, HashSet};
+
+/// Gamepad axes pushed past this point count as pressed when bound as buttons.
+const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+/// The state of an action during the current tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ButtonState {
+    /// The action became active this tick.
+    pub pressed: bool,
+
+    /// The action is active this tick.
+    pub held: bool,
+
+    /// The action stopped being active this tick.
+    pub released: bool,
+}
+
+/// Turns raw window events into per-tick action and axis states.
+///
+/// Events are fed with [`InputMap::handle_event`] as they arrive, then [`InputMap::update`] is
+/// called once at the start of every simulation tick to evaluate the bindings. Inputs pressed
+/// and released between two ticks still register as pressed for one tick, so quick taps are
+/// never lost regardless of the frame rate.
+pub struct InputMap {
+    bindings: Bindings,
+
+    /// Inputs currently held down.
+    down: HashSet<InputSource>,
+
+    /// Inputs pressed since the last update, including ones already released.
+    tapped: HashSet<InputSource>,
+
+    /// Last known cursor position, if any.
+    cursor: Option<(f64, f64)>,
+
+    /// Cursor motion accumulated since the last update.
+    motion: (f64, f64),
+
+    /// Cursor motion accumulated since the last call to [`InputMap::take_frame_motion`].
+    frame_motion: (f64, f64),
+
+    /// Current value of every gamepad axis, indexed by GLFW axis id, triggers in `[0, 1]`.
+    gamepad_axes: [f32; 6],
+
+    actions: HashMap<String, ButtonState>,
+    axes: HashMap<String, f32>,
+
+    /// Set while waiting for the next input to rebind an action.
+    capture: Option<Option<Binding>>,
+}
+
+impl InputMap {
+    /// Creates an input map evaluating the given bindings.
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            down: HashSet::new(),
+            tapped: HashSet::new(),
+            cursor: None,
+            motion: (0.0, 0.0),
+            frame_motion: (0.0, 0.0),
+            gamepad_axes: [0.0; 6],
+            actions: HashMap::new(),
+            axes: HashMap::new(),
+            capture: None,
+        }
+    }
+
+    /// Returns the active bindings.
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    /// Returns the active bindings mutably. Changes apply from the next update.
+    pub fn bindings_mut(&mut self) -> &mut Bindings {
+        &mut self.bindings
+    }
+
+    /// Records a window event. Events other than input events are ignored.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::Key(key, _, action, _) => self.set(InputSource::Key(key), action),
+            WindowEvent::MouseButton(button, action, _) => self.set(InputSource::Mouse(button), action),
+
+            WindowEvent::Scroll(x, y) => {
+                let directions = [
+                    (y > 0.0, ScrollDirection::Up),
+                    (y < 0.0, ScrollDirection::Down),
+                    (x < 0.0, ScrollDirection::Left),
+                    (x > 0.0, ScrollDirection::Right),
+                ];
+
+                for (_, direction) in directions.into_iter().filter(|(scrolled, _)| *scrolled) {
+                    self.tap(InputSource::Scroll(direction));
+                }
+            }
+
+            WindowEvent::CursorPos(x, y) => {
+                if let Some((last_x, last_y)) = self.cursor {
+                    self.motion.0 += x - last_x;
+                    self.motion.1 += y - last_y;
+                    self.frame_motion.0 += x - last_x;
+                    self.frame_motion.1 += y - last_y;
+                }
+
+                self.cursor = Some((x, y));
+            }
+
+            // Releases are not reported while unfocused, so forget everything to avoid
+            // actions getting stuck.
+            WindowEvent::Focus(false) => {
+                self.down.clear();
+                self.cursor = None;
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Records the state of the active gamepad, or `None` if no gamepad is connected.
+    ///
+    /// Should be called once per frame, before [`InputMap::update`].
+    pub fn update_gamepad(&mut self, state: Option<&GamepadState>) {
+        for id in 0..15 {
+            let Some(button) = GamepadButton::from_i32(id) else { continue };
+            let action = state.map_or(Action::Release, |state| state.get_button_state(button));
+
+            self.set(InputSource::GamepadButton(button), action);
+        }
+
+        for id in 0..6 {
+            let Some(axis) = GamepadAxis::from_i32(id) else { continue };
+            let value = state.map_or(0.0, |state| normalize_axis(axis, state.get_axis(axis)));
+
+            self.set_gamepad_axis(axis, value);
+        }
+    }
+
+    /// Records the value of a gamepad axis, pressing or releasing its directions.
+    fn set_gamepad_axis(&mut self, axis: GamepadAxis, value: f32) {
+        self.gamepad_axes[axis as usize] = value;
+
+        for positive in [true, false] {
+            let pushed = if positive { value > AXIS_PRESS_THRESHOLD } else { value < -AXIS_PRESS_THRESHOLD };
+            let action = if pushed { Action::Press } else { Action::Release };
+
+            self.set(InputSource::GamepadAxis(axis, positive), action);
+        }
+    }
+
+    /// Evaluates every binding against the inputs received since the last update.
+    ///
+    /// Call exactly once per simulation tick, before reading any action or axis.
+    pub fn update(&mut self) {
+        let modifiers = self.modifiers();
+
+        if let Some(None) = self.capture {
+            let trigger = self.tapped.iter().copied().find(|&input| !is_modifier_key(input));
+            self.capture = Some(trigger.map(|input| Binding::chord(modifiers, vec![input])));
+        }
+
+        let down = &self.down;
+        let tapped = &self.tapped;
+        let is_down = |input| down.contains(&input) || tapped.contains(&input);
+
+        let active: Vec<(&str, &Binding)> = self
+            .bindings
+            .actions
+            .iter()
+            .flat_map(|(action, bindings)| bindings.iter().map(move |binding| (action.as_str(), binding)))
+            .filter(|(_, binding)| binding.is_active(modifiers, is_down))
+            .collect();
+
+        for action in self.bindings.actions.keys() {
+            let held = active.iter().any(|&(name, binding)| name == action && !active.iter().any(|(_, other)| other.overrides(binding)));
+            let was_held = self.actions.get(action).is_some_and(|state| state.held);
+
+            let state = ButtonState { pressed: held && !was_held, held, released: was_held && !held };
+            self.actions.insert(action.clone(), state);
+        }
+
+        self.actions.retain(|action, _| self.bindings.actions.contains_key(action));
+
+        let mut axes = HashMap::with_capacity(self.bindings.axes.len());
+
+        for (name, axis) in &self.bindings.axes {
+            let held = |action: &Option<String>| action.as_deref().is_some_and(|a| self.held(a));
+            let digital = held(&axis.positive) as i32 - held(&axis.negative) as i32;
+
+            let analog: f32 = axis
+                .analog
+                .iter()
+                .map(|source| match *source {
+                    AnalogSource::MouseX => self.motion.0 as f32,
+                    AnalogSource::MouseY => self.motion.1 as f32,
+                    AnalogSource::Gamepad(gamepad_axis) => {
+                        let value = self.gamepad_axes[gamepad_axis as usize];
+                        if value.abs() < axis.deadzone { 0.0 } else { value }
+                    }
+                })
+                .sum();
+
+            axes.insert(name.clone(), (digital as f32 + analog) * axis.scale);
+        }
+
+        self.axes = axes;
+        self.tapped.clear();
+        self.motion = (0.0, 0.0);
+    }
+
+    /// Returns the state of `action` this tick.
+    pub fn state(&self, action: &str) -> ButtonState {
+        self.actions.get(action).copied().unwrap_or_default()
+    }
+
+    /// Returns whether `action` became active this tick.
+    pub fn pressed(&self, action: &str) -> bool {
+        self.state(action).pressed
+    }
+
+    /// Returns whether `action` is active this tick.
+    pub fn held(&self, action: &str) -> bool {
+        self.state(action).held
+    }
+
+    /// Returns whether `action` stopped being active this tick.
+    pub fn released(&self, action: &str) -> bool {
+        self.state(action).released
+    }
+
+    /// Returns the value of axis `name` this tick, or zero if there is no such axis.
+    pub fn axis(&self, name: &str) -> f32 {
+        self.axes.get(name).copied().unwrap_or_default()
+    }
+
+    /// Returns the cursor motion in pixels since the last call, and resets it.
+    ///
+    /// Unlike axes, which only change once per tick, this is meant to be read every frame
+    /// for responsive mouse look.
+    pub fn take_frame_motion(&mut self) -> (f32, f32) {
+        let (x, y) = std::mem::take(&mut self.frame_motion);
+        (x as f32, y as f32)
+    }
+
+    /// Starts listening for the next input, to let the player rebind an action.
+    pub fn start_capture(&mut self) {
+        self.capture = Some(None);
+    }
+
+    /// Returns the binding captured since [`InputMap::start_capture`], if an input was
+    /// pressed, and stops listening.
+    pub fn take_capture(&mut self) -> Option<Binding> {
+        let binding = self.capture.clone()??;
+        self.capture = None;
+        Some(binding)
+    }
+
+    /// Returns whether the map is waiting for an input to capture.
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    fn set(&mut self, input: InputSource, action: Action) {
+        match action {
+            Action::Press if !self.down.contains(&input) => self.tap(input),
+            Action::Release => {
+                let was_down = self.down.remove(&input);
+
+                // A modifier key pressed and released on its own is bound by itself.
+                if was_down && self.capture == Some(None) && is_modifier_key(input) {
+                    self.capture = Some(Some(Binding::new(input)));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn tap(&mut self, input: InputSource) {
+        if !matches!(input, InputSource::Scroll(_)) {
+            self.down.insert(input);
+        }
+
+        self.tapped.insert(input);
+    }
+
+    /// Returns the modifiers held, derived from the modifier keys down.
+    fn modifiers(&self) -> Modifiers {
+        let keys = [
+            (Key::LeftControl, Modifiers::Control),
+            (Key::RightControl, Modifiers::Control),
+            (Key::LeftShift, Modifiers::Shift),
+            (Key::RightShift, Modifiers::Shift),
+            (Key::LeftAlt, Modifiers::Alt),
+            (Key::RightAlt, Modifiers::Alt),
+            (Key::LeftSuper, Modifiers::Super),
+            (Key::RightSuper, Modifiers::Super),
+        ];
+
+        keys.into_iter()
+            .filter(|(key, _)| self.down.contains(&InputSource::Key(*key)))
+            .fold(Modifiers::empty(), |modifiers, (_, modifier)| modifiers | modifier)
+    }
+}
+
+/// Returns the value of `axis` read as `raw` from GLFW, moving triggers from `[-1, 1]` to
+/// `[0, 1]` so that they rest at zero like sticks do.
+fn normalize_axis(axis: GamepadAxis, raw: f32) -> f32 {
+    match axis {
+        GamepadAxis::AxisLeftTrigger | GamepadAxis::AxisRightTrigger => (raw + 1.0) / 2.0,
+        _ => raw,
+    }
+}
+
+fn is_modifier_key(input: InputSource) -> bool {
+    matches!(
+        input,
+        InputSource::Key(
+            Key::LeftControl | Key::RightControl | Key::LeftShift | Key::RightShift |
+            Key::LeftAlt | Key::RightAlt | Key::LeftSuper | Key::RightSuper
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{action, AxisBinding};
+
+    use std::collections::BTreeMap;
+
+    /// Returns an input map with each action of `actions` bound to the binding written.
+    fn map(actions: &[(&str, &str)]) -> InputMap {
+        let mut bindings = Bindings { actions: BTreeMap::new(), axes: BTreeMap::new() };
+
+        for (action, binding) in actions {
+            bindings.bind(action, binding.parse().unwrap());
+        }
+
+        InputMap::new(bindings)
+    }
+
+    fn key(map: &mut InputMap, key: Key, action: Action) {
+        map.handle_event(&WindowEvent::Key(key, 0, action, Modifiers::empty()));
+    }
+
+    #[test]
+    fn lets_bindings_with_more_modifiers_take_over() {
+        let mut map = map(&[("drop", "Q"), ("drop_stack", "Ctrl+Q"), ("quit", "Ctrl+Shift+Q")]);
+
+        key(&mut map, Key::LeftControl, Action::Press);
+        key(&mut map, Key::Q, Action::Press);
+        map.update();
+        assert!(map.pressed("drop_stack") && !map.held("drop") && !map.held("quit"));
+
+        key(&mut map, Key::RightShift, Action::Press);
+        map.update();
+        assert!(map.pressed("quit") && map.released("drop_stack") && !map.held("drop"));
+
+        key(&mut map, Key::LeftControl, Action::Release);
+        key(&mut map, Key::RightShift, Action::Release);
+        map.update();
+        assert!(map.pressed("drop") && map.released("quit"));
+    }
+
+    #[test]
+    fn keeps_bindings_active_with_other_modifiers_held() {
+        let mut map = InputMap::new(Bindings::default());
+
+        key(&mut map, Key::LeftControl, Action::Press);
+        key(&mut map, Key::W, Action::Press);
+        map.update();
+
+        assert!(map.held(action::SPRINT) && map.held(action::MOVE_FORWARD));
+    }
+
+    #[test]
+    fn reads_triggers_at_rest_as_released() {
+        let mut map = map(&[("use", "Gamepad.LeftTrigger"), ("rest", "Gamepad.LeftTriggerReleased"), ("walk", "Gamepad.LeftStickDown")]);
+        map.bindings_mut().axes.insert(String::from("trigger"), AxisBinding::analog(vec![AnalogSource::Gamepad(GamepadAxis::AxisLeftTrigger)]));
+
+        let set = |map: &mut InputMap, axis, raw| map.set_gamepad_axis(axis, normalize_axis(axis, raw));
+
+        set(&mut map, GamepadAxis::AxisLeftTrigger, -1.0);
+        set(&mut map, GamepadAxis::AxisLeftY, 0.0);
+        map.update();
+        assert!(!map.held("use") && !map.held("rest") && !map.held("walk"));
+        assert_eq!(map.axis("trigger"), 0.0);
+
+        set(&mut map, GamepadAxis::AxisLeftTrigger, 0.2);
+        set(&mut map, GamepadAxis::AxisLeftY, 0.8);
+        map.update();
+        assert!(map.pressed("use") && !map.held("rest") && map.pressed("walk"));
+        assert_eq!(map.axis("trigger"), 0.6);
+
+        map.update_gamepad(None);
+        map.update();
+        assert!(map.released("use") && map.released("walk"));
+    }
+}
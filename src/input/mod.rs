@@ -0,0 +1,48 @@
+mod input_map;
+mod bindings;
+mod binding;
+mod source;
+
+pub use source::{InputSource, AnalogSource, ScrollDirection};
+pub use input_map::{InputMap, ButtonState};
+pub use bindings::{Bindings, AxisBinding};
+pub use binding::Binding;
+
+/// Names of the actions bound by default.
+pub mod action {
+    pub const MOVE_FORWARD: &str = "move_forward";
+    pub const MOVE_BACKWARD: &str = "move_backward";
+    pub const MOVE_LEFT: &str = "move_left";
+    pub const MOVE_RIGHT: &str = "move_right";
+    pub const JUMP: &str = "jump";
+    pub const SNEAK: &str = "sneak";
+    pub const SPRINT: &str = "sprint";
+    pub const TOGGLE_FLY: &str = "toggle_fly";
+    pub const ATTACK: &str = "attack";
+    pub const USE: &str = "use";
+    pub const PICK_BLOCK: &str = "pick_block";
+    pub const OPEN_INVENTORY: &str = "open_inventory";
+    pub const DROP_ITEM: &str = "drop_item";
+    pub const HOTBAR_NEXT: &str = "hotbar_next";
+    pub const HOTBAR_PREVIOUS: &str = "hotbar_previous";
+    pub const PAUSE: &str = "pause";
+    pub const TOGGLE_DEBUG: &str = "toggle_debug";
+    pub const TOGGLE_FULLSCREEN: &str = "toggle_fullscreen";
+    pub const SCREENSHOT: &str = "screenshot";
+    pub const RELOAD_RESOURCES: &str = "reload_resources";
+}
+
+/// Names of the axes bound by default.
+pub mod axis {
+    /// Strafing, positive to the right.
+    pub const MOVE_X: &str = "move_x";
+
+    /// Walking, positive forwards.
+    pub const MOVE_Z: &str = "move_z";
+
+    /// Turning with a stick, positive to the right. Mouse look uses the cursor directly.
+    pub const LOOK_X: &str = "look_x";
+
+    /// Looking up and down with a stick, positive downwards.
+    pub const LOOK_Y: &str = "look_y";
+}
@@ -0,0 +1,213 @@
+use glfw::{GamepadAxis, GamepadButton, Key, MouseButton};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Generates the conversions between an enum's variants and their names in binding files.
+macro_rules! names {
+    ($name:ident, $parse:ident, $type:ident { $($variant:ident => $text:literal),* $(,)? }) => {
+        fn $name(value: $type) -> &'static str {
+            match value { $($type::$variant => $text),* }
+        }
+
+        fn $parse(text: &str) -> Option<$type> {
+            match text { $($text => Some($type::$variant),)* _ => None }
+        }
+    };
+}
+
+names!(key_name, parse_key, Key {
+    Space => "Space", Apostrophe => "Apostrophe", Comma => "Comma", Minus => "Minus",
+    Period => "Period", Slash => "Slash", Semicolon => "Semicolon", Equal => "Equal",
+    Num0 => "0", Num1 => "1", Num2 => "2", Num3 => "3", Num4 => "4",
+    Num5 => "5", Num6 => "6", Num7 => "7", Num8 => "8", Num9 => "9",
+    A => "A", B => "B", C => "C", D => "D", E => "E", F => "F", G => "G", H => "H", I => "I",
+    J => "J", K => "K", L => "L", M => "M", N => "N", O => "O", P => "P", Q => "Q", R => "R",
+    S => "S", T => "T", U => "U", V => "V", W => "W", X => "X", Y => "Y", Z => "Z",
+    LeftBracket => "LeftBracket", Backslash => "Backslash", RightBracket => "RightBracket",
+    GraveAccent => "GraveAccent", World1 => "World1", World2 => "World2",
+    Escape => "Escape", Enter => "Enter", Tab => "Tab", Backspace => "Backspace",
+    Insert => "Insert", Delete => "Delete", Right => "Right", Left => "Left", Down => "Down",
+    Up => "Up", PageUp => "PageUp", PageDown => "PageDown", Home => "Home", End => "End",
+    CapsLock => "CapsLock", ScrollLock => "ScrollLock", NumLock => "NumLock",
+    PrintScreen => "PrintScreen", Pause => "Pause",
+    F1 => "F1", F2 => "F2", F3 => "F3", F4 => "F4", F5 => "F5", F6 => "F6", F7 => "F7",
+    F8 => "F8", F9 => "F9", F10 => "F10", F11 => "F11", F12 => "F12", F13 => "F13",
+    F14 => "F14", F15 => "F15", F16 => "F16", F17 => "F17", F18 => "F18", F19 => "F19",
+    F20 => "F20", F21 => "F21", F22 => "F22", F23 => "F23", F24 => "F24", F25 => "F25",
+    Kp0 => "Keypad0", Kp1 => "Keypad1", Kp2 => "Keypad2", Kp3 => "Keypad3", Kp4 => "Keypad4",
+    Kp5 => "Keypad5", Kp6 => "Keypad6", Kp7 => "Keypad7", Kp8 => "Keypad8", Kp9 => "Keypad9",
+    KpDecimal => "KeypadDecimal", KpDivide => "KeypadDivide", KpMultiply => "KeypadMultiply",
+    KpSubtract => "KeypadSubtract", KpAdd => "KeypadAdd", KpEnter => "KeypadEnter",
+    KpEqual => "KeypadEqual", LeftShift => "LeftShift", LeftControl => "LeftControl",
+    LeftAlt => "LeftAlt", LeftSuper => "LeftSuper", RightShift => "RightShift",
+    RightControl => "RightControl", RightAlt => "RightAlt", RightSuper => "RightSuper",
+    Menu => "Menu", Unknown => "Unknown",
+});
+
+names!(mouse_name, parse_mouse, MouseButton {
+    Button1 => "Mouse1", Button2 => "Mouse2", Button3 => "Mouse3", Button4 => "Mouse4",
+    Button5 => "Mouse5", Button6 => "Mouse6", Button7 => "Mouse7", Button8 => "Mouse8",
+});
+
+names!(button_name, parse_button, GamepadButton {
+    ButtonA => "Gamepad.A", ButtonB => "Gamepad.B", ButtonX => "Gamepad.X",
+    ButtonY => "Gamepad.Y", ButtonLeftBumper => "Gamepad.LeftBumper",
+    ButtonRightBumper => "Gamepad.RightBumper", ButtonBack => "Gamepad.Back",
+    ButtonStart => "Gamepad.Start", ButtonGuide => "Gamepad.Guide",
+    ButtonLeftThumb => "Gamepad.LeftThumb", ButtonRightThumb => "Gamepad.RightThumb",
+    ButtonDpadUp => "Gamepad.DpadUp", ButtonDpadRight => "Gamepad.DpadRight",
+    ButtonDpadDown => "Gamepad.DpadDown", ButtonDpadLeft => "Gamepad.DpadLeft",
+});
+
+names!(axis_name, parse_axis, GamepadAxis {
+    AxisLeftX => "Gamepad.LeftX", AxisLeftY => "Gamepad.LeftY",
+    AxisRightX => "Gamepad.RightX", AxisRightY => "Gamepad.RightY",
+    AxisLeftTrigger => "Gamepad.LeftTrigger", AxisRightTrigger => "Gamepad.RightTrigger",
+});
+
+/// Names of gamepad axes pushed in one direction, used as buttons.
+const AXIS_DIRECTIONS: [(GamepadAxis, bool, &str); 12] = [
+    (GamepadAxis::AxisLeftX, false, "Gamepad.LeftStickLeft"),
+    (GamepadAxis::AxisLeftX, true, "Gamepad.LeftStickRight"),
+    (GamepadAxis::AxisLeftY, false, "Gamepad.LeftStickUp"),
+    (GamepadAxis::AxisLeftY, true, "Gamepad.LeftStickDown"),
+    (GamepadAxis::AxisRightX, false, "Gamepad.RightStickLeft"),
+    (GamepadAxis::AxisRightX, true, "Gamepad.RightStickRight"),
+    (GamepadAxis::AxisRightY, false, "Gamepad.RightStickUp"),
+    (GamepadAxis::AxisRightY, true, "Gamepad.RightStickDown"),
+    (GamepadAxis::AxisLeftTrigger, true, "Gamepad.LeftTrigger"),
+    (GamepadAxis::AxisLeftTrigger, false, "Gamepad.LeftTriggerReleased"),
+    (GamepadAxis::AxisRightTrigger, true, "Gamepad.RightTrigger"),
+    (GamepadAxis::AxisRightTrigger, false, "Gamepad.RightTriggerReleased"),
+];
+
+/// Direction of a scroll wheel step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// A physical input that can be pressed or released.
+///
+/// Sources are written in binding files by name: keys by their key name (`W`, `Space`,
+/// `LeftShift`, `F3`), mouse buttons as `Mouse1` to `Mouse8`, scroll steps as `ScrollUp` and
+/// friends, gamepad buttons as `Gamepad.A`, and gamepad axes pushed past half-way as
+/// `Gamepad.LeftStickUp` or `Gamepad.RightTrigger`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputSource {
+    Key(Key),
+    Mouse(MouseButton),
+    Scroll(ScrollDirection),
+    GamepadButton(GamepadButton),
+
+    /// A gamepad axis treated as a button, pressed when pushed past half-way in the given
+    /// direction (`true` for positive). Triggers only go from `0` at rest to `1`, so their
+    /// negative direction is never pressed.
+    GamepadAxis(GamepadAxis, bool),
+}
+
+/// A continuous input, read as a value rather than a pressed state.
+///
+/// Written `Mouse.X` and `Mouse.Y` for cursor motion, or by gamepad axis name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum AnalogSource {
+    /// Horizontal cursor motion in screen pixels.
+    MouseX,
+
+    /// Vertical cursor motion in screen pixels, positive downwards.
+    MouseY,
+
+    /// A gamepad axis in `[-1, 1]`, or `[0, 1]` for triggers.
+    Gamepad(GamepadAxis),
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Key(key) => f.write_str(key_name(key)),
+            Self::Mouse(button) => f.write_str(mouse_name(button)),
+            Self::Scroll(ScrollDirection::Up) => f.write_str("ScrollUp"),
+            Self::Scroll(ScrollDirection::Down) => f.write_str("ScrollDown"),
+            Self::Scroll(ScrollDirection::Left) => f.write_str("ScrollLeft"),
+            Self::Scroll(ScrollDirection::Right) => f.write_str("ScrollRight"),
+            Self::GamepadButton(button) => f.write_str(button_name(button)),
+            Self::GamepadAxis(axis, positive) => {
+                let (.., name) = AXIS_DIRECTIONS
+                    .iter()
+                    .find(|&&(a, p, _)| a == axis && p == positive)
+                    .expect("every axis direction is named");
+
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for InputSource {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let source = match text {
+            "ScrollUp" => Self::Scroll(ScrollDirection::Up),
+            "ScrollDown" => Self::Scroll(ScrollDirection::Down),
+            "ScrollLeft" => Self::Scroll(ScrollDirection::Left),
+            "ScrollRight" => Self::Scroll(ScrollDirection::Right),
+            "MouseLeft" => Self::Mouse(MouseButton::Button1),
+            "MouseRight" => Self::Mouse(MouseButton::Button2),
+            "MouseMiddle" => Self::Mouse(MouseButton::Button3),
+            _ => None
+                .or_else(|| parse_key(text).map(Self::Key))
+                .or_else(|| parse_mouse(text).map(Self::Mouse))
+                .or_else(|| parse_button(text).map(Self::GamepadButton))
+                .or_else(|| {
+                    let &(axis, positive, _) = AXIS_DIRECTIONS.iter().find(|(.., name)| *name == text)?;
+                    Some(Self::GamepadAxis(axis, positive))
+                })
+                .ok_or_else(|| format!("unknown input: {text}"))?,
+        };
+
+        Ok(source)
+    }
+}
+
+impl fmt::Display for AnalogSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::MouseX => f.write_str("Mouse.X"),
+            Self::MouseY => f.write_str("Mouse.Y"),
+            Self::Gamepad(axis) => f.write_str(axis_name(axis)),
+        }
+    }
+}
+
+impl std::str::FromStr for AnalogSource {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "Mouse.X" => Ok(Self::MouseX),
+            "Mouse.Y" => Ok(Self::MouseY),
+            _ => parse_axis(text)
+                .map(Self::Gamepad)
+                .ok_or_else(|| format!("unknown analog input: {text}")),
+        }
+    }
+}
+
+impl TryFrom<String> for AnalogSource {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<AnalogSource> for String {
+    fn from(source: AnalogSource) -> Self {
+        source.to_string()
+    }
+}
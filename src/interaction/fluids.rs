@@ -0,0 +1,523 @@
+use super::{BlockRules, BlockUpdates, WorldAccess};
+use crate::block::{BlockRegistry, Fluid};
+use crate::physics::BlockAccess;
+use crate::world::{BlockPos, BlockState, Direction};
+
+/// Levels of the `level` property, from a source at 0 to falling fluid at 8 and above.
+const LEVELS: usize = 16;
+
+/// First level of falling fluid, which fills its block whatever it flowed from.
+const FALLING: u8 = 8;
+
+/// Slope distance of directions without any way down within reach.
+const NO_SLOPE: u32 = 1000;
+
+/// How much of a fluid a block holds.
+///
+/// Levels follow the `level` property of fluid blocks: 0 is a source, 1 to 7 flowing fluid
+/// getting thinner away from it, and 8 or more fluid falling down. Waterlogged blocks hold a
+/// water source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FluidState {
+    pub fluid: Fluid,
+    pub level: u8,
+}
+
+impl FluidState {
+    /// Returns whether the fluid is a source, which stays without anything flowing into it.
+    pub fn is_source(self) -> bool {
+        self.level == 0
+    }
+
+    /// Returns whether the fluid is falling down.
+    pub fn is_falling(self) -> bool {
+        self.level >= FALLING
+    }
+
+    /// Returns the amount of fluid, from 8 for sources and falling fluid down to 1.
+    pub fn amount(self) -> u8 {
+        match self.is_source() || self.is_falling() {
+            true => 8,
+            false => 8 - self.level,
+        }
+    }
+}
+
+/// The blocks fluids are made of and harden into, looked up once.
+#[derive(Debug, Clone)]
+pub(super) struct FluidBlocks {
+    /// State of each level of each fluid, indexed by [`Fluid`], or empty if no block is made
+    /// of it.
+    levels: [Vec<BlockState>; Fluid::ALL.len()],
+
+    /// What lava flowing down onto water turns it into.
+    stone: BlockState,
+
+    /// What flowing lava touching water turns into.
+    cobblestone: BlockState,
+
+    /// What a lava source touching water turns into.
+    obsidian: BlockState,
+}
+
+impl FluidBlocks {
+    /// Looks up the first block made of each fluid in `blocks`, and the blocks lava hardens
+    /// into.
+    ///
+    /// # Returns
+    /// * `Err(String)` if `stone`, `cobblestone` or `obsidian` is missing.
+    pub(super) fn new(blocks: &BlockRegistry) -> Result<Self, String> {
+        let levels = Fluid::ALL.map(|fluid| {
+            let Some(block) = blocks.blocks().iter().find(|block| blocks.attributes(block.default_state()).fluid == Some(fluid)) else {
+                return Vec::new();
+            };
+
+            (0..LEVELS)
+                .map(|level| blocks.with_value(block.default_state(), "level", &level.to_string()).unwrap_or(block.default_state()))
+                .collect()
+        });
+
+        let find = |name: &str| blocks.default_state(name).ok_or_else(|| format!("fluids need a {name} block"));
+
+        Ok(Self { levels, stone: find("stone")?, cobblestone: find("cobblestone")?, obsidian: find("obsidian")? })
+    }
+
+    /// Returns the block state holding `fluid` at `level`, if a block is made of it.
+    pub(super) fn get(&self, fluid: Fluid, level: u8) -> Option<BlockState> {
+        self.levels[fluid as usize].get(level as usize).copied()
+    }
+}
+
+/// Returns what the lava at `pos` hardens into because it touches water from above or the
+/// side: obsidian for a source, cobblestone otherwise. Returns `None` for anything else.
+pub(super) fn hardened(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos, state: BlockState) -> Option<BlockState> {
+    let lava = rules.fluid(state).filter(|fluid| fluid.fluid == Fluid::Lava)?;
+
+    let touches_water = Direction::ALL
+        .into_iter()
+        .filter(|&direction| direction != Direction::Down)
+        .any(|direction| fluid_at(rules, world, pos.step(direction)).is_some_and(|fluid| fluid.fluid == Fluid::Water));
+
+    let blocks = rules.fluid_blocks();
+
+    touches_water.then_some(match lava.is_source() {
+        true => blocks.obsidian,
+        false => blocks.cobblestone,
+    })
+}
+
+/// Handles a scheduled tick of the fluid in `state` at `pos`: flowing fluid takes the level
+/// of what flows into it, or dries up, then the fluid spreads down if it can and sideways
+/// otherwise.
+pub(super) fn fluid_tick(updates: &mut BlockUpdates, rules: &BlockRules, world: &mut impl WorldAccess, pos: BlockPos, state: BlockState) {
+    let Some(mut fluid) = rules.fluid(state) else {
+        return;
+    };
+
+    if !fluid.is_source() {
+        match inflow(rules, world, pos, fluid.fluid) {
+            None => {
+                updates.set_block(world, pos, BlockState::AIR);
+                return;
+            }
+            Some(level) if level != fluid.level => {
+                let Some(state) = rules.fluid_block(fluid.fluid, level) else {
+                    return;
+                };
+
+                updates.set_block(world, pos, state);
+                fluid.level = level;
+            }
+            Some(_) => {}
+        }
+    }
+
+    spread(updates, rules, world, pos, fluid);
+}
+
+/// Returns the level of `fluid` flowing into `pos` from its neighbors, or `None` if none
+/// does.
+///
+/// Falling fluid comes from above; otherwise the fullest neighbor flows in, losing the
+/// fluid's drop-off. Infinite fluids make a new source between two sources above ground.
+fn inflow(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos, fluid: Fluid) -> Option<u8> {
+    let mut amount = 0;
+    let mut sources = 0;
+
+    for direction in Direction::HORIZONTAL {
+        if let Some(neighbor) = fluid_at(rules, world, pos.step(direction)).filter(|neighbor| neighbor.fluid == fluid) {
+            sources += neighbor.is_source() as u32;
+            amount = amount.max(neighbor.amount());
+        }
+    }
+
+    if fluid.is_infinite() && sources >= 2 {
+        let below = world.block(pos.step(Direction::Down));
+        let grounded = below.is_some_and(|below| match rules.fluid(below) {
+            Some(below) => below.fluid == fluid && below.is_source(),
+            None => !rules.is_washed_away(below),
+        });
+
+        if grounded {
+            return Some(0);
+        }
+    }
+
+    if fluid_at(rules, world, pos.step(Direction::Up)).is_some_and(|above| above.fluid == fluid) {
+        return Some(FALLING);
+    }
+
+    let amount = amount.saturating_sub(fluid.drop_off());
+    (amount > 0).then(|| 8 - amount)
+}
+
+/// Spreads `fluid` at `pos` into the blocks around it.
+///
+/// Fluid falls into the block below whenever it can, and only spreads sideways when it
+/// cannot, or from a source surrounded by other sources. Lava falling onto water turns it
+/// into stone.
+fn spread(updates: &mut BlockUpdates, rules: &BlockRules, world: &mut impl WorldAccess, pos: BlockPos, fluid: FluidState) {
+    let below = pos.step(Direction::Down);
+
+    if below.local().is_none() {
+        spread_sideways(updates, rules, world, pos, fluid);
+        return;
+    }
+
+    let Some(below_state) = world.block(below) else {
+        return;
+    };
+
+    if fluid.fluid == Fluid::Lava
+        && rules.fluid(below_state).is_some_and(|below| below.fluid == Fluid::Water)
+        && rules.is_fluid_block(below_state)
+    {
+        updates.set_block(world, below, rules.fluid_blocks().stone);
+        return;
+    }
+
+    if rules.is_washed_away(below_state) {
+        if let Some(falling) = rules.fluid_block(fluid.fluid, FALLING) {
+            flow_into(updates, rules, world, below, falling);
+        }
+
+        if fluid.is_source() && source_neighbors(rules, world, pos, fluid.fluid) >= 3 {
+            spread_sideways(updates, rules, world, pos, fluid);
+        }
+    }
+    else if fluid.is_source() || !is_hole(rules, world, pos, fluid.fluid) {
+        spread_sideways(updates, rules, world, pos, fluid);
+    }
+}
+
+/// Spreads `fluid` at `pos` sideways, one drop-off thinner, towards the nearest ways down
+/// within its slope distance or evenly if there are none.
+fn spread_sideways(updates: &mut BlockUpdates, rules: &BlockRules, world: &mut impl WorldAccess, pos: BlockPos, fluid: FluidState) {
+    let amount = match fluid.is_falling() {
+        true => 7,
+        false => fluid.amount().saturating_sub(fluid.fluid.drop_off()),
+    };
+
+    let Some(state) = rules.fluid_block(fluid.fluid, 8 - amount).filter(|_| amount > 0) else {
+        return;
+    };
+
+    for direction in spread_directions(rules, world, pos, fluid.fluid) {
+        flow_into(updates, rules, world, pos.step(direction), state);
+    }
+}
+
+/// Returns the horizontal directions `fluid` at `pos` spreads in: those leading to the
+/// nearest way down.
+fn spread_directions(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos, fluid: Fluid) -> Vec<Direction> {
+    let mut best = u32::MAX;
+    let mut directions = Vec::new();
+
+    for direction in Direction::HORIZONTAL {
+        let next = pos.step(direction);
+
+        if !world.block(next).is_some_and(|state| can_pass(rules, state, fluid)) {
+            continue;
+        }
+
+        let distance = match is_hole(rules, world, next, fluid) {
+            true => 0,
+            false => slope_distance(rules, world, next, 1, direction.opposite(), fluid),
+        };
+
+        if distance < best {
+            best = distance;
+            directions.clear();
+        }
+
+        if distance == best {
+            directions.push(direction);
+        }
+    }
+
+    directions
+}
+
+/// Returns how many blocks it takes from `pos` to a way down for `fluid`, not going back
+/// towards `from`, or [`NO_SLOPE`] if there is none within its slope distance.
+fn slope_distance(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos, depth: u32, from: Direction, fluid: Fluid) -> u32 {
+    let mut best = NO_SLOPE;
+
+    for direction in Direction::HORIZONTAL.into_iter().filter(|&direction| direction != from) {
+        let next = pos.step(direction);
+
+        if !world.block(next).is_some_and(|state| can_pass(rules, state, fluid)) {
+            continue;
+        }
+
+        if is_hole(rules, world, next, fluid) {
+            return depth;
+        }
+
+        if depth < fluid.slope_distance() {
+            best = best.min(slope_distance(rules, world, next, depth + 1, direction.opposite(), fluid));
+        }
+    }
+
+    best
+}
+
+/// Places fluid `state` at `pos` if the block there is washed away by fluids, dropping its
+/// items.
+fn flow_into(updates: &mut BlockUpdates, rules: &BlockRules, world: &mut impl WorldAccess, pos: BlockPos, state: BlockState) {
+    let Some(existing) = world.block(pos).filter(|&existing| rules.is_washed_away(existing)) else {
+        return;
+    };
+
+    if updates.set_block(world, pos, state).is_some() {
+        updates.drop_items(pos, rules.natural_drops(existing));
+    }
+}
+
+/// Returns whether `fluid` can flow through `state`: it is washed away, or flowing fluid of
+/// the same kind.
+fn can_pass(rules: &BlockRules, state: BlockState, fluid: Fluid) -> bool {
+    match rules.fluid(state) {
+        Some(other) => other.fluid == fluid && !other.is_source(),
+        None => rules.is_washed_away(state),
+    }
+}
+
+/// Returns whether `fluid` at `pos` can go down: the block below is washed away, or the same
+/// fluid.
+fn is_hole(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos, fluid: Fluid) -> bool {
+    world.block(pos.step(Direction::Down)).is_some_and(|below| match rules.fluid(below) {
+        Some(below) => below.fluid == fluid,
+        None => rules.is_washed_away(below),
+    })
+}
+
+/// Returns the number of sources of `fluid` beside `pos`.
+fn source_neighbors(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos, fluid: Fluid) -> usize {
+    Direction::HORIZONTAL
+        .into_iter()
+        .filter(|&direction| fluid_at(rules, world, pos.step(direction)).is_some_and(|other| other.fluid == fluid && other.is_source()))
+        .count()
+}
+
+/// Returns the fluid held by the block at `pos`, if it is loaded and holds any.
+fn fluid_at(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos) -> Option<FluidState> {
+    world.block(pos).and_then(|state| rules.fluid(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockRegistryBuilder;
+    use crate::item::ItemRegistry;
+
+    use std::collections::HashMap;
+
+    const BLOCKS: &str = "
+        [stone]
+        [cobblestone]
+        [obsidian]
+
+        [water]
+        replaceable = true
+        drops = []
+        properties = { level = { min = 0, max = 15 } }
+        collision = \"empty\"
+        fluid = \"water\"
+
+        [lava]
+        replaceable = true
+        drops = []
+        properties = { level = { min = 0, max = 15 } }
+        collision = \"empty\"
+        fluid = \"lava\"
+    ";
+
+    /// A few blocks set by hand in a world of air, lit by the sky everywhere.
+    struct TestWorld {
+        rules: BlockRules,
+        states: HashMap<&'static str, BlockState>,
+        blocks: HashMap<BlockPos, BlockState>,
+        updates: BlockUpdates,
+    }
+
+    impl BlockAccess for TestWorld {
+        fn block(&self, pos: BlockPos) -> Option<BlockState> {
+            Some(self.blocks.get(&pos).copied().unwrap_or(BlockState::AIR))
+        }
+    }
+
+    impl WorldAccess for TestWorld {
+        fn set_block(&mut self, pos: BlockPos, state: BlockState) -> Option<BlockState> {
+            Some(self.blocks.insert(pos, state).unwrap_or(BlockState::AIR))
+        }
+
+        fn light(&self, _pos: BlockPos) -> Option<(u8, u8)> {
+            Some((15, 0))
+        }
+    }
+
+    impl TestWorld {
+        /// Creates a world with a floor of stone at y `floor`, from x and z -16 to 16.
+        fn new(floor: i32) -> Self {
+            let mut builder = BlockRegistryBuilder::new();
+            builder.load_str("rustedcraft", BLOCKS).unwrap();
+            let blocks = builder.build().unwrap();
+            let rules = BlockRules::new(&blocks, &ItemRegistry::new(&blocks)).unwrap();
+            let states = ["stone", "water", "lava"].into_iter().map(|name| (name, blocks.default_state(name).unwrap())).collect();
+
+            let mut world = Self { rules, states, blocks: HashMap::new(), updates: BlockUpdates::new(0) };
+
+            for z in -16..=16 {
+                for x in -16..=16 {
+                    world.blocks.insert(BlockPos::new(x, floor, z), world.states["stone"]);
+                }
+            }
+
+            world
+        }
+
+        /// Sets the block at `pos` to the block called `name` as gameplay does, then runs
+        /// ticks until the fluids settle.
+        fn place(&mut self, pos: BlockPos, name: &str) {
+            let state = match name {
+                "air" => BlockState::AIR,
+                name => self.states[name],
+            };
+
+            let mut updates = std::mem::replace(&mut self.updates, BlockUpdates::new(0));
+            let rules = self.rules.clone();
+            updates.set_block(self, pos, state);
+
+            for _ in 0..2_000 {
+                updates.process(&rules, self);
+                updates.tick(&rules, self);
+
+                if updates.pending() == 0 && updates.scheduler().is_empty() {
+                    self.updates = updates;
+                    return;
+                }
+            }
+
+            panic!("fluids still flowing after 2000 ticks");
+        }
+
+        /// Returns the level of the fluid at `(x, y, z)`, or `None` if there is none.
+        fn level(&self, x: i32, y: i32, z: i32) -> Option<u8> {
+            self.rules.fluid(self.block(BlockPos::new(x, y, z)).unwrap()).map(|fluid| fluid.level)
+        }
+
+        /// Returns whether there is any fluid at all.
+        fn is_dry(&self) -> bool {
+            self.blocks.values().all(|&state| self.rules.fluid(state).is_none())
+        }
+    }
+
+    #[test]
+    fn spreads_as_far_as_it_drops_off() {
+        let mut world = TestWorld::new(0);
+        world.place(BlockPos::new(0, 1, 0), "water");
+
+        for z in -9i32..=9 {
+            for x in -9i32..=9 {
+                let distance = (x.abs() + z.abs()) as u8;
+                assert_eq!(world.level(x, 1, z), (distance <= 7).then_some(distance), "water at {x}, {z}");
+            }
+        }
+
+        let mut world = TestWorld::new(0);
+        world.place(BlockPos::new(0, 1, 0), "lava");
+
+        let levels: Vec<_> = (0..=5).map(|x| world.level(x, 1, 0)).collect();
+        assert_eq!(levels, [Some(0), Some(2), Some(4), Some(6), None, None]);
+    }
+
+    #[test]
+    fn falls_then_spreads_from_where_it_lands() {
+        let mut world = TestWorld::new(0);
+        world.place(BlockPos::new(0, 5, 0), "water");
+
+        // The source spreads one block around once its own column stops it falling, and
+        // every one of those falls too.
+        assert_eq!([world.level(0, 5, 0), world.level(1, 5, 0), world.level(0, 5, -1), world.level(1, 5, 1)], [Some(0), Some(1), Some(1), None]);
+
+        for x in -1..=1 {
+            let column: Vec<_> = (1..=4).map(|y| world.level(x, y, 0)).collect();
+            assert_eq!(column, [Some(FALLING); 4], "column at x {x}");
+        }
+
+        let spread: Vec<_> = (2..=9).map(|x| world.level(x, 1, 0)).collect();
+        assert_eq!(spread, [Some(1), Some(2), Some(3), Some(4), Some(5), Some(6), Some(7), None]);
+        assert_eq!(world.level(2, 2, 0), None);
+    }
+
+    #[test]
+    fn flows_only_towards_the_nearest_way_down() {
+        let mut world = TestWorld::new(0);
+        world.blocks.extend((-16..=16).flat_map(|x| (-16..=16).map(move |z| BlockPos::new(x, 1, z))).map(|pos| (pos, world.states["stone"])));
+        world.blocks.remove(&BlockPos::new(2, 1, 0));
+        world.place(BlockPos::new(0, 2, 0), "water");
+
+        assert_eq!([world.level(1, 2, 0), world.level(2, 2, 0), world.level(2, 1, 0)], [Some(1), Some(2), Some(FALLING)]);
+        assert_eq!([world.level(-1, 2, 0), world.level(0, 2, 1), world.level(0, 2, -1), world.level(3, 2, 0)], [None; 4]);
+    }
+
+    #[test]
+    fn makes_sources_between_water_sources_on_the_ground() {
+        let mut world = TestWorld::new(0);
+        world.place(BlockPos::new(0, 1, 0), "water");
+        world.place(BlockPos::new(2, 1, 0), "water");
+        assert_eq!(world.level(1, 1, 0), Some(0));
+
+        // Removing one of the first sources keeps the new one between the others.
+        world.place(BlockPos::new(2, 1, 0), "air");
+        assert_eq!(world.level(2, 1, 0), Some(1));
+
+        let mut world = TestWorld::new(0);
+        world.place(BlockPos::new(0, 1, 0), "lava");
+        world.place(BlockPos::new(2, 1, 0), "lava");
+        assert_eq!(world.level(1, 1, 0), Some(2));
+
+        // Not over the water falling from between them.
+        let mut world = TestWorld::new(0);
+        world.blocks.insert(BlockPos::new(0, 4, 0), world.states["stone"]);
+        world.blocks.insert(BlockPos::new(2, 4, 0), world.states["stone"]);
+        world.place(BlockPos::new(0, 5, 0), "water");
+        world.place(BlockPos::new(2, 5, 0), "water");
+        assert_eq!([world.level(1, 5, 0), world.level(1, 4, 0)], [Some(1), Some(FALLING)]);
+    }
+
+    #[test]
+    fn drains_once_the_source_is_gone() {
+        let mut world = TestWorld::new(0);
+        world.place(BlockPos::new(0, 5, 0), "water");
+        assert!(world.level(7, 1, 0).is_some());
+
+        world.place(BlockPos::new(0, 5, 0), "air");
+        assert!(world.is_dry());
+
+        world.place(BlockPos::new(0, 1, 0), "lava");
+        world.place(BlockPos::new(0, 1, 0), "stone");
+        assert!(world.is_dry());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::world::{BlockPos, BlockState};
+
+/// Number of crack stages shown while a block is being broken.
+pub const BREAK_STAGES: u8 = 10;
+
+/// Ticks to wait after breaking a block before starting on the next one, so holding the
+/// button down does not clear a block every tick.
+const BREAK_COOLDOWN: u32 = 5;
+
+/// Progress of a player breaking a block, advanced once per tick while they hold the button
+/// on it.
+///
+/// Progress restarts whenever the player aims at another block or the block changes, and is
+/// lost when they let go.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mining {
+    target: Option<(BlockPos, BlockState)>,
+
+    /// Share of the block broken, from 0 to 1.
+    progress: f32,
+    cooldown: u32,
+}
+
+impl Mining {
+    /// Creates progress on no block.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances by one tick of `tick_seconds` breaking `state` at `pos`, which takes `time`
+    /// seconds in all, or forever if `None`. Returns whether the block broke this tick.
+    pub fn tick(&mut self, pos: BlockPos, state: BlockState, time: Option<f32>, tick_seconds: f32) -> bool {
+        if self.cooldown > 0 {
+            self.cooldown -= 1;
+            return false;
+        }
+
+        if self.target != Some((pos, state)) {
+            self.target = Some((pos, state));
+            self.progress = 0.0;
+        }
+
+        let Some(time) = time else {
+            return false;
+        };
+
+        self.progress = match time > 0.0 {
+            true => self.progress + tick_seconds / time,
+            false => 1.0,
+        };
+
+        if self.progress < 1.0 {
+            return false;
+        }
+
+        self.stop();
+        self.cooldown = BREAK_COOLDOWN;
+        true
+    }
+
+    /// Forgets the progress, when the player lets go or looks away from every block.
+    pub fn stop(&mut self) {
+        self.target = None;
+        self.progress = 0.0;
+    }
+
+    /// Returns the share of the target broken, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Returns the block being broken and its crack stage, from 0 to
+    /// [`BREAK_STAGES`]` - 1`, once it has started cracking.
+    pub fn stage(&self) -> Option<(BlockPos, u8)> {
+        let (pos, _) = self.target?;
+        let stage = (self.progress * BREAK_STAGES as f32) as u8;
+
+        (self.progress > 0.0).then_some((pos, stage.min(BREAK_STAGES - 1)))
+    }
+}
@@ -0,0 +1,13 @@
+mod rules;
+mod fluids;
+mod mining;
+mod placement;
+mod scheduler;
+mod updates;
+
+pub use rules::BlockRules;
+pub use fluids::FluidState;
+pub use mining::{Mining, BREAK_STAGES};
+pub use placement::{place_target, PlaceContext};
+pub use scheduler::TickScheduler;
+pub use updates::{BlockUpdates, WorldAccess, RANDOM_TICK_SPEED};
@@ -0,0 +1,168 @@
+use super::BlockRules;
+use crate::block::{Block, BlockRegistry, Fluid, Placement};
+use crate::math::{Aabb, Vec3};
+use crate::physics::{BlockAccess, PhysicsTable, RayHit};
+use crate::world::{BlockPos, BlockState, Direction};
+
+/// Where a player places a block and how they look at it.
+#[derive(Debug, Clone, Copy)]
+pub struct PlaceContext<'a> {
+    /// The block aimed at.
+    pub hit: &'a RayHit,
+
+    /// Direction the player looks, not necessarily normalized.
+    pub look: Vec3,
+
+    /// Boxes of the bodies placed blocks may not overlap, in world coordinates.
+    pub bodies: &'a [Aabb],
+}
+
+/// Works out where and in which state `block` goes when placed as in `context`, or `None`
+/// if it cannot be placed there.
+///
+/// Clicking the top of a bottom slab or of snow layers with the same block adds to it;
+/// otherwise the block replaces the one aimed at if that is replaceable, like short grass,
+/// or goes in front of the face clicked. Properties are set from how the block is placed:
+///
+/// * `facing` as chosen by the block's [`Placement`], from the direction looked in or the
+///   face clicked,
+/// * `axis` along the face clicked, for logs,
+/// * `half` and slab `type` on the side of the block clicked, top or bottom,
+/// * `waterlogged` when replacing a water source,
+/// * `persistent` always, so that leaves placed by players never decay.
+///
+/// The block must be able to stay where it goes and not overlap any of the bodies.
+pub fn place_target(
+    blocks: &BlockRegistry,
+    rules: &BlockRules,
+    physics: &PhysicsTable,
+    world: &impl BlockAccess,
+    block: &Block,
+    context: &PlaceContext,
+) -> Option<(BlockPos, BlockState)> {
+    let hit = context.hit;
+
+    let (pos, state) = match merged(blocks, block, hit.state, Some(hit.face)) {
+        Some(state) => (hit.pos, state),
+        None => {
+            let pos = match rules.is_replaceable(hit.state) {
+                true => hit.pos,
+                false => hit.adjacent(),
+            };
+
+            pos.local()?;
+            let existing = world.block(pos)?;
+
+            match merged(blocks, block, existing, None) {
+                Some(state) => (pos, state),
+                None if rules.is_replaceable(existing) => (pos, oriented(blocks, block, existing, context)?),
+                None => return None,
+            }
+        }
+    };
+
+    let corner = Vec3::new(pos.x as f32, pos.y as f32, pos.z as f32);
+    let blocked = physics
+        .boxes(state)
+        .iter()
+        .any(|b| context.bodies.iter().any(|body| body.intersects(b.offset(corner))));
+
+    (rules.can_stay(world, pos, state) && !blocked).then_some((pos, state))
+}
+
+/// Returns `existing` with one more layer or slab of `block` added, if it is `block` with
+/// room for it. When clicked, only the top face adds to bottom slabs and snow, and the
+/// bottom face to top slabs.
+fn merged(blocks: &BlockRegistry, block: &Block, existing: BlockState, face: Option<Direction>) -> Option<BlockState> {
+    if !block.contains(existing) {
+        return None;
+    }
+
+    let from = |direction: Direction| face.is_none_or(|face| face == direction);
+
+    if let Some(kind) = blocks.value(existing, "type") {
+        return match kind {
+            "bottom" if from(Direction::Up) => double(blocks, existing),
+            "top" if from(Direction::Down) => double(blocks, existing),
+            _ => None,
+        };
+    }
+
+    let layers: u32 = blocks.value(existing, "layers")?.parse().ok()?;
+
+    match from(Direction::Up) {
+        true => blocks.with_value(existing, "layers", &(layers + 1).to_string()),
+        false => None,
+    }
+}
+
+/// Returns the double slab made of `slab`, which is too full to hold any water.
+fn double(blocks: &BlockRegistry, slab: BlockState) -> Option<BlockState> {
+    let double = blocks.with_value(slab, "type", "double")?;
+    Some(blocks.with_value(double, "waterlogged", "false").unwrap_or(double))
+}
+
+/// Returns the state of `block` placed into `existing` as in `context`, or `None` if it
+/// cannot face the way it would have to.
+fn oriented(blocks: &BlockRegistry, block: &Block, existing: BlockState, context: &PlaceContext) -> Option<BlockState> {
+    let hit = context.hit;
+    let mut state = block.default_state();
+
+    if let Some(property) = block.property("facing") {
+        let horizontal = property.values().len() == Direction::HORIZONTAL.len();
+
+        let facing = match blocks.attributes(state).placement {
+            Placement::Player => looking(context.look, horizontal).opposite(),
+            Placement::Away => looking(context.look, horizontal),
+            Placement::Face => hit.face,
+        };
+
+        state = blocks.with_value(state, "facing", facing.name())?;
+    }
+
+    if block.property("axis").is_some() {
+        state = blocks.with_value(state, "axis", ["x", "y", "z"][hit.face.axis()])?;
+    }
+
+    // Clicking the underside of a block, or the upper half of its side, places on top.
+    let upper = match hit.face {
+        Direction::Down => true,
+        Direction::Up => false,
+        _ => hit.point.y - hit.pos.y as f32 > 0.5,
+    };
+
+    let half = if upper { "top" } else { "bottom" };
+
+    for property in ["half", "type"] {
+        if block.property(property).is_some_and(|p| p.index_of(half).is_some()) {
+            state = blocks.with_value(state, property, half)?;
+        }
+    }
+
+    let water_source = blocks.attributes(existing).fluid == Some(Fluid::Water) && blocks.value(existing, "level") == Some("0");
+
+    if water_source && block.property("waterlogged").is_some() {
+        state = blocks.with_value(state, "waterlogged", "true")?;
+    }
+
+    if block.property("persistent").is_some() {
+        state = blocks.with_value(state, "persistent", "true")?;
+    }
+
+    Some(state)
+}
+
+/// Returns the direction closest to `look`, only among the horizontal ones if `horizontal`.
+fn looking(look: Vec3, horizontal: bool) -> Direction {
+    let candidates: &[Direction] = match horizontal {
+        true => &Direction::HORIZONTAL,
+        false => &Direction::ALL,
+    };
+
+    let along = |direction: &&Direction| {
+        let (x, y, z) = direction.offset();
+        look.dot(Vec3::new(x as f32, y as f32, z as f32))
+    };
+
+    candidates.iter().max_by(|a, b| along(a).total_cmp(&along(b))).copied().unwrap_or(Direction::North)
+}
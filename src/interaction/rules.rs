@@ -0,0 +1,325 @@
+use super::fluids::{FluidBlocks, FluidState};
+use crate::block::{BlockId, BlockRegistry, Fluid, Support, ToolKind};
+use crate::item::{ItemRegistry, ItemStack, Tool};
+use crate::physics::BlockAccess;
+use crate::world::{BlockPos, BlockState, Direction};
+
+/// Breaking time is multiplied by this without the tool a block requires.
+const WRONG_TOOL_PENALTY: f32 = 10.0 / 3.0;
+
+/// Breaking time is multiplied by this while in a fluid, and again while off the ground.
+const HINDERED_PENALTY: f32 = 5.0;
+
+/// How players break, place and knock over a single block state.
+#[derive(Debug, Clone)]
+struct StateRules {
+    block: BlockId,
+    hardness: f32,
+    tool: Option<ToolKind>,
+    requires_tool: bool,
+    drops: Vec<ItemStack>,
+    replaceable: bool,
+
+    /// Direction of the block this one rests on.
+    support: Option<Direction>,
+    falls: bool,
+
+    /// State one age older, for crops not fully grown.
+    grows_into: Option<BlockState>,
+
+    /// State spread onto and turned back into, for grass.
+    spreads: Option<BlockState>,
+
+    /// Whether grass below turns back into dirt.
+    smothers: bool,
+    leaves: bool,
+    decays: bool,
+    holds_leaves: bool,
+
+    /// Fluid held, by fluid blocks and waterlogged blocks.
+    fluid: Option<FluidState>,
+
+    /// Whether the state is a block of fluid, not a waterlogged one.
+    fluid_block: bool,
+
+    /// Whether fluids flowing into the block break it.
+    washed_away: bool,
+
+    /// Faces fully covered by the collision shape, one bit per [`Direction`].
+    sturdy_faces: u8,
+}
+
+/// How players break and place every block state of a registry, resolved ahead of time.
+#[derive(Debug, Clone)]
+pub struct BlockRules {
+    states: Vec<StateRules>,
+    fluids: FluidBlocks,
+}
+
+impl BlockRules {
+    /// Builds the rules of every state of `blocks`, taking their drops from `items`.
+    ///
+    /// # Returns
+    /// * `Err(String)` if a block drops an unknown item, rests on the block behind it
+    ///   without a horizontal `facing` property, grows without an `age` property or spreads
+    ///   onto an unknown block, or if `stone`, `cobblestone` or `obsidian`, which lava hardens
+    ///   into, is missing.
+    pub fn new(blocks: &BlockRegistry, items: &ItemRegistry) -> Result<Self, String> {
+        let fluids = FluidBlocks::new(blocks)?;
+
+        let states = blocks
+            .states()
+            .map(|state| {
+                let attributes = blocks.attributes(state);
+                let name = || blocks.block_of(state).name();
+                let mut drops: Vec<ItemStack> = Vec::new();
+
+                for drop in &attributes.drops {
+                    let item = items.get(drop).ok_or_else(|| format!("{}: unknown item {drop}", name()))?.id();
+
+                    match drops.iter_mut().find(|stack| stack.item == item) {
+                        Some(stack) => stack.count += 1,
+                        None => drops.push(ItemStack::one(item)),
+                    }
+                }
+
+                let support = match attributes.support {
+                    Some(Support::Below) => Some(Direction::Down),
+                    Some(Support::Behind) => {
+                        let facing = blocks.value(state, "facing").and_then(|facing| facing.parse::<Direction>().ok());
+                        let facing = facing.filter(|facing| facing.axis() != 1);
+                        Some(facing.ok_or_else(|| format!("{}: resting behind needs a horizontal facing property", name()))?.opposite())
+                    }
+                    None => None,
+                };
+
+                let grows_into = match attributes.grows {
+                    true => {
+                        let age = blocks.value(state, "age").and_then(|age| age.parse::<u32>().ok());
+                        let age = age.ok_or_else(|| format!("{}: growing needs an age property", name()))?;
+                        blocks.with_value(state, "age", &(age + 1).to_string())
+                    }
+                    false => None,
+                };
+
+                let spreads = match &attributes.spreads {
+                    Some(onto) => Some(blocks.default_state(onto).ok_or_else(|| format!("{}: spreads onto unknown block {onto}", name()))?),
+                    None => None,
+                };
+
+                let fluid = match blocks.value(state, "waterlogged") == Some("true") {
+                    true => Some(FluidState { fluid: Fluid::Water, level: 0 }),
+                    false => attributes.fluid.map(|fluid| {
+                        let level = blocks.value(state, "level").and_then(|level| level.parse().ok());
+                        FluidState { fluid, level: level.unwrap_or(0) }
+                    }),
+                };
+
+                let sturdy_faces = Direction::ALL
+                    .iter()
+                    .filter(|&&direction| attributes.collision.covers(direction))
+                    .fold(0, |faces, &direction| faces | 1 << direction as u8);
+
+                Ok(StateRules {
+                    block: blocks.block_of(state).id(),
+                    hardness: attributes.hardness,
+                    tool: attributes.tool,
+                    requires_tool: attributes.requires_tool,
+                    drops,
+                    replaceable: attributes.replaceable,
+                    support,
+                    falls: attributes.falls,
+                    grows_into,
+                    spreads,
+                    smothers: attributes.opacity >= 15 || attributes.fluid.is_some(),
+                    leaves: attributes.decays,
+                    decays: attributes.decays && blocks.value(state, "persistent") != Some("true"),
+                    holds_leaves: attributes.holds_leaves,
+                    fluid,
+                    fluid_block: attributes.fluid.is_some(),
+                    washed_away: fluid.is_none() && (attributes.replaceable || attributes.collision.is_empty()),
+                    sturdy_faces,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self { states, fluids })
+    }
+
+    /// Returns the seconds it takes to break `state` holding `tool`, or `None` if it cannot
+    /// be broken.
+    ///
+    /// The hardness of a block is its time by hand. The right tool divides it by its speed;
+    /// blocks requiring a tool take ten thirds as long without it. Being in a fluid or off
+    /// the ground makes it five times longer each.
+    pub fn break_time(&self, state: BlockState, tool: Option<&Tool>, in_fluid: bool, on_ground: bool) -> Option<f32> {
+        let rules = &self.states[state.id() as usize];
+
+        if rules.hardness < 0.0 {
+            return None;
+        }
+
+        let mut time = match tool.filter(|tool| rules.tool == Some(tool.kind)) {
+            Some(tool) => rules.hardness / tool.speed,
+            None if rules.requires_tool => rules.hardness * WRONG_TOOL_PENALTY,
+            None => rules.hardness,
+        };
+
+        if in_fluid {
+            time *= HINDERED_PENALTY;
+        }
+
+        if !on_ground {
+            time *= HINDERED_PENALTY;
+        }
+
+        Some(time)
+    }
+
+    /// Returns the block `state` is a state of.
+    #[inline]
+    pub fn block(&self, state: BlockState) -> BlockId {
+        self.states[state.id() as usize].block
+    }
+
+    /// Returns the items `state` drops when broken with `tool`, or by hand if `None`.
+    pub fn drops(&self, state: BlockState, tool: Option<&Tool>) -> &[ItemStack] {
+        let rules = &self.states[state.id() as usize];
+
+        match rules.requires_tool && tool.is_none_or(|tool| rules.tool != Some(tool.kind)) {
+            true => &[],
+            false => &rules.drops,
+        }
+    }
+
+    /// Returns the items `state` drops when it breaks on its own, like a torch losing the
+    /// block it stands on.
+    pub fn natural_drops(&self, state: BlockState) -> &[ItemStack] {
+        &self.states[state.id() as usize].drops
+    }
+
+    /// Returns whether placing a block into `state` replaces it.
+    #[inline]
+    pub fn is_replaceable(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].replaceable
+    }
+
+    /// Returns whether `state` falls when nothing is below it.
+    #[inline]
+    pub fn falls(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].falls
+    }
+
+    /// Returns whether `state` does anything on random ticks: grow, spread or decay.
+    #[inline]
+    pub fn ticks_randomly(&self, state: BlockState) -> bool {
+        let rules = &self.states[state.id() as usize];
+        rules.grows_into.is_some() || rules.spreads.is_some() || rules.decays
+    }
+
+    /// Returns the state `state` grows into on a random tick, for crops not fully grown.
+    #[inline]
+    pub fn grows_into(&self, state: BlockState) -> Option<BlockState> {
+        self.states[state.id() as usize].grows_into
+    }
+
+    /// Returns the state `state` spreads onto and turns back into when covered, for grass.
+    #[inline]
+    pub fn spreads(&self, state: BlockState) -> Option<BlockState> {
+        self.states[state.id() as usize].spreads
+    }
+
+    /// Returns whether `state` covers grass below it so that it turns back into dirt: it is
+    /// opaque, or a fluid.
+    #[inline]
+    pub fn smothers(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].smothers
+    }
+
+    /// Returns whether `state` is leaves, persistent or not.
+    #[inline]
+    pub fn is_leaves(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].leaves
+    }
+
+    /// Returns whether `state` decays when cut off from blocks holding it: leaves that were
+    /// not placed by a player.
+    #[inline]
+    pub fn decays(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].decays
+    }
+
+    /// Returns whether `state` keeps leaves around it from decaying, like logs.
+    #[inline]
+    pub fn holds_leaves(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].holds_leaves
+    }
+
+    /// Returns the fluid `state` holds: its own for fluid blocks, a water source for
+    /// waterlogged blocks.
+    #[inline]
+    pub fn fluid(&self, state: BlockState) -> Option<FluidState> {
+        self.states[state.id() as usize].fluid
+    }
+
+    /// Returns whether `state` is a block of fluid, rather than a block holding one.
+    #[inline]
+    pub fn is_fluid_block(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].fluid_block
+    }
+
+    /// Returns the state of a block of `fluid` at `level`, if a block is made of it.
+    pub fn fluid_block(&self, fluid: Fluid, level: u8) -> Option<BlockState> {
+        self.fluids.get(fluid, level)
+    }
+
+    /// Returns whether fluids flowing into `state` break it and take its place, like air,
+    /// torches and flowers.
+    #[inline]
+    pub fn is_washed_away(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].washed_away
+    }
+
+    /// Returns what is left when `state` is broken: the water of waterlogged blocks, or air.
+    pub fn remains(&self, state: BlockState) -> BlockState {
+        match self.fluid(state).filter(|_| !self.is_fluid_block(state)) {
+            Some(fluid) => self.fluid_block(fluid.fluid, fluid.level).unwrap_or(BlockState::AIR),
+            None => BlockState::AIR,
+        }
+    }
+
+    /// Returns the fluid blocks and the blocks lava hardens into.
+    pub(super) fn fluid_blocks(&self) -> &FluidBlocks {
+        &self.fluids
+    }
+
+    /// Returns the direction of the block `state` rests on, if any.
+    #[inline]
+    pub fn support(&self, state: BlockState) -> Option<Direction> {
+        self.states[state.id() as usize].support
+    }
+
+    /// Returns whether the collision shape of `state` fully covers its face pointing in
+    /// `direction`, so that blocks can rest on it.
+    #[inline]
+    pub fn is_sturdy(&self, state: BlockState, direction: Direction) -> bool {
+        self.states[state.id() as usize].sturdy_faces & 1 << direction as u8 != 0
+    }
+
+    /// Returns whether `state` can stay at `pos`: it rests on nothing, or on a face covered
+    /// by the block it rests on. Blocks resting on unloaded blocks stay; blocks resting on
+    /// something out of the world do not.
+    pub fn can_stay(&self, world: &impl BlockAccess, pos: BlockPos, state: BlockState) -> bool {
+        let Some(direction) = self.support(state) else {
+            return true;
+        };
+
+        let support = pos.step(direction);
+
+        if support.local().is_none() {
+            return false;
+        }
+
+        world.block(support).is_none_or(|support| self.is_sturdy(support, direction.opposite()))
+    }
+}
@@ -0,0 +1,167 @@
+use crate::block::{BlockId, BlockRegistry};
+use crate::save::SavedTick;
+use crate::world::BlockPos;
+
+use std::collections::{BTreeMap, HashSet};
+
+/// Block ticks scheduled to happen a number of game ticks later, like sand starting to fall.
+///
+/// A tick is for a block at a position, and only happens if that block is still there when
+/// it comes. The same block at the same position is scheduled at most once at a time. Ticks
+/// due on the same game tick happen in the order they were scheduled.
+#[derive(Debug, Clone, Default)]
+pub struct TickScheduler {
+    time: u64,
+    order: u64,
+    queue: BTreeMap<(u64, u64), (BlockPos, BlockId)>,
+    scheduled: HashSet<(BlockPos, BlockId)>,
+}
+
+impl TickScheduler {
+    /// Creates a scheduler without any tick.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedules a tick of `block` at `pos` in `delay` game ticks, at least one. Returns
+    /// false if one is already scheduled.
+    pub fn schedule(&mut self, pos: BlockPos, block: BlockId, delay: u32) -> bool {
+        if !self.scheduled.insert((pos, block)) {
+            return false;
+        }
+
+        self.queue.insert((self.time + delay.max(1) as u64, self.order), (pos, block));
+        self.order += 1;
+        true
+    }
+
+    /// Returns whether a tick of `block` at `pos` is waiting.
+    pub fn is_scheduled(&self, pos: BlockPos, block: BlockId) -> bool {
+        self.scheduled.contains(&(pos, block))
+    }
+
+    /// Returns the number of ticks waiting.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns whether no tick is waiting.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Moves on to the next game tick.
+    pub fn advance(&mut self) {
+        self.time += 1;
+    }
+
+    /// Takes the next tick due by the current game tick, if any.
+    pub fn pop_due(&mut self) -> Option<(BlockPos, BlockId)> {
+        let entry = self.queue.first_entry().filter(|entry| entry.key().0 <= self.time)?;
+        let tick = entry.remove();
+
+        self.scheduled.remove(&tick);
+        Some(tick)
+    }
+
+    /// Returns the ticks waiting, with the block names of `blocks`, to be saved.
+    pub fn save(&self, blocks: &BlockRegistry) -> Vec<SavedTick> {
+        self.queue
+            .iter()
+            .map(|(&(time, _), &(pos, block))| SavedTick {
+                pos: [pos.x, pos.y, pos.z],
+                block: blocks.block(block).name().to_string(),
+                delay: (time - self.time) as u32,
+            })
+            .collect()
+    }
+
+    /// Schedules ticks saved by [`save`](Self::save), in the order they were saved.
+    ///
+    /// # Returns
+    /// * `Err(String)` naming a block missing from `blocks`. The ticks before it are kept.
+    pub fn load(&mut self, blocks: &BlockRegistry, ticks: &[SavedTick]) -> Result<(), String> {
+        for tick in ticks {
+            let block = blocks.get(&tick.block).ok_or_else(|| format!("scheduled tick of unknown block {}", tick.block))?;
+            let [x, y, z] = tick.pos;
+
+            self.schedule(BlockPos::new(x, y, z), block.id(), tick.delay);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockRegistryBuilder;
+    use crate::save::LevelData;
+
+    /// Pops every tick, returning each with the number of game ticks it took to come.
+    fn drain(scheduler: &mut TickScheduler) -> Vec<(u32, BlockPos, BlockId)> {
+        let mut ticks = Vec::new();
+
+        for elapsed in 0..100 {
+            while let Some((pos, block)) = scheduler.pop_due() {
+                ticks.push((elapsed, pos, block));
+            }
+
+            scheduler.advance();
+        }
+
+        ticks
+    }
+
+    #[test]
+    fn survives_saving_and_loading() {
+        let mut builder = BlockRegistryBuilder::new();
+        builder.load_str("test", "[sand]\n[gravel]\n").unwrap();
+        let blocks = builder.build().unwrap();
+        let (sand, gravel) = (blocks.get("test:sand").unwrap().id(), blocks.get("test:gravel").unwrap().id());
+
+        let mut scheduler = TickScheduler::new();
+        scheduler.schedule(BlockPos::new(0, 64, 0), sand, 5);
+        scheduler.schedule(BlockPos::new(1, 64, 0), gravel, 2);
+        scheduler.schedule(BlockPos::new(2, 64, 0), sand, 5);
+        scheduler.advance();
+        scheduler.schedule(BlockPos::new(-3, 70, 9), gravel, 4);
+
+        // Written out and read back as the level data is.
+        let mut level = LevelData::new(0);
+        level.scheduled_ticks = scheduler.save(&blocks);
+        let level: LevelData = toml::from_str(&toml::to_string_pretty(&level).unwrap()).unwrap();
+
+        let delays: Vec<u32> = level.scheduled_ticks.iter().map(|tick| tick.delay).collect();
+        assert_eq!(delays, [1, 4, 4, 4]);
+
+        let mut loaded = TickScheduler::new();
+        loaded.load(&blocks, &level.scheduled_ticks).unwrap();
+
+        assert_eq!(loaded.len(), 4);
+        assert!(loaded.is_scheduled(BlockPos::new(-3, 70, 9), gravel));
+
+        let expected = vec![
+            (1, BlockPos::new(1, 64, 0), gravel),
+            (4, BlockPos::new(0, 64, 0), sand),
+            (4, BlockPos::new(2, 64, 0), sand),
+            (4, BlockPos::new(-3, 70, 9), gravel),
+        ];
+
+        assert_eq!(drain(&mut loaded), expected);
+        assert_eq!(drain(&mut scheduler), expected);
+    }
+
+    #[test]
+    fn stops_loading_at_unknown_blocks() {
+        let mut builder = BlockRegistryBuilder::new();
+        builder.load_str("test", "[sand]\n").unwrap();
+        let blocks = builder.build().unwrap();
+
+        let tick = |block: &str| SavedTick { pos: [0, 0, 0], block: block.to_string(), delay: 1 };
+        let mut scheduler = TickScheduler::new();
+
+        assert!(scheduler.load(&blocks, &[tick("test:sand"), tick("test:marble")]).is_err());
+        assert_eq!(scheduler.len(), 1);
+    }
+}
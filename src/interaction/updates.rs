@@ -0,0 +1,335 @@
+use super::fluids;
+use super::{BlockRules, TickScheduler};
+use crate::item::{ItemStack, Tool};
+use crate::jobs::ChunkStreamer;
+use crate::physics::BlockAccess;
+use crate::world::{BlockPos, BlockState, Chunk, Direction, SECTION_SIZE};
+use crate::worldgen::WorldRandom;
+
+use std::collections::{HashSet, VecDeque};
+
+/// Most neighbor updates handled per call to [`BlockUpdates::process`]; the rest wait for
+/// the next one, so a chain reaction cannot stall a tick.
+const MAX_UPDATES: usize = 4096;
+
+/// Most scheduled ticks handled per game tick; the rest wait for the next one, so that a
+/// flood of fluid spreading in every direction takes longer instead of stalling a frame.
+const MAX_SCHEDULED_TICKS: usize = 1024;
+
+/// Blocks picked at random in each section of every chunk ticked per game tick, unless
+/// changed with [`BlockUpdates::set_random_tick_speed`].
+pub const RANDOM_TICK_SPEED: usize = 3;
+
+/// Game ticks between a block that falls losing what is below it and starting to fall.
+const FALL_DELAY: u32 = 2;
+
+/// Game ticks before a tick due in an unloaded chunk is tried again.
+const UNLOADED_DELAY: u32 = 20;
+
+/// Light crops need to grow, and grass to spread.
+const GROW_LIGHT: u8 = 9;
+
+/// Chance of crops growing one age on a random tick.
+const GROW_CHANCE: f64 = 1.0 / 3.0;
+
+/// Tries of grass to spread on a random tick.
+const SPREAD_TRIES: usize = 4;
+
+/// Steps through other leaves within which leaves must find a block holding them.
+const LEAF_REACH: u32 = 6;
+
+/// Read and write access to the blocks of the world, for gameplay changing them.
+pub trait WorldAccess: BlockAccess {
+    /// Sets the block at `pos` and returns the previous one, or `None` if it is not loaded
+    /// or out of the world.
+    fn set_block(&mut self, pos: BlockPos, state: BlockState) -> Option<BlockState>;
+
+    /// Returns the sky and block light at `pos`, or `None` if it is not loaded. Sky light is
+    /// as at noon, whatever the time of day.
+    fn light(&self, pos: BlockPos) -> Option<(u8, u8)>;
+}
+
+impl WorldAccess for ChunkStreamer {
+    fn set_block(&mut self, pos: BlockPos, state: BlockState) -> Option<BlockState> {
+        ChunkStreamer::set_block(self, pos, state)
+    }
+
+    fn light(&self, pos: BlockPos) -> Option<(u8, u8)> {
+        ChunkStreamer::light(self, pos)
+    }
+}
+
+/// Changes made to blocks by gameplay, and the reactions they set off, over time.
+///
+/// Every change notifies the block and its six neighbors, which are checked in turn when
+/// updates are processed: blocks that lost what they rest on, like torches and flowers,
+/// break and drop their items, blocks that fall, like sand, schedule a tick to start
+/// falling, and fluids schedule a tick to flow, or harden where lava meets water. Each
+/// reaction is a change of its own, so a column of sand or a stack of blocks
+/// resting on each other all react.
+///
+/// Each game tick also runs the block ticks scheduled for it, and random ticks of a few
+/// blocks picked in each section around the player: crops grow, grass spreads onto dirt
+/// and leaves cut off from logs decay.
+#[derive(Debug, Clone)]
+pub struct BlockUpdates {
+    pending: VecDeque<BlockPos>,
+    scheduler: TickScheduler,
+    random_ticks: Vec<BlockPos>,
+    random_tick_speed: usize,
+    random: WorldRandom,
+    drops: Vec<(BlockPos, ItemStack)>,
+    falling: Vec<(BlockPos, BlockState)>,
+}
+
+impl BlockUpdates {
+    /// Creates empty queues. `seed` drives which blocks random ticks pick.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            pending: VecDeque::new(),
+            scheduler: TickScheduler::new(),
+            random_ticks: Vec::new(),
+            random_tick_speed: RANDOM_TICK_SPEED,
+            random: WorldRandom::new(seed),
+            drops: Vec::new(),
+            falling: Vec::new(),
+        }
+    }
+
+    /// Sets the block at `pos` and notifies it and its neighbors if it changed. Returns the
+    /// previous block, or `None` if it is not loaded.
+    pub fn set_block(&mut self, world: &mut impl WorldAccess, pos: BlockPos, state: BlockState) -> Option<BlockState> {
+        let old = world.set_block(pos, state)?;
+
+        if old != state {
+            self.pending.push_back(pos);
+            self.notify_neighbors(pos);
+        }
+
+        Some(old)
+    }
+
+    /// Breaks the block at `pos` as a player holding `tool` does, leaving air, or the water
+    /// of a waterlogged block, and dropping its items. Returns the block broken, or `None` if
+    /// it is not loaded.
+    pub fn break_block(&mut self, rules: &BlockRules, world: &mut impl WorldAccess, pos: BlockPos, tool: Option<&Tool>) -> Option<BlockState> {
+        let old = world.block(pos)?;
+        self.set_block(world, pos, rules.remains(old))?;
+
+        self.drop_items(pos, rules.drops(old, tool));
+        Some(old)
+    }
+
+    /// Schedules the six neighbors of `pos` to check whether they can stay.
+    pub fn notify_neighbors(&mut self, pos: BlockPos) {
+        self.pending.extend(pos.neighbors());
+    }
+
+    /// Returns the number of neighbor updates waiting.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the block ticks scheduled.
+    pub fn scheduler(&self) -> &TickScheduler {
+        &self.scheduler
+    }
+
+    /// Returns the block ticks scheduled, to schedule more.
+    pub fn scheduler_mut(&mut self) -> &mut TickScheduler {
+        &mut self.scheduler
+    }
+
+    /// Handles waiting neighbor updates, including those they set off, up to a limit.
+    /// Returns the number handled.
+    pub fn process(&mut self, rules: &BlockRules, world: &mut impl WorldAccess) -> usize {
+        let mut handled = 0;
+
+        while handled < MAX_UPDATES
+            && let Some(pos) = self.pending.pop_front()
+        {
+            handled += 1;
+
+            let Some(state) = pos.local().and_then(|_| world.block(pos)) else {
+                continue;
+            };
+
+            if !rules.can_stay(world, pos, state) {
+                self.destroy_block(rules, world, pos, state);
+            }
+            else if rules.falls(state) && can_fall_into(rules, world, pos) {
+                self.scheduler.schedule(pos, rules.block(state), FALL_DELAY);
+            }
+            else if let Some(hardened) = fluids::hardened(rules, world, pos, state) {
+                self.set_block(world, pos, hardened);
+            }
+            else if let Some(fluid) = rules.fluid(state) {
+                self.scheduler.schedule(pos, rules.block(state), fluid.fluid.tick_delay());
+            }
+        }
+
+        handled
+    }
+
+    /// Changes the number of blocks [`queue_random_ticks`](Self::queue_random_ticks) picks in
+    /// each section, 0 to stop random ticks.
+    pub fn set_random_tick_speed(&mut self, speed: usize) {
+        self.random_tick_speed = speed;
+    }
+
+    /// Picks the random tick speed of blocks in each section of `chunks` that is not empty,
+    /// and queues those doing anything on random ticks for the next [`tick`](Self::tick).
+    pub fn queue_random_ticks<'a>(&mut self, rules: &BlockRules, chunks: impl IntoIterator<Item = &'a Chunk>) {
+        for chunk in chunks {
+            for (index, section) in chunk.sections().iter().enumerate().filter(|(_, section)| !section.is_empty()) {
+                for _ in 0..self.random_tick_speed {
+                    let bits = self.random.next_u32() as usize;
+                    let (x, y, z) = (bits & 15, bits >> 4 & 15, bits >> 8 & 15);
+
+                    if rules.ticks_randomly(section.get(x, y, z)) {
+                        self.random_ticks.push(chunk.pos().block(x, index * SECTION_SIZE + y, z));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves on to the next game tick: runs the block ticks scheduled for it, then the random
+    /// ticks queued. Returns the number of ticks handled.
+    ///
+    /// Ticks due in unloaded chunks wait until they are loaded again.
+    pub fn tick(&mut self, rules: &BlockRules, world: &mut impl WorldAccess) -> usize {
+        let mut handled = 0;
+
+        self.scheduler.advance();
+
+        while handled < MAX_SCHEDULED_TICKS
+            && let Some((pos, block)) = self.scheduler.pop_due()
+        {
+            handled += 1;
+
+            match world.block(pos) {
+                Some(state) if rules.block(state) == block => self.scheduled_tick(rules, world, pos, state),
+                Some(_) => {}
+                None if pos.local().is_some() => {
+                    self.scheduler.schedule(pos, block, UNLOADED_DELAY);
+                }
+                None => {}
+            }
+        }
+
+        for pos in std::mem::take(&mut self.random_ticks) {
+            if let Some(state) = world.block(pos)
+                && rules.ticks_randomly(state)
+            {
+                handled += 1;
+                self.random_tick(rules, world, pos, state);
+            }
+        }
+
+        handled
+    }
+
+    /// Takes the items dropped by blocks broken since the last call, with the position of
+    /// the block each came from.
+    pub fn take_drops(&mut self) -> Vec<(BlockPos, ItemStack)> {
+        std::mem::take(&mut self.drops)
+    }
+
+    /// Takes the blocks that started falling since the last call, with where they were. They
+    /// are air now, and are up to the caller to move down.
+    pub fn take_falling(&mut self) -> Vec<(BlockPos, BlockState)> {
+        std::mem::take(&mut self.falling)
+    }
+
+    /// Adds `items` to the drops of the block at `pos`.
+    pub(super) fn drop_items(&mut self, pos: BlockPos, items: &[ItemStack]) {
+        self.drops.extend(items.iter().map(|&stack| (pos, stack)));
+    }
+
+    /// Breaks `state` at `pos` on its own, leaving what remains of it and dropping its items.
+    fn destroy_block(&mut self, rules: &BlockRules, world: &mut impl WorldAccess, pos: BlockPos, state: BlockState) {
+        self.set_block(world, pos, rules.remains(state));
+        self.drop_items(pos, rules.natural_drops(state));
+    }
+
+    /// Handles a scheduled tick of `state` at `pos`.
+    fn scheduled_tick(&mut self, rules: &BlockRules, world: &mut impl WorldAccess, pos: BlockPos, state: BlockState) {
+        if rules.falls(state) && can_fall_into(rules, world, pos) {
+            self.set_block(world, pos, BlockState::AIR);
+            self.falling.push((pos, state));
+        }
+        else if rules.fluid(state).is_some() {
+            fluids::fluid_tick(self, rules, world, pos, state);
+        }
+    }
+
+    /// Handles a random tick of `state` at `pos`.
+    fn random_tick(&mut self, rules: &BlockRules, world: &mut impl WorldAccess, pos: BlockPos, state: BlockState) {
+        if let Some(older) = rules.grows_into(state)
+            && light(world, pos) >= GROW_LIGHT
+            && self.random.chance(GROW_CHANCE)
+        {
+            self.set_block(world, pos, older);
+        }
+
+        if let Some(soil) = rules.spreads(state) {
+            let above = pos.step(Direction::Up);
+
+            if world.block(above).is_some_and(|above| rules.smothers(above)) {
+                self.set_block(world, pos, soil);
+            }
+            else if light(world, above) >= GROW_LIGHT {
+                for _ in 0..SPREAD_TRIES {
+                    let target = pos.offset(self.random.next_range(-1, 1), self.random.next_range(-3, 1), self.random.next_range(-1, 1));
+                    let covered = world.block(target.step(Direction::Up)).is_none_or(|above| rules.smothers(above));
+
+                    if world.block(target) == Some(soil) && !covered {
+                        self.set_block(world, target, state);
+                    }
+                }
+            }
+        }
+
+        if rules.decays(state) && !is_held(rules, world, pos) {
+            self.destroy_block(rules, world, pos, state);
+        }
+    }
+}
+
+/// Returns whether the block at `pos` can fall: the block below is loaded and replaceable.
+fn can_fall_into(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos) -> bool {
+    let below = pos.step(Direction::Down);
+    below.local().is_some() && world.block(below).is_some_and(|below| rules.is_replaceable(below))
+}
+
+/// Returns the brightest of the sky and block light at `pos`, or 0 if it is not loaded.
+fn light(world: &impl WorldAccess, pos: BlockPos) -> u8 {
+    world.light(pos).map_or(0, |(sky, block)| sky.max(block))
+}
+
+/// Returns whether leaves at `pos` are within [`LEAF_REACH`] steps through other leaves of a
+/// block holding them. Unloaded blocks count as holding them, so that leaves on the edge of
+/// the loaded world do not decay.
+fn is_held(rules: &BlockRules, world: &impl BlockAccess, pos: BlockPos) -> bool {
+    let mut visited = HashSet::from([pos]);
+    let mut queue = VecDeque::from([(pos, 0)]);
+
+    while let Some((current, distance)) = queue.pop_front() {
+        for neighbor in current.neighbors() {
+            if !visited.insert(neighbor) {
+                continue;
+            }
+
+            match world.block(neighbor) {
+                None if neighbor.local().is_some() => return true,
+                None => {}
+                Some(state) if rules.holds_leaves(state) => return true,
+                Some(state) if rules.is_leaves(state) && distance + 1 < LEAF_REACH => queue.push_back((neighbor, distance + 1)),
+                Some(_) => {}
+            }
+        }
+    }
+
+    false
+}
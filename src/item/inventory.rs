@@ -0,0 +1,118 @@
+use super::{ItemId, ItemRegistry, ItemStack};
+
+/// Number of slots of a player's inventory.
+pub const INVENTORY_SIZE: usize = 36;
+
+/// Number of slots of the hotbar, the first slots of the inventory.
+pub const HOTBAR_SIZE: usize = 9;
+
+/// The items a player carries, in a fixed number of slots.
+///
+/// The first [`HOTBAR_SIZE`] slots make up the hotbar, one of which is selected: its item is
+/// the one held, placed and used as a tool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inventory {
+    slots: [Option<ItemStack>; INVENTORY_SIZE],
+    selected: usize,
+}
+
+impl Inventory {
+    /// Creates an empty inventory with the first slot selected.
+    pub fn new() -> Self {
+        Self { slots: [None; INVENTORY_SIZE], selected: 0 }
+    }
+
+    /// Returns every slot, the hotbar first.
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    /// Returns the stack in `slot`, if any.
+    pub fn get(&self, slot: usize) -> Option<ItemStack> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    /// Replaces the stack in `slot`, returning the previous one. Empty stacks clear the slot.
+    pub fn set(&mut self, slot: usize, stack: Option<ItemStack>) -> Option<ItemStack> {
+        std::mem::replace(&mut self.slots[slot], stack.filter(|stack| stack.count > 0))
+    }
+
+    /// Returns the index of the selected hotbar slot.
+    pub fn selected_slot(&self) -> usize {
+        self.selected
+    }
+
+    /// Selects a hotbar slot, wrapping around past either end.
+    pub fn select(&mut self, slot: isize) {
+        self.selected = slot.rem_euclid(HOTBAR_SIZE as isize) as usize;
+    }
+
+    /// Moves the selection `offset` slots to the right, wrapping around past either end.
+    pub fn scroll(&mut self, offset: isize) {
+        self.select(self.selected as isize + offset);
+    }
+
+    /// Returns the stack held, in the selected slot.
+    pub fn selected(&self) -> Option<ItemStack> {
+        self.slots[self.selected]
+    }
+
+    /// Takes up to `count` items from the selected slot and returns them.
+    pub fn take_selected(&mut self, count: u8) -> Option<ItemStack> {
+        let slot = self.slots[self.selected].as_mut()?;
+        let taken = ItemStack::new(slot.item, count.min(slot.count));
+
+        slot.count -= taken.count;
+
+        if slot.count == 0 {
+            self.slots[self.selected] = None;
+        }
+
+        (taken.count > 0).then_some(taken)
+    }
+
+    /// Puts `stack` into the inventory, topping up stacks of the same item before filling
+    /// empty slots, the hotbar first. Returns the items that did not fit.
+    pub fn add(&mut self, items: &ItemRegistry, stack: ItemStack) -> Option<ItemStack> {
+        let max = items.item(stack.item).max_stack();
+        let mut left = stack.count;
+
+        for slot in self.slots.iter_mut().flatten().filter(|slot| slot.item == stack.item) {
+            let moved = left.min(max.saturating_sub(slot.count));
+            slot.count += moved;
+            left -= moved;
+        }
+
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if left == 0 {
+                break;
+            }
+
+            let moved = left.min(max);
+            *slot = Some(ItemStack::new(stack.item, moved));
+            left -= moved;
+        }
+
+        (left > 0).then_some(ItemStack::new(stack.item, left))
+    }
+
+    /// Makes `item` the held item if the inventory has any: selects its hotbar slot, or
+    /// swaps it from the rest of the inventory into the selected slot. Returns whether it is
+    /// now held.
+    pub fn pick(&mut self, item: ItemId) -> bool {
+        let Some(slot) = self.slots.iter().position(|slot| slot.is_some_and(|slot| slot.item == item)) else {
+            return false;
+        };
+
+        match slot < HOTBAR_SIZE {
+            true => self.selected = slot,
+            false => self.slots.swap(slot, self.selected),
+        }
+
+        true
+    }
+}
+
+impl Default for Inventory {
+    fn default() -> Self { Self::new() }
+}
@@ -0,0 +1,7 @@
+mod registry;
+mod stack;
+mod inventory;
+
+pub use registry::{Item, ItemDefinition, ItemId, ItemRegistry, Tool};
+pub use stack::ItemStack;
+pub use inventory::{Inventory, HOTBAR_SIZE, INVENTORY_SIZE};
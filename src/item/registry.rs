@@ -0,0 +1,229 @@
+use crate::block::{BlockId, BlockRegistry, ToolKind, AIR, DEFAULT_NAMESPACE};
+use crate::resource::ResourceManager;
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Directory of `assets/` holding item data files, one per namespace.
+const ITEMS_DIR: &str = "items/";
+
+/// Most items of a kind held in one inventory slot, unless the item says otherwise.
+const DEFAULT_MAX_STACK: u8 = 64;
+
+/// Index of an item in an [`ItemRegistry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ItemId(u16);
+
+impl ItemId {
+    /// Returns the numeric index of the item.
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// What makes an item a tool.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tool {
+    /// Blocks the tool breaks faster.
+    pub kind: ToolKind,
+
+    /// How many times faster than by hand those blocks break.
+    pub speed: f32,
+}
+
+/// An item as written in an `items/<namespace>.toml` data file.
+///
+/// # Example
+///
+/// ```toml
+/// [stone_pickaxe]
+/// tool = { kind = "pickaxe", speed = 4.0 }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemDefinition {
+    /// Most items held in one slot. Defaults to 64, or 1 for tools.
+    pub max_stack: Option<u8>,
+
+    /// Tool properties, if the item is a tool.
+    pub tool: Option<Tool>,
+
+    /// Texture drawn for the item. Defaults to `item/<name>`.
+    pub texture: Option<String>,
+}
+
+/// A registered item, such as `rustedcraft:stone_pickaxe` or the item placing a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    id: ItemId,
+    name: String,
+    max_stack: u8,
+    block: Option<BlockId>,
+    tool: Option<Tool>,
+    texture: Option<String>,
+}
+
+impl Item {
+    /// Returns the index of the item in its registry.
+    pub fn id(&self) -> ItemId {
+        self.id
+    }
+
+    /// Returns the namespaced name, such as `rustedcraft:torch`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the most items of this kind held in one slot.
+    pub fn max_stack(&self) -> u8 {
+        self.max_stack
+    }
+
+    /// Returns the block the item places, if any.
+    pub fn block(&self) -> Option<BlockId> {
+        self.block
+    }
+
+    /// Returns the tool properties of the item, if it is a tool.
+    pub fn tool(&self) -> Option<&Tool> {
+        self.tool.as_ref()
+    }
+
+    /// Returns the texture drawn for the item, or `None` for block items, drawn as their
+    /// block.
+    pub fn texture(&self) -> Option<&str> {
+        self.texture.as_deref()
+    }
+}
+
+/// Every item known to the game.
+///
+/// Each block but air comes with an item of the same name placing it, registered first in
+/// block order. Other items, like tools, are loaded from data files; see
+/// `assets/items/rustedcraft.toml` for the format.
+#[derive(Debug, Clone)]
+pub struct ItemRegistry {
+    items: Vec<Item>,
+    names: HashMap<String, ItemId>,
+
+    /// Item of each block, by block index.
+    block_items: Vec<Option<ItemId>>,
+}
+
+impl ItemRegistry {
+    /// Creates a registry holding the items of the blocks of `blocks`.
+    pub fn new(blocks: &BlockRegistry) -> Self {
+        let mut registry = Self { items: Vec::new(), names: HashMap::new(), block_items: vec![None; blocks.blocks().len()] };
+
+        for block in blocks.blocks().iter().filter(|block| block.name() != AIR) {
+            let id = registry.push(block.name(), DEFAULT_MAX_STACK, Some(block.id()), None, None);
+            registry.block_items[block.id().index()] = Some(id);
+        }
+
+        registry
+    }
+
+    /// Loads the items of every `items/<namespace>.toml` file provided by `resources`, in
+    /// file name order, after the items of the blocks of `blocks`.
+    ///
+    /// # Returns
+    /// * `Err(String)` if a file is invalid.
+    pub fn load(resources: &ResourceManager, blocks: &BlockRegistry) -> Result<Self, String> {
+        let mut registry = Self::new(blocks);
+
+        for path in resources.list(ITEMS_DIR) {
+            let Some(namespace) = path.strip_prefix(ITEMS_DIR).and_then(|file| file.strip_suffix(".toml")) else {
+                continue;
+            };
+
+            if namespace.contains('/') {
+                continue;
+            }
+
+            let source = resources.read_to_string(&path)?;
+            registry.load_str(namespace, &source).map_err(|e| format!("{path}: {e}"))?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Registers every item of a data file whose items belong to `namespace`. Each top-level
+    /// table is an item named after its key, see [`ItemDefinition`].
+    pub fn load_str(&mut self, namespace: &str, source: &str) -> Result<(), String> {
+        let table: toml::Table = toml::from_str(source).map_err(|e| e.to_string())?;
+
+        for (path, value) in table {
+            let name = format!("{namespace}:{path}");
+            let definition = value.try_into().map_err(|e| format!("{name}: {e}"))?;
+            self.register(&name, &definition).map_err(|e| format!("{name}: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds an item.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the name is taken, the stack size is 0 or the tool speed is not
+    ///   positive.
+    pub fn register(&mut self, name: &str, definition: &ItemDefinition) -> Result<ItemId, String> {
+        if self.names.contains_key(name) {
+            return Err(format!("item {name} is registered twice"));
+        }
+
+        if self.items.len() > u16::MAX as usize {
+            return Err(String::from("too many items, at most 65536 are supported"));
+        }
+
+        let max_stack = definition.max_stack.unwrap_or(if definition.tool.is_some() { 1 } else { DEFAULT_MAX_STACK });
+
+        if max_stack == 0 {
+            return Err(String::from("max_stack must be at least 1"));
+        }
+
+        if let Some(tool) = definition.tool
+            && (tool.speed.is_nan() || tool.speed <= 0.0)
+        {
+            return Err(format!("invalid tool speed {}", tool.speed));
+        }
+
+        let path = name.split_once(':').map_or(name, |(_, path)| path);
+        let texture = definition.texture.clone().unwrap_or_else(|| format!("item/{path}"));
+
+        Ok(self.push(name, max_stack, None, definition.tool, Some(texture)))
+    }
+
+    /// Returns every item, in identifier order.
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Returns the item with the given identifier.
+    pub fn item(&self, id: ItemId) -> &Item {
+        &self.items[id.index()]
+    }
+
+    /// Returns the item called `name`. The namespace may be left out for built-in items.
+    pub fn get(&self, name: &str) -> Option<&Item> {
+        let id = match name.contains(':') {
+            true => self.names.get(name),
+            false => self.names.get(&format!("{DEFAULT_NAMESPACE}:{name}")),
+        };
+
+        id.map(|id| self.item(*id))
+    }
+
+    /// Returns the item placing the block `block`, if it has one.
+    pub fn block_item(&self, block: BlockId) -> Option<&Item> {
+        self.block_items.get(block.index()).copied().flatten().map(|id| self.item(id))
+    }
+
+    fn push(&mut self, name: &str, max_stack: u8, block: Option<BlockId>, tool: Option<Tool>, texture: Option<String>) -> ItemId {
+        let id = ItemId(self.items.len() as u16);
+
+        self.items.push(Item { id, name: name.to_string(), max_stack, block, tool, texture });
+        self.names.insert(name.to_string(), id);
+        id
+    }
+}
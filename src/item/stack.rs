@@ -0,0 +1,20 @@
+use super::ItemId;
+
+/// A number of items of one kind, as held in an inventory slot or lying on the ground.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ItemStack {
+    pub item: ItemId,
+    pub count: u8,
+}
+
+impl ItemStack {
+    /// Creates a stack of `count` items.
+    pub const fn new(item: ItemId, count: u8) -> Self {
+        Self { item, count }
+    }
+
+    /// Creates a stack of a single item.
+    pub const fn one(item: ItemId) -> Self {
+        Self::new(item, 1)
+    }
+}
@@ -0,0 +1,7 @@
+mod pool;
+mod settings;
+mod streamer;
+
+pub use pool::{CancelToken, WorkerPool};
+pub use settings::ChunkSettings;
+pub use streamer::{ChunkMesh, ChunkStreamer};
@@ -0,0 +1,123 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed set of threads running jobs in the order they are spawned.
+///
+/// The pool knows nothing about priorities: callers keep their own ordered backlog and only
+/// hand over as many jobs as there are threads to run them, so the order can still change
+/// while jobs wait. Dropping the pool waits for the jobs already spawned to finish.
+pub struct WorkerPool {
+    sender: Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+
+    /// Jobs spawned and not finished yet, queued or running.
+    pending: Arc<AtomicUsize>,
+}
+
+impl WorkerPool {
+    /// Starts `threads` workers named after `name`, or one less than the number of cores when
+    /// `threads` is 0, leaving a core to the main thread.
+    pub fn new(name: &str, threads: usize) -> Self {
+        let threads = match threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get().saturating_sub(1).max(1)),
+            threads => threads,
+        };
+
+        let (sender, receiver) = channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        let workers = (0..threads)
+            .map(|index| {
+                let receiver = receiver.clone();
+                let pending = pending.clone();
+
+                std::thread::Builder::new()
+                    .name(format!("{name}-{index}"))
+                    .spawn(move || work(&receiver, &pending))
+                    .expect("failed to start a worker thread")
+            })
+            .collect();
+
+        Self { sender: Some(sender), workers, pending }
+    }
+
+    /// Returns the number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Returns the number of jobs queued or running.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+
+    /// Queues `job` to run on the first free worker.
+    pub fn spawn(&self, job: impl FnOnce() + Send + 'static) {
+        self.pending.fetch_add(1, Ordering::AcqRel);
+
+        if let Some(sender) = &self.sender {
+            sender.send(Box::new(job)).expect("worker threads stopped");
+        }
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        // Closing the channel makes every worker return once the queue is empty.
+        self.sender = None;
+
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn work(receiver: &Mutex<Receiver<Job>>, pending: &AtomicUsize) {
+    loop {
+        // The lock is only held while waiting, so other workers can pick up the next job.
+        let job = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return,
+        };
+
+        let Ok(job) = job else {
+            return;
+        };
+
+        if catch_unwind(AssertUnwindSafe(job)).is_err() {
+            eprintln!("A job panicked on {}", std::thread::current().name().unwrap_or("a worker thread"));
+        }
+
+        pending.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A flag telling a job its result is no longer wanted.
+///
+/// Cancelling never interrupts a job; long jobs check [`is_cancelled`](Self::is_cancelled)
+/// between steps and give up early.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks the job as cancelled.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    /// Returns whether the job was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// How many chunks are kept around the player and how fast they are built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkSettings {
+    /// Radius of the square of chunks meshed around the player, in chunks. Chunks one further
+    /// are generated too, as meshing reads their blocks.
+    pub view_distance: u32,
+
+    /// Number of worker threads generating and meshing chunks, 0 to pick from the number of
+    /// cores.
+    pub worker_threads: usize,
+
+    /// Maximum number of chunk meshes uploaded to the GPU per frame, to keep frame times even
+    /// while many chunks finish at once.
+    pub upload_budget: usize,
+}
+
+impl Default for ChunkSettings {
+    fn default() -> Self {
+        Self { view_distance: 8, worker_threads: 0, upload_budget: 4 }
+    }
+}
@@ -0,0 +1,569 @@
+use super::{CancelToken, ChunkSettings, WorkerPool};
+use crate::light::{LightEngine, LightRegion};
+use crate::math::Vec3;
+use crate::mesh::{mesh_section, BiomeTints, BlockMeshTable, ChunkNeighbors, ColumnTints, MeshSettings, SectionMesh, SectionNeighborhood};
+use crate::save::WorldStorage;
+use crate::world::{BlockPos, BlockState, Chunk, ChunkGenerator, ChunkPos, SECTION_COUNT, SECTION_SIZE};
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+
+/// Jobs handed to each worker at most, so priorities can still change for the rest.
+const JOBS_PER_THREAD: usize = 2;
+
+/// Mask of every section of a chunk.
+const ALL_SECTIONS: u16 = u16::MAX;
+
+/// New meshes of the sections of a chunk, built on a worker thread and waiting for upload.
+///
+/// The first mesh of a chunk holds every section; later ones only hold the sections that
+/// changed.
+#[derive(Debug, Clone)]
+pub struct ChunkMesh {
+    pub pos: ChunkPos,
+
+    /// Meshes by section index, from 0 at the bottom of the world.
+    pub sections: Vec<(usize, SectionMesh)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobKind {
+    Generate,
+    Mesh,
+}
+
+/// A job running or queued on the pool for a chunk.
+#[derive(Debug)]
+struct RunningJob {
+    id: u64,
+    cancel: CancelToken,
+}
+
+/// What the streamer knows about one chunk in range.
+#[derive(Debug, Default)]
+struct Entry {
+    chunk: Option<Arc<Chunk>>,
+    job: Option<RunningJob>,
+
+    /// Sections with no mesh yet, or one older than their blocks or light, one bit each.
+    ///
+    /// Bits are cleared when a mesh job starts, so sections changed while it runs are
+    /// meshed again afterwards.
+    dirty: u16,
+
+    /// Whether blocks changed since the chunk was generated, loaded or last saved, or the
+    /// saved chunk it was generated in place of is corrupted.
+    modified: bool,
+}
+
+/// The result of a job, sent back to the main thread.
+enum Finished {
+    /// `modified` is set for chunks generated again in place of corrupted ones, so the bad
+    /// data gets saved over.
+    Generated { pos: ChunkPos, id: u64, chunk: Chunk, modified: bool },
+    Meshed { pos: ChunkPos, id: u64, sections: Vec<(usize, SectionMesh)> },
+}
+
+/// Generates and meshes the chunks around the player on a pool of worker threads.
+///
+/// The streamer lives on the main thread and owns every loaded chunk. Each
+/// [`update`](Self::update) collects finished jobs, unloads chunks out of range, cancelling
+/// their jobs, and hands the pool the most urgent work: the chunks closest to the player,
+/// favoring those in front of the camera. Chunks are lit on their own right after being
+/// generated, then joined with the light of their neighbors on arrival, and meshed once they
+/// and their eight neighbors are generated.
+///
+/// With a [`WorldStorage`], chunks are loaded from it before being generated, and those
+/// whose blocks changed are saved when unloaded or by [`save_modified`](Self::save_modified).
+/// Chunks never changed are not saved, as they are generated the same again.
+///
+/// Nothing here touches OpenGL. Finished meshes wait in a queue until the thread owning the
+/// context takes them with [`take_mesh`](Self::take_mesh) and uploads them.
+pub struct ChunkStreamer {
+    pool: WorkerPool,
+    generator: Arc<dyn ChunkGenerator>,
+    table: Arc<BlockMeshTable>,
+    lighting: Arc<LightEngine>,
+    tints: Arc<BiomeTints>,
+    storage: Option<Arc<WorldStorage>>,
+    mesh_settings: MeshSettings,
+    view_distance: i32,
+
+    /// The chunk the player is in, or `None` before the first update.
+    center: Option<ChunkPos>,
+    entries: HashMap<ChunkPos, Entry>,
+    next_id: u64,
+
+    sender: Sender<Finished>,
+    receiver: Receiver<Finished>,
+    meshes: VecDeque<ChunkMesh>,
+    unloaded: Vec<ChunkPos>,
+}
+
+impl ChunkStreamer {
+    /// Creates a streamer generating chunks with `generator`, lighting them with `lighting`
+    /// and meshing them with `table`.
+    ///
+    /// Chunks are drawn untinted until biome colors are given with
+    /// [`set_biome_tints`](Self::set_biome_tints).
+    pub fn new(
+        generator: Arc<dyn ChunkGenerator>,
+        table: Arc<BlockMeshTable>,
+        lighting: Arc<LightEngine>,
+        settings: &ChunkSettings,
+        mesh_settings: MeshSettings,
+    ) -> Self {
+        let (sender, receiver) = channel();
+
+        Self {
+            pool: WorkerPool::new("chunk-worker", settings.worker_threads),
+            generator,
+            table,
+            lighting,
+            tints: Arc::new(BiomeTints::default()),
+            storage: None,
+            mesh_settings,
+            view_distance: settings.view_distance.max(1) as i32,
+            center: None,
+            entries: HashMap::new(),
+            next_id: 0,
+            sender,
+            receiver,
+            meshes: VecDeque::new(),
+            unloaded: Vec::new(),
+        }
+    }
+
+    /// Returns the radius of the square of chunks meshed around the player.
+    pub fn view_distance(&self) -> i32 {
+        self.view_distance
+    }
+
+    /// Changes the view distance, unloading chunks out of the new range on the next update.
+    pub fn set_view_distance(&mut self, view_distance: u32) {
+        self.view_distance = view_distance.max(1) as i32;
+        self.center = None;
+    }
+
+    /// Changes how sections are meshed and remeshes every chunk.
+    pub fn set_mesh_settings(&mut self, settings: MeshSettings) {
+        self.mesh_settings = settings;
+        self.mark_all_dirty();
+    }
+
+    /// Changes the textures and shapes sections are meshed with, as after the block atlas is
+    /// rebuilt, and remeshes every chunk.
+    pub fn set_mesh_table(&mut self, table: Arc<BlockMeshTable>) {
+        self.table = table;
+        self.mark_all_dirty();
+    }
+
+    /// Changes the colors biomes tint blocks with and remeshes every chunk.
+    pub fn set_biome_tints(&mut self, tints: Arc<BiomeTints>) {
+        self.tints = tints;
+        self.mark_all_dirty();
+    }
+
+    /// Loads chunks from `storage` before generating them, and saves changed chunks to it.
+    ///
+    /// Chunks already loaded are kept as they are.
+    pub fn set_storage(&mut self, storage: Arc<WorldStorage>) {
+        self.storage = Some(storage);
+    }
+
+    /// Queues every loaded chunk changed since it was last saved to be saved, if there is a
+    /// storage.
+    pub fn save_modified(&mut self) {
+        let Some(storage) = &self.storage else {
+            return;
+        };
+
+        for entry in self.entries.values_mut().filter(|entry| entry.modified) {
+            if let Some(chunk) = &entry.chunk {
+                storage.save_chunk(chunk.clone());
+                entry.modified = false;
+            }
+        }
+    }
+
+    /// Collects finished jobs and schedules new ones for a player at `position` looking
+    /// along `forward`.
+    pub fn update(&mut self, position: Vec3, forward: Vec3) {
+        let center = ChunkPos::new(
+            (position.x / SECTION_SIZE as f32).floor() as i32,
+            (position.z / SECTION_SIZE as f32).floor() as i32,
+        );
+
+        if self.center != Some(center) {
+            self.center = Some(center);
+            self.unload_out_of_range(center);
+        }
+
+        while let Ok(finished) = self.receiver.try_recv() {
+            self.finish(finished);
+        }
+
+        self.schedule(center, position, forward);
+    }
+
+    /// Returns the loaded chunk at `pos`.
+    pub fn chunk(&self, pos: ChunkPos) -> Option<&Chunk> {
+        self.entries.get(&pos)?.chunk.as_deref()
+    }
+
+    /// Returns the block at `pos`, or `None` if its chunk is not loaded or it is above or
+    /// below the world.
+    pub fn block(&self, pos: BlockPos) -> Option<BlockState> {
+        let (x, y, z) = pos.local()?;
+        Some(self.chunk(pos.chunk())?.get(x, y, z))
+    }
+
+    /// Returns the sky and block light levels at `pos`, or `None` if its chunk is not loaded
+    /// or it is above or below the world.
+    pub fn light(&self, pos: BlockPos) -> Option<(u8, u8)> {
+        let (x, y, z) = pos.local()?;
+        let chunk = self.chunk(pos.chunk())?;
+        Some((chunk.sky_light(x, y, z), chunk.block_light(x, y, z)))
+    }
+
+    /// Returns the loaded chunk at `pos` for editing, and schedules it to be remeshed.
+    ///
+    /// Jobs still reading the chunk keep their own copy. Neither light nor neighbors are
+    /// updated; [`set_block`](Self::set_block) takes care of both.
+    pub fn chunk_mut(&mut self, pos: ChunkPos) -> Option<&mut Chunk> {
+        let entry = self.entries.get_mut(&pos)?;
+        let chunk = entry.chunk.as_mut()?;
+
+        entry.dirty = ALL_SECTIONS;
+        entry.modified = true;
+
+        Some(Arc::make_mut(chunk))
+    }
+
+    /// Sets the block at `pos` in a loaded chunk and updates light around it, returning the
+    /// previous block, or `None` if the chunk is not loaded.
+    ///
+    /// Only the sections whose blocks or light the change shows in are scheduled to be
+    /// remeshed, in this chunk and its neighbors.
+    pub fn set_block(&mut self, pos: BlockPos, state: BlockState) -> Option<BlockState> {
+        let (x, y, z) = pos.local()?;
+        let center = pos.chunk();
+        let entry = self.entries.get_mut(&center)?;
+        let old = Arc::make_mut(entry.chunk.as_mut()?).set(x, y, z, state);
+
+        if old == state {
+            return Some(old);
+        }
+
+        entry.modified = true;
+
+        self.relight(center, |lighting, region| lighting.block_changed(region, pos, old));
+
+        // Faces and ambient occlusion of the blocks around depend on this one, and those may
+        // lie in the sections above and below or across the border.
+        let below = y.saturating_sub(1) / SECTION_SIZE;
+        let above = ((y + 1) / SECTION_SIZE).min(SECTION_COUNT - 1);
+        let sections = (below..=above).fold(0, |mask, section| mask | 1 << section);
+
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                self.mark_sections_dirty(pos.offset(dx, 0, dz).chunk(), sections);
+            }
+        }
+
+        Some(old)
+    }
+
+    /// Schedules the chunk at `pos` to be remeshed, if it is loaded.
+    pub fn mark_dirty(&mut self, pos: ChunkPos) {
+        self.mark_sections_dirty(pos, ALL_SECTIONS);
+    }
+
+    /// Schedules the sections of the chunk at `pos` whose bits are set in `sections` to be
+    /// remeshed, if it is loaded. Bit 0 is the bottom section.
+    pub fn mark_sections_dirty(&mut self, pos: ChunkPos, sections: u16) {
+        if let Some(entry) = self.entries.get_mut(&pos)
+            && entry.chunk.is_some()
+        {
+            entry.dirty |= sections;
+        }
+    }
+
+    /// Returns the loaded chunks.
+    pub fn chunks(&self) -> impl Iterator<Item = &Chunk> {
+        self.entries.values().filter_map(|entry| entry.chunk.as_deref())
+    }
+
+    /// Returns the number of loaded chunks.
+    pub fn chunk_count(&self) -> usize {
+        self.entries.values().filter(|entry| entry.chunk.is_some()).count()
+    }
+
+    /// Returns the number of jobs queued or running on the workers.
+    pub fn pending_jobs(&self) -> usize {
+        self.pool.pending()
+    }
+
+    /// Takes the oldest mesh waiting for upload.
+    pub fn take_mesh(&mut self) -> Option<ChunkMesh> {
+        self.meshes.pop_front()
+    }
+
+    /// Returns the number of meshes waiting for upload.
+    pub fn queued_meshes(&self) -> usize {
+        self.meshes.len()
+    }
+
+    /// Takes the positions of chunks unloaded since the last call, whose GPU buffers can be
+    /// freed.
+    pub fn take_unloaded(&mut self) -> Vec<ChunkPos> {
+        std::mem::take(&mut self.unloaded)
+    }
+
+    fn mark_all_dirty(&mut self) {
+        let positions: Vec<ChunkPos> = self.entries.keys().copied().collect();
+
+        for pos in positions {
+            self.mark_dirty(pos);
+        }
+    }
+
+    fn unload_out_of_range(&mut self, center: ChunkPos) {
+        let range = self.view_distance + 1;
+        let unloaded = &mut self.unloaded;
+        let storage = self.storage.as_deref();
+
+        self.entries.retain(|&pos, entry| {
+            if pos.distance(center) <= range {
+                return true;
+            }
+
+            if let Some(job) = &entry.job {
+                job.cancel.cancel();
+            }
+
+            if let Some(storage) = storage
+                && entry.modified
+                && let Some(chunk) = entry.chunk.take()
+            {
+                storage.save_chunk(chunk);
+            }
+
+            unloaded.push(pos);
+            false
+        });
+
+        self.meshes.retain(|mesh| mesh.pos.distance(center) <= range);
+    }
+
+    fn finish(&mut self, finished: Finished) {
+        match finished {
+            Finished::Generated { pos, id, chunk, modified } => {
+                let Some(entry) = self.entries.get_mut(&pos).filter(|entry| entry.job.as_ref().is_some_and(|job| job.id == id)) else {
+                    return;
+                };
+
+                entry.job = None;
+                entry.chunk = Some(Arc::new(chunk));
+                entry.dirty = ALL_SECTIONS;
+                entry.modified = modified;
+
+                self.relight(pos, |lighting, region| lighting.join(region));
+            }
+            Finished::Meshed { pos, id, sections } => {
+                let Some(entry) = self.entries.get_mut(&pos).filter(|entry| entry.job.as_ref().is_some_and(|job| job.id == id)) else {
+                    return;
+                };
+
+                entry.job = None;
+
+                // Sections of a mesh still waiting for upload are replaced, the others kept.
+                match self.meshes.iter_mut().find(|mesh| mesh.pos == pos) {
+                    Some(queued) => {
+                        queued.sections.retain(|(index, _)| sections.iter().all(|(new, _)| new != index));
+                        queued.sections.extend(sections);
+                    }
+                    None => self.meshes.push_back(ChunkMesh { pos, sections }),
+                }
+            }
+        }
+    }
+
+    /// Runs `update` on the light of the chunk at `center` and its loaded neighbors, and
+    /// schedules the sections whose light changed to be remeshed, along with those above and
+    /// below, whose faces show that light.
+    fn relight(&mut self, center: ChunkPos, update: impl FnOnce(&LightEngine, &mut LightRegion)) {
+        let positions: [ChunkPos; 9] = std::array::from_fn(|slot| center.offset(slot as i32 % 3 - 1, slot as i32 / 3 - 1));
+
+        // Jobs still reading these chunks keep their own copy.
+        let chunks = self.entries.get_disjoint_mut(positions.each_ref()).map(|entry| entry.and_then(|entry| entry.chunk.as_mut()).map(Arc::make_mut));
+
+        if chunks[4].is_none() {
+            return;
+        }
+
+        let mut region = LightRegion::from_grid(center, chunks);
+        update(&self.lighting, &mut region);
+        let changed: Vec<(ChunkPos, u16)> = region.changed().collect();
+
+        for (pos, sections) in changed {
+            self.mark_sections_dirty(pos, sections | sections << 1 | sections >> 1);
+        }
+    }
+
+    fn schedule(&mut self, center: ChunkPos, position: Vec3, forward: Vec3) {
+        let capacity = self.pool.threads() * JOBS_PER_THREAD;
+
+        if self.pool.pending() >= capacity {
+            return;
+        }
+
+        let range = self.view_distance + 1;
+        let mut candidates = Vec::new();
+
+        for dz in -range..=range {
+            for dx in -range..=range {
+                let pos = center.offset(dx, dz);
+
+                let kind = match self.entries.get(&pos) {
+                    None => JobKind::Generate,
+                    Some(entry) if entry.job.is_none() && entry.dirty != 0 && pos.distance(center) < range && self.neighbors_loaded(pos) => {
+                        JobKind::Mesh
+                    }
+                    Some(_) => continue,
+                };
+
+                candidates.push((priority(pos, position, forward), pos, kind));
+            }
+        }
+
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        for (_, pos, kind) in candidates.into_iter().take(capacity - self.pool.pending()) {
+            match kind {
+                JobKind::Generate => self.spawn_generate(pos),
+                JobKind::Mesh => self.spawn_mesh(pos),
+            }
+        }
+    }
+
+    fn neighbors_loaded(&self, pos: ChunkPos) -> bool {
+        (-1..=1).all(|dz| (-1..=1).all(|dx| self.chunk(pos.offset(dx, dz)).is_some()))
+    }
+
+    fn start_job(&mut self, pos: ChunkPos) -> (u64, CancelToken) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let cancel = CancelToken::new();
+        let entry = self.entries.entry(pos).or_default();
+        entry.job = Some(RunningJob { id, cancel: cancel.clone() });
+
+        (id, cancel)
+    }
+
+    fn spawn_generate(&mut self, pos: ChunkPos) {
+        let (id, cancel) = self.start_job(pos);
+        let generator = self.generator.clone();
+        let lighting = self.lighting.clone();
+        let storage = self.storage.clone();
+        let sender = self.sender.clone();
+
+        self.pool.spawn(move || {
+            if cancel.is_cancelled() {
+                return;
+            }
+
+            let mut corrupted = false;
+
+            let loaded = storage.and_then(|storage| {
+                storage.load(pos).unwrap_or_else(|e| {
+                    eprintln!("Chunk {}, {} is corrupted, generating it again: {e}", pos.x, pos.z);
+                    corrupted = true;
+                    None
+                })
+            });
+
+            let mut chunk = loaded.unwrap_or_else(|| generator.generate(pos));
+            lighting.light_chunk(&mut chunk);
+            chunk.compact();
+
+            let _ = sender.send(Finished::Generated { pos, id, chunk, modified: corrupted });
+        });
+    }
+
+    fn spawn_mesh(&mut self, pos: ChunkPos) {
+        // Chunks are shared with the job rather than copied; edits made meanwhile copy them.
+        let chunks: Vec<Option<Arc<Chunk>>> = (-1..=1)
+            .flat_map(|dz| (-1..=1).map(move |dx| (dx, dz)))
+            .map(|(dx, dz)| self.entries.get(&pos.offset(dx, dz)).and_then(|entry| entry.chunk.clone()))
+            .collect();
+
+        // Sections changed from here on are meshed again by a later job.
+        let dirty = std::mem::take(&mut self.entries.get_mut(&pos).expect("meshed chunks are loaded").dirty);
+        let (id, cancel) = self.start_job(pos);
+        let table = self.table.clone();
+        let tints = self.tints.clone();
+        let settings = self.mesh_settings;
+        let sender = self.sender.clone();
+
+        self.pool.spawn(move || {
+            let center = chunks[4].as_deref().expect("meshed chunks are loaded");
+            let mut neighbors = ChunkNeighbors::new(center);
+
+            for (slot, chunk) in chunks.iter().enumerate() {
+                if slot != 4 {
+                    neighbors = neighbors.with(slot as i32 % 3 - 1, slot as i32 / 3 - 1, chunk.as_deref());
+                }
+            }
+
+            let column_tints = ColumnTints::blend(&neighbors, &tints, settings.biome_blend);
+            let mut sections = Vec::with_capacity(dirty.count_ones() as usize);
+
+            for section in (0..SECTION_COUNT).filter(|section| dirty & 1 << section != 0) {
+                if cancel.is_cancelled() {
+                    return;
+                }
+
+                if center.section(section).is_empty() {
+                    sections.push((section, SectionMesh::default()));
+                    continue;
+                }
+
+                let mut neighborhood = SectionNeighborhood::from_chunks(&neighbors, section);
+                neighborhood.set_tints(column_tints.clone());
+                sections.push((section, mesh_section(&neighborhood, &table, &settings)));
+            }
+
+            let _ = sender.send(Finished::Meshed { pos, id, sections });
+        });
+    }
+}
+
+impl Drop for ChunkStreamer {
+    fn drop(&mut self) {
+        // Let the workers skip what is left so dropping the pool does not wait on it.
+        for job in self.entries.values().filter_map(|entry| entry.job.as_ref()) {
+            job.cancel.cancel();
+        }
+    }
+}
+
+/// Returns how urgent work on the chunk at `pos` is, lower first.
+///
+/// This is the horizontal distance from the player to the chunk center, up to doubled for
+/// chunks behind the camera, so what the player looks at appears first.
+fn priority(pos: ChunkPos, position: Vec3, forward: Vec3) -> f32 {
+    let half = SECTION_SIZE as f32 / 2.0;
+    let offset = Vec3::new(pos.x as f32 * SECTION_SIZE as f32 + half - position.x, 0.0, pos.z as f32 * SECTION_SIZE as f32 + half - position.z);
+    let distance = offset.length();
+    let facing = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
+
+    // The chunk the player stands in is always in view, whatever the angle.
+    if distance <= SECTION_SIZE as f32 {
+        return distance;
+    }
+
+    distance * (1.5 - 0.5 * offset.normalize_or_zero().dot(facing))
+}
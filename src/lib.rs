@@ -1,2 +1,24 @@
 #![cfg_attr(debug_assertions, allow(dead_code, unused))]
 pub mod opengl;
+pub mod resource;
+pub mod game_loop;
+pub mod input;
+pub mod camera;
+pub mod math;
+pub mod window;
+pub mod settings;
+pub mod world;
+pub mod block;
+pub mod mesh;
+pub mod render;
+pub mod jobs;
+pub mod worldgen;
+pub mod light;
+pub mod command;
+pub mod save;
+pub mod nbt;
+pub mod import;
+pub mod physics;
+pub mod item;
+pub mod interaction;
+pub mod entity;
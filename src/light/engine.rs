@@ -0,0 +1,469 @@
+use super::region::Channel;
+use super::{LightRegion, LightTable};
+use crate::world::{BlockPos, BlockState, Chunk, Direction, CHUNK_HEIGHT, MAX_LIGHT, SECTION_COUNT, SECTION_SIZE};
+
+/// Computes sky light and block light by flood filling them from their sources.
+///
+/// Light loses at least one level per block it travels, more through blocks with an opacity
+/// above one, except full sky light going straight down through clear blocks, which keeps
+/// its level so columns open to the sky are lit all the way to the ground. Blocks emitting
+/// light are at least as bright as what they emit.
+///
+/// A chunk is first lit on its own with [`light_chunk`](Self::light_chunk), typically on the
+/// worker that generated it, then [`join`](Self::join)ed with its neighbors once they are
+/// loaded so light flows across their borders. Changing a block afterwards is followed by
+/// [`block_changed`](Self::block_changed), which takes away the light that depended on the
+/// old block before spreading light again from what is left.
+///
+/// The engine holds no state of its own besides its table, so it can be shared between
+/// threads each working on its own chunks.
+#[derive(Debug, Clone)]
+pub struct LightEngine {
+    table: LightTable,
+}
+
+impl LightEngine {
+    /// Creates an engine reading the opacity and light of blocks from `table`.
+    pub fn new(table: LightTable) -> Self {
+        Self { table }
+    }
+
+    /// Returns the opacity and light of every block state.
+    pub fn table(&self) -> &LightTable {
+        &self.table
+    }
+
+    /// Rebuilds the heightmap and light of `chunk` on its own, as if it had no neighbors.
+    pub fn light_chunk(&self, chunk: &mut Chunk) {
+        self.build_heightmap(chunk);
+
+        let heightmap = chunk.heightmap().clone();
+        let (lowest, highest) = (heightmap.min(), heightmap.max());
+
+        // Everything at and above the heightmap is in full sky light.
+        for index in 0..SECTION_COUNT {
+            let base = index * SECTION_SIZE;
+            let sky = chunk.sky_light_section_mut(index);
+            sky.fill(if base >= highest { MAX_LIGHT } else { 0 });
+
+            if base < highest && base + SECTION_SIZE > lowest {
+                for z in 0..SECTION_SIZE {
+                    for x in 0..SECTION_SIZE {
+                        for y in heightmap.get(x, z).max(base)..base + SECTION_SIZE {
+                            sky.set(x, y - base, z, MAX_LIGHT);
+                        }
+                    }
+                }
+            }
+
+            chunk.block_light_section_mut(index).fill(0);
+        }
+
+        // Sky light only spreads from blocks next to darker columns, and from the top of each
+        // column into blocks dimming it partly, like leaves.
+        let mut sky = Vec::new();
+
+        for z in 0..SECTION_SIZE {
+            for x in 0..SECTION_SIZE {
+                let height = heightmap.get(x, z);
+                let mut top = height + 1;
+
+                for (dx, dz) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (nx, nz) = (x as i32 + dx, z as i32 + dz);
+
+                    if (0..SECTION_SIZE as i32).contains(&nx) && (0..SECTION_SIZE as i32).contains(&nz) {
+                        top = top.max(heightmap.get(nx as usize, nz as usize));
+                    }
+                }
+
+                for y in height..top.min(CHUNK_HEIGHT) {
+                    sky.push(node(x as i32, y as i32, z as i32, 0));
+                }
+            }
+        }
+
+        let mut block = Vec::new();
+
+        for (index, section) in chunk.sections().iter().enumerate() {
+            if section.is_empty() || !section.blocks().palette().any(|state| self.table.emission(state) > 0) {
+                continue;
+            }
+
+            for (x, y, z, state) in section.iter() {
+                let emission = self.table.emission(state);
+
+                if emission > 0 {
+                    block.push(node(x as i32, (index * SECTION_SIZE + y) as i32, z as i32, emission));
+                }
+            }
+        }
+
+        for &emitter in &block {
+            let (x, y, z, level) = unpack(emitter);
+            chunk.set_block_light(x as usize, y as usize, z as usize, level);
+        }
+
+        let mut region = LightRegion::new(chunk);
+        self.spread(&mut region, Channel::Sky, &mut sky);
+        self.spread(&mut region, Channel::Block, &mut block);
+    }
+
+    /// Lets light flow between the center chunk of `region` and the neighbors it shares a
+    /// side with, after either was lit without the other.
+    ///
+    /// Joining only ever brightens blocks, so chunks may be joined in any order and more than
+    /// once; the result is the same as lighting them all at once.
+    pub fn join(&self, region: &mut LightRegion) {
+        let mut queues = [Vec::new(), Vec::new()];
+        let last = SECTION_SIZE as i32 - 1;
+
+        for direction in Direction::HORIZONTAL {
+            let (dx, _, dz) = direction.offset();
+
+            if region.get(dx, dz).is_none() {
+                continue;
+            }
+
+            for i in 0..SECTION_SIZE as i32 {
+                let (x, z) = match direction {
+                    Direction::North => (i, 0),
+                    Direction::South => (i, last),
+                    Direction::West => (0, i),
+                    _ => (last, i),
+                };
+
+                for y in 0..CHUNK_HEIGHT as i32 {
+                    for (channel, queue) in [Channel::Sky, Channel::Block].into_iter().zip(queues.iter_mut()) {
+                        let inside = region.light(channel, x, y, z).unwrap_or(0);
+                        let across = region.light(channel, x + dx, y, z + dz).unwrap_or(0);
+
+                        // Light only ever loses a level or more crossing over.
+                        if inside > across + 1 {
+                            queue.push(node(x, y, z, 0));
+                        }
+                        else if across > inside + 1 {
+                            queue.push(node(x + dx, y, z + dz, 0));
+                        }
+                    }
+                }
+            }
+        }
+
+        let [sky, block] = &mut queues;
+        self.spread(region, Channel::Sky, sky);
+        self.spread(region, Channel::Block, block);
+    }
+
+    /// Updates light and the heightmap after the block at `pos`, in the center chunk of
+    /// `region`, was changed from `old` to the block now there.
+    ///
+    /// # Panics
+    /// Panics if `pos` is not in the center chunk.
+    pub fn block_changed(&self, region: &mut LightRegion, pos: BlockPos, old: BlockState) {
+        let origin = region.center_pos().block(0, 0, 0);
+        let (x, y, z) = (pos.x - origin.x, pos.y, pos.z - origin.z);
+        let size = SECTION_SIZE as i32;
+
+        assert!((0..size).contains(&x) && (0..size).contains(&z), "block {pos:?} outside the center chunk of the light region");
+
+        let Some(state) = region.block(x, y, z) else {
+            return;
+        };
+
+        if self.table.same_light(old, state) {
+            return;
+        }
+
+        let center = region.get_mut(0, 0).expect("the center chunk is always set");
+        self.update_height(center, x as usize, y as usize, z as usize, state);
+
+        for channel in [Channel::Sky, Channel::Block] {
+            let mut removed = Vec::new();
+            let mut queue = Vec::new();
+            let level = region.light(channel, x, y, z).unwrap_or(0);
+
+            if level > 0 {
+                region.set_light(channel, x, y, z, 0);
+                removed.push(node(x, y, z, level));
+            }
+
+            self.unspread(region, channel, &mut removed, &mut queue);
+
+            let emission = self.table.emission(state);
+
+            if channel == Channel::Block && emission > 0 {
+                region.set_light(channel, x, y, z, emission);
+                queue.push(node(x, y, z, 0));
+            }
+
+            // The block may now let through light it used to stop.
+            for direction in Direction::ALL {
+                let (dx, dy, dz) = direction.offset();
+
+                if region.light(channel, x + dx, y + dy, z + dz).is_some_and(|level| level > 0) {
+                    queue.push(node(x + dx, y + dy, z + dz, 0));
+                }
+            }
+
+            self.spread(region, channel, &mut queue);
+        }
+    }
+
+    fn build_heightmap(&self, chunk: &mut Chunk) {
+        let top = (0..SECTION_COUNT).rev().find(|&index| !chunk.section(index).is_empty()).map_or(0, |index| (index + 1) * SECTION_SIZE);
+
+        for z in 0..SECTION_SIZE {
+            for x in 0..SECTION_SIZE {
+                let height = self.column_height(chunk, x, z, top);
+                chunk.heightmap_mut().set(x, z, height);
+            }
+        }
+    }
+
+    /// Returns the height sky light falls freely from in a column, looking below `top` only.
+    fn column_height(&self, chunk: &Chunk, x: usize, z: usize, top: usize) -> usize {
+        (0..top).rev().find(|&y| self.table.opacity(chunk.get(x, y, z)) > 0).map_or(0, |y| y + 1)
+    }
+
+    fn update_height(&self, chunk: &mut Chunk, x: usize, y: usize, z: usize, state: BlockState) {
+        let height = chunk.heightmap().get(x, z);
+
+        if self.table.opacity(state) > 0 {
+            if y >= height {
+                chunk.heightmap_mut().set(x, z, y + 1);
+            }
+        }
+        else if y + 1 == height {
+            let height = self.column_height(chunk, x, z, y);
+            chunk.heightmap_mut().set(x, z, height);
+        }
+    }
+
+    /// Returns the level light at `level` has once it moved in `direction` into `state`.
+    #[inline]
+    fn propagated(&self, channel: Channel, level: u8, direction: Direction, state: BlockState) -> u8 {
+        let opacity = self.table.opacity(state);
+
+        if channel == Channel::Sky && direction == Direction::Down && level == MAX_LIGHT && opacity == 0 {
+            return MAX_LIGHT;
+        }
+
+        level.saturating_sub(opacity.max(1))
+    }
+
+    /// Spreads light from every block in `queue` until nothing gets brighter.
+    fn spread(&self, region: &mut LightRegion, channel: Channel, queue: &mut Vec<u32>) {
+        let mut head = 0;
+
+        while let Some(&next) = queue.get(head) {
+            head += 1;
+
+            let (x, y, z, _) = unpack(next);
+
+            // Read again rather than queued, as the block may have been brightened since.
+            let level = region.light(channel, x, y, z).unwrap_or(0);
+
+            if level <= 1 {
+                continue;
+            }
+
+            for direction in Direction::ALL {
+                let (dx, dy, dz) = direction.offset();
+                let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+
+                let Some(state) = region.block(nx, ny, nz) else {
+                    continue;
+                };
+
+                let level = self.propagated(channel, level, direction, state);
+
+                if region.light(channel, nx, ny, nz).is_some_and(|current| current < level) {
+                    region.set_light(channel, nx, ny, nz, level);
+                    queue.push(node(nx, ny, nz, 0));
+                }
+            }
+        }
+
+        queue.clear();
+    }
+
+    /// Darkens every block that may have been lit through the blocks in `removed`, which hold
+    /// the level they had, and queues the blocks around them still lit by something else so
+    /// their light can spread back.
+    ///
+    /// A neighbor dimmer than the removed block may have been lit by it, so it is darkened in
+    /// turn; one as bright or brighter has its own source. Sky light falling straight down is
+    /// the exception, keeping its level: a column below a removed block in full sky light is
+    /// darkened all the way down.
+    fn unspread(&self, region: &mut LightRegion, channel: Channel, removed: &mut Vec<u32>, queue: &mut Vec<u32>) {
+        let mut head = 0;
+
+        while let Some(&next) = removed.get(head) {
+            head += 1;
+
+            let (x, y, z, level) = unpack(next);
+
+            for direction in Direction::ALL {
+                let (dx, dy, dz) = direction.offset();
+                let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+
+                let Some(current) = region.light(channel, nx, ny, nz).filter(|&current| current > 0) else {
+                    continue;
+                };
+
+                let falling = channel == Channel::Sky && direction == Direction::Down && level == MAX_LIGHT && current == MAX_LIGHT;
+
+                if current >= level && !falling {
+                    queue.push(node(nx, ny, nz, 0));
+                    continue;
+                }
+
+                region.set_light(channel, nx, ny, nz, 0);
+                removed.push(node(nx, ny, nz, current));
+
+                if channel == Channel::Block
+                    && let Some(state) = region.block(nx, ny, nz)
+                    && self.table.emission(state) > 0
+                {
+                    region.set_light(channel, nx, ny, nz, self.table.emission(state));
+                    queue.push(node(nx, ny, nz, 0));
+                }
+            }
+        }
+
+        removed.clear();
+    }
+}
+
+/// Packs region coordinates and a light level into a queue entry.
+#[inline]
+fn node(x: i32, y: i32, z: i32, level: u8) -> u32 {
+    let size = SECTION_SIZE as i32;
+    debug_assert!((-size..2 * size).contains(&x) && (-size..2 * size).contains(&z) && (0..CHUNK_HEIGHT as i32).contains(&y));
+    ((x + size) as u32) | (((z + size) as u32) << 6) | ((y as u32) << 12) | ((level as u32) << 20)
+}
+
+#[inline]
+fn unpack(node: u32) -> (i32, i32, i32, u8) {
+    let size = SECTION_SIZE as i32;
+    ((node & 63) as i32 - size, (node >> 12 & 255) as i32, (node >> 6 & 63) as i32 - size, (node >> 20 & 15) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockRegistryBuilder;
+    use crate::world::ChunkPos;
+
+    const BLOCKS: &str = "
+        [stone]
+
+        [torch]
+        render = \"cutout\"
+        light = 14
+    ";
+
+    /// Returns the engine, with stone and a torch.
+    fn engine() -> (LightEngine, BlockState, BlockState) {
+        let mut builder = BlockRegistryBuilder::new();
+        builder.load_str("test", BLOCKS).unwrap();
+        let registry = builder.build().unwrap();
+        let state = |name| registry.default_state(name).unwrap();
+        (LightEngine::new(LightTable::new(&registry)), state("test:stone"), state("test:torch"))
+    }
+
+    /// Returns the sky and block light of every block of `chunk`.
+    fn levels(chunk: &Chunk) -> Vec<[u8; 2]> {
+        let mut levels = Vec::new();
+
+        for y in 0..CHUNK_HEIGHT {
+            for z in 0..SECTION_SIZE {
+                for x in 0..SECTION_SIZE {
+                    levels.push([chunk.sky_light(x, y, z), chunk.block_light(x, y, z)]);
+                }
+            }
+        }
+
+        levels
+    }
+
+    /// Sets a block of `chunk` and updates its light, returning the chunk lit from scratch.
+    fn change(engine: &LightEngine, chunk: &mut Chunk, (x, y, z): (usize, usize, usize), state: BlockState) -> Chunk {
+        let (old, pos) = (chunk.set(x, y, z, state), chunk.pos().block(x, y, z));
+        engine.block_changed(&mut LightRegion::new(chunk), pos, old);
+
+        let mut fresh = chunk.clone();
+        engine.light_chunk(&mut fresh);
+        fresh
+    }
+
+    #[test]
+    fn removing_a_torch_keeps_the_light_of_its_neighbor() {
+        let (engine, _, torch) = engine();
+        let mut chunk = Chunk::new(ChunkPos::new(0, 0));
+        chunk.set(4, 64, 8, torch);
+        chunk.set(5, 64, 8, torch);
+        engine.light_chunk(&mut chunk);
+
+        let fresh = change(&engine, &mut chunk, (4, 64, 8), BlockState::AIR);
+
+        assert_eq!(chunk.block_light(5, 64, 8), 14);
+        assert_eq!(chunk.block_light(4, 64, 8), 13);
+        assert_eq!(chunk.block_light(0, 64, 8), 9);
+        assert!(levels(&chunk) == levels(&fresh));
+    }
+
+    #[test]
+    fn opaque_blocks_shade_and_uncover_the_sky() {
+        let (engine, stone, _) = engine();
+        let mut chunk = Chunk::new(ChunkPos::new(2, -3));
+        engine.light_chunk(&mut chunk);
+
+        let fresh = change(&engine, &mut chunk, (8, 100, 8), stone);
+
+        assert_eq!(chunk.heightmap().get(8, 8), 101);
+        assert_eq!(chunk.sky_light(8, 100, 8), 0);
+        assert_eq!(chunk.sky_light(8, 99, 8), 14);
+        assert_eq!(chunk.sky_light(8, 0, 8), 14);
+        assert!(levels(&chunk) == levels(&fresh));
+
+        let fresh = change(&engine, &mut chunk, (8, 100, 8), BlockState::AIR);
+
+        assert_eq!(chunk.heightmap().get(8, 8), 0);
+        assert!(levels(&chunk).iter().all(|&level| level == [MAX_LIGHT, 0]));
+        assert!(levels(&chunk) == levels(&fresh));
+    }
+
+    #[test]
+    fn joins_the_same_in_any_order() {
+        let (engine, stone, torch) = engine();
+
+        // A covered chunk to the east gets its sky light from the side.
+        let lit = |x: i32, at: (usize, usize, usize), roof: bool| {
+            let mut chunk = Chunk::new(ChunkPos::new(x, 0));
+            chunk.set(at.0, at.1, at.2, torch);
+
+            if roof {
+                chunk.fill((0, 200, 0), (15, 200, 15), stone);
+            }
+
+            engine.light_chunk(&mut chunk);
+            chunk
+        };
+
+        let [mut west, mut center, mut east] = [lit(-1, (15, 64, 8), false), lit(0, (15, 30, 2), false), lit(1, (0, 64, 3), true)];
+        let [mut west_2, mut center_2, mut east_2] = [west.clone(), center.clone(), east.clone()];
+
+        // The center, then the east, then the west.
+        engine.join(&mut LightRegion::new(&mut east).with(-1, 0, Some(&mut center)));
+        engine.join(&mut LightRegion::new(&mut west).with(1, 0, Some(&mut center)));
+
+        // Both sides, then the center.
+        engine.join(&mut LightRegion::new(&mut center_2).with(-1, 0, Some(&mut west_2)).with(1, 0, Some(&mut east_2)));
+
+        assert_eq!(east.sky_light(0, 100, 8), 14);
+        assert!(levels(&west) == levels(&west_2));
+        assert!(levels(&center) == levels(&center_2));
+        assert!(levels(&east) == levels(&east_2));
+    }
+}
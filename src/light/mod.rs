@@ -0,0 +1,7 @@
+mod table;
+mod region;
+mod engine;
+
+pub use table::LightTable;
+pub use region::LightRegion;
+pub use engine::LightEngine;
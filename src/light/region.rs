@@ -0,0 +1,158 @@
+use crate::world::{BlockState, Chunk, ChunkPos, CHUNK_HEIGHT, SECTION_SIZE};
+
+/// Which of the two light levels of a block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Channel {
+    Sky,
+    Block,
+}
+
+/// A chunk and its eight neighbors, borrowed mutably for light to spread through.
+///
+/// Light travels at most 15 blocks, so every change starting in the center chunk stays within
+/// this 3x3 grid. Chunks left out neither give nor receive light; once loaded, they catch up
+/// with [`LightEngine::join`](super::LightEngine::join).
+///
+/// Positions inside the region are relative to the lowest corner of the center chunk, X and
+/// Z in `-16..32`. The region records which sections had their light changed, so only their
+/// meshes are rebuilt.
+#[derive(Debug)]
+pub struct LightRegion<'a> {
+    center: ChunkPos,
+    chunks: [Option<&'a mut Chunk>; 9],
+
+    /// One bit per section whose light changed, for each chunk.
+    changed: [u16; 9],
+}
+
+impl<'a> LightRegion<'a> {
+    /// Creates a region holding only `center`.
+    pub fn new(center: &'a mut Chunk) -> Self {
+        let pos = center.pos();
+        let mut chunks = [const { None }; 9];
+        chunks[4] = Some(center);
+        Self { center: pos, chunks, changed: [0; 9] }
+    }
+
+    /// Creates a region from the chunks around `center`, in rows from north to south of
+    /// chunks from west to east.
+    ///
+    /// # Panics
+    /// Panics if the center chunk is missing or a chunk is not at its place in the grid.
+    pub fn from_grid(center: ChunkPos, chunks: [Option<&'a mut Chunk>; 9]) -> Self {
+        assert!(chunks[4].is_some(), "the center chunk of a light region is required");
+
+        for (slot, chunk) in chunks.iter().enumerate() {
+            if let Some(chunk) = chunk {
+                assert_eq!(chunk.pos(), center.offset(slot as i32 % 3 - 1, slot as i32 / 3 - 1), "chunk out of place in a light region");
+            }
+        }
+
+        Self { center, chunks, changed: [0; 9] }
+    }
+
+    /// Sets the chunk at offset `(dx, dz)` from the center, each in `-1..=1`.
+    ///
+    /// # Panics
+    /// Panics if the chunk is not at that offset.
+    pub fn with(mut self, dx: i32, dz: i32, chunk: Option<&'a mut Chunk>) -> Self {
+        let slot = slot(dx, dz);
+        assert!(slot != 4, "the center chunk of a light region cannot be replaced");
+
+        if let Some(chunk) = &chunk {
+            assert_eq!(chunk.pos(), self.center.offset(dx, dz), "chunk out of place in a light region");
+        }
+
+        self.chunks[slot] = chunk;
+        self
+    }
+
+    /// Returns the position of the center chunk.
+    pub fn center_pos(&self) -> ChunkPos {
+        self.center
+    }
+
+    /// Returns the chunk at offset `(dx, dz)` from the center, each in `-1..=1`.
+    pub fn get(&self, dx: i32, dz: i32) -> Option<&Chunk> {
+        self.chunks[slot(dx, dz)].as_deref()
+    }
+
+    /// Returns the chunk at offset `(dx, dz)` from the center mutably, each in `-1..=1`.
+    pub fn get_mut(&mut self, dx: i32, dz: i32) -> Option<&mut Chunk> {
+        self.chunks[slot(dx, dz)].as_deref_mut()
+    }
+
+    /// Iterates over the chunks whose light changed, with one bit per changed section.
+    pub fn changed(&self) -> impl Iterator<Item = (ChunkPos, u16)> + '_ {
+        let center = self.center;
+
+        self.changed
+            .iter()
+            .enumerate()
+            .filter(|(_, sections)| **sections != 0)
+            .map(move |(slot, &sections)| (center.offset(slot as i32 % 3 - 1, slot as i32 / 3 - 1), sections))
+    }
+
+    /// Forgets which sections changed.
+    pub fn clear_changed(&mut self) {
+        self.changed = [0; 9];
+    }
+
+    /// Returns the block at region coordinates, or `None` outside the loaded chunks.
+    #[inline]
+    pub(super) fn block(&self, x: i32, y: i32, z: i32) -> Option<BlockState> {
+        let (slot, lx, ly, lz) = locate(x, y, z)?;
+        Some(self.chunks[slot].as_deref()?.get(lx, ly, lz))
+    }
+
+    /// Returns a light level at region coordinates, or `None` outside the loaded chunks.
+    #[inline]
+    pub(super) fn light(&self, channel: Channel, x: i32, y: i32, z: i32) -> Option<u8> {
+        let (slot, lx, ly, lz) = locate(x, y, z)?;
+        let chunk = self.chunks[slot].as_deref()?;
+
+        Some(match channel {
+            Channel::Sky => chunk.sky_light(lx, ly, lz),
+            Channel::Block => chunk.block_light(lx, ly, lz),
+        })
+    }
+
+    /// Sets a light level at region coordinates, ignoring positions outside the loaded chunks.
+    #[inline]
+    pub(super) fn set_light(&mut self, channel: Channel, x: i32, y: i32, z: i32, level: u8) {
+        let Some((slot, lx, ly, lz)) = locate(x, y, z) else {
+            return;
+        };
+
+        let Some(chunk) = self.chunks[slot].as_deref_mut() else {
+            return;
+        };
+
+        let old = match channel {
+            Channel::Sky => chunk.set_sky_light(lx, ly, lz, level),
+            Channel::Block => chunk.set_block_light(lx, ly, lz, level),
+        };
+
+        if old != level {
+            self.changed[slot] |= 1 << (ly / SECTION_SIZE);
+        }
+    }
+}
+
+fn slot(dx: i32, dz: i32) -> usize {
+    assert!((-1..=1).contains(&dx) && (-1..=1).contains(&dz), "neighbor offset out of range");
+    ((dz + 1) * 3 + dx + 1) as usize
+}
+
+/// Returns the slot of the chunk holding region coordinates and the local coordinates in it.
+#[inline]
+fn locate(x: i32, y: i32, z: i32) -> Option<(usize, usize, usize, usize)> {
+    let size = SECTION_SIZE as i32;
+
+    if !(-size..2 * size).contains(&x) || !(-size..2 * size).contains(&z) || !(0..CHUNK_HEIGHT as i32).contains(&y) {
+        return None;
+    }
+
+    let slot = ((z.div_euclid(size) + 1) * 3 + x.div_euclid(size) + 1) as usize;
+    Some((slot, x.rem_euclid(size) as usize, y as usize, z.rem_euclid(size) as usize))
+}
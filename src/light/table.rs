@@ -0,0 +1,43 @@
+use crate::block::BlockRegistry;
+use crate::world::BlockState;
+
+/// How every block state of a registry dims and emits light, resolved ahead of time.
+#[derive(Debug, Clone)]
+pub struct LightTable {
+    /// Opacity in the high nibble, emitted light in the low one.
+    states: Vec<u8>,
+}
+
+impl LightTable {
+    /// Builds the table for every state of `registry`.
+    pub fn new(registry: &BlockRegistry) -> Self {
+        let states = registry
+            .states()
+            .map(|state| {
+                let attributes = registry.attributes(state);
+                (attributes.opacity.min(15) << 4) | attributes.light.min(15)
+            })
+            .collect();
+
+        Self { states }
+    }
+
+    /// Returns how much light is lost passing through `state`, from 0 to 15.
+    #[inline]
+    pub fn opacity(&self, state: BlockState) -> u8 {
+        self.states[state.id() as usize] >> 4
+    }
+
+    /// Returns the light level emitted by `state`, from 0 to 15.
+    #[inline]
+    pub fn emission(&self, state: BlockState) -> u8 {
+        self.states[state.id() as usize] & 15
+    }
+
+    /// Returns whether `state` and `other` dim and emit light the same, so swapping one for
+    /// the other leaves light unchanged.
+    #[inline]
+    pub fn same_light(&self, state: BlockState, other: BlockState) -> bool {
+        self.states[state.id() as usize] == self.states[other.id() as usize]
+    }
+}
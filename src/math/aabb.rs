@@ -0,0 +1,58 @@
+use super::Vec3;
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// The unit cube from the origin to `(1, 1, 1)`, the shape of a full block.
+    pub const UNIT: Self = Self::new(Vec3::ZERO, Vec3::ONE);
+
+    /// Creates a box from its minimum and maximum corners.
+    pub const fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns the box moved by `offset`.
+    pub fn offset(self, offset: Vec3) -> Self {
+        Self::new(self.min + offset, self.max + offset)
+    }
+
+    /// Returns the box grown by `amount` on every side, or shrunk if negative.
+    pub fn inflate(self, amount: f32) -> Self {
+        Self::new(self.min - Vec3::splat(amount), self.max + Vec3::splat(amount))
+    }
+
+    /// Returns the box stretched to also cover itself moved by `delta`, the space it sweeps
+    /// through moving in a straight line.
+    pub fn expand_towards(self, delta: Vec3) -> Self {
+        Self::new(self.min + delta.min(Vec3::ZERO), self.max + delta.max(Vec3::ZERO))
+    }
+
+    /// Returns the size of the box along each axis.
+    pub fn size(self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// Returns the center of the box.
+    pub fn center(self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Returns whether the two boxes overlap. Boxes that only touch do not intersect.
+    pub fn intersects(self, other: Self) -> bool {
+        self.min.x < other.max.x && self.max.x > other.min.x &&
+        self.min.y < other.max.y && self.max.y > other.min.y &&
+        self.min.z < other.max.z && self.max.z > other.min.z
+    }
+
+    /// Returns whether `point` is inside the box, borders included.
+    pub fn contains(self, point: Vec3) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x &&
+        point.y >= self.min.y && point.y <= self.max.y &&
+        point.z >= self.min.z && point.z <= self.max.z
+    }
+}
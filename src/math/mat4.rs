@@ -0,0 +1,168 @@
+use super::Vec3;
+use std::ops::Mul;
+
+/// A 4x4 matrix of `f32`, stored in column-major order as OpenGL expects.
+///
+/// Matrices transform column vectors, so `a * b` applies `b` first and then `a`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat4 {
+    /// The four columns of the matrix.
+    pub cols: [[f32; 4]; 4],
+}
+
+impl Mat4 {
+    pub const IDENTITY: Self = Self {
+        cols: [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ],
+    };
+
+    /// Creates a matrix from its columns.
+    pub const fn from_cols(cols: [[f32; 4]; 4]) -> Self {
+        Self { cols }
+    }
+
+    /// Creates a matrix translating points by `offset`.
+    pub fn translation(offset: Vec3) -> Self {
+        let mut matrix = Self::IDENTITY;
+        matrix.cols[3] = [offset.x, offset.y, offset.z, 1.0];
+        matrix
+    }
+
+    /// Creates a matrix scaling points by `scale` along each axis.
+    pub fn scale(scale: Vec3) -> Self {
+        let mut matrix = Self::IDENTITY;
+        matrix.cols[0][0] = scale.x;
+        matrix.cols[1][1] = scale.y;
+        matrix.cols[2][2] = scale.z;
+        matrix
+    }
+
+    /// Creates a right-handed view matrix for an eye at `eye` looking along `forward`.
+    ///
+    /// # Arguments
+    /// * `eye` - Position of the viewer.
+    /// * `forward` - Direction the viewer is facing, need not be normalized.
+    /// * `up` - Approximate up direction, must not be parallel to `forward`.
+    pub fn look_to(eye: Vec3, forward: Vec3, up: Vec3) -> Self {
+        let f = forward.normalize_or_zero();
+        let s = f.cross(up).normalize_or_zero();
+        let u = s.cross(f);
+
+        Self::from_cols([
+            [s.x, u.x, -f.x, 0.0],
+            [s.y, u.y, -f.y, 0.0],
+            [s.z, u.z, -f.z, 0.0],
+            [-s.dot(eye), -u.dot(eye), f.dot(eye), 1.0],
+        ])
+    }
+
+    /// Creates an OpenGL perspective projection mapping depth to `[-1, 1]`.
+    ///
+    /// # Arguments
+    /// * `fov_y` - Vertical field of view, in radians.
+    /// * `aspect` - Width divided by height of the viewport.
+    /// * `near` - Distance to the near clipping plane, must be positive.
+    /// * `far` - Distance to the far clipping plane, must be greater than `near`.
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        let f = 1.0 / (fov_y / 2.0).tan();
+        let range = near - far;
+
+        Self::from_cols([
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [0.0, 0.0, (far + near) / range, -1.0],
+            [0.0, 0.0, 2.0 * far * near / range, 0.0],
+        ])
+    }
+
+    /// Creates a reverse-Z perspective projection with an infinite far plane.
+    ///
+    /// Depth is `1` at the near plane and tends to `0` at infinity, which spreads floating
+    /// point precision evenly over distance. It requires clip control set to a `[0, 1]` depth
+    /// range, a depth buffer cleared to `0` and a `GREATER` depth test.
+    pub fn perspective_reverse_z(fov_y: f32, aspect: f32, near: f32) -> Self {
+        let f = 1.0 / (fov_y / 2.0).tan();
+
+        Self::from_cols([
+            [f / aspect, 0.0, 0.0, 0.0],
+            [0.0, f, 0.0, 0.0],
+            [0.0, 0.0, 0.0, -1.0],
+            [0.0, 0.0, near, 0.0],
+        ])
+    }
+
+    /// Creates an orthographic projection mapping the given box to clip space.
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        let (w, h, d) = (right - left, top - bottom, far - near);
+
+        Self::from_cols([
+            [2.0 / w, 0.0, 0.0, 0.0],
+            [0.0, 2.0 / h, 0.0, 0.0],
+            [0.0, 0.0, -2.0 / d, 0.0],
+            [-(right + left) / w, -(top + bottom) / h, -(far + near) / d, 1.0],
+        ])
+    }
+
+    /// Returns the transpose of the matrix.
+    pub fn transpose(&self) -> Self {
+        let mut cols = [[0.0; 4]; 4];
+
+        for (c, col) in cols.iter_mut().enumerate() {
+            for (r, value) in col.iter_mut().enumerate() {
+                *value = self.cols[r][c];
+            }
+        }
+
+        Self { cols }
+    }
+
+    /// Transforms the point `point`, applying translation and the perspective divide.
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        let [x, y, z, w] = *self * [point.x, point.y, point.z, 1.0];
+        Vec3::new(x, y, z) / w
+    }
+
+    /// Transforms the direction `vector`, ignoring translation.
+    pub fn transform_vector(&self, vector: Vec3) -> Vec3 {
+        let [x, y, z, _] = *self * [vector.x, vector.y, vector.z, 0.0];
+        Vec3::new(x, y, z)
+    }
+
+    /// Returns a pointer to the first element, for passing to OpenGL.
+    pub fn as_ptr(&self) -> *const f32 {
+        self.cols.as_ptr() as *const f32
+    }
+}
+
+impl Default for Mat4 {
+    fn default() -> Self { Self::IDENTITY }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Self { cols: rhs.cols.map(|col| self * col) }
+    }
+}
+
+impl Mul<[f32; 4]> for Mat4 {
+    type Output = [f32; 4];
+
+    fn mul(self, rhs: [f32; 4]) -> [f32; 4] {
+        let mut out = [0.0; 4];
+
+        for (col, &factor) in self.cols.iter().zip(rhs.iter()) {
+            for (value, &element) in out.iter_mut().zip(col.iter()) {
+                *value += element * factor;
+            }
+        }
+
+        out
+    }
+}
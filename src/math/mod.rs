@@ -0,0 +1,9 @@
+mod mat4;
+mod vec3;
+mod aabb;
+mod trig;
+
+pub use mat4::Mat4;
+pub use vec3::Vec3;
+pub use aabb::Aabb;
+pub use trig::sin_cos;
@@ -0,0 +1,25 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+/// Returns the sine and cosine of `angle`, in radians.
+///
+/// The standard library leaves these to the platform, whose results may differ in the last
+/// bits; this only uses basic arithmetic so that terrain and
+/// movement come out the same everywhere.
+pub fn sin_cos(angle: f64) -> (f64, f64) {
+    // Reduced to within a quarter turn around 0, where the series converge quickly.
+    let turns = (angle / TAU).round();
+    let angle = angle - turns * TAU;
+    let quadrant = (angle / FRAC_PI_2).round();
+    let x = angle - quadrant * FRAC_PI_2;
+    let x2 = x * x;
+
+    let sin = x * (1.0 - x2 / 6.0 * (1.0 - x2 / 20.0 * (1.0 - x2 / 42.0 * (1.0 - x2 / 72.0 * (1.0 - x2 / 110.0)))));
+    let cos = 1.0 - x2 / 2.0 * (1.0 - x2 / 12.0 * (1.0 - x2 / 30.0 * (1.0 - x2 / 56.0 * (1.0 - x2 / 90.0 * (1.0 - x2 / 132.0)))));
+
+    match quadrant as i32 {
+        0 => (sin, cos),
+        1 => (cos, -sin),
+        -1 => (-cos, sin),
+        _ => (-sin, -cos),
+    }
+}
@@ -0,0 +1,174 @@
+use super::mesher::{FACE_AXES, NO_AO};
+use super::{BlockMeshTable, BlockVertex, FluidMeshInfo, SectionMesh, SectionNeighborhood, POSITION_SCALE};
+use crate::world::{Direction, SECTION_SIZE};
+
+use std::f32::consts::TAU;
+
+/// Weight of neighbors nearly full of fluid in the height of the corners they share, so that
+/// the surface stays level around sources.
+const FULL_WEIGHT: f32 = 10.0;
+
+/// Height from which a fluid counts as nearly full.
+const NEARLY_FULL: f32 = 0.8;
+
+/// Slope of the surface below which it counts as still.
+const STILL_SLOPE: f32 = 1e-3;
+
+const SIZE: i32 = SECTION_SIZE as i32;
+
+/// Adds the fluids of the section at the center of `neighborhood` to `mesh`.
+///
+/// Each corner of a fluid surface is as high as the fluid around it: full if the same fluid
+/// is above any of the four blocks sharing it, otherwise the average height of those holding
+/// the same fluid, nearly full ones weighing more, and of those it could flow into, which
+/// count as empty. Surfaces sloping down use the flowing texture, turned along the slope;
+/// level ones use the still texture. The surface is drawn from both sides so that it can be
+/// seen from within the fluid.
+///
+/// Sides are drawn up to the corners against anything but the same fluid and opaque cubes,
+/// and the bottom unless the same fluid or an opaque cube is below. Faces take the light of
+/// the block in front of them, without ambient occlusion.
+pub fn mesh_fluids(neighborhood: &SectionNeighborhood, table: &BlockMeshTable, mesh: &mut SectionMesh) {
+    let fluid_at = |x: i32, y: i32, z: i32| table.get(neighborhood.get(x, y, z)).fluid;
+
+    for y in 0..SIZE {
+        for z in 0..SIZE {
+            for x in 0..SIZE {
+                let Some(info) = fluid_at(x, y, z) else {
+                    continue;
+                };
+
+                let Some(data) = mesh.layer_mut(info.layer) else {
+                    continue;
+                };
+
+                let same = |dx: i32, dy: i32, dz: i32| fluid_at(x + dx, y + dy, z + dz).is_some_and(|other| other.fluid == info.fluid);
+                let opaque = |dx: i32, dy: i32, dz: i32| table.get(neighborhood.get(x + dx, y + dy, z + dz)).opaque_cube;
+
+                // Heights of the corners, indexed by their x then z offset.
+                let heights = [0, 1].map(|cx| [0, 1].map(|cz| corner_height(neighborhood, table, info, [x, y, z], cx, cz)));
+                let color = neighborhood.tints().get(x as usize, z as usize).get(info.tint);
+                let origin = [x, y, z].map(|c| c as u32 * POSITION_SCALE);
+
+                let light = |direction: Direction| {
+                    let (dx, dy, dz) = direction.offset();
+                    (neighborhood.sky_light(x + dx, y + dy, z + dz), neighborhood.block_light(x + dx, y + dy, z + dz))
+                };
+
+                for direction in Direction::ALL {
+                    let (dx, dy, dz) = direction.offset();
+
+                    let visible = match direction {
+                        Direction::Up => !same(0, 1, 0),
+                        _ => !same(dx, dy, dz) && !opaque(dx, dy, dz),
+                    };
+
+                    if !visible {
+                        continue;
+                    }
+
+                    let corners = face_corners(direction).map(|mut position| {
+                        if position[1] == POSITION_SCALE {
+                            let height = heights[(position[0] / POSITION_SCALE) as usize][(position[2] / POSITION_SCALE) as usize];
+                            position[1] = (height * POSITION_SCALE as f32).round() as u32;
+                        }
+
+                        [0, 1, 2].map(|axis| origin[axis] + position[axis])
+                    });
+
+                    let (sky, block) = light(direction);
+
+                    let flow = match direction {
+                        Direction::Up => flow_angle(heights),
+                        _ => None,
+                    };
+
+                    let layer = match direction {
+                        Direction::Up if flow.is_none() => info.still,
+                        Direction::Down => info.still,
+                        _ => info.flowing,
+                    };
+
+                    let vertex = |position: [u32; 3], face: Direction| {
+                        let vertex = BlockVertex::new(position, face, NO_AO, layer, sky, block).with_color(color);
+
+                        match flow {
+                            Some(angle) => vertex.with_flow(angle),
+                            None => vertex,
+                        }
+                    };
+
+                    data.push_quad(corners.map(|position| vertex(position, direction)));
+
+                    // The underside of the surface, seen from within the fluid.
+                    if direction == Direction::Up {
+                        let [a, b, c, d] = corners;
+                        data.push_quad([d, c, b, a].map(|position| vertex(position, Direction::Down)));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the corners of the face of a full block pointing in `direction`, in sixteenths of
+/// a block and counter-clockwise when seen from outside.
+fn face_corners(direction: Direction) -> [[u32; 3]; 4] {
+    let (normal, u, v) = FACE_AXES[direction as usize];
+
+    let corner = |use_max_u: bool, use_max_v: bool| {
+        let mut position = [0; 3];
+        position[normal] = if direction.is_positive() { POSITION_SCALE } else { 0 };
+        position[u] = if use_max_u { POSITION_SCALE } else { 0 };
+        position[v] = if use_max_v { POSITION_SCALE } else { 0 };
+        position
+    };
+
+    [corner(false, false), corner(true, false), corner(true, true), corner(false, true)]
+}
+
+/// Returns the height of the corner at offset `cx`, `cz` of the top of the fluid `info` at
+/// `block`, from the four blocks sharing it.
+fn corner_height(neighborhood: &SectionNeighborhood, table: &BlockMeshTable, info: FluidMeshInfo, block: [i32; 3], cx: i32, cz: i32) -> f32 {
+    let [x, y, z] = block;
+    let (mut total, mut weight) = (0.0, 0.0);
+
+    for (nx, nz) in [(x + cx - 1, z + cz - 1), (x + cx, z + cz - 1), (x + cx - 1, z + cz), (x + cx, z + cz)] {
+        let same = |other: Option<FluidMeshInfo>| other.filter(|other| other.fluid == info.fluid);
+
+        if same(table.get(neighborhood.get(nx, y + 1, nz)).fluid).is_some() {
+            return 1.0;
+        }
+
+        let neighbor = table.get(neighborhood.get(nx, y, nz));
+
+        match same(neighbor.fluid) {
+            Some(other) if other.height >= NEARLY_FULL => {
+                total += other.height * FULL_WEIGHT;
+                weight += FULL_WEIGHT;
+            }
+            Some(other) => {
+                total += other.height;
+                weight += 1.0;
+            }
+            None if !neighbor.opaque_cube => weight += 1.0,
+            None => {}
+        }
+    }
+
+    total / weight
+}
+
+/// Returns the angle the surface with corner `heights` slopes down towards, in 128ths of a
+/// turn from +X towards +Z, or `None` if it is level.
+fn flow_angle(heights: [[f32; 2]; 2]) -> Option<u8> {
+    let x = heights[0][0] + heights[0][1] - heights[1][0] - heights[1][1];
+    let z = heights[0][0] + heights[1][0] - heights[0][1] - heights[1][1];
+
+    if x.abs() < STILL_SLOPE && z.abs() < STILL_SLOPE {
+        return None;
+    }
+
+    let turns = z.atan2(x) / TAU;
+    Some((turns.rem_euclid(1.0) * 128.0).round() as u8 & 127)
+}
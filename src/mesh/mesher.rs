@@ -0,0 +1,420 @@
+use super::fluid::mesh_fluids;
+use super::{BlockMeshTable, BlockVertex, MeshSettings, MeshingMode, SectionNeighborhood, POSITION_SCALE};
+use crate::block::RenderLayer;
+use crate::world::{BlockState, Direction, SECTION_SIZE};
+
+/// Axes of each face as `(normal, u, v)`, indexed by [`Direction`].
+///
+/// `u` and `v` are chosen so that `u × v` points along the face normal, which makes the corners
+/// `(0, 0)`, `(1, 0)`, `(1, 1)`, `(0, 1)` counter-clockwise when seen from outside.
+pub(super) const FACE_AXES: [(usize, usize, usize); 6] = [
+    (1, 0, 2), // Down
+    (1, 2, 0), // Up
+    (2, 1, 0), // North
+    (2, 0, 1), // South
+    (0, 2, 1), // West
+    (0, 1, 2), // East
+];
+
+/// Ambient occlusion level meaning no occlusion.
+pub(super) const NO_AO: u8 = 3;
+
+/// The shape of a full block, in sixteenths of a block.
+const FULL_BLOCK: [u8; 6] = [0, 0, 0, 16, 16, 16];
+
+const SIZE: usize = SECTION_SIZE;
+
+/// Vertices of one render layer, four per quad.
+///
+/// Quads all share the same triangulation, so no index data is kept: every layer is drawn
+/// with a shared index buffer repeating `0 1 2 2 3 0`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    pub vertices: Vec<BlockVertex>,
+}
+
+impl MeshData {
+    /// Returns the number of quads.
+    pub fn quad_count(&self) -> usize {
+        self.vertices.len() / 4
+    }
+
+    /// Returns whether there is nothing to draw.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Appends a quad given its corners in counter-clockwise order.
+    pub fn push_quad(&mut self, corners: [BlockVertex; 4]) {
+        self.vertices.extend_from_slice(&corners);
+    }
+}
+
+/// The geometry of a section, split by render layer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SectionMesh {
+    pub opaque: MeshData,
+    pub cutout: MeshData,
+    pub translucent: MeshData,
+}
+
+impl SectionMesh {
+    /// Returns the mesh of a render layer, or `None` for [`RenderLayer::Invisible`].
+    pub fn layer(&self, layer: RenderLayer) -> Option<&MeshData> {
+        match layer {
+            RenderLayer::Invisible => None,
+            RenderLayer::Opaque => Some(&self.opaque),
+            RenderLayer::Cutout => Some(&self.cutout),
+            RenderLayer::Translucent => Some(&self.translucent),
+        }
+    }
+
+    /// Returns the mesh of a render layer mutably, or `None` for [`RenderLayer::Invisible`].
+    pub fn layer_mut(&mut self, layer: RenderLayer) -> Option<&mut MeshData> {
+        match layer {
+            RenderLayer::Invisible => None,
+            RenderLayer::Opaque => Some(&mut self.opaque),
+            RenderLayer::Cutout => Some(&mut self.cutout),
+            RenderLayer::Translucent => Some(&mut self.translucent),
+        }
+    }
+
+    /// Returns whether no layer has anything to draw.
+    pub fn is_empty(&self) -> bool {
+        self.opaque.is_empty() && self.cutout.is_empty() && self.translucent.is_empty()
+    }
+
+    /// Returns the number of quads across all layers.
+    pub fn quad_count(&self) -> usize {
+        self.opaque.quad_count() + self.cutout.quad_count() + self.translucent.quad_count()
+    }
+}
+
+/// Lighting of the four corners of a face, in the corner order of [`FACE_AXES`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FaceLight {
+    ao: [u8; 4],
+    sky: [u8; 4],
+    block: [u8; 4],
+}
+
+impl FaceLight {
+    /// The same light on every corner, without occlusion.
+    fn flat(sky: u8, block: u8) -> Self {
+        Self { ao: [NO_AO; 4], sky: [sky; 4], block: [block; 4] }
+    }
+
+    /// Returns whether the quad should be split along the `1-3` diagonal rather than `0-2`.
+    ///
+    /// Interpolation across a quad is done per triangle, so the diagonal shows whenever the
+    /// corners differ. Splitting along the brighter pair of corners keeps the gradient
+    /// symmetric instead of stretching it along one triangle.
+    fn flipped(&self) -> bool {
+        let brightness = |i: usize| self.ao[i] as u32 * 16 + self.sky[i].max(self.block[i]) as u32;
+        brightness(0) + brightness(2) < brightness(1) + brightness(3)
+    }
+}
+
+/// What must match for two faces to be merged by greedy meshing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FaceKey {
+    state: BlockState,
+    light: FaceLight,
+    color: [u8; 3],
+}
+
+/// Builds the mesh of the section at the center of `neighborhood`.
+///
+/// A face is emitted unless it lies on the block boundary and the block behind it hides it:
+/// either a full opaque cube, or the very same state for non-opaque blocks such as glass and
+/// water, so their insides are not drawn.
+///
+/// Faces on the block boundary are lit from the blocks in front of them, with smooth light
+/// and ambient occlusion unless [`MeshSettings::smooth_lighting`] is off; faces inside the
+/// block, such as the top of a slab, take the light of the block itself. Tinted faces take
+/// the color of their column from [`SectionNeighborhood::tints`].
+///
+/// Layers set to [`MeshingMode::Greedy`] merge the full block faces of each slice into as few
+/// rectangles as possible; smaller shapes such as slabs are always meshed face by face.
+/// Fluids are meshed on their own afterwards, see [`mesh_fluids`].
+pub fn mesh_section(neighborhood: &SectionNeighborhood, table: &BlockMeshTable, settings: &MeshSettings) -> SectionMesh {
+    let mut mesh = SectionMesh::default();
+
+    // Faces left for greedy meshing, by direction, then slice along the normal, then v and u.
+    let mut greedy: Vec<Option<FaceKey>> = vec![None; 6 * SIZE * SIZE * SIZE];
+    let mut has_greedy = false;
+
+    for y in 0..SIZE as i32 {
+        for z in 0..SIZE as i32 {
+            for x in 0..SIZE as i32 {
+                let state = neighborhood.get(x, y, z);
+                let info = table.get(state);
+
+                let Some(data) = mesh.layer_mut(info.layer) else {
+                    continue;
+                };
+
+                let merge = settings.mode(info.layer) == MeshingMode::Greedy;
+                let column_tints = neighborhood.tints().get(x as usize, z as usize);
+
+                for direction in Direction::ALL {
+                    let (dx, dy, dz) = direction.offset();
+                    let behind = neighborhood.get(x + dx, y + dy, z + dz);
+                    let hidden = table.get(behind).opaque_cube || (behind == state && info.layer != RenderLayer::Opaque);
+                    let block = [x, y, z].map(|c| c as u32);
+                    let color = column_tints.get(info.tints[direction as usize]);
+
+                    for shape in &info.boxes {
+                        let (normal, u, v) = FACE_AXES[direction as usize];
+                        let plane = if direction.is_positive() { shape[normal + 3] } else { shape[normal] };
+                        let on_boundary = plane == 0 || plane == 16;
+
+                        if on_boundary && hidden {
+                            continue;
+                        }
+
+                        let light = match on_boundary {
+                            true => face_light(neighborhood, table, [x, y, z], direction, settings.smooth_lighting),
+                            false => FaceLight::flat(neighborhood.sky_light(x, y, z), neighborhood.block_light(x, y, z)),
+                        };
+
+                        if merge && *shape == FULL_BLOCK {
+                            let index = greedy_index(direction, block[normal], block[u], block[v]);
+                            greedy[index] = Some(FaceKey { state, light, color });
+                            has_greedy = true;
+                            continue;
+                        }
+
+                        let origin = block.map(|c| c * POSITION_SCALE);
+                        let min = [0, 1, 2].map(|axis| origin[axis] + shape[axis] as u32);
+                        let max = [0, 1, 2].map(|axis| origin[axis] + shape[axis + 3] as u32);
+                        data.push_quad(face_quad(min, max, direction, info.textures[direction as usize], &light, color));
+                    }
+                }
+            }
+        }
+    }
+
+    if has_greedy {
+        merge_faces(&mut greedy, table, &mut mesh);
+    }
+
+    mesh_fluids(neighborhood, table, &mut mesh);
+    mesh
+}
+
+/// Computes the light of a face lying on the side of block `block` facing `direction`.
+///
+/// Every corner is lit by the four blocks in front of the face that touch it: the one right
+/// in front, the two along the edges and the one across the corner. Corners take the average
+/// light of those that are not opaque cubes, and get darker the more of them are; with both
+/// edges blocked the diagonal one cannot be seen and counts as blocked too.
+///
+/// Both meshing modes light faces with this, before greedy meshing merges those that match,
+/// so merged quads look exactly like the faces they replace.
+fn face_light(neighborhood: &SectionNeighborhood, table: &BlockMeshTable, block: [i32; 3], direction: Direction, smooth: bool) -> FaceLight {
+    let (dx, dy, dz) = direction.offset();
+    let front = [block[0] + dx, block[1] + dy, block[2] + dz];
+    let sky = |p: [i32; 3]| neighborhood.sky_light(p[0], p[1], p[2]);
+    let light = |p: [i32; 3]| neighborhood.block_light(p[0], p[1], p[2]);
+
+    if !smooth {
+        return FaceLight::flat(sky(front), light(front));
+    }
+
+    let (_, u, v) = FACE_AXES[direction as usize];
+    let opaque = |p: [i32; 3]| table.get(neighborhood.get(p[0], p[1], p[2])).opaque_cube;
+    let mut result = FaceLight::flat(0, 0);
+
+    for (corner, (du, dv)) in [(-1, -1), (1, -1), (1, 1), (-1, 1)].into_iter().enumerate() {
+        let mut side_u = front;
+        side_u[u] += du;
+        let mut side_v = front;
+        side_v[v] += dv;
+        let mut diagonal = side_u;
+        diagonal[v] += dv;
+
+        let (blocked_u, blocked_v) = (opaque(side_u), opaque(side_v));
+        let blocked_diagonal = (blocked_u && blocked_v) || opaque(diagonal);
+
+        result.ao[corner] = match blocked_u && blocked_v {
+            true => 0,
+            false => NO_AO - blocked_u as u8 - blocked_v as u8 - blocked_diagonal as u8,
+        };
+
+        let samples = [(front, false), (side_u, blocked_u), (side_v, blocked_v), (diagonal, blocked_diagonal)];
+        let (mut sky_sum, mut block_sum, mut count) = (0, 0, 0);
+
+        for (position, blocked) in samples {
+            if !blocked {
+                sky_sum += sky(position) as u32;
+                block_sum += light(position) as u32;
+                count += 1;
+            }
+        }
+
+        result.sky[corner] = ((sky_sum + count / 2) / count) as u8;
+        result.block[corner] = ((block_sum + count / 2) / count) as u8;
+    }
+
+    result
+}
+
+fn greedy_index(direction: Direction, slice: u32, u: u32, v: u32) -> usize {
+    ((direction as usize * SIZE + slice as usize) * SIZE + v as usize) * SIZE + u as usize
+}
+
+/// Merges the faces collected in `faces` into rectangles, slice by slice, and emits them.
+///
+/// Each rectangle grows along `u` as long as faces match, then along `v` as long as whole
+/// rows match, which gives a good cover in a single pass.
+fn merge_faces(faces: &mut [Option<FaceKey>], table: &BlockMeshTable, mesh: &mut SectionMesh) {
+    for direction in Direction::ALL {
+        let (normal, u_axis, v_axis) = FACE_AXES[direction as usize];
+
+        for slice in 0..SIZE as u32 {
+            let mask = &mut faces[greedy_index(direction, slice, 0, 0)..][..SIZE * SIZE];
+
+            for v in 0..SIZE {
+                let mut u = 0;
+
+                while u < SIZE {
+                    let Some(key) = mask[v * SIZE + u] else {
+                        u += 1;
+                        continue;
+                    };
+
+                    let width = (u..SIZE).take_while(|&i| mask[v * SIZE + i] == Some(key)).count();
+                    let height = (v..SIZE)
+                        .take_while(|&j| (u..u + width).all(|i| mask[j * SIZE + i] == Some(key)))
+                        .count();
+
+                    for j in v..v + height {
+                        mask[j * SIZE + u..j * SIZE + u + width].fill(None);
+                    }
+
+                    let mut min = [0; 3];
+                    let mut max = [0; 3];
+                    min[normal] = slice * POSITION_SCALE;
+                    max[normal] = (slice + 1) * POSITION_SCALE;
+                    min[u_axis] = u as u32 * POSITION_SCALE;
+                    max[u_axis] = (u + width) as u32 * POSITION_SCALE;
+                    min[v_axis] = v as u32 * POSITION_SCALE;
+                    max[v_axis] = (v + height) as u32 * POSITION_SCALE;
+
+                    let info = table.get(key.state);
+
+                    if let Some(data) = mesh.layer_mut(info.layer) {
+                        data.push_quad(face_quad(min, max, direction, info.textures[direction as usize], &key.light, key.color));
+                    }
+
+                    u += width;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the four corners of the face pointing in `direction` of the box from `min` to
+/// `max`, in sixteenths of a block relative to the section, tinted with `color`.
+///
+/// The corners stay counter-clockwise but start from the second one when the light asks for
+/// the other diagonal, since every quad is drawn as the triangles `0 1 2` and `2 3 0`.
+fn face_quad(min: [u32; 3], max: [u32; 3], direction: Direction, layer: u16, light: &FaceLight, color: [u8; 3]) -> [BlockVertex; 4] {
+    let (normal, u, v) = FACE_AXES[direction as usize];
+
+    let corner = |index: usize, use_max_u: bool, use_max_v: bool| {
+        let mut position = [0; 3];
+        position[normal] = if direction.is_positive() { max[normal] } else { min[normal] };
+        position[u] = if use_max_u { max[u] } else { min[u] };
+        position[v] = if use_max_v { max[v] } else { min[v] };
+        BlockVertex::new(position, direction, light.ao[index], layer, light.sky[index], light.block[index]).with_color(color)
+    };
+
+    let corners = [corner(0, false, false), corner(1, true, false), corner(2, true, true), corner(3, false, true)];
+
+    match light.flipped() {
+        true => [corners[1], corners[2], corners[3], corners[0]],
+        false => corners,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{BlockRegistry, BlockRegistryBuilder};
+
+    const BLOCKS: &str = "
+        [stone]
+
+        [glass]
+        render = \"cutout\"
+        opacity = 0
+    ";
+
+    fn registry() -> BlockRegistry {
+        let mut builder = BlockRegistryBuilder::new();
+        builder.load_str("test", BLOCKS).unwrap();
+        builder.build().unwrap()
+    }
+
+    fn naive() -> MeshSettings {
+        MeshSettings { opaque: MeshingMode::Naive, cutout: MeshingMode::Naive, ..MeshSettings::default() }
+    }
+
+    /// Meshes the blocks at the given section coordinates, which may lie in the border.
+    fn mesh(blocks: &[((i32, i32, i32), &str)], settings: &MeshSettings) -> SectionMesh {
+        let registry = registry();
+        let table = BlockMeshTable::new(&registry, |_| 0);
+        let mut neighborhood = SectionNeighborhood::new();
+
+        for &((x, y, z), name) in blocks {
+            neighborhood.set(x, y, z, registry.default_state(&format!("test:{name}")).unwrap());
+        }
+
+        mesh_section(&neighborhood, &table, settings)
+    }
+
+    #[test]
+    fn meshes_every_face_of_a_lone_block() {
+        let mesh = mesh(&[((4, 4, 4), "stone")], &naive());
+        assert_eq!((mesh.opaque.quad_count(), mesh.cutout.quad_count()), (6, 0));
+
+        let faces: Vec<Direction> = mesh.opaque.vertices.chunks(4).map(|quad| quad[0].face()).collect();
+        assert!(Direction::ALL.iter().all(|direction| faces.contains(direction)));
+    }
+
+    #[test]
+    fn culls_faces_between_blocks() {
+        let pair = [((4, 4, 4), "stone"), ((5, 4, 4), "stone")];
+        assert_eq!(mesh(&pair, &naive()).opaque.quad_count(), 10);
+
+        // Greedy meshing merges the remaining faces of the pair into one per side.
+        assert_eq!(mesh(&pair, &MeshSettings::default()).opaque.quad_count(), 6);
+    }
+
+    #[test]
+    fn culls_glass_against_glass_only() {
+        let glass = mesh(&[((4, 4, 4), "glass"), ((4, 5, 4), "glass")], &naive());
+        assert_eq!((glass.opaque.quad_count(), glass.cutout.quad_count()), (0, 10));
+
+        // Stone hides the glass face against it, but still shows through the glass.
+        let mixed = mesh(&[((4, 4, 4), "glass"), ((4, 5, 4), "stone")], &naive());
+        assert_eq!((mixed.opaque.quad_count(), mixed.cutout.quad_count()), (6, 5));
+    }
+
+    #[test]
+    fn culls_faces_against_neighboring_sections() {
+        let corner = [((15, 0, 15), "stone")];
+        assert_eq!(mesh(&corner, &naive()).opaque.quad_count(), 6);
+
+        let covered = [((15, 0, 15), "stone"), ((16, 0, 15), "stone"), ((15, -1, 15), "stone"), ((15, 0, 16), "glass")];
+        let mesh = mesh(&covered, &naive());
+
+        // Border blocks are never meshed themselves, and only opaque ones hide faces.
+        assert_eq!((mesh.opaque.quad_count(), mesh.cutout.quad_count()), (4, 0));
+        let faces: Vec<Direction> = mesh.opaque.vertices.chunks(4).map(|quad| quad[0].face()).collect();
+        assert!(!faces.contains(&Direction::East) && !faces.contains(&Direction::Down));
+        assert!(faces.contains(&Direction::South));
+    }
+}
@@ -0,0 +1,14 @@
+mod vertex;
+mod neighborhood;
+mod table;
+mod mesher;
+mod fluid;
+mod settings;
+mod tint;
+
+pub use vertex::{BlockVertex, POSITION_SCALE};
+pub use neighborhood::{ChunkNeighbors, SectionNeighborhood, PADDED_SIZE};
+pub use table::{BlockMeshInfo, BlockMeshTable, FluidMeshInfo};
+pub use mesher::{mesh_section, MeshData, SectionMesh};
+pub use settings::{MeshSettings, MeshingMode};
+pub use tint::{BiomeTints, ColumnTints, TintColors, MAX_BLEND_RADIUS};
@@ -0,0 +1,192 @@
+use super::ColumnTints;
+use crate::world::{BlockState, Chunk, ChunkPos, CHUNK_HEIGHT, SECTION_COUNT, SECTION_SIZE};
+
+/// Width of a section with a one block border on every side.
+pub const PADDED_SIZE: usize = SECTION_SIZE + 2;
+
+const PADDED_VOLUME: usize = PADDED_SIZE * PADDED_SIZE * PADDED_SIZE;
+
+/// The chunks around a chunk being meshed, as a 3x3 grid centered on it.
+///
+/// Chunks left out are treated as air, so faces towards them are kept. Only the four chunks
+/// sharing a side are needed to cull faces; the diagonal ones are only read by effects
+/// looking at corners, like ambient occlusion.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkNeighbors<'a> {
+    chunks: [Option<&'a Chunk>; 9],
+}
+
+impl<'a> ChunkNeighbors<'a> {
+    /// Creates a grid holding only `center`.
+    pub fn new(center: &'a Chunk) -> Self {
+        let mut chunks = [None; 9];
+        chunks[4] = Some(center);
+        Self { chunks }
+    }
+
+    /// Creates a grid by looking up every neighbor of `center` with `get`.
+    pub fn gather(center: &'a Chunk, mut get: impl FnMut(ChunkPos) -> Option<&'a Chunk>) -> Self {
+        let mut neighbors = Self::new(center);
+
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                if dx != 0 || dz != 0 {
+                    neighbors.chunks[Self::slot(dx, dz)] = get(center.pos().offset(dx, dz));
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    /// Sets the chunk at offset `(dx, dz)` from the center, each in `-1..=1`.
+    pub fn with(mut self, dx: i32, dz: i32, chunk: Option<&'a Chunk>) -> Self {
+        self.chunks[Self::slot(dx, dz)] = chunk;
+        self
+    }
+
+    /// Returns the chunk being meshed.
+    pub fn center(&self) -> &'a Chunk {
+        self.chunks[4].expect("the center chunk is always set")
+    }
+
+    /// Returns the chunk at offset `(dx, dz)` from the center, each in `-1..=1`.
+    pub fn get(&self, dx: i32, dz: i32) -> Option<&'a Chunk> {
+        self.chunks[Self::slot(dx, dz)]
+    }
+
+    fn slot(dx: i32, dz: i32) -> usize {
+        assert!((-1..=1).contains(&dx) && (-1..=1).contains(&dz), "neighbor offset out of range");
+        ((dz + 1) * 3 + dx + 1) as usize
+    }
+}
+
+/// Sky and block light of a fully lit, open space.
+const FULL_LIGHT: u8 = 0xf0;
+
+/// The blocks and light levels of a section plus a one block border taken from its neighbors,
+/// and the biome tints of its columns.
+///
+/// This is everything the mesher reads, copied out of the chunks so meshing can run on another
+/// thread while the world keeps changing. Coordinates range from -1 to 16 on every axis.
+#[derive(Debug, Clone)]
+pub struct SectionNeighborhood {
+    blocks: Box<[BlockState; PADDED_VOLUME]>,
+
+    /// Sky light in the high nibble, block light in the low one.
+    light: Box<[u8; PADDED_VOLUME]>,
+
+    tints: ColumnTints,
+}
+
+impl SectionNeighborhood {
+    /// Creates a neighborhood filled with air in full sky light, without tints.
+    pub fn new() -> Self {
+        Self {
+            blocks: Box::new([BlockState::AIR; PADDED_VOLUME]),
+            light: Box::new([FULL_LIGHT; PADDED_VOLUME]),
+            tints: ColumnTints::new(),
+        }
+    }
+
+    /// Copies section `section` of the center chunk and the surrounding blocks, with their
+    /// light.
+    ///
+    /// Blocks above and below the world, and in chunks left out, are air in full sky light.
+    /// Tints are left out, see [`set_tints`](Self::set_tints).
+    pub fn from_chunks(neighbors: &ChunkNeighbors, section: usize) -> Self {
+        assert!(section < SECTION_COUNT, "section {section} out of range");
+
+        let mut neighborhood = Self::new();
+        let base_y = (section * SECTION_SIZE) as i32;
+        let size = SECTION_SIZE as i32;
+        let center = neighbors.center();
+        let (sky, block) = (center.sky_light_section(section), center.block_light_section(section));
+
+        for (index, (x, y, z, state)) in center.section(section).iter().enumerate() {
+            let (x, y, z) = (x as i32, y as i32, z as i32);
+            neighborhood.set(x, y, z, state);
+            neighborhood.set_light(x, y, z, sky.get_index(index), block.get_index(index));
+        }
+
+        for y in -1..=size {
+            let world_y = base_y + y;
+
+            if world_y < 0 || world_y >= CHUNK_HEIGHT as i32 {
+                continue;
+            }
+
+            for z in -1..=size {
+                for x in -1..=size {
+                    let inside = (0..size).contains(&x) && (0..size).contains(&y) && (0..size).contains(&z);
+
+                    if inside {
+                        continue;
+                    }
+
+                    let Some(chunk) = neighbors.get(x.div_euclid(size), z.div_euclid(size)) else {
+                        continue;
+                    };
+
+                    let (lx, ly, lz) = (x.rem_euclid(size) as usize, world_y as usize, z.rem_euclid(size) as usize);
+                    neighborhood.set(x, y, z, chunk.get(lx, ly, lz));
+                    neighborhood.set_light(x, y, z, chunk.sky_light(lx, ly, lz), chunk.block_light(lx, ly, lz));
+                }
+            }
+        }
+
+        neighborhood
+    }
+
+    /// Returns the block at section coordinates, each in `-1..=16`.
+    #[inline]
+    pub fn get(&self, x: i32, y: i32, z: i32) -> BlockState {
+        self.blocks[Self::index(x, y, z)]
+    }
+
+    /// Sets the block at section coordinates, each in `-1..=16`.
+    #[inline]
+    pub fn set(&mut self, x: i32, y: i32, z: i32, state: BlockState) {
+        self.blocks[Self::index(x, y, z)] = state;
+    }
+
+    /// Returns the sky light level at section coordinates, each in `-1..=16`.
+    #[inline]
+    pub fn sky_light(&self, x: i32, y: i32, z: i32) -> u8 {
+        self.light[Self::index(x, y, z)] >> 4
+    }
+
+    /// Returns the block light level at section coordinates, each in `-1..=16`.
+    #[inline]
+    pub fn block_light(&self, x: i32, y: i32, z: i32) -> u8 {
+        self.light[Self::index(x, y, z)] & 15
+    }
+
+    /// Sets the sky and block light levels at section coordinates, each in `-1..=16`.
+    #[inline]
+    pub fn set_light(&mut self, x: i32, y: i32, z: i32, sky: u8, block: u8) {
+        self.light[Self::index(x, y, z)] = (sky.min(15) << 4) | block.min(15);
+    }
+
+    /// Returns the biome tints of the columns of the section.
+    pub fn tints(&self) -> &ColumnTints {
+        &self.tints
+    }
+
+    /// Sets the biome tints of the columns of the section, usually blended once per chunk with
+    /// [`ColumnTints::blend`].
+    pub fn set_tints(&mut self, tints: ColumnTints) {
+        self.tints = tints;
+    }
+
+    #[inline]
+    fn index(x: i32, y: i32, z: i32) -> usize {
+        let size = PADDED_SIZE as i32;
+        debug_assert!((-1..size - 1).contains(&x) && (-1..size - 1).contains(&y) && (-1..size - 1).contains(&z));
+        (((y + 1) * size + z + 1) * size + x + 1) as usize
+    }
+}
+
+impl Default for SectionNeighborhood {
+    fn default() -> Self { Self::new() }
+}
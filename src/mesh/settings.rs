@@ -0,0 +1,57 @@
+use crate::block::RenderLayer;
+
+use serde::{Deserialize, Serialize};
+
+/// How the faces of a render layer are turned into quads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MeshingMode {
+    /// One quad per visible block face.
+    Naive,
+
+    /// Coplanar neighboring faces that look the same are merged into larger quads.
+    #[default]
+    Greedy,
+}
+
+/// Meshing preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshSettings {
+    pub opaque: MeshingMode,
+    pub cutout: MeshingMode,
+
+    /// Naive by default: merged translucent quads are harder to sort back to front.
+    pub translucent: MeshingMode,
+
+    /// Blends light between neighboring blocks and darkens corners with ambient occlusion.
+    /// When off, every face takes the light of the block in front of it.
+    pub smooth_lighting: bool,
+
+    /// Radius in blocks over which biome colors fade into each other, up to 16. Zero changes
+    /// colors at once on biome borders.
+    pub biome_blend: u8,
+}
+
+impl MeshSettings {
+    /// Returns the meshing mode of a render layer.
+    pub fn mode(&self, layer: RenderLayer) -> MeshingMode {
+        match layer {
+            RenderLayer::Invisible | RenderLayer::Opaque => self.opaque,
+            RenderLayer::Cutout => self.cutout,
+            RenderLayer::Translucent => self.translucent,
+        }
+    }
+}
+
+impl Default for MeshSettings {
+    fn default() -> Self {
+        Self {
+            opaque: MeshingMode::Greedy,
+            cutout: MeshingMode::Greedy,
+            translucent: MeshingMode::Naive,
+            smooth_lighting: true,
+            biome_blend: 4,
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::block::{BlockRegistry, CollisionShape, Fluid, RenderLayer, Tint};
+use crate::world::{BlockState, Direction};
+
+/// What the mesher needs to know about a block state, resolved ahead of time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockMeshInfo {
+    /// Render pass the faces go to.
+    pub layer: RenderLayer,
+
+    /// Whether the block is a full opaque cube hiding its neighbors' faces.
+    pub opaque_cube: bool,
+
+    /// Texture array layer of each face, indexed by [`Direction`].
+    pub textures: [u16; 6],
+
+    /// Biome color of each face, indexed by [`Direction`].
+    pub tints: [Tint; 6],
+
+    /// Boxes to draw as `[min_x, min_y, min_z, max_x, max_y, max_z]` in sixteenths of a block.
+    pub boxes: Vec<[u8; 6]>,
+
+    /// Fluid drawn by the fluid mesher, for fluid blocks and waterlogged blocks.
+    pub fluid: Option<FluidMeshInfo>,
+}
+
+/// What the fluid mesher needs to know about the fluid in a block state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FluidMeshInfo {
+    pub fluid: Fluid,
+
+    /// Height of the surface above the bottom of the block when no fluid is above, from 1/9
+    /// for the thinnest flowing fluid to 8/9 for sources and falling fluid.
+    pub height: f32,
+
+    /// Render pass the faces go to.
+    pub layer: RenderLayer,
+
+    /// Texture array layer of still surfaces and of the bottom.
+    pub still: u16,
+
+    /// Texture array layer of sloped surfaces and of the sides.
+    pub flowing: u16,
+
+    pub tint: Tint,
+}
+
+/// Meshing information for every block state of a registry.
+///
+/// Texture names are turned into array layers once here rather than for every face. The table
+/// must be rebuilt whenever texture layers change, such as after reloading resource packs.
+#[derive(Debug, Clone)]
+pub struct BlockMeshTable {
+    states: Vec<BlockMeshInfo>,
+}
+
+impl BlockMeshTable {
+    /// Builds the table for every state of `registry`, asking `texture_layer` for the array
+    /// layer of each texture name.
+    ///
+    /// Fluid blocks have no boxes; their fluid is drawn by the fluid mesher instead, and so is
+    /// the water of waterlogged blocks, which looks like the first block made of water.
+    pub fn new(registry: &BlockRegistry, mut texture_layer: impl FnMut(&str) -> u16) -> Self {
+        let water = registry
+            .blocks()
+            .iter()
+            .map(|block| block.default_state())
+            .find(|&state| registry.attributes(state).fluid == Some(Fluid::Water));
+
+        let states = registry
+            .states()
+            .map(|state| {
+                let attributes = registry.attributes(state);
+
+                let level = registry.value(state, "level").and_then(|level| level.parse::<u8>().ok()).unwrap_or(0);
+
+                let fluid = match registry.value(state, "waterlogged") == Some("true") {
+                    true => water.map(|water| (water, 0)),
+                    false => attributes.fluid.map(|_| (state, level)),
+                };
+
+                let fluid = fluid.map(|(fluid_state, level)| {
+                    let fluid_attributes = registry.attributes(fluid_state);
+                    let amount = if level == 0 || level >= 8 { 8 } else { 8 - level };
+
+                    FluidMeshInfo {
+                        fluid: fluid_attributes.fluid.unwrap_or(Fluid::Water),
+                        height: amount as f32 / 9.0,
+                        layer: fluid_attributes.render_layer,
+                        still: texture_layer(fluid_attributes.texture(Direction::Up)),
+                        flowing: texture_layer(fluid_attributes.texture(Direction::North)),
+                        tint: fluid_attributes.tint(Direction::Up),
+                    }
+                });
+
+                // Shapeless blocks such as flowers are drawn as full cubes.
+                let boxes = match &attributes.collision {
+                    _ if attributes.fluid.is_some() => Vec::new(),
+                    CollisionShape::Boxes(boxes) => boxes
+                        .iter()
+                        .map(|b| {
+                            let [min, max] = [b.min, b.max].map(|v| [v.x, v.y, v.z].map(|c| (c * 16.0).round() as u8));
+                            [min[0], min[1], min[2], max[0], max[1], max[2]]
+                        })
+                        .collect(),
+                    _ => vec![[0, 0, 0, 16, 16, 16]],
+                };
+
+                BlockMeshInfo {
+                    layer: attributes.render_layer,
+                    opaque_cube: attributes.is_opaque_cube(),
+                    textures: Direction::ALL.map(|direction| texture_layer(attributes.texture(direction))),
+                    tints: Direction::ALL.map(|direction| attributes.tint(direction)),
+                    boxes,
+                    fluid,
+                }
+            })
+            .collect();
+
+        Self { states }
+    }
+
+    /// Returns the meshing information of `state`.
+    #[inline]
+    pub fn get(&self, state: BlockState) -> &BlockMeshInfo {
+        &self.states[state.id() as usize]
+    }
+}
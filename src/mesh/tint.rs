@@ -0,0 +1,136 @@
+use super::ChunkNeighbors;
+use crate::block::Tint;
+use crate::world::{BiomeId, SECTION_SIZE};
+
+/// The colors a biome tints blocks with, as RGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TintColors {
+    pub grass: [u8; 3],
+    pub foliage: [u8; 3],
+    pub water: [u8; 3],
+}
+
+impl TintColors {
+    /// Colors leaving textures unchanged.
+    pub const WHITE: Self = Self { grass: [255; 3], foliage: [255; 3], water: [255; 3] };
+
+    /// Returns the color faces with `tint` are multiplied by.
+    #[inline]
+    pub fn get(&self, tint: Tint) -> [u8; 3] {
+        match tint {
+            Tint::None => [255; 3],
+            Tint::Grass => self.grass,
+            Tint::Foliage => self.foliage,
+            Tint::Water => self.water,
+        }
+    }
+}
+
+impl Default for TintColors {
+    fn default() -> Self { Self::WHITE }
+}
+
+/// The tint colors of every biome, indexed by [`BiomeId`].
+///
+/// Biomes missing from the table are drawn untinted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BiomeTints {
+    colors: Vec<TintColors>,
+}
+
+impl BiomeTints {
+    /// Creates the table from the colors of every biome, in identifier order.
+    pub fn new(colors: Vec<TintColors>) -> Self {
+        Self { colors }
+    }
+
+    /// Returns the colors of `biome`.
+    #[inline]
+    pub fn get(&self, biome: BiomeId) -> TintColors {
+        self.colors.get(biome.index()).copied().unwrap_or_default()
+    }
+}
+
+/// Largest blend radius, so that every column read belongs to a neighboring chunk.
+pub const MAX_BLEND_RADIUS: u8 = SECTION_SIZE as u8;
+
+/// The tint colors of each column of a chunk, blended across biome borders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnTints {
+    columns: Box<[TintColors; SECTION_SIZE * SECTION_SIZE]>,
+}
+
+impl ColumnTints {
+    /// Creates tints leaving every column untinted.
+    pub fn new() -> Self {
+        Self { columns: Box::new([TintColors::WHITE; SECTION_SIZE * SECTION_SIZE]) }
+    }
+
+    /// Computes the tints of the center chunk of `neighbors`.
+    ///
+    /// Each column takes the average color of the columns in the square of `radius` blocks
+    /// around it, so colors fade over `2 * radius + 1` blocks instead of changing at once on
+    /// biome borders. Columns of missing neighbors are left out of the average.
+    pub fn blend(neighbors: &ChunkNeighbors, tints: &BiomeTints, radius: u8) -> Self {
+        let radius = radius.min(MAX_BLEND_RADIUS) as i32;
+        let size = SECTION_SIZE as i32;
+
+        // Biome colors of every column around the chunk, for the chunk's width plus the radius.
+        let width = (size + 2 * radius) as usize;
+        let mut samples: Vec<Option<TintColors>> = vec![None; width * width];
+
+        for z in -radius..size + radius {
+            for x in -radius..size + radius {
+                let Some(chunk) = neighbors.get(x.div_euclid(size), z.div_euclid(size)) else {
+                    continue;
+                };
+
+                let biome = chunk.biome(x.rem_euclid(size) as usize, z.rem_euclid(size) as usize);
+                samples[((z + radius) * width as i32 + x + radius) as usize] = Some(tints.get(biome));
+            }
+        }
+
+        let mut result = Self::new();
+
+        for z in 0..SECTION_SIZE {
+            for x in 0..SECTION_SIZE {
+                let mut sums = [0u32; 9];
+                let mut count = 0;
+
+                for sample_z in z..z + 2 * radius as usize + 1 {
+                    for colors in samples[sample_z * width + x..][..2 * radius as usize + 1].iter().flatten() {
+                        let channels = [colors.grass, colors.foliage, colors.water];
+
+                        for (sum, channel) in sums.iter_mut().zip(channels.as_flattened()) {
+                            *sum += *channel as u32;
+                        }
+
+                        count += 1;
+                    }
+                }
+
+                // The center column always belongs to the chunk, so there is at least one.
+                let average = |i: usize| std::array::from_fn(|c| ((sums[i * 3 + c] + count / 2) / count) as u8);
+                result.set(x, z, TintColors { grass: average(0), foliage: average(1), water: average(2) });
+            }
+        }
+
+        result
+    }
+
+    /// Returns the colors of the column at local `x` and `z`, each in `0..16`.
+    #[inline]
+    pub fn get(&self, x: usize, z: usize) -> TintColors {
+        self.columns[z * SECTION_SIZE + x]
+    }
+
+    /// Sets the colors of the column at local `x` and `z`, each in `0..16`.
+    #[inline]
+    pub fn set(&mut self, x: usize, z: usize, colors: TintColors) {
+        self.columns[z * SECTION_SIZE + x] = colors;
+    }
+}
+
+impl Default for ColumnTints {
+    fn default() -> Self { Self::new() }
+}
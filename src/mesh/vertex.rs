@@ -0,0 +1,102 @@
+use crate::world::Direction;
+
+/// Number of position units per block. Positions are stored in sixteenths of a block so that
+/// shapes smaller than a full block, like slabs, can be meshed.
+pub const POSITION_SCALE: u32 = 16;
+
+/// A chunk mesh vertex packed into three 32-bit words.
+///
+/// | word | bits  | content                                               |
+/// |------|-------|-------------------------------------------------------|
+/// | 0    | 0-8   | X within the section, in sixteenths of a block (0-256) |
+/// | 0    | 9-17  | Y                                                     |
+/// | 0    | 18-26 | Z                                                     |
+/// | 0    | 27-29 | face, as a [`Direction`]                              |
+/// | 0    | 30-31 | ambient occlusion, 0 (darkest) to 3 (none)            |
+/// | 1    | 0-15  | texture array layer                                   |
+/// | 1    | 16-19 | sky light                                             |
+/// | 1    | 20-23 | block light                                           |
+/// | 1    | 24-30 | flow angle of fluid surfaces, in 128ths of a turn      |
+/// | 1    | 31    | whether the surface flows                             |
+/// | 2    | 0-23  | tint color as RGB, red in the lowest byte             |
+///
+/// Texture coordinates are not stored: the shader derives them from the position on the face
+/// plane, which also makes textures tile across merged faces. Flowing fluid surfaces turn
+/// them so that the texture runs along the flow angle, measured from +X towards +Z.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockVertex {
+    pub position: u32,
+    pub data: u32,
+    pub color: u32,
+}
+
+impl BlockVertex {
+    /// Packs an untinted vertex. Coordinates are in sixteenths of a block, relative to the
+    /// section.
+    #[inline]
+    pub fn new(position: [u32; 3], face: Direction, ao: u8, layer: u16, sky_light: u8, block_light: u8) -> Self {
+        debug_assert!(position.iter().all(|&c| c <= 16 * POSITION_SCALE));
+
+        let [x, y, z] = position;
+        let position = x | (y << 9) | (z << 18) | ((face as u32) << 27) | (((ao & 3) as u32) << 30);
+        let data = layer as u32 | (((sky_light & 15) as u32) << 16) | (((block_light & 15) as u32) << 20);
+
+        Self { position, data, color: 0xff_ffff }
+    }
+
+    /// Returns the vertex with its texture multiplied by `color`.
+    #[inline]
+    pub fn with_color(self, color: [u8; 3]) -> Self {
+        let [r, g, b] = color.map(u32::from);
+        Self { color: r | (g << 8) | (b << 16), ..self }
+    }
+
+    /// Returns the vertex of a fluid surface flowing at `angle`, in 128ths of a turn from +X
+    /// towards +Z.
+    #[inline]
+    pub fn with_flow(self, angle: u8) -> Self {
+        Self { data: (self.data & 0xff_ffff) | (((angle & 127) as u32) << 24) | 1 << 31, ..self }
+    }
+
+    /// Returns the position in sixteenths of a block, relative to the section.
+    pub fn position(self) -> [u32; 3] {
+        [self.position & 0x1ff, (self.position >> 9) & 0x1ff, (self.position >> 18) & 0x1ff]
+    }
+
+    /// Returns the face the vertex belongs to.
+    pub fn face(self) -> Direction {
+        Direction::ALL[((self.position >> 27) & 7) as usize]
+    }
+
+    /// Returns the ambient occlusion level, from 0 (darkest) to 3 (none).
+    pub fn ao(self) -> u8 {
+        (self.position >> 30) as u8
+    }
+
+    /// Returns the texture array layer.
+    pub fn layer(self) -> u16 {
+        self.data as u16
+    }
+
+    /// Returns the sky light level.
+    pub fn sky_light(self) -> u8 {
+        ((self.data >> 16) & 15) as u8
+    }
+
+    /// Returns the block light level.
+    pub fn block_light(self) -> u8 {
+        ((self.data >> 20) & 15) as u8
+    }
+
+    /// Returns the flow angle of a flowing fluid surface, in 128ths of a turn, or `None` if it
+    /// does not flow.
+    pub fn flow(self) -> Option<u8> {
+        (self.data >> 31 == 1).then_some(((self.data >> 24) & 127) as u8)
+    }
+
+    /// Returns the tint color as RGB.
+    pub fn color(self) -> [u8; 3] {
+        [self.color as u8, (self.color >> 8) as u8, (self.color >> 16) as u8]
+    }
+}
@@ -0,0 +1,55 @@
+use super::{BYTE_ARRAY, INT_ARRAY, LONG_ARRAY};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Bytes stored as a byte array tag rather than a list of bytes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ByteArray(pub Vec<i8>);
+
+/// Ints stored as an int array tag rather than a list of ints, as Minecraft does for UUIDs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct IntArray(pub Vec<i32>);
+
+/// Longs stored as a long array tag rather than a list of longs, as Minecraft does for
+/// packed block states and heightmaps.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LongArray(pub Vec<i64>);
+
+// Each array is a newtype with a name the NBT serializer recognizes; other formats see a
+// plain sequence. All of them read back from arrays and lists alike.
+
+impl Serialize for ByteArray {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(BYTE_ARRAY, &self.0)
+    }
+}
+
+impl Serialize for IntArray {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(INT_ARRAY, &self.0)
+    }
+}
+
+impl Serialize for LongArray {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(LONG_ARRAY, &self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteArray {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for IntArray {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self)
+    }
+}
+
+impl<'de> Deserialize<'de> for LongArray {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self)
+    }
+}
@@ -0,0 +1,469 @@
+use super::{Compound, Tag, TagType};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use std::io::{Read, Write};
+
+/// Deepest nesting of lists and compounds read, as in Minecraft, so that crafted files
+/// cannot overflow the stack.
+const MAX_DEPTH: usize = 512;
+
+/// Byte order of numbers in binary NBT.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Endian {
+    /// Java Edition files, whose strings are in Java's modified UTF-8.
+    #[default]
+    Big,
+
+    /// Bedrock Edition files, whose strings are in plain UTF-8.
+    Little,
+}
+
+/// How a binary NBT file is compressed as a whole.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NbtCompression {
+    #[default]
+    None,
+
+    /// As `level.dat` and structure files.
+    Gzip,
+
+    /// As chunks in region files.
+    Zlib,
+}
+
+/// Reads the named root tag of binary NBT, returning its name and value. Bytes after it are
+/// ignored.
+///
+/// # Returns
+/// * `Err(String)` if the data is truncated, holds an unknown tag type, a list mixing types,
+///   an invalid string or nests deeper than 512 levels.
+pub fn read_nbt(bytes: &[u8], endian: Endian) -> Result<(String, Tag), String> {
+    let mut reader = Reader { bytes, pos: 0, endian };
+    let tag_type = reader.tag_type()?;
+
+    if tag_type == TagType::End {
+        return Err(String::from("NBT without a root tag"));
+    }
+
+    let name = reader.string()?;
+    let tag = reader.tag(tag_type, 0)?;
+    Ok((name, tag))
+}
+
+/// Reads binary NBT compressed with gzip or zlib, or not compressed at all, telling which
+/// from its first bytes.
+pub fn read_compressed(bytes: &[u8], endian: Endian) -> Result<(String, Tag), String> {
+    let mut data = Vec::new();
+
+    let result = match bytes {
+        [0x1f, 0x8b, ..] => GzDecoder::new(bytes).read_to_end(&mut data),
+        [0x78, ..] => ZlibDecoder::new(bytes).read_to_end(&mut data),
+        _ => return read_nbt(bytes, endian),
+    };
+
+    result.map_err(|e| format!("corrupted NBT compression: {e}"))?;
+    read_nbt(&data, endian)
+}
+
+/// Writes `tag` as the root of binary NBT named `name`.
+///
+/// # Returns
+/// * `Err(String)` if a list mixes tag types or a string or array is too long for the
+///   format.
+pub fn write_nbt(name: &str, tag: &Tag, endian: Endian) -> Result<Vec<u8>, String> {
+    let mut writer = Writer { bytes: Vec::new(), endian };
+    writer.bytes.push(tag.tag_type() as u8);
+    writer.string(name)?;
+    writer.tag(tag)?;
+    Ok(writer.bytes)
+}
+
+/// Writes `tag` as the root of binary NBT named `name`, compressed with `compression`.
+pub fn write_compressed(name: &str, tag: &Tag, endian: Endian, compression: NbtCompression) -> Result<Vec<u8>, String> {
+    let bytes = write_nbt(name, tag, endian)?;
+    let level = flate2::Compression::default();
+
+    let compressed = match compression {
+        NbtCompression::None => return Ok(bytes),
+        NbtCompression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), level);
+            encoder.write_all(&bytes).and_then(|_| encoder.finish())
+        }
+        NbtCompression::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), level);
+            encoder.write_all(&bytes).and_then(|_| encoder.finish())
+        }
+    };
+
+    Ok(compressed.expect("writing to memory cannot fail"))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    endian: Endian,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let taken = self.bytes.get(self.pos..self.pos.saturating_add(len)).ok_or_else(|| format!("NBT truncated at byte {}", self.pos))?;
+        self.pos += len;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut bytes: [u8; N] = self.take(N)?.try_into().expect("N bytes");
+
+        if self.endian == Endian::Little {
+            bytes.reverse();
+        }
+
+        Ok(bytes)
+    }
+
+    fn tag_type(&mut self) -> Result<TagType, String> {
+        let id = self.take(1)?[0];
+        TagType::from_id(id).ok_or_else(|| format!("unknown NBT tag type {id} at byte {}", self.pos - 1))
+    }
+
+    /// Reads the length of an array or list, checking that it fits what is left for
+    /// elements of `size` bytes at least, so that corrupted lengths do not allocate wildly.
+    fn len(&mut self, size: usize) -> Result<usize, String> {
+        let len = i32::from_be_bytes(self.array()?);
+        let left = self.bytes.len() - self.pos;
+
+        match usize::try_from(len) {
+            Ok(len) if len.saturating_mul(size) <= left => Ok(len),
+            _ => Err(format!("NBT length {len} at byte {} past the end", self.pos - 4)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        let bytes = self.take(len)?;
+
+        match self.endian {
+            Endian::Big => decode_mutf8(bytes),
+            Endian::Little => String::from_utf8(bytes.to_vec()).map_err(|e| format!("invalid NBT string: {e}")),
+        }
+    }
+
+    fn tag(&mut self, tag_type: TagType, depth: usize) -> Result<Tag, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("NBT nested deeper than {MAX_DEPTH} levels"));
+        }
+
+        Ok(match tag_type {
+            TagType::End => return Err(format!("unexpected TAG_End at byte {}", self.pos)),
+            TagType::Byte => Tag::Byte(self.take(1)?[0] as i8),
+            TagType::Short => Tag::Short(i16::from_be_bytes(self.array()?)),
+            TagType::Int => Tag::Int(i32::from_be_bytes(self.array()?)),
+            TagType::Long => Tag::Long(i64::from_be_bytes(self.array()?)),
+            TagType::Float => Tag::Float(f32::from_be_bytes(self.array()?)),
+            TagType::Double => Tag::Double(f64::from_be_bytes(self.array()?)),
+            TagType::ByteArray => {
+                let len = self.len(1)?;
+                Tag::ByteArray(self.take(len)?.iter().map(|&byte| byte as i8).collect())
+            }
+            TagType::String => Tag::String(self.string()?),
+            TagType::List => {
+                let element = self.tag_type()?;
+                let len = self.len(1)?;
+
+                if element == TagType::End && len > 0 {
+                    return Err(format!("list of {len} TAG_End at byte {}", self.pos));
+                }
+
+                Tag::List((0..len).map(|_| self.tag(element, depth + 1)).collect::<Result<_, _>>()?)
+            }
+            TagType::Compound => {
+                let mut compound = Compound::new();
+
+                loop {
+                    let entry = self.tag_type()?;
+
+                    if entry == TagType::End {
+                        break;
+                    }
+
+                    let name = self.string()?;
+                    compound.insert(name, self.tag(entry, depth + 1)?);
+                }
+
+                Tag::Compound(compound)
+            }
+            TagType::IntArray => {
+                let len = self.len(4)?;
+                Tag::IntArray((0..len).map(|_| self.array().map(i32::from_be_bytes)).collect::<Result<_, _>>()?)
+            }
+            TagType::LongArray => {
+                let len = self.len(8)?;
+                Tag::LongArray((0..len).map(|_| self.array().map(i64::from_be_bytes)).collect::<Result<_, _>>()?)
+            }
+        })
+    }
+}
+
+struct Writer {
+    bytes: Vec<u8>,
+    endian: Endian,
+}
+
+impl Writer {
+    /// Appends `bytes`, given big-endian.
+    fn array<const N: usize>(&mut self, mut bytes: [u8; N]) {
+        if self.endian == Endian::Little {
+            bytes.reverse();
+        }
+
+        self.bytes.extend_from_slice(&bytes);
+    }
+
+    fn len(&mut self, len: usize) -> Result<(), String> {
+        let len = i32::try_from(len).map_err(|_| format!("NBT array of {len} elements too long"))?;
+        self.array(len.to_be_bytes());
+        Ok(())
+    }
+
+    fn string(&mut self, value: &str) -> Result<(), String> {
+        let encoded = match self.endian {
+            Endian::Big => encode_mutf8(value),
+            Endian::Little => value.as_bytes().to_vec(),
+        };
+
+        let len = u16::try_from(encoded.len()).map_err(|_| format!("NBT string of {} bytes too long", encoded.len()))?;
+        self.array(len.to_be_bytes());
+        self.bytes.extend_from_slice(&encoded);
+        Ok(())
+    }
+
+    fn tag(&mut self, tag: &Tag) -> Result<(), String> {
+        match tag {
+            Tag::Byte(value) => self.bytes.push(*value as u8),
+            Tag::Short(value) => self.array(value.to_be_bytes()),
+            Tag::Int(value) => self.array(value.to_be_bytes()),
+            Tag::Long(value) => self.array(value.to_be_bytes()),
+            Tag::Float(value) => self.array(value.to_be_bytes()),
+            Tag::Double(value) => self.array(value.to_be_bytes()),
+            Tag::ByteArray(values) => {
+                self.len(values.len())?;
+                self.bytes.extend(values.iter().map(|&value| value as u8));
+            }
+            Tag::String(value) => self.string(value)?,
+            Tag::List(values) => {
+                let element = values.first().map_or(TagType::End, Tag::tag_type);
+
+                if let Some(other) = values.iter().find(|value| value.tag_type() != element) {
+                    return Err(format!("list of {} holding a {}", element.name(), other.tag_type().name()));
+                }
+
+                self.bytes.push(element as u8);
+                self.len(values.len())?;
+
+                for value in values {
+                    self.tag(value)?;
+                }
+            }
+            Tag::Compound(compound) => {
+                for (name, value) in compound.iter() {
+                    self.bytes.push(value.tag_type() as u8);
+                    self.string(name)?;
+                    self.tag(value)?;
+                }
+
+                self.bytes.push(TagType::End as u8);
+            }
+            Tag::IntArray(values) => {
+                self.len(values.len())?;
+
+                for value in values {
+                    self.array(value.to_be_bytes());
+                }
+            }
+            Tag::LongArray(values) => {
+                self.len(values.len())?;
+
+                for value in values {
+                    self.array(value.to_be_bytes());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes `value` in Java's modified UTF-8: the null character takes two bytes, and
+/// characters outside the Basic Multilingual Plane are written as two surrogates of three
+/// bytes each.
+fn encode_mutf8(value: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(value.len());
+
+    for unit in value.encode_utf16() {
+        match unit {
+            1..=0x7f => bytes.push(unit as u8),
+            0 | 0x80..=0x7ff => bytes.extend_from_slice(&[0xc0 | (unit >> 6) as u8, 0x80 | (unit & 0x3f) as u8]),
+            _ => bytes.extend_from_slice(&[0xe0 | (unit >> 12) as u8, 0x80 | (unit >> 6 & 0x3f) as u8, 0x80 | (unit & 0x3f) as u8]),
+        }
+    }
+
+    bytes
+}
+
+/// Decodes Java's modified UTF-8, see [`encode_mutf8`].
+fn decode_mutf8(bytes: &[u8]) -> Result<String, String> {
+    // Most strings are plain ASCII, which both encodings share.
+    if bytes.iter().all(|&byte| byte != 0 && byte < 0x80) {
+        return Ok(String::from_utf8(bytes.to_vec()).expect("ASCII is UTF-8"));
+    }
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut pos = 0;
+    let invalid = || String::from("invalid modified UTF-8 in an NBT string");
+
+    while pos < bytes.len() {
+        let continuation = |offset: usize| bytes.get(pos + offset).filter(|&&byte| byte & 0xc0 == 0x80).map(|&byte| (byte & 0x3f) as u16).ok_or_else(invalid);
+
+        let byte = bytes[pos];
+
+        let (unit, len) = match byte {
+            0x01..=0x7f => (byte as u16, 1),
+            0xc0..=0xdf => (((byte & 0x1f) as u16) << 6 | continuation(1)?, 2),
+            0xe0..=0xef => (((byte & 0x0f) as u16) << 12 | continuation(1)? << 6 | continuation(2)?, 3),
+            _ => return Err(invalid()),
+        };
+
+        units.push(unit);
+        pos += len;
+    }
+
+    String::from_utf16(&units).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A compound holding every tag type, with strings needing modified UTF-8.
+    fn sample() -> Tag {
+        let mut item = Compound::new();
+        item.insert("id", Tag::String(String::from("minecraft:stone")));
+        item.insert("Count", Tag::Byte(3));
+
+        let mut root = Compound::new();
+        root.insert("byte", Tag::Byte(-128));
+        root.insert("short", Tag::Short(-300));
+        root.insert("int", Tag::Int(i32::MIN));
+        root.insert("long", Tag::Long(i64::MAX));
+        root.insert("float", Tag::Float(-0.5));
+        root.insert("double", Tag::Double(1e300));
+        root.insert("bytes", Tag::ByteArray(vec![0, -1, 127]));
+        root.insert("string", Tag::String(String::from("nul \0 and \u{1f600} and é")));
+        root.insert("items", Tag::List(vec![Tag::Compound(item.clone()), Tag::Compound(item)]));
+        root.insert("empty", Tag::List(Vec::new()));
+        root.insert("nested", Tag::Compound(Compound::new()));
+        root.insert("ints", Tag::IntArray(vec![1, -2, i32::MAX]));
+        root.insert("longs", Tag::LongArray(vec![i64::MIN, 0]));
+        Tag::Compound(root)
+    }
+
+    /// Returns `depth` lists nested in one another.
+    fn nested_lists(depth: usize) -> Tag {
+        (0..depth).fold(Tag::List(Vec::new()), |tag, _| Tag::List(vec![tag]))
+    }
+
+    /// Runs `test` on a thread with the stack of a main thread, as unoptimized builds need
+    /// more than test threads have to read 512 levels.
+    fn with_main_stack(test: impl FnOnce() + Send + 'static) {
+        std::thread::Builder::new().stack_size(8 << 20).spawn(test).unwrap().join().unwrap();
+    }
+
+    #[test]
+    fn reads_what_it_writes_in_both_byte_orders() {
+        for endian in [Endian::Big, Endian::Little] {
+            let bytes = write_nbt("root name", &sample(), endian).unwrap();
+            assert_eq!(read_nbt(&bytes, endian).unwrap(), (String::from("root name"), sample()));
+        }
+    }
+
+    #[test]
+    fn writes_numbers_in_the_byte_order_asked_for() {
+        let big = write_nbt("", &Tag::Int(1), Endian::Big).unwrap();
+        let little = write_nbt("", &Tag::Int(1), Endian::Little).unwrap();
+
+        assert_eq!(big, [3, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(little, [3, 0, 0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn reads_what_it_writes_compressed() {
+        for compression in [NbtCompression::None, NbtCompression::Gzip, NbtCompression::Zlib] {
+            let bytes = write_compressed("", &sample(), Endian::Big, compression).unwrap();
+            assert_eq!(read_compressed(&bytes, Endian::Big).unwrap().1, sample());
+        }
+    }
+
+    #[test]
+    fn encodes_modified_utf8() {
+        assert_eq!(encode_mutf8("a\0b"), [b'a', 0xc0, 0x80, b'b']);
+        assert_eq!(encode_mutf8("é"), "é".as_bytes());
+        assert_eq!(encode_mutf8("\u{1f600}"), [0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80]);
+
+        let text = "\0\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}";
+        assert_eq!(decode_mutf8(&encode_mutf8(text)).unwrap(), text);
+    }
+
+    #[test]
+    fn rejects_invalid_modified_utf8() {
+        assert!(decode_mutf8(&[b'a', 0]).is_err());
+        assert!(decode_mutf8(&[0xc0]).is_err());
+        assert!(decode_mutf8(&[0xe0, 0x80, b'a']).is_err());
+        assert!(decode_mutf8(&[0xf0, 0x9f, 0x98, 0x80]).is_err());
+    }
+
+    #[test]
+    fn writes_plain_utf8_little_endian() {
+        let bytes = write_nbt("", &Tag::String(String::from("\0\u{1f600}")), Endian::Little).unwrap();
+        assert_eq!(bytes[3..], [5, 0, 0, 0xf0, 0x9f, 0x98, 0x80]);
+    }
+
+    #[test]
+    fn rejects_nesting_past_the_limit() {
+        with_main_stack(|| {
+            let bytes = write_nbt("", &nested_lists(MAX_DEPTH), Endian::Big).unwrap();
+            assert_eq!(read_nbt(&bytes, Endian::Big).unwrap().1, nested_lists(MAX_DEPTH));
+
+            let bytes = write_nbt("", &nested_lists(MAX_DEPTH + 1), Endian::Big).unwrap();
+            assert!(read_nbt(&bytes, Endian::Big).unwrap_err().contains("deeper"));
+        });
+    }
+
+    #[test]
+    fn rejects_lengths_past_the_end() {
+        // A list of bytes, an int array and a string claiming more than there is.
+        assert!(read_nbt(&[9, 0, 0, 1, 0x7f, 0xff, 0xff, 0xff, 0], Endian::Big).unwrap_err().contains("past the end"));
+        assert!(read_nbt(&[11, 0, 0, 0, 0, 0, 2, 0, 0, 0, 1], Endian::Big).is_err());
+        assert!(read_nbt(&[8, 0, 0, 0xff, 0xff, b'a'], Endian::Big).is_err());
+        assert!(read_nbt(&[7, 0, 0, 0xff, 0xff, 0xff, 0xff], Endian::Big).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let bytes = write_nbt("", &sample(), Endian::Big).unwrap();
+
+        for len in 0..bytes.len() {
+            assert!(read_nbt(&bytes[..len], Endian::Big).is_err(), "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn rejects_what_the_format_cannot_hold() {
+        let long = Tag::String("a".repeat(u16::MAX as usize + 1));
+        assert!(write_nbt("", &long, Endian::Big).is_err());
+        assert!(write_nbt("", &Tag::String("a".repeat(u16::MAX as usize)), Endian::Big).is_ok());
+
+        let mixed = Tag::List(vec![Tag::Int(1), Tag::Byte(2)]);
+        assert!(write_nbt("", &mixed, Endian::Big).is_err());
+    }
+}
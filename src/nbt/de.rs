@@ -0,0 +1,220 @@
+use super::ser::Error;
+use super::Tag;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt::Display;
+
+/// Builds a `T` from `tag`, the reverse of [`to_tag`](super::to_tag).
+///
+/// Numbers convert to any numeric type they fit in, and unsigned integers also take the
+/// signed tag of their size bit for bit. Bytes stand for booleans, and missing compound
+/// entries for `None`.
+///
+/// # Returns
+/// * `Err(String)` if `tag` does not have the shape of `T`.
+pub fn from_tag<T: DeserializeOwned>(tag: Tag) -> Result<T, String> {
+    T::deserialize(TagDeserializer(tag)).map_err(|e| e.0)
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Self(message.to_string())
+    }
+}
+
+struct TagDeserializer(Tag);
+
+/// Deserializes unsigned integers from the signed tag of the same size bit for bit, and
+/// from any other numeric tag they fit in.
+macro_rules! deserialize_unsigned {
+    ($method:ident, $visit:ident, $tag:ident, $unsigned:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.0 {
+                Tag::$tag(value) => visitor.$visit(value as $unsigned),
+                tag => TagDeserializer(tag).deserialize_any(visitor),
+            }
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for TagDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Tag::Byte(value) => visitor.visit_i8(value),
+            Tag::Short(value) => visitor.visit_i16(value),
+            Tag::Int(value) => visitor.visit_i32(value),
+            Tag::Long(value) => visitor.visit_i64(value),
+            Tag::Float(value) => visitor.visit_f32(value),
+            Tag::Double(value) => visitor.visit_f64(value),
+            Tag::String(value) => visitor.visit_string(value),
+            Tag::ByteArray(values) => visitor.visit_seq(de::value::SeqDeserializer::new(values.into_iter().map(Tag::Byte).map(TagDeserializer))),
+            Tag::IntArray(values) => visitor.visit_seq(de::value::SeqDeserializer::new(values.into_iter().map(Tag::Int).map(TagDeserializer))),
+            Tag::LongArray(values) => visitor.visit_seq(de::value::SeqDeserializer::new(values.into_iter().map(Tag::Long).map(TagDeserializer))),
+            Tag::List(values) => visitor.visit_seq(de::value::SeqDeserializer::new(values.into_iter().map(TagDeserializer))),
+            Tag::Compound(compound) => {
+                let entries = compound.into_iter().map(|(name, tag)| (KeyDeserializer(name), TagDeserializer(tag)));
+                visitor.visit_map(de::value::MapDeserializer::new(entries))
+            }
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Tag::Byte(value) => visitor.visit_bool(value != 0),
+            tag => TagDeserializer(tag).deserialize_any(visitor),
+        }
+    }
+
+    deserialize_unsigned!(deserialize_u8, visit_u8, Byte, u8);
+    deserialize_unsigned!(deserialize_u16, visit_u16, Short, u16);
+    deserialize_unsigned!(deserialize_u32, visit_u32, Int, u32);
+    deserialize_unsigned!(deserialize_u64, visit_u64, Long, u64);
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Tag::ByteArray(values) => visitor.visit_byte_buf(values.into_iter().map(|value| value as u8).collect()),
+            tag => TagDeserializer(tag).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Tag::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Tag::Compound(compound) if compound.len() == 1 => {
+                let (variant, tag) = compound.into_iter().next().expect("one entry");
+                visitor.visit_enum(VariantDeserializer { variant, tag })
+            }
+            tag => Err(Error(format!("expected an enum as a string or a compound of one entry, found a {}", tag.tag_type().name()))),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u128 f32 f64 char str string seq tuple tuple_struct map struct identifier
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for TagDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserializes the name of a compound entry, parsing it for integer keys.
+struct KeyDeserializer(String);
+
+macro_rules! deserialize_integer_key {
+    ($($method:ident, $visit:ident, $integer:ty;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                match self.0.parse::<$integer>() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => visitor.visit_string(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_integer_key! {
+        deserialize_i8, visit_i8, i8;
+        deserialize_i16, visit_i16, i16;
+        deserialize_i32, visit_i32, i32;
+        deserialize_i64, visit_i64, i64;
+        deserialize_u8, visit_u8, u8;
+        deserialize_u16, visit_u16, u16;
+        deserialize_u32, visit_u32, u32;
+        deserialize_u64, visit_u64, u64;
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        bool i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for KeyDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Deserializes an enum variant stored as a compound of one entry named after it.
+struct VariantDeserializer {
+    variant: String,
+    tag: Tag,
+}
+
+impl<'de> de::EnumAccess<'de> for VariantDeserializer {
+    type Error = Error;
+    type Variant = TagDeserializer;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), Error> {
+        let variant = seed.deserialize(KeyDeserializer(self.variant))?;
+        Ok((variant, TagDeserializer(self.tag)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for TagDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
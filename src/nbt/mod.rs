@@ -0,0 +1,17 @@
+mod array;
+mod binary;
+mod de;
+mod ser;
+mod snbt;
+mod tag;
+
+pub use array::{ByteArray, IntArray, LongArray};
+pub use binary::{read_compressed, read_nbt, write_compressed, write_nbt, Endian, NbtCompression};
+pub use de::from_tag;
+pub use ser::to_tag;
+pub use tag::{Compound, Tag, TagType};
+
+// Names of the newtypes serializing to arrays, which no other type should use.
+const BYTE_ARRAY: &str = "__nbt_byte_array";
+const INT_ARRAY: &str = "__nbt_int_array";
+const LONG_ARRAY: &str = "__nbt_long_array";
@@ -0,0 +1,459 @@
+use super::{Compound, Tag, BYTE_ARRAY, INT_ARRAY, LONG_ARRAY};
+
+use serde::ser::{self, Serialize};
+use std::fmt::{self, Display};
+
+/// Turns `value` into a tag.
+///
+/// Structs and maps become compounds, sequences and tuples lists, and `None` fields are left
+/// out. Booleans are bytes, unsigned integers are stored in the signed tag of the same size
+/// bit for bit, and `char`s are strings. Enum variants without data are strings of their
+/// name; others are compounds with one entry named after the variant. Byte, int and long
+/// arrays come from [`ByteArray`](super::ByteArray), [`IntArray`](super::IntArray) and
+/// [`LongArray`](super::LongArray), and from `serialize_bytes`.
+///
+/// As binary lists hold a single tag type, tuples of different types and lists of enums
+/// mixing variants with and without data make tags that cannot be written.
+///
+/// # Returns
+/// * `Err(String)` if `value` is `None`, holds a `None` in a list or a map with keys that
+///   are not strings or integers, or fails to serialize.
+pub fn to_tag<T: Serialize + ?Sized>(value: &T) -> Result<Tag, String> {
+    match value.serialize(TagSerializer) {
+        Ok(Some(tag)) => Ok(tag),
+        Ok(None) => Err(String::from("nothing to store in NBT")),
+        Err(e) => Err(e.0),
+    }
+}
+
+/// Error of serializing to or deserializing from tags.
+#[derive(Debug)]
+pub(super) struct Error(pub(super) String);
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(message: T) -> Self {
+        Self(message.to_string())
+    }
+}
+
+/// Serializes values to tags, or to `None` for values left out.
+struct TagSerializer;
+
+impl ser::Serializer for TagSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+    type SerializeSeq = ListSerializer;
+    type SerializeTuple = ListSerializer;
+    type SerializeTupleStruct = ListSerializer;
+    type SerializeTupleVariant = VariantSerializer<ListSerializer>;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = VariantSerializer<MapSerializer>;
+
+    fn serialize_bool(self, value: bool) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Byte(value as i8)))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Byte(value)))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Short(value)))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Int(value)))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Long(value)))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Byte(value as i8)))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Short(value as i16)))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Int(value as i32)))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Long(value as i64)))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Float(value)))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Double(value)))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::String(value.to_string())))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::String(value.to_string())))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::ByteArray(value.iter().map(|&byte| byte as i8).collect())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Compound(Compound::new())))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Self::Ok, Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<Self::Ok, Error> {
+        let tag = value.serialize(self)?;
+
+        // Arrays are lists of their elements marked with the name of their wrapper.
+        let elements = match (name, &tag) {
+            (BYTE_ARRAY | INT_ARRAY | LONG_ARRAY, Some(Tag::List(elements))) => elements,
+            _ => return Ok(tag),
+        };
+
+        let array = match name {
+            BYTE_ARRAY => elements.iter().map(|tag| if let Tag::Byte(value) = tag { Some(*value) } else { None }).collect::<Option<_>>().map(Tag::ByteArray),
+            INT_ARRAY => elements.iter().map(|tag| if let Tag::Int(value) = tag { Some(*value) } else { None }).collect::<Option<_>>().map(Tag::IntArray),
+            _ => elements.iter().map(|tag| if let Tag::Long(value) = tag { Some(*value) } else { None }).collect::<Option<_>>().map(Tag::LongArray),
+        };
+
+        array.map(Some).ok_or_else(|| Error(format!("{name} holding something else")))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Error> {
+        let mut compound = Compound::new();
+
+        if let Some(tag) = value.serialize(self)? {
+            compound.insert(variant, tag);
+        }
+
+        Ok(Some(Tag::Compound(compound)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Ok(ListSerializer { values: Vec::with_capacity(len.unwrap_or(0)) })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> Result<Self::SerializeTupleVariant, Error> {
+        Ok(VariantSerializer { variant, inner: ListSerializer { values: Vec::with_capacity(len) } })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(MapSerializer { compound: Compound::new(), key: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant, Error> {
+        Ok(VariantSerializer { variant, inner: MapSerializer { compound: Compound::new(), key: None } })
+    }
+}
+
+struct ListSerializer {
+    values: Vec<Tag>,
+}
+
+impl ListSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let tag = value.serialize(TagSerializer)?.ok_or_else(|| Error(String::from("NBT lists cannot hold None")))?;
+        self.values.push(tag);
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::List(self.values)))
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::List(self.values)))
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::List(self.values)))
+    }
+}
+
+struct MapSerializer {
+    compound: Compound,
+
+    /// Key of the value serialized next.
+    key: Option<String>,
+}
+
+impl MapSerializer {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), Error> {
+        if let Some(tag) = value.serialize(TagSerializer)? {
+            self.compound.insert(key, tag);
+        }
+
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = match key.serialize(TagSerializer)? {
+            Some(Tag::String(key)) => key,
+            Some(tag @ (Tag::Byte(_) | Tag::Short(_) | Tag::Int(_) | Tag::Long(_))) => tag.as_i64().expect("integer").to_string(),
+            _ => return Err(Error(String::from("NBT compound names must be strings or integers"))),
+        };
+
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self.key.take().expect("values follow their key");
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Compound(self.compound)))
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Ok(Some(Tag::Compound(self.compound)))
+    }
+}
+
+/// Serializes the data of an enum variant, then wraps it in a compound named after the
+/// variant.
+struct VariantSerializer<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl<S> VariantSerializer<S> {
+    fn wrap(variant: &'static str, tag: Option<Tag>) -> Result<Option<Tag>, Error> {
+        let mut compound = Compound::new();
+        compound.insert(variant, tag.expect("variant data is always stored"));
+        Ok(Some(Tag::Compound(compound)))
+    }
+}
+
+impl ser::SerializeTupleVariant for VariantSerializer<ListSerializer> {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Self::wrap(self.variant, ser::SerializeSeq::end(self.inner)?)
+    }
+}
+
+impl ser::SerializeStructVariant for VariantSerializer<MapSerializer> {
+    type Ok = Option<Tag>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.inner.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Error> {
+        Self::wrap(self.variant, ser::SerializeStruct::end(self.inner)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt::{from_tag, read_nbt, write_nbt, Endian, IntArray, LongArray};
+
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Cube,
+        Sphere(f32),
+        Box { width: u8, height: u8 },
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        id: String,
+        count: u8,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Player {
+        name: String,
+        letter: char,
+        flying: bool,
+        seed: u64,
+        health: f32,
+        uuid: IntArray,
+        states: LongArray,
+        items: Vec<Item>,
+        shapes: Vec<Shape>,
+        pos: (f64, f64, f64),
+        levels: BTreeMap<u32, String>,
+        nickname: Option<String>,
+        spawn: Option<(i32, i32, i32)>,
+    }
+
+    fn player() -> Player {
+        Player {
+            name: String::from("Steve"),
+            letter: 'é',
+            flying: true,
+            seed: u64::MAX,
+            health: 19.5,
+            uuid: IntArray(vec![1, -2, 3, -4]),
+            states: LongArray(vec![i64::MIN, 0]),
+            items: vec![Item { id: String::from("stone"), count: 200 }],
+            shapes: vec![Shape::Sphere(1.5), Shape::Box { width: 2, height: 3 }],
+            pos: (0.5, 64.0, -3.25),
+            levels: BTreeMap::from([(1, String::from("one")), (20, String::from("twenty"))]),
+            nickname: None,
+            spawn: Some((0, 64, 0)),
+        }
+    }
+
+    #[test]
+    fn maps_values_to_tags() {
+        let tag = to_tag(&player()).unwrap();
+        let root = tag.as_compound().unwrap();
+
+        assert_eq!(root.get("letter"), Some(&Tag::String(String::from("é"))));
+        assert_eq!(root.get("flying"), Some(&Tag::Byte(1)));
+        assert_eq!(root.get("seed"), Some(&Tag::Long(-1)));
+        assert_eq!(root.get("uuid"), Some(&Tag::IntArray(vec![1, -2, 3, -4])));
+        assert_eq!(root.get("states"), Some(&Tag::LongArray(vec![i64::MIN, 0])));
+        assert_eq!(root.get_list("items").unwrap()[0].as_compound().unwrap().get("count"), Some(&Tag::Byte(-56)));
+        assert_eq!(root.get_compound("levels").unwrap().get("20"), Some(&Tag::String(String::from("twenty"))));
+        assert!(root.get("nickname").is_none());
+        assert_eq!(to_tag(&Shape::Cube).unwrap(), Tag::String(String::from("Cube")));
+        assert_eq!(to_tag(&Shape::Sphere(1.0)).unwrap().to_string(), "{Sphere:1.0f}");
+    }
+
+    #[test]
+    fn reads_back_what_it_stores() {
+        let tag = to_tag(&player()).unwrap();
+        assert_eq!(from_tag::<Player>(tag.clone()).unwrap(), player());
+
+        for endian in [Endian::Big, Endian::Little] {
+            let bytes = write_nbt("", &tag, endian).unwrap();
+            assert_eq!(from_tag::<Player>(read_nbt(&bytes, endian).unwrap().1).unwrap(), player());
+        }
+
+        let cube = to_tag(&vec![Shape::Cube, Shape::Cube]).unwrap();
+        assert_eq!(from_tag::<Vec<Shape>>(cube).unwrap(), [Shape::Cube, Shape::Cube]);
+    }
+
+    #[test]
+    fn reads_arrays_from_lists_and_numbers_into_wider_types() {
+        let tag: Tag = "{a:[I;1,2],b:[3,4],c:5b,d:1b}".parse().unwrap();
+
+        #[derive(Deserialize)]
+        struct Loose {
+            a: Vec<i64>,
+            b: IntArray,
+            c: i64,
+            d: bool,
+            e: Option<u8>,
+        }
+
+        let loose: Loose = from_tag(tag).unwrap();
+        assert_eq!((loose.a, loose.b, loose.c, loose.d, loose.e), (vec![1, 2], IntArray(vec![3, 4]), 5, true, None));
+    }
+
+    #[test]
+    fn rejects_what_tags_cannot_hold() {
+        assert!(to_tag(&None::<i32>).is_err());
+        assert!(to_tag(&vec![Some(1), None]).is_err());
+        assert!(to_tag(&BTreeMap::from([((1, 2), 3)])).is_err());
+
+        // A list mixing a string and a compound can be built but not written.
+        let mixed = to_tag(&vec![Shape::Cube, Shape::Sphere(1.0)]).unwrap();
+        assert!(write_nbt("", &mixed, Endian::Big).is_err());
+
+        assert!(from_tag::<Item>(Tag::Int(1)).is_err());
+        assert!(from_tag::<Shape>(Tag::Int(1)).is_err());
+        assert!(from_tag::<u8>(Tag::Int(300)).is_err());
+    }
+}
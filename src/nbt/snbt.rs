@@ -0,0 +1,440 @@
+use super::{Compound, Tag};
+
+use std::fmt::{self, Display, Formatter, Write};
+use std::str::FromStr;
+
+/// Deepest nesting of lists and compounds parsed.
+const MAX_DEPTH: usize = 512;
+
+/// Writes the tag as SNBT, the text form of NBT used by Minecraft commands, on one line:
+/// `{name:"Steve",pos:[I;1,64,-3],health:20.0f,items:[{id:"stone",count:3b}]}`.
+///
+/// Numbers carry the suffix of their type (`b`, `s`, `L`, `f`, `d`, none for ints), strings
+/// are double-quoted and names are only quoted when they need to be. Infinite and NaN floats
+/// are written as `inf` and `NaN`, which cannot be parsed back.
+impl Display for Tag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Byte(value) => write!(f, "{value}b"),
+            Self::Short(value) => write!(f, "{value}s"),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Long(value) => write!(f, "{value}L"),
+            Self::Float(value) => write!(f, "{value:?}f"),
+            Self::Double(value) => write!(f, "{value:?}d"),
+            Self::ByteArray(values) => write_array(f, "B", values.iter().map(|value| Tag::Byte(*value))),
+            Self::String(value) => write_quoted(f, value),
+            Self::List(values) => write_array(f, "", values.iter().cloned()),
+            Self::Compound(compound) => write!(f, "{compound}"),
+            Self::IntArray(values) => write_array(f, "I", values.iter().map(|value| Tag::Int(*value))),
+            Self::LongArray(values) => write_array(f, "L", values.iter().map(|value| Tag::Long(*value))),
+        }
+    }
+}
+
+impl Display for Compound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_char('{')?;
+
+        for (index, (name, tag)) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_char(',')?;
+            }
+
+            match name.chars().all(is_unquoted) && !name.is_empty() {
+                true => f.write_str(name)?,
+                false => write_quoted(f, name)?,
+            }
+
+            write!(f, ":{tag}")?;
+        }
+
+        f.write_char('}')
+    }
+}
+
+/// Parses SNBT as written by [`Tag`]'s `Display`, and as typed in Minecraft commands.
+///
+/// Besides what is written, strings may be single-quoted or left unquoted when made of
+/// letters, digits and `_-.+`, suffixes may be lowercase or uppercase, `true` and `false`
+/// are bytes, and numbers with a decimal point or exponent but no suffix are doubles.
+/// Unquoted words that are not numbers, and integers too large for an int, are strings.
+impl FromStr for Tag {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { source, pos: 0 };
+        let tag = parser.value(0)?;
+        parser.skip_whitespace();
+
+        match parser.pos == source.len() {
+            true => Ok(tag),
+            false => Err(parser.error("trailing characters")),
+        }
+    }
+}
+
+fn write_array(f: &mut Formatter<'_>, prefix: &str, values: impl Iterator<Item = Tag>) -> fmt::Result {
+    f.write_char('[')?;
+
+    if !prefix.is_empty() {
+        write!(f, "{prefix};")?;
+    }
+
+    for (index, value) in values.enumerate() {
+        if index > 0 {
+            f.write_char(',')?;
+        }
+
+        write!(f, "{value}")?;
+    }
+
+    f.write_char(']')
+}
+
+fn write_quoted(f: &mut Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            f.write_char('\\')?;
+        }
+
+        f.write_char(c)?;
+    }
+
+    f.write_char('"')
+}
+
+fn is_unquoted(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("invalid SNBT at character {}: {message}", self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.source[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(self.error(&format!("expected '{expected}'"))),
+        }
+    }
+
+    /// Consumes `c` if it comes next.
+    fn accept(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        self.pos += found as usize;
+        found
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Tag, String> {
+        if depth > MAX_DEPTH {
+            return Err(self.error(&format!("nested deeper than {MAX_DEPTH} levels")));
+        }
+
+        match self.peek() {
+            Some('{') => self.compound(depth).map(Tag::Compound),
+            Some('[') => self.list(depth),
+            Some('"' | '\'') => self.quoted().map(Tag::String),
+            Some(_) => {
+                let word = self.word()?;
+                Ok(literal(word))
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn compound(&mut self, depth: usize) -> Result<Compound, String> {
+        self.expect('{')?;
+        let mut compound = Compound::new();
+
+        if self.accept('}') {
+            return Ok(compound);
+        }
+
+        loop {
+            let name = match self.peek() {
+                Some('"' | '\'') => self.quoted()?,
+                _ => self.word()?.to_string(),
+            };
+
+            self.expect(':')?;
+            compound.insert(name, self.value(depth + 1)?);
+
+            if self.accept('}') {
+                return Ok(compound);
+            }
+
+            self.expect(',')?;
+        }
+    }
+
+    fn list(&mut self, depth: usize) -> Result<Tag, String> {
+        self.expect('[')?;
+
+        // Arrays start with their type and a semicolon.
+        let rest = self.source[self.pos..].trim_start();
+        let array = ['B', 'I', 'L'].into_iter().find(|&prefix| rest.starts_with(prefix) && rest[1..].trim_start().starts_with(';'));
+
+        if let Some(prefix) = array {
+            self.accept(prefix);
+            self.expect(';')?;
+        }
+
+        let mut values = Vec::new();
+
+        if !self.accept(']') {
+            loop {
+                values.push(self.value(depth + 1)?);
+
+                if self.accept(']') {
+                    break;
+                }
+
+                self.expect(',')?;
+            }
+        }
+
+        let mismatch = |expected: &str| self.error(&format!("array of {expected} holding something else"));
+
+        Ok(match array {
+            Some('B') => Tag::ByteArray(values.iter().map(|value| match value {
+                Tag::Byte(value) => Ok(*value),
+                _ => Err(mismatch("bytes")),
+            }).collect::<Result<_, _>>()?),
+            Some('I') => Tag::IntArray(values.iter().map(|value| match value {
+                Tag::Int(value) => Ok(*value),
+                _ => Err(mismatch("ints")),
+            }).collect::<Result<_, _>>()?),
+            Some(_) => Tag::LongArray(values.iter().map(|value| match value {
+                Tag::Long(value) => Ok(*value),
+                _ => Err(mismatch("longs")),
+            }).collect::<Result<_, _>>()?),
+            None => {
+                if values.iter().any(|value| value.tag_type() != values[0].tag_type()) {
+                    return Err(self.error("list mixing tag types"));
+                }
+
+                Tag::List(values)
+            }
+        })
+    }
+
+    fn quoted(&mut self) -> Result<String, String> {
+        let quote = self.peek().ok_or_else(|| self.error("expected a string"))?;
+        let mut chars = self.source[self.pos + 1..].char_indices();
+        let mut value = String::new();
+
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, escaped @ ('\\' | '"' | '\''))) => value.push(escaped),
+                    _ => {
+                        self.pos += 1 + offset;
+                        return Err(self.error("invalid escape"));
+                    }
+                },
+                c if c == quote => {
+                    self.pos += 1 + offset + 1;
+                    return Ok(value);
+                }
+                c => value.push(c),
+            }
+        }
+
+        Err(self.error("unterminated string"))
+    }
+
+    fn word(&mut self) -> Result<&str, String> {
+        self.skip_whitespace();
+        let rest = &self.source[self.pos..];
+        let len = rest.find(|c: char| !is_unquoted(c)).unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(self.error("expected a value"));
+        }
+
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+}
+
+/// Returns the tag an unquoted word stands for.
+fn literal(word: &str) -> Tag {
+    match word {
+        "true" => return Tag::Byte(1),
+        "false" => return Tag::Byte(0),
+        _ => {}
+    }
+
+    let (body, suffix) = match word.char_indices().last() {
+        Some((index, c)) if c.is_ascii_alphabetic() => (&word[..index], Some(c.to_ascii_lowercase())),
+        _ => (word, None),
+    };
+
+    let integer = !body.is_empty() && body.trim_start_matches(['-', '+']).chars().all(|c| c.is_ascii_digit()) && body.len() - body.trim_start_matches(['-', '+']).len() <= 1;
+    let decimal = body.contains(['.', 'e', 'E']) && body.parse::<f64>().is_ok();
+
+    let parsed = match suffix {
+        Some('b') if integer => body.parse().ok().map(Tag::Byte),
+        Some('s') if integer => body.parse().ok().map(Tag::Short),
+        Some('l') if integer => body.parse().ok().map(Tag::Long),
+        Some('f') if integer || decimal => body.parse().ok().map(Tag::Float),
+        Some('d') if integer || decimal => body.parse().ok().map(Tag::Double),
+        None if integer => body.parse().ok().map(Tag::Int),
+        None if decimal => body.parse().ok().map(Tag::Double),
+        _ => None,
+    };
+
+    parsed.unwrap_or_else(|| Tag::String(word.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Tag {
+        source.parse().unwrap_or_else(|e| panic!("{source}: {e}"))
+    }
+
+    fn compound(entries: &[(&str, Tag)]) -> Tag {
+        Tag::Compound(entries.iter().map(|(name, tag)| (name.to_string(), tag.clone())).collect())
+    }
+
+    #[test]
+    fn prints_on_one_line() {
+        let item = compound(&[("id", Tag::String(String::from("stone"))), ("count", Tag::Byte(3))]);
+        let tag = compound(&[
+            ("name", Tag::String(String::from("Steve"))),
+            ("pos", Tag::IntArray(vec![1, 64, -3])),
+            ("health", Tag::Float(20.0)),
+            ("items", Tag::List(vec![item])),
+        ]);
+
+        assert_eq!(tag.to_string(), r#"{name:"Steve",pos:[I;1,64,-3],health:20.0f,items:[{id:"stone",count:3b}]}"#);
+    }
+
+    #[test]
+    fn prints_every_suffix_and_array() {
+        let tag = Tag::List(vec![
+            Tag::List(vec![Tag::Byte(-1)]),
+            Tag::List(vec![Tag::Short(2)]),
+            Tag::List(vec![Tag::Int(3)]),
+            Tag::List(vec![Tag::Long(4)]),
+            Tag::List(vec![Tag::Float(0.5)]),
+            Tag::List(vec![Tag::Double(1e-7)]),
+            Tag::List(vec![Tag::ByteArray(vec![1, -2])]),
+            Tag::List(vec![Tag::LongArray(Vec::new())]),
+        ]);
+
+        assert_eq!(tag.to_string(), "[[-1b],[2s],[3],[4L],[0.5f],[1e-7d],[[B;1b,-2b]],[[L;]]]");
+    }
+
+    #[test]
+    fn quotes_names_and_strings_only_as_needed() {
+        let tag = compound(&[("plain_name-1.+", Tag::Int(0)), ("two words", Tag::Int(0)), ("", Tag::Int(0)), ("x", Tag::String(String::from(r#"say "hi" \o/"#)))]);
+
+        assert_eq!(tag.to_string(), r#"{plain_name-1.+:0,"two words":0,"":0,x:"say \"hi\" \\o/"}"#);
+        assert_eq!(parse(&tag.to_string()), tag);
+    }
+
+    #[test]
+    fn parses_what_it_prints() {
+        let tag = compound(&[
+            ("numbers", Tag::List(vec![Tag::Double(f64::MAX), Tag::Double(-0.0), Tag::Double(0.1)])),
+            ("floats", Tag::List(vec![Tag::Float(f32::MIN_POSITIVE), Tag::Float(3.0)])),
+            ("extremes", compound(&[("b", Tag::Byte(i8::MIN)), ("s", Tag::Short(i16::MAX)), ("i", Tag::Int(i32::MIN)), ("l", Tag::Long(i64::MIN))])),
+            ("arrays", Tag::List(vec![Tag::IntArray(vec![i32::MAX]), Tag::IntArray(Vec::new())])),
+            ("text", Tag::String(String::from("'single' and \u{1f600}"))),
+            ("empty", Tag::List(Vec::new())),
+            ("nested", compound(&[])),
+        ]);
+
+        assert_eq!(parse(&tag.to_string()), tag);
+    }
+
+    #[test]
+    fn parses_numbers_by_suffix() {
+        let cases = [
+            ("1b", Tag::Byte(1)),
+            ("-2B", Tag::Byte(-2)),
+            ("3s", Tag::Short(3)),
+            ("+4", Tag::Int(4)),
+            ("5l", Tag::Long(5)),
+            ("6L", Tag::Long(6)),
+            ("7f", Tag::Float(7.0)),
+            ("0.5F", Tag::Float(0.5)),
+            ("8d", Tag::Double(8.0)),
+            ("1.5", Tag::Double(1.5)),
+            ("1e3", Tag::Double(1000.0)),
+            ("true", Tag::Byte(1)),
+            ("false", Tag::Byte(0)),
+        ];
+
+        for (source, expected) in cases {
+            assert_eq!(parse(source), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn parses_other_words_as_strings() {
+        for source in ["stone", "minecraft.stone", "128b", "2147483648", "1.5x", "--1", "1.2.3"] {
+            assert_eq!(parse(source), Tag::String(source.to_string()), "{source}");
+        }
+    }
+
+    #[test]
+    fn parses_quoted_strings() {
+        assert_eq!(parse(r#""a 'b' \"c\" \\""#), Tag::String(String::from(r#"a 'b' "c" \"#)));
+        assert_eq!(parse(r#"'a "b" \'c\''"#), Tag::String(String::from(r#"a "b" 'c'"#)));
+        assert_eq!(parse(r#"{'two words' : "x", y: z}"#), compound(&[("two words", Tag::String(String::from("x"))), ("y", Tag::String(String::from("z")))]));
+    }
+
+    #[test]
+    fn parses_typed_arrays() {
+        assert_eq!(parse("[B; 1b, -2b]"), Tag::ByteArray(vec![1, -2]));
+        assert_eq!(parse("[I;1,2]"), Tag::IntArray(vec![1, 2]));
+        assert_eq!(parse("[ L ; 3L ]"), Tag::LongArray(vec![3]));
+        assert_eq!(parse("[I;]"), Tag::IntArray(Vec::new()));
+        assert_eq!(parse("[B, I]"), Tag::List(vec![Tag::String(String::from("B")), Tag::String(String::from("I"))]));
+    }
+
+    #[test]
+    fn rejects_invalid_snbt() {
+        let cases = ["[I;1b]", "[B;1]", "[L;1]", "[1,2b]", "{a:1", "{a 1}", "[1,]", "\"open", r#""\n""#, "1 2", "{a:1}}", ""];
+
+        for source in cases {
+            assert!(source.parse::<Tag>().is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn rejects_nesting_past_the_limit() {
+        // Unoptimized builds need more stack than test threads have to parse 512 levels.
+        std::thread::Builder::new().stack_size(8 << 20).spawn(|| {
+            let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+            assert!(nested(MAX_DEPTH + 1).parse::<Tag>().is_ok());
+            assert!(nested(MAX_DEPTH + 2).parse::<Tag>().unwrap_err().contains("deeper"));
+        }).unwrap().join().unwrap();
+    }
+}
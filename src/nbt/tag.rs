@@ -0,0 +1,241 @@
+/// A value of the Named Binary Tag format.
+///
+/// Lists hold values of a single type; writing a list mixing types fails. Arrays of bytes,
+/// ints and longs are their own types, stored more compactly than lists of them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+/// The type of a [`Tag`], numbered as in the binary format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum TagType {
+    /// Ends compounds, and is the type of empty lists.
+    End = 0,
+    Byte = 1,
+    Short = 2,
+    Int = 3,
+    Long = 4,
+    Float = 5,
+    Double = 6,
+    ByteArray = 7,
+    String = 8,
+    List = 9,
+    Compound = 10,
+    IntArray = 11,
+    LongArray = 12,
+}
+
+impl TagType {
+    /// Returns the type numbered `id` in the binary format.
+    pub fn from_id(id: u8) -> Option<Self> {
+        use TagType::*;
+
+        [End, Byte, Short, Int, Long, Float, Double, ByteArray, String, List, Compound, IntArray, LongArray].get(id as usize).copied()
+    }
+
+    /// Returns the name of the type, as in `TAG_Compound`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::End => "TAG_End",
+            Self::Byte => "TAG_Byte",
+            Self::Short => "TAG_Short",
+            Self::Int => "TAG_Int",
+            Self::Long => "TAG_Long",
+            Self::Float => "TAG_Float",
+            Self::Double => "TAG_Double",
+            Self::ByteArray => "TAG_Byte_Array",
+            Self::String => "TAG_String",
+            Self::List => "TAG_List",
+            Self::Compound => "TAG_Compound",
+            Self::IntArray => "TAG_Int_Array",
+            Self::LongArray => "TAG_Long_Array",
+        }
+    }
+}
+
+impl Tag {
+    /// Returns the type of the tag.
+    pub fn tag_type(&self) -> TagType {
+        match self {
+            Self::Byte(_) => TagType::Byte,
+            Self::Short(_) => TagType::Short,
+            Self::Int(_) => TagType::Int,
+            Self::Long(_) => TagType::Long,
+            Self::Float(_) => TagType::Float,
+            Self::Double(_) => TagType::Double,
+            Self::ByteArray(_) => TagType::ByteArray,
+            Self::String(_) => TagType::String,
+            Self::List(_) => TagType::List,
+            Self::Compound(_) => TagType::Compound,
+            Self::IntArray(_) => TagType::IntArray,
+            Self::LongArray(_) => TagType::LongArray,
+        }
+    }
+
+    /// Returns the value of a numeric tag as an `i64`, truncating floating point ones, or
+    /// `None` for other tags.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::Byte(value) => Some(value as i64),
+            Self::Short(value) => Some(value as i64),
+            Self::Int(value) => Some(value as i64),
+            Self::Long(value) => Some(value),
+            Self::Float(value) => Some(value as i64),
+            Self::Double(value) => Some(value as i64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a numeric tag as an `f64`, or `None` for other tags.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::Float(value) => Some(value as f64),
+            Self::Double(value) => Some(value),
+            _ => self.as_i64().map(|value| value as f64),
+        }
+    }
+
+    /// Returns the text of a string tag.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of a list tag.
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Self::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a compound tag.
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Self::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of a long array tag.
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        match self {
+            Self::LongArray(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// The named entries of a compound tag, kept in the order they were inserted so that files
+/// are written back as they were read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Compound {
+    entries: Vec<(String, Tag)>,
+}
+
+impl Compound {
+    /// Creates an empty compound.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the entry named `name`.
+    pub fn get(&self, name: &str) -> Option<&Tag> {
+        self.entries.iter().find(|(key, _)| key == name).map(|(_, tag)| tag)
+    }
+
+    /// Returns the entry named `name` mutably.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Tag> {
+        self.entries.iter_mut().find(|(key, _)| key == name).map(|(_, tag)| tag)
+    }
+
+    /// Sets the entry named `name` to `tag`, returning the previous one. New entries go last.
+    pub fn insert(&mut self, name: impl Into<String>, tag: Tag) -> Option<Tag> {
+        let name = name.into();
+
+        match self.get_mut(&name) {
+            Some(existing) => Some(std::mem::replace(existing, tag)),
+            None => {
+                self.entries.push((name, tag));
+                None
+            }
+        }
+    }
+
+    /// Removes the entry named `name`, returning it.
+    pub fn remove(&mut self, name: &str) -> Option<Tag> {
+        let index = self.entries.iter().position(|(key, _)| key == name)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the compound has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the entries as `(name, tag)`, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Tag)> {
+        self.entries.iter().map(|(key, tag)| (key.as_str(), tag))
+    }
+
+    /// Returns the number in the entry named `name`, if it is numeric.
+    pub fn get_i64(&self, name: &str) -> Option<i64> {
+        self.get(name)?.as_i64()
+    }
+
+    /// Returns the text of the entry named `name`, if it is a string.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        self.get(name)?.as_str()
+    }
+
+    /// Returns the elements of the entry named `name`, if it is a list.
+    pub fn get_list(&self, name: &str) -> Option<&[Tag]> {
+        self.get(name)?.as_list()
+    }
+
+    /// Returns the entry named `name`, if it is a compound.
+    pub fn get_compound(&self, name: &str) -> Option<&Compound> {
+        self.get(name)?.as_compound()
+    }
+}
+
+impl IntoIterator for Compound {
+    type Item = (String, Tag);
+    type IntoIter = std::vec::IntoIter<(String, Tag)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl FromIterator<(String, Tag)> for Compound {
+    fn from_iter<I: IntoIterator<Item = (String, Tag)>>(iter: I) -> Self {
+        let mut compound = Self::new();
+
+        for (name, tag) in iter {
+            compound.insert(name, tag);
+        }
+
+        compound
+    }
+}
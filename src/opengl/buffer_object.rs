@@ -1,3 +1,5 @@
+use super::ContextBound;
+
 use std::ffi::c_void;
 
 /// A struct representing an OpenGL Buffer Object.
@@ -11,6 +13,8 @@ pub struct BufferObject {
 
     /// The unique identifier of the buffer object.
     id: u32,
+
+    _context: ContextBound,
 }
 
 /// An enum representing the various target types for OpenGL buffer objects.
@@ -82,7 +86,7 @@ impl BufferObject {
         unsafe { gl::GenBuffers(1, &mut id) };
 
         // Return a new `BufferObject` with the generated ID, target, and usage.
-        BufferObject { id, target, usage }
+        BufferObject { id, target, usage, _context: ContextBound::default() }
     }
 
     /// Uploads data to the OpenGL buffer.
@@ -103,6 +107,32 @@ impl BufferObject {
         }
     }
 
+    /// Overwrites part of the buffer's data store, without reallocating it.
+    ///
+    /// # Parameters
+    /// - `offset`: The offset in bytes into the buffer where the data will be written.
+    /// - `data`: The data to write; it must fit in the buffer from `offset` onwards.
+    pub fn sub_data<T>(&self, offset: usize, data: &[T]) {
+        let ptr = data.as_ptr() as *const c_void; // Convert the data slice into a raw pointer (c_void).
+        let size = size_of_val(data) as isize; // Calculate the size of the data in bytes.
+
+        unsafe {
+            gl::BindBuffer(self.target as u32, self.id); // Bind the buffer to the OpenGL context.
+            gl::BufferSubData(self.target as u32, offset as isize, size, ptr); // Update the data.
+        }
+    }
+
+    /// Binds the buffer to an indexed binding point of its target.
+    ///
+    /// Only meaningful for indexed targets such as `UniformBuffer` or `TransformFeedbackBuffer`,
+    /// where shaders refer to buffers by binding point rather than by ID.
+    ///
+    /// # Parameters
+    /// - `index`: The binding point to attach the buffer to.
+    pub fn bind_base(&self, index: u32) {
+        unsafe { gl::BindBufferBase(self.target as u32, index, self.id) };
+    }
+
     /// Binds the buffer to its target in OpenGL.
     ///
     /// This method ensures that all future OpenGL operations on this buffer will refer to the correct buffer.
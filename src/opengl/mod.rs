@@ -13,3 +13,12 @@ pub use shader::{ShaderType, Shader};
 pub use vertex_arrays::VertexArray;
 pub use program::Program;
 pub use types::GLType;
+
+use std::marker::PhantomData;
+
+/// Marker keeping OpenGL objects on the thread that created them.
+///
+/// OpenGL names only mean something to the context current on that thread, so objects
+/// holding one are neither `Send` nor `Sync`. Work done on other threads hands plain data
+/// back to the context thread instead.
+pub(crate) type ContextBound = PhantomData<*const ()>;
@@ -1,4 +1,4 @@
-use super::Shader;
+use super::{ContextBound, Shader};
 
 use std::ptr::{null_mut, null};
 use std::ffi::{CString, CStr};
@@ -10,6 +10,7 @@ use gl::types::*;
 /// within OpenGL operations, such as shader linking, usage, and resource management.
 pub struct Program {
     id: u32,
+    _context: ContextBound,
 }
 
 impl Program {
@@ -19,7 +20,7 @@ impl Program {
     /// A new `Program` instance with an OpenGL-generated ID.
     pub fn new() -> Self {
         let id = unsafe { gl::CreateProgram() };
-        Program { id }
+        Program { id, _context: ContextBound::default() }
     }
 
     /// Attaches a compiled shader to this program.
@@ -70,6 +71,34 @@ impl Program {
         unsafe { gl::UseProgram(self.id) };
     }
 
+    /// Connects the uniform block called `name` to the uniform buffer binding point `binding`.
+    ///
+    /// # Returns
+    /// * `true` if the program declares the block.
+    /// * `false` if it does not, or the block was optimized out.
+    pub fn bind_uniform_block(&self, name: &str, binding: u32) -> bool {
+        let name = CString::new(name).unwrap();
+
+        unsafe {
+            let index = gl::GetUniformBlockIndex(self.id, name.as_ptr());
+
+            if index == gl::INVALID_INDEX {
+                return false;
+            }
+
+            gl::UniformBlockBinding(self.id, index, binding);
+        }
+
+        true
+    }
+
+    /// Returns the location of the uniform called `name`, or `-1` if the program does not
+    /// declare it or it was optimized out. Setting a uniform at `-1` is silently ignored.
+    pub fn uniform_location(&self, name: &str) -> i32 {
+        let name = CString::new(name).unwrap();
+        unsafe { gl::GetUniformLocation(self.id, name.as_ptr()) }
+    }
+
     /// Returns the OpenGL program ID.
     ///
     /// This can be used for setting uniforms or other OpenGL calls.
@@ -1,3 +1,5 @@
+use super::ContextBound;
+
 use std::ptr::{null, null_mut};
 use std::ffi::CString;
 use gl::types::*;
@@ -7,6 +9,7 @@ use gl::types::*;
 /// Use `Shader::from_source` to compile a shader from GLSL source code.
 pub struct Shader {
     id: u32,
+    _context: ContextBound,
 }
 
 /// Enum representing the different kinds of shaders supported by OpenGL.
@@ -65,7 +68,7 @@ impl Shader {
             }
         }
 
-        Ok(Self { id: shader })
+        Ok(Self { id: shader, _context: ContextBound::default() })
     }
 
     /// Returns the OpenGL shader ID.
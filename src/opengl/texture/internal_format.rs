@@ -1,4 +1,8 @@
 #[repr(u32)]
 pub enum InternalFormat {
     RGB = gl::RGB,
+    RGBA = gl::RGBA,
+    RGB8 = gl::RGB8,
+    RGBA8 = gl::RGBA8,
+    SRGB8Alpha8 = gl::SRGB8_ALPHA8,
 }
@@ -8,9 +8,12 @@ pub use internal_format::InternalFormat;
 pub use parameter_name::ParameterName;
 pub use target::TextureTarget;
 
+use super::ContextBound;
+
 pub struct Texture {
     target: TextureTarget,
     id: u32,
+    _context: ContextBound,
 }
 
 impl Texture {
@@ -18,15 +21,72 @@ impl Texture {
         let mut id = 0;
         unsafe { gl::GenTextures(1, &mut id) };
 
-        Self { id, target }
+        Self { id, target, _context: ContextBound::default() }
     }
 
     pub fn parameter(&self, pname: ParameterName, pvalue: ParameterValue) {
         self.bind();
-        unsafe { gl::TextureParameteri(self.target as u32, pname as u32, pvalue as i32) };
+        unsafe { gl::TexParameteri(self.target as u32, pname as u32, pvalue as i32) };
+    }
+
+    /// Sets an integer parameter, such as `MaxLevel`, that has no [`ParameterValue`].
+    pub fn parameter_int(&self, pname: ParameterName, value: i32) {
+        self.bind();
+        unsafe { gl::TexParameteri(self.target as u32, pname as u32, value) };
+    }
+
+    /// Allocates storage for a three dimensional texture or a two dimensional array, where
+    /// `depth` is the number of layers.
+    pub fn storage(&self, width: u32, height: u32, depth: u32, format: InternalFormat) {
+        self.bind();
+
+        unsafe {
+            gl::TexImage3D(
+                self.target as u32, 0, format as i32,
+                width as i32, height as i32, depth as i32,
+                0, gl::RGBA, gl::UNSIGNED_BYTE, std::ptr::null(),
+            )
+        };
+    }
+
+    /// Writes RGBA8 pixels into layer `z` of a texture allocated with [`Texture::storage`].
+    pub fn sub_image_layer(&self, z: u32, width: u32, height: u32, pixels: &[u8]) {
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "pixel data does not match the size");
+        self.bind();
+
+        unsafe {
+            gl::TexSubImage3D(
+                self.target as u32, 0, 0, 0, z as i32,
+                width as i32, height as i32, 1,
+                gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_ptr().cast(),
+            )
+        };
+    }
+
+    /// Generates every mipmap level from the base level.
+    pub fn generate_mipmap(&self) {
+        self.bind();
+        unsafe { gl::GenerateMipmap(self.target as u32) };
     }
 
     pub fn bind(&self) {
         unsafe { gl::BindTexture(self.target as u32, self.id) };
     }
+
+    /// Binds the texture to texture unit `unit`, as sampled by shaders through a sampler set
+    /// to that unit.
+    pub fn bind_unit(&self, unit: u32) {
+        unsafe { gl::ActiveTexture(gl::TEXTURE0 + unit) };
+        self.bind();
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteTextures(1, &self.id) };
+    }
 }
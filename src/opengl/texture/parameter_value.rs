@@ -1,6 +1,11 @@
 #[repr(u32)]
 pub enum ParameterValue {
     LinearMipmapLinear = gl::LINEAR_MIPMAP_LINEAR,
+    LinearMipmapNearest = gl::LINEAR_MIPMAP_NEAREST,
+    NearestMipmapLinear = gl::NEAREST_MIPMAP_LINEAR,
+    NearestMipmapNearest = gl::NEAREST_MIPMAP_NEAREST,
     Linear = gl::LINEAR,
+    Nearest = gl::NEAREST,
     Repeat = gl::REPEAT,
+    ClampToEdge = gl::CLAMP_TO_EDGE,
 }
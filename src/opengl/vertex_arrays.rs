@@ -1,3 +1,5 @@
+use super::ContextBound;
+
 use gl::types::*;
 
 /// Represents an OpenGL Vertex Array Object (VAO).
@@ -7,6 +9,8 @@ use gl::types::*;
 pub struct VertexArray {
     /// The OpenGL-generated ID for the VAO.
     id: u32,
+
+    _context: ContextBound,
 }
 
 impl VertexArray {
@@ -19,7 +23,7 @@ impl VertexArray {
         let mut id: GLuint = 0;
         unsafe { gl::GenVertexArrays(1, &mut id) };
 
-        VertexArray { id }
+        VertexArray { id, _context: ContextBound::default() }
     }
 
     /// Binds this VAO, making it the current active vertex array.
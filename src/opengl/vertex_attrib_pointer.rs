@@ -31,7 +31,8 @@ impl VertexAttribPointer {
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
+    /// # use rustedcraft::opengl::VertexAttribPointer;
     /// let position_attrib = VertexAttribPointer::new::<f32>(0, 3, false, 3, 0);
     /// ```
     pub fn new<T>(index: u32, size: usize, normalized: bool, stride: usize, pointer: usize) -> Self
@@ -51,6 +52,38 @@ impl VertexAttribPointer {
         Self { index }
     }
 
+    /// Creates a new `VertexAttribPointer` for an integer attribute, read by the shader as
+    /// `int`, `uint` or one of their vector types without any conversion to float.
+    ///
+    /// # Parameters
+    ///
+    /// * `index` - The index of the attribute in the shader (e.g., `layout(location = 0)`).
+    /// * `size` - The number of components in the attribute (e.g., 2 for a uvec2).
+    /// * `stride` - The spacing (in elements of type `T`) between consecutive attributes.
+    /// * `pointer` - The offset (in elements of type `T`) from the beginning of the buffer to the attribute data.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use rustedcraft::opengl::VertexAttribPointer;
+    /// let packed_attrib = VertexAttribPointer::new_integer::<u32>(0, 2, 2, 0);
+    /// ```
+    pub fn new_integer<T>(index: u32, size: usize, stride: usize, pointer: usize) -> Self
+    where
+        T: GLType,
+    {
+        let pointer = (std::mem::size_of::<T>() * pointer) as *const c_void;
+        let stride = (std::mem::size_of::<T>() * stride) as i32;
+        let r#type = T::gl_type();
+
+        unsafe {
+            gl::VertexAttribIPointer(index, size as i32, r#type, stride, pointer);
+            gl::EnableVertexAttribArray(index);
+        }
+
+        Self { index }
+    }
+
     /// Enables the vertex attribute array at this index.
     pub fn enable(&self) {
         unsafe { gl::EnableVertexAttribArray(self.index) };
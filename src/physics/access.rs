@@ -0,0 +1,17 @@
+use crate::jobs::ChunkStreamer;
+use crate::world::{BlockPos, BlockState};
+
+/// Read access to the blocks bodies move through.
+pub trait BlockAccess {
+    /// Returns the block at `pos`, or `None` if it is not loaded.
+    ///
+    /// Only asked for positions within the height of the world. Blocks that are not loaded
+    /// are solid, so bodies wait at the edge of the loaded world instead of falling through.
+    fn block(&self, pos: BlockPos) -> Option<BlockState>;
+}
+
+impl BlockAccess for ChunkStreamer {
+    fn block(&self, pos: BlockPos) -> Option<BlockState> {
+        ChunkStreamer::block(self, pos)
+    }
+}
@@ -0,0 +1,421 @@
+use super::{BlockAccess, PhysicsTable};
+use crate::block::Fluid;
+use crate::math::{Aabb, Vec3};
+use crate::world::{BlockPos, CHUNK_HEIGHT};
+
+/// How far a box may already overlap an obstacle along the axis it moves on and still be
+/// stopped by it, absorbing rounding errors of earlier moves.
+const EPSILON: f32 = 1.0e-4;
+
+/// How far a sneaking body is pulled back at a time when a move would take it over an edge.
+const EDGE_STEP: f32 = 0.05;
+
+/// Something moving through the world as an axis-aligned box, like the player.
+///
+/// Motion is resolved one axis at a time against the collision boxes of the blocks around,
+/// vertically first, so sliding along walls and landing on the ground come for free. Blocks
+/// the body already overlaps are ignored, letting it walk out of them.
+///
+/// Everything is plain `f32` arithmetic with no platform-dependent functions, so the same
+/// moves from the same state give the same result on every machine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Body {
+    /// Position of the center of the bottom face of the box.
+    pub position: Vec3,
+
+    /// Velocity in blocks per tick.
+    pub velocity: Vec3,
+
+    width: f32,
+    height: f32,
+    step_height: f32,
+    on_ground: bool,
+    horizontal_collision: bool,
+    vertical_collision: bool,
+    fluid: Option<Fluid>,
+    on_ladder: bool,
+}
+
+impl Body {
+    /// Creates a body at rest standing at `position`, `width` blocks wide along X and Z and
+    /// `height` blocks tall, climbing up to `step_height` blocks without jumping.
+    pub fn new(position: Vec3, width: f32, height: f32, step_height: f32) -> Self {
+        Self {
+            position,
+            velocity: Vec3::ZERO,
+            width,
+            height,
+            step_height,
+            on_ground: false,
+            horizontal_collision: false,
+            vertical_collision: false,
+            fluid: None,
+            on_ladder: false,
+        }
+    }
+
+    /// Returns the box of the body in world coordinates.
+    pub fn aabb(&self) -> Aabb {
+        let half = self.width * 0.5;
+        let min = Vec3::new(self.position.x - half, self.position.y, self.position.z - half);
+        Aabb::new(min, min + Vec3::new(self.width, self.height, self.width))
+    }
+
+    /// Returns the size of the body as its width and height.
+    pub fn size(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// Returns how high a step the body climbs without jumping.
+    pub fn step_height(&self) -> f32 {
+        self.step_height
+    }
+
+    /// Returns whether the last move ended standing on something.
+    pub fn on_ground(&self) -> bool {
+        self.on_ground
+    }
+
+    /// Returns whether the last move was stopped along X or Z.
+    pub fn horizontal_collision(&self) -> bool {
+        self.horizontal_collision
+    }
+
+    /// Returns whether the last move was stopped along Y.
+    pub fn vertical_collision(&self) -> bool {
+        self.vertical_collision
+    }
+
+    /// Returns the fluid the body was in when its surroundings were last updated, lava
+    /// winning over water.
+    pub fn fluid(&self) -> Option<Fluid> {
+        self.fluid
+    }
+
+    /// Returns whether the feet of the body were in a climbable block when its surroundings
+    /// were last updated.
+    pub fn on_ladder(&self) -> bool {
+        self.on_ladder
+    }
+
+    /// Moves the body by `motion` as far as the world lets it and returns how far it went.
+    ///
+    /// Steps up blocks no higher than the step height when walking into them from the
+    /// ground. While `sneaking` on the ground, horizontal motion is cut short rather than
+    /// dropping more than the step height off an edge. Velocity is zeroed along the axes the
+    /// move was stopped on.
+    pub fn move_by(&mut self, table: &PhysicsTable, world: &impl BlockAccess, motion: Vec3, sneaking: bool) -> Vec3 {
+        let start = self.aabb();
+
+        let motion = match sneaking && self.on_ground && motion.y <= 0.0 {
+            true => self.back_off_edge(table, world, start, motion),
+            false => motion,
+        };
+
+        let area = start.expand_towards(motion).expand_towards(Vec3::new(0.0, self.step_height, 0.0));
+        let obstacles = obstacles(table, world, area);
+        let mut moved = collide(start, &obstacles, motion);
+
+        let blocked = moved.x != motion.x || moved.z != motion.z;
+        let landed = motion.y < 0.0 && moved.y != motion.y;
+
+        if self.step_height > 0.0 && blocked && (self.on_ground || landed) {
+            // Go up as high as a step, across, then back down onto whatever is there.
+            let up = clip(start, &obstacles, 1, self.step_height);
+            let raised = start.offset(Vec3::new(0.0, up, 0.0));
+            let across = collide(raised, &obstacles, Vec3::new(motion.x, 0.0, motion.z));
+            let down = clip(raised.offset(across), &obstacles, 1, motion.y.min(0.0) - up);
+            let stepped = Vec3::new(across.x, up + down, across.z);
+
+            if stepped.x * stepped.x + stepped.z * stepped.z > moved.x * moved.x + moved.z * moved.z {
+                moved = stepped;
+            }
+        }
+
+        self.horizontal_collision = moved.x != motion.x || moved.z != motion.z;
+        self.vertical_collision = moved.y != motion.y;
+        self.on_ground = self.vertical_collision && motion.y < 0.0;
+
+        if moved.x != motion.x {
+            self.velocity.x = 0.0;
+        }
+
+        if moved.y != motion.y {
+            self.velocity.y = 0.0;
+        }
+
+        if moved.z != motion.z {
+            self.velocity.z = 0.0;
+        }
+
+        self.position += moved;
+        moved
+    }
+
+    /// Looks for fluids and ladders around the body. Call before working out the next move.
+    pub fn update_surroundings(&mut self, table: &PhysicsTable, world: &impl BlockAccess) {
+        self.fluid = fluid_in(table, world, self.aabb().inflate(-0.001));
+
+        let feet = self.position.floor();
+        let feet = BlockPos::new(feet.x as i32, feet.y as i32, feet.z as i32);
+
+        self.on_ladder = in_world(feet.y) && world.block(feet).is_some_and(|state| table.is_climbable(state));
+    }
+
+    /// Shortens horizontal `motion` until the box moved by it would still stand on
+    /// something no lower than the step height.
+    fn back_off_edge(&self, table: &PhysicsTable, world: &impl BlockAccess, start: Aabb, motion: Vec3) -> Vec3 {
+        let supported = |dx: f32, dz: f32| {
+            let area = start.offset(Vec3::new(dx, -self.step_height, dz));
+            obstacles(table, world, area).iter().any(|obstacle| obstacle.intersects(area))
+        };
+
+        let toward_zero = |value: f32| match value.abs() < EDGE_STEP {
+            true => 0.0,
+            false => value - EDGE_STEP.copysign(value),
+        };
+
+        let (mut x, mut z) = (motion.x, motion.z);
+
+        while x != 0.0 && !supported(x, 0.0) {
+            x = toward_zero(x);
+        }
+
+        while z != 0.0 && !supported(0.0, z) {
+            z = toward_zero(z);
+        }
+
+        while x != 0.0 && z != 0.0 && !supported(x, z) {
+            x = toward_zero(x);
+            z = toward_zero(z);
+        }
+
+        Vec3::new(x, motion.y, z)
+    }
+}
+
+/// Returns whether `area` is clear of every collision box and fluid.
+pub(super) fn is_free(table: &PhysicsTable, world: &impl BlockAccess, area: Aabb) -> bool {
+    obstacles(table, world, area).iter().all(|obstacle| !obstacle.intersects(area)) && fluid_in(table, world, area).is_none()
+}
+
+/// Returns whether blocks at height `y` are part of the world. Nothing above or below it
+/// collides.
+fn in_world(y: i32) -> bool {
+    (0..CHUNK_HEIGHT as i32).contains(&y)
+}
+
+/// Returns the range of block coordinates `min..=max` touches.
+fn block_range(min: f32, max: f32) -> std::ops::RangeInclusive<i32> {
+    min.floor() as i32..=max.floor() as i32
+}
+
+/// Collects the collision boxes of the blocks touching `area`, in world coordinates.
+fn obstacles(table: &PhysicsTable, world: &impl BlockAccess, area: Aabb) -> Vec<Aabb> {
+    let mut boxes = Vec::new();
+
+    for y in block_range(area.min.y, area.max.y).filter(|&y| in_world(y)) {
+        for z in block_range(area.min.z, area.max.z) {
+            for x in block_range(area.min.x, area.max.x) {
+                let origin = Vec3::new(x as f32, y as f32, z as f32);
+
+                match world.block(BlockPos::new(x, y, z)) {
+                    Some(state) => boxes.extend(table.boxes(state).iter().map(|b| b.offset(origin))),
+                    None => boxes.push(Aabb::UNIT.offset(origin)),
+                }
+            }
+        }
+    }
+
+    boxes
+}
+
+/// Returns the fluid `area` reaches into, lava winning over water.
+fn fluid_in(table: &PhysicsTable, world: &impl BlockAccess, area: Aabb) -> Option<Fluid> {
+    let mut found = None;
+
+    for y in block_range(area.min.y, area.max.y).filter(|&y| in_world(y)) {
+        for z in block_range(area.min.z, area.max.z) {
+            for x in block_range(area.min.x, area.max.x) {
+                let Some((fluid, height)) = world.block(BlockPos::new(x, y, z)).and_then(|state| table.fluid(state)) else {
+                    continue;
+                };
+
+                if y as f32 + height >= area.min.y {
+                    if fluid == Fluid::Lava {
+                        return Some(fluid);
+                    }
+
+                    found = Some(fluid);
+                }
+            }
+        }
+    }
+
+    found
+}
+
+/// Moves `body` by `motion` against `obstacles`, vertically first and then along the
+/// horizontal axis with the larger motion, returning how far it gets.
+fn collide(body: Aabb, obstacles: &[Aabb], motion: Vec3) -> Vec3 {
+    let y = clip(body, obstacles, 1, motion.y);
+    let body = body.offset(Vec3::new(0.0, y, 0.0));
+
+    if motion.x.abs() < motion.z.abs() {
+        let z = clip(body, obstacles, 2, motion.z);
+        let x = clip(body.offset(Vec3::new(0.0, 0.0, z)), obstacles, 0, motion.x);
+        Vec3::new(x, y, z)
+    }
+    else {
+        let x = clip(body, obstacles, 0, motion.x);
+        let z = clip(body.offset(Vec3::new(x, 0.0, 0.0)), obstacles, 2, motion.z);
+        Vec3::new(x, y, z)
+    }
+}
+
+/// Shortens `motion` along `axis` (0 for X, 1 for Y, 2 for Z) so `body` stops against the
+/// first obstacle in the way.
+fn clip(body: Aabb, obstacles: &[Aabb], axis: usize, mut motion: f32) -> f32 {
+    if motion == 0.0 {
+        return 0.0;
+    }
+
+    let (min, max) = (body.min.to_array(), body.max.to_array());
+
+    for obstacle in obstacles {
+        let (other_min, other_max) = (obstacle.min.to_array(), obstacle.max.to_array());
+        let beside = (0..3).filter(|&a| a != axis).any(|a| other_min[a] >= max[a] || other_max[a] <= min[a]);
+
+        if beside {
+            continue;
+        }
+
+        if motion > 0.0 && other_min[axis] >= max[axis] - EPSILON {
+            motion = motion.min(other_min[axis] - max[axis]);
+        }
+        else if motion < 0.0 && other_max[axis] <= min[axis] + EPSILON {
+            motion = motion.max(other_max[axis] - min[axis]);
+        }
+    }
+
+    motion
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use crate::block::BlockRegistryBuilder;
+    use crate::world::BlockState;
+
+    use std::collections::HashMap;
+
+    const BLOCKS: &str = "
+        [stone]
+
+        [slab]
+        collision = [[0, 0, 0, 16, 8, 16]]
+        opacity = 0
+
+        # Hangs on the south side of its block.
+        [ladder]
+        render = \"cutout\"
+        collision = [[0, 0, 13, 16, 16, 16]]
+        climbable = true
+    ";
+
+    /// A few blocks set by hand in a world of air.
+    pub(in crate::physics) struct TestWorld {
+        pub table: PhysicsTable,
+        blocks: HashMap<BlockPos, BlockState>,
+        states: HashMap<&'static str, BlockState>,
+    }
+
+    impl TestWorld {
+        /// Creates a world with a floor of stone at y 0, from x and z -8 up to but not
+        /// including `floor_end`.
+        pub fn new(floor_end: i32) -> Self {
+            let mut builder = BlockRegistryBuilder::new();
+            builder.load_str("test", BLOCKS).unwrap();
+            let registry = builder.build().unwrap();
+            let states = ["stone", "slab", "ladder"].into_iter().map(|name| (name, registry.default_state(&format!("test:{name}")).unwrap())).collect();
+
+            let mut world = Self { table: PhysicsTable::new(&registry), blocks: HashMap::new(), states };
+            world.fill((-8, 0, -8), (floor_end - 1, 0, floor_end - 1), "stone");
+            world
+        }
+
+        /// Sets every block from `min` to `max`, both inclusive, to the block called `name`.
+        pub fn fill(&mut self, min: (i32, i32, i32), max: (i32, i32, i32), name: &str) {
+            for y in min.1..=max.1 {
+                for z in min.2..=max.2 {
+                    for x in min.0..=max.0 {
+                        self.blocks.insert(BlockPos::new(x, y, z), self.states[name]);
+                    }
+                }
+            }
+        }
+    }
+
+    impl BlockAccess for TestWorld {
+        fn block(&self, pos: BlockPos) -> Option<BlockState> {
+            Some(self.blocks.get(&pos).copied().unwrap_or(BlockState::AIR))
+        }
+    }
+
+    /// Asserts that `position` is `expected` give or take rounding errors.
+    pub fn assert_near(position: Vec3, expected: Vec3) {
+        assert!((position - expected).length() < 1.0e-4, "{position:?} instead of {expected:?}");
+    }
+
+    /// Returns a body standing in the middle of the block at x and z 0 of the floor.
+    fn landed(world: &TestWorld) -> Body {
+        let mut body = Body::new(Vec3::new(0.5, 1.0, 0.5), 0.6, 1.8, 0.6);
+        body.move_by(&world.table, world, Vec3::new(0.0, -0.1, 0.0), false);
+        assert!(body.on_ground());
+        body
+    }
+
+    #[test]
+    fn steps_up_onto_slabs() {
+        let mut world = TestWorld::new(8);
+        world.fill((-8, 1, -8), (7, 1, -2), "slab");
+        let mut body = landed(&world);
+
+        for _ in 0..10 {
+            body.move_by(&world.table, &world, Vec3::new(0.0, -0.08, -0.2), false);
+        }
+
+        assert_near(body.position, Vec3::new(0.5, 1.5, -1.5));
+        assert!(body.on_ground());
+    }
+
+    #[test]
+    fn stays_below_steps_too_high() {
+        let mut world = TestWorld::new(8);
+        world.fill((-8, 1, -8), (7, 1, -2), "stone");
+        let mut body = landed(&world);
+
+        for _ in 0..10 {
+            body.move_by(&world.table, &world, Vec3::new(0.0, -0.08, -0.2), false);
+        }
+
+        assert_near(body.position, Vec3::new(0.5, 1.0, -0.7));
+        assert!(body.horizontal_collision());
+    }
+
+    #[test]
+    fn sneaking_stops_at_edges() {
+        let world = TestWorld::new(1);
+        let mut sneaking = landed(&world);
+        let mut walking = landed(&world);
+
+        for _ in 0..10 {
+            sneaking.move_by(&world.table, &world, Vec3::new(0.2, -0.08, 0.2), true);
+            walking.move_by(&world.table, &world, Vec3::new(0.2, -0.08, 0.2), false);
+        }
+
+        assert_eq!(sneaking.position.y, 1.0);
+        assert!((1.0..=1.3).contains(&sneaking.position.x) && (1.0..=1.3).contains(&sneaking.position.z));
+        assert!(walking.position.y < 1.0);
+    }
+}
@@ -0,0 +1,11 @@
+mod table;
+mod access;
+mod body;
+mod movement;
+mod raycast;
+
+pub use table::PhysicsTable;
+pub use access::BlockAccess;
+pub use body::Body;
+pub use movement::{MoveInput, PLAYER_EYE_HEIGHT, PLAYER_HEIGHT, PLAYER_REACH, PLAYER_SNEAK_EYE_HEIGHT, PLAYER_STEP_HEIGHT, PLAYER_WIDTH};
+pub use raycast::{raycast, RayHit};
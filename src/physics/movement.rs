@@ -0,0 +1,279 @@
+use super::body::is_free;
+use super::{BlockAccess, Body, PhysicsTable};
+use crate::block::Fluid;
+use crate::math::{sin_cos, Vec3};
+
+/// Width of the player's box along X and Z, in blocks.
+pub const PLAYER_WIDTH: f32 = 0.6;
+
+/// Height of the player's box, in blocks.
+pub const PLAYER_HEIGHT: f32 = 1.8;
+
+/// Height of the player's eyes above their feet.
+pub const PLAYER_EYE_HEIGHT: f32 = 1.62;
+
+/// Height of the player's eyes above their feet while sneaking.
+pub const PLAYER_SNEAK_EYE_HEIGHT: f32 = 1.54;
+
+/// Farthest the player reaches to break and place blocks, from their eyes.
+pub const PLAYER_REACH: f32 = 4.5;
+
+/// Highest step the player walks up without jumping, enough for slabs.
+pub const PLAYER_STEP_HEIGHT: f32 = 0.6;
+
+// Speeds are in blocks per tick, accelerations in blocks per tick squared, and drags are
+// the share of velocity kept from one tick to the next.
+
+const GRAVITY: f32 = 0.08;
+const AIR_DRAG: f32 = 0.98;
+const GROUND_FRICTION: f32 = 0.546;
+const AIR_FRICTION: f32 = 0.91;
+const GROUND_ACCELERATION: f32 = 0.1;
+const AIR_ACCELERATION: f32 = 0.02;
+const SPRINT_FACTOR: f32 = 1.3;
+const SNEAK_FACTOR: f32 = 0.3;
+const JUMP_VELOCITY: f32 = 0.42;
+const SPRINT_JUMP_BOOST: f32 = 0.2;
+
+const FLUID_ACCELERATION: f32 = 0.02;
+const FLUID_GRAVITY: f32 = 0.02;
+const WATER_DRAG: f32 = 0.8;
+const LAVA_DRAG: f32 = 0.5;
+const SWIM_SPEED: f32 = 0.04;
+
+/// Upward speed to climb out of a fluid onto the bank in front.
+const FLUID_EXIT_SPEED: f32 = 0.3;
+
+/// Highest speed along a ladder, except when climbing.
+const LADDER_SPEED: f32 = 0.15;
+const CLIMB_SPEED: f32 = 0.2;
+
+/// Speeds below this are rounded to zero so bodies come to a full stop.
+const MIN_SPEED: f32 = 0.003;
+
+/// What a player asks their body to do during one tick.
+///
+/// Recording these along with the starting [`Body`] is enough to replay a player's movement
+/// exactly, which is how a server can check the positions a client reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MoveInput {
+    /// Walking, from -1 to 1, positive forwards.
+    pub forward: f32,
+
+    /// Strafing, from -1 to 1, positive to the right.
+    pub strafe: f32,
+
+    /// Direction faced in degrees, like [`Camera::yaw`](crate::camera::Camera::yaw): 0
+    /// towards negative Z, increasing to the right.
+    pub yaw: f32,
+
+    /// Jumping, swimming up or climbing.
+    pub jump: bool,
+
+    /// Walking slowly without falling off edges, sinking in fluids or holding on to ladders.
+    pub sneak: bool,
+
+    /// Walking and jumping further forwards.
+    pub sprint: bool,
+}
+
+impl Body {
+    /// Creates a body the size of the player standing at `position`.
+    pub fn player(position: Vec3) -> Self {
+        Self::new(position, PLAYER_WIDTH, PLAYER_HEIGHT, PLAYER_STEP_HEIGHT)
+    }
+
+    /// Advances the body by one tick of walking, jumping, falling, swimming or climbing as
+    /// asked by `input`.
+    pub fn tick(&mut self, table: &PhysicsTable, world: &impl BlockAccess, input: &MoveInput) {
+        self.update_surroundings(table, world);
+
+        for speed in [&mut self.velocity.x, &mut self.velocity.y, &mut self.velocity.z] {
+            if speed.abs() < MIN_SPEED {
+                *speed = 0.0;
+            }
+        }
+
+        let (sin, cos) = sin_cos((input.yaw as f64).to_radians());
+        let facing = Vec3::new(sin as f32, 0.0, -cos as f32);
+        let right = Vec3::new(cos as f32, 0.0, sin as f32);
+
+        let (mut forward, mut strafe) = (input.forward.clamp(-1.0, 1.0), input.strafe.clamp(-1.0, 1.0));
+        let sprinting = input.sprint && forward > 0.0 && !input.sneak;
+
+        if input.sneak {
+            forward *= SNEAK_FACTOR;
+            strafe *= SNEAK_FACTOR;
+        }
+
+        // Longer than one when walking diagonally or with a large analog input.
+        let length = (forward * forward + strafe * strafe).sqrt();
+        let wish = match length > 1.0 {
+            true => (facing * forward + right * strafe) / length,
+            false => facing * forward + right * strafe,
+        };
+
+        match self.fluid() {
+            Some(fluid) => {
+                if input.jump {
+                    self.velocity.y += SWIM_SPEED;
+                }
+
+                if input.sneak {
+                    self.velocity.y -= SWIM_SPEED;
+                }
+
+                self.velocity += wish * FLUID_ACCELERATION;
+                let moved = self.move_by(table, world, self.velocity, false);
+
+                let drag = match fluid {
+                    Fluid::Water => WATER_DRAG,
+                    Fluid::Lava => LAVA_DRAG,
+                };
+
+                self.velocity *= drag;
+                self.velocity.y -= FLUID_GRAVITY;
+
+                // Swimming into a bank low enough to get onto.
+                let exit = Vec3::new(self.velocity.x, self.velocity.y + 0.6 - moved.y, self.velocity.z);
+
+                if self.horizontal_collision() && is_free(table, world, self.aabb().offset(exit)) {
+                    self.velocity.y = FLUID_EXIT_SPEED;
+                }
+            }
+            None => {
+                let on_ground = self.on_ground();
+
+                if input.jump && on_ground {
+                    self.velocity.y = JUMP_VELOCITY;
+
+                    if sprinting {
+                        self.velocity += facing * SPRINT_JUMP_BOOST;
+                    }
+                }
+
+                let acceleration = match on_ground {
+                    true => GROUND_ACCELERATION,
+                    false => AIR_ACCELERATION,
+                };
+
+                let acceleration = match sprinting {
+                    true => acceleration * SPRINT_FACTOR,
+                    false => acceleration,
+                };
+
+                self.velocity += wish * acceleration;
+
+                if self.on_ladder() {
+                    self.velocity.x = self.velocity.x.clamp(-LADDER_SPEED, LADDER_SPEED);
+                    self.velocity.z = self.velocity.z.clamp(-LADDER_SPEED, LADDER_SPEED);
+                    self.velocity.y = self.velocity.y.max(-LADDER_SPEED);
+
+                    if input.sneak && self.velocity.y < 0.0 {
+                        self.velocity.y = 0.0;
+                    }
+                }
+
+                self.move_by(table, world, self.velocity, input.sneak);
+
+                if self.on_ladder() && (self.horizontal_collision() || input.jump) {
+                    self.velocity.y = CLIMB_SPEED;
+                }
+
+                let friction = match on_ground {
+                    true => GROUND_FRICTION,
+                    false => AIR_FRICTION,
+                };
+
+                self.velocity.y = (self.velocity.y - GRAVITY) * AIR_DRAG;
+                self.velocity.x *= friction;
+                self.velocity.z *= friction;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::body::tests::{assert_near, TestWorld};
+
+    /// Facing positive Z.
+    const SOUTH: f32 = 180.0;
+
+    /// Replays `inputs`, each for its number of ticks, from standing at `start`.
+    fn replay(world: &TestWorld, start: Vec3, inputs: &[(MoveInput, usize)]) -> Body {
+        let mut body = Body::player(start);
+
+        for &(input, ticks) in inputs {
+            for _ in 0..ticks {
+                body.tick(&world.table, world, &input);
+            }
+        }
+
+        body
+    }
+
+    fn walk(yaw: f32) -> MoveInput {
+        MoveInput { forward: 1.0, yaw, ..MoveInput::default() }
+    }
+
+    #[test]
+    fn jumps_and_lands() {
+        let world = TestWorld::new(8);
+        let jump = MoveInput { jump: true, ..MoveInput::default() };
+        let mut body = replay(&world, Vec3::new(0.5, 1.0, 0.5), &[(MoveInput::default(), 2), (jump, 1)]);
+        let mut highest = body.position.y;
+
+        for _ in 0..20 {
+            body.tick(&world.table, &world, &MoveInput::default());
+            highest = highest.max(body.position.y);
+        }
+
+        assert!((2.2..2.3).contains(&highest), "jumped up to {highest}");
+        assert_near(body.position, Vec3::new(0.5, 1.0, 0.5));
+        assert!(body.on_ground());
+    }
+
+    #[test]
+    fn walks_up_onto_slabs() {
+        let mut world = TestWorld::new(8);
+        world.fill((-8, 1, 2), (7, 1, 7), "slab");
+        let body = replay(&world, Vec3::new(0.5, 1.0, 0.5), &[(walk(SOUTH), 20), (MoveInput::default(), 20)]);
+
+        assert_near(body.position, Vec3::new(0.5, 1.5, 4.595694));
+        assert!(body.on_ground());
+    }
+
+    #[test]
+    fn sneaks_up_to_edges() {
+        let world = TestWorld::new(2);
+        let sneak = MoveInput { sneak: true, ..walk(SOUTH) };
+        let body = replay(&world, Vec3::new(0.5, 1.0, 0.5), &[(sneak, 60)]);
+
+        // Still on the last stone, 0.3 blocks wide on either side.
+        assert_near(body.position, Vec3::new(0.5, 1.0, 2.2933395));
+        assert!(body.on_ground());
+
+        let body = replay(&world, Vec3::new(0.5, 1.0, 0.5), &[(walk(SOUTH), 60)]);
+        assert!(body.position.y < 0.0);
+    }
+
+    #[test]
+    fn climbs_ladders() {
+        let mut world = TestWorld::new(8);
+        world.fill((0, 1, 0), (0, 8, 0), "ladder");
+        world.fill((0, 1, 1), (0, 9, 1), "stone");
+        let climbed = replay(&world, Vec3::new(0.5, 1.0, 0.5), &[(walk(SOUTH), 20)]);
+
+        // Against the ladder, not the wall behind it.
+        assert_near(climbed.position, Vec3::new(0.5, 3.2343993, 0.5125));
+
+        let hold = MoveInput { sneak: true, ..MoveInput::default() };
+        let held = replay(&world, Vec3::new(0.5, 1.0, 0.5), &[(walk(SOUTH), 20), (hold, 20)]);
+
+        // Coasts up a little further, then holds on without sliding down.
+        assert_near(held.position, Vec3::new(0.5, 3.3888474, 0.5125));
+        assert!(held.on_ladder());
+    }
+}
@@ -0,0 +1,253 @@
+use super::{BlockAccess, PhysicsTable};
+use crate::math::{Aabb, Vec3};
+use crate::world::{BlockPos, BlockState, Direction, CHUNK_HEIGHT};
+
+/// A block hit by a ray.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RayHit {
+    /// Position of the block.
+    pub pos: BlockPos,
+
+    /// State of the block.
+    pub state: BlockState,
+
+    /// Face of the selection shape the ray went in through.
+    pub face: Direction,
+
+    /// Point where the ray meets the selection shape, in world coordinates.
+    pub point: Vec3,
+
+    /// Distance from the origin of the ray to [`point`](Self::point).
+    pub distance: f32,
+
+    /// Selection shape of the block, in world coordinates.
+    pub shape: Vec<Aabb>,
+}
+
+impl RayHit {
+    /// Returns the position in front of the face hit, where a block placed against it goes.
+    pub fn adjacent(&self) -> BlockPos {
+        self.pos.step(self.face)
+    }
+
+    /// Returns the unit vector pointing out of the face hit.
+    pub fn normal(&self) -> Vec3 {
+        let (x, y, z) = self.face.offset();
+        Vec3::new(x as f32, y as f32, z as f32)
+    }
+}
+
+/// Casts a ray from `origin` along `direction` and returns the first block whose selection
+/// shape it hits within `max_distance` blocks.
+///
+/// The ray walks the grid one block at a time, always into the next block boundary it
+/// crosses, so only the blocks it goes through are looked at. Boxes are tested exactly, so
+/// a ray passing over a slab or beside a torch goes on to what is behind. Rays stop at the
+/// first block that is not loaded, and boxes the origin is inside of are ignored.
+pub fn raycast(table: &PhysicsTable, world: &impl BlockAccess, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<RayHit> {
+    let direction = direction.normalize_or_zero();
+
+    if direction == Vec3::ZERO {
+        return None;
+    }
+
+    let (o, d) = (origin.to_array(), direction.to_array());
+    let mut cell = o.map(|c| c.floor() as i32);
+    let step = d.map(|c| if c > 0.0 { 1 } else { -1 });
+
+    // Distance along the ray to cross one block on each axis, and to the next boundary.
+    let delta = d.map(|c| if c == 0.0 { f32::INFINITY } else { 1.0 / c.abs() });
+    let mut next: [f32; 3] = std::array::from_fn(|a| {
+        if d[a] > 0.0 {
+            (cell[a] as f32 + 1.0 - o[a]) / d[a]
+        }
+        else if d[a] < 0.0 {
+            (cell[a] as f32 - o[a]) / d[a]
+        }
+        else {
+            f32::INFINITY
+        }
+    });
+
+    loop {
+        let pos = BlockPos::new(cell[0], cell[1], cell[2]);
+
+        if (0..CHUNK_HEIGHT as i32).contains(&pos.y) {
+            let state = world.block(pos)?;
+            let corner = Vec3::new(pos.x as f32, pos.y as f32, pos.z as f32);
+            let shape: Vec<Aabb> = table.selection(state).iter().map(|b| b.offset(corner)).collect();
+
+            let nearest = shape
+                .iter()
+                .filter_map(|b| intersect(*b, o, d))
+                .min_by(|a, b| a.0.total_cmp(&b.0));
+
+            if let Some((distance, face)) = nearest {
+                return (distance <= max_distance).then(|| RayHit {
+                    pos,
+                    state,
+                    face,
+                    point: origin + direction * distance,
+                    distance,
+                    shape,
+                });
+            }
+        }
+
+        let axis = (0..3).min_by(|&a, &b| next[a].total_cmp(&next[b])).unwrap_or(0);
+
+        if next[axis] > max_distance {
+            return None;
+        }
+
+        cell[axis] += step[axis];
+        next[axis] += delta[axis];
+    }
+}
+
+/// Returns the distance along the ray from `origin` along the unit vector `direction` to
+/// where it enters `aabb`, and the face it enters through, or `None` if it misses or starts
+/// inside it.
+fn intersect(aabb: Aabb, origin: [f32; 3], direction: [f32; 3]) -> Option<(f32, Direction)> {
+    let (min, max) = (aabb.min.to_array(), aabb.max.to_array());
+    let (mut near, mut far, mut axis) = (f32::NEG_INFINITY, f32::INFINITY, 0);
+
+    for a in 0..3 {
+        if direction[a] == 0.0 {
+            if origin[a] < min[a] || origin[a] > max[a] {
+                return None;
+            }
+
+            continue;
+        }
+
+        let t1 = (min[a] - origin[a]) / direction[a];
+        let t2 = (max[a] - origin[a]) / direction[a];
+
+        if t1.min(t2) > near {
+            near = t1.min(t2);
+            axis = a;
+        }
+
+        far = far.min(t1.max(t2));
+    }
+
+    if near < 0.0 || near > far {
+        return None;
+    }
+
+    // The face looks back against the ray.
+    let face = match (axis, direction[axis] > 0.0) {
+        (0, true) => Direction::West,
+        (0, false) => Direction::East,
+        (1, true) => Direction::Down,
+        (1, false) => Direction::Up,
+        (_, true) => Direction::North,
+        (_, false) => Direction::South,
+    };
+
+    Some((near, face))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::body::tests::{assert_near, TestWorld};
+
+    /// Leaves out the chunks from x 16 on, as if they were not loaded yet.
+    struct HalfLoaded<'a>(&'a TestWorld);
+
+    impl BlockAccess for HalfLoaded<'_> {
+        fn block(&self, pos: BlockPos) -> Option<BlockState> {
+            (pos.x < 16).then(|| self.0.block(pos)).flatten()
+        }
+    }
+
+    fn center(x: i32, y: i32, z: i32) -> Vec3 {
+        Vec3::new(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5)
+    }
+
+    #[test]
+    fn hits_the_face_facing_the_ray() {
+        let mut world = TestWorld::new(8);
+        world.fill((0, 10, 0), (0, 10, 0), "stone");
+
+        for face in Direction::ALL {
+            let (x, y, z) = face.offset();
+            let outwards = Vec3::new(x as f32, y as f32, z as f32);
+            let hit = raycast(&world.table, &world, center(0, 10, 0) + outwards * 3.0, -outwards, 5.0).expect("hit");
+
+            assert_eq!((hit.pos, hit.face), (BlockPos::new(0, 10, 0), face));
+            assert_near(hit.point, center(0, 10, 0) + outwards * 0.5);
+            assert!((hit.distance - 2.5).abs() < 1.0e-4);
+            assert_eq!(hit.adjacent(), BlockPos::new(x, 10 + y, z));
+            assert_eq!(hit.normal(), outwards);
+        }
+    }
+
+    #[test]
+    fn hits_shapes_smaller_than_their_block() {
+        let mut world = TestWorld::new(8);
+        world.fill((2, 10, 0), (2, 10, 0), "slab");
+        world.fill((4, 10, 0), (4, 10, 0), "stone");
+
+        // Over the slab and on to the stone behind it.
+        let hit = raycast(&world.table, &world, Vec3::new(0.5, 10.75, 0.5), Vec3::X, 8.0).unwrap();
+        assert_eq!((hit.pos, hit.face), (BlockPos::new(4, 10, 0), Direction::West));
+
+        // Down onto its top, half a block into its block.
+        let hit = raycast(&world.table, &world, Vec3::new(2.5, 12.0, 0.5), -Vec3::Y, 8.0).unwrap();
+        assert_eq!((hit.pos, hit.face), (BlockPos::new(2, 10, 0), Direction::Up));
+        assert!((hit.distance - 1.5).abs() < 1.0e-4);
+        assert_eq!(hit.shape, [Aabb::new(Vec3::new(2.0, 10.0, 0.0), Vec3::new(3.0, 10.5, 1.0))]);
+    }
+
+    #[test]
+    fn ignores_the_block_it_starts_in() {
+        let mut world = TestWorld::new(8);
+        world.fill((0, 10, 0), (0, 10, 0), "stone");
+        world.fill((2, 10, 0), (2, 10, 0), "stone");
+
+        let hit = raycast(&world.table, &world, center(0, 10, 0), Vec3::X, 8.0).unwrap();
+        assert_eq!((hit.pos, hit.face), (BlockPos::new(2, 10, 0), Direction::West));
+        assert!((hit.distance - 1.5).abs() < 1.0e-4);
+
+        // Within a slab's block but above the slab.
+        world.fill((5, 10, 0), (5, 10, 0), "slab");
+        let hit = raycast(&world.table, &world, Vec3::new(5.5, 10.75, 0.5), -Vec3::Y, 8.0).unwrap();
+        assert_eq!((hit.pos, hit.face), (BlockPos::new(5, 10, 0), Direction::Up));
+        assert!((hit.distance - 0.25).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn crosses_chunk_borders() {
+        let mut world = TestWorld::new(8);
+        world.fill((17, 10, 0), (17, 10, 0), "stone");
+        world.fill((-18, 10, -1), (-18, 10, -1), "stone");
+        world.fill((-17, 10, -17), (-17, 10, -17), "stone");
+
+        let hit = raycast(&world.table, &world, center(15, 10, 0), Vec3::X, 8.0).unwrap();
+        assert_eq!((hit.pos, hit.face), (BlockPos::new(17, 10, 0), Direction::West));
+
+        let hit = raycast(&world.table, &world, Vec3::new(0.5, 10.5, -0.5), -Vec3::X, 20.0).unwrap();
+        assert_eq!((hit.pos, hit.face), (BlockPos::new(-18, 10, -1), Direction::East));
+        assert!((hit.distance - 17.5).abs() < 1.0e-4);
+
+        // Diagonally through the corner of four chunks.
+        let hit = raycast(&world.table, &world, center(0, 10, 0), Vec3::new(-1.0, 0.0, -1.0), 30.0).unwrap();
+        assert_eq!(hit.pos, BlockPos::new(-17, 10, -17));
+
+        assert!(raycast(&world.table, &HalfLoaded(&world), center(15, 10, 0), Vec3::X, 8.0).is_none());
+    }
+
+    #[test]
+    fn misses_blocks_past_the_distance() {
+        let mut world = TestWorld::new(8);
+        world.fill((5, 10, 0), (5, 10, 0), "stone");
+
+        assert!(raycast(&world.table, &world, center(0, 10, 0), Vec3::X, 4.5).is_some());
+        assert!(raycast(&world.table, &world, center(0, 10, 0), Vec3::X, 4.4).is_none());
+        assert!(raycast(&world.table, &world, center(0, 10, 0), Vec3::Y, 300.0).is_none());
+        assert!(raycast(&world.table, &world, center(0, 10, 0), Vec3::ZERO, 8.0).is_none());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::block::{BlockRegistry, Fluid};
+use crate::math::Aabb;
+use crate::world::BlockState;
+
+/// How bodies collide with and move through a single block state.
+#[derive(Debug, Clone)]
+struct StatePhysics {
+    boxes: Vec<Aabb>,
+    selection: Vec<Aabb>,
+    climbable: bool,
+    fluid: Option<Fluid>,
+
+    /// Height of the fluid surface above the bottom of the block.
+    fluid_height: f32,
+}
+
+/// How bodies collide with and move through every block state of a registry, and where rays
+/// hit them, resolved ahead of time.
+#[derive(Debug, Clone)]
+pub struct PhysicsTable {
+    states: Vec<StatePhysics>,
+}
+
+impl PhysicsTable {
+    /// Builds the table for every state of `registry`.
+    ///
+    /// Fluids with a `level` property below 8 fill the block up to `(8 - level) / 9`, so a
+    /// source is not quite full; higher levels are falling fluid filling the whole block.
+    /// Waterlogged blocks hold a water source.
+    pub fn new(registry: &BlockRegistry) -> Self {
+        let states = registry
+            .states()
+            .map(|state| {
+                let attributes = registry.attributes(state);
+                let waterlogged = registry.value(state, "waterlogged") == Some("true");
+                let level = match waterlogged {
+                    true => Some(0),
+                    false => registry.value(state, "level").and_then(|level| level.parse::<u8>().ok()),
+                };
+
+                let fluid_height = match level {
+                    Some(level) if level < 8 => (8 - level) as f32 / 9.0,
+                    _ => 1.0,
+                };
+
+                StatePhysics {
+                    boxes: attributes.collision.boxes().to_vec(),
+                    selection: attributes.selection.boxes().to_vec(),
+                    climbable: attributes.climbable,
+                    fluid: attributes.fluid.or(waterlogged.then_some(Fluid::Water)),
+                    fluid_height,
+                }
+            })
+            .collect();
+
+        Self { states }
+    }
+
+    /// Returns the collision boxes of `state`, in block coordinates.
+    #[inline]
+    pub fn boxes(&self, state: BlockState) -> &[Aabb] {
+        &self.states[state.id() as usize].boxes
+    }
+
+    /// Returns the boxes of `state` rays hit, in block coordinates.
+    #[inline]
+    pub fn selection(&self, state: BlockState) -> &[Aabb] {
+        &self.states[state.id() as usize].selection
+    }
+
+    /// Returns whether bodies touching `state` can climb it.
+    #[inline]
+    pub fn is_climbable(&self, state: BlockState) -> bool {
+        self.states[state.id() as usize].climbable
+    }
+
+    /// Returns the fluid `state` is made of and the height of its surface above the bottom
+    /// of the block, or `None` if it is not a fluid.
+    #[inline]
+    pub fn fluid(&self, state: BlockState) -> Option<(Fluid, f32)> {
+        let physics = &self.states[state.id() as usize];
+        physics.fluid.map(|fluid| (fluid, physics.fluid_height))
+    }
+}
@@ -0,0 +1,129 @@
+use crate::block::BlockRegistry;
+use crate::opengl::texture::{InternalFormat, ParameterName, ParameterValue, Texture, TextureTarget};
+use crate::resource::{Image, ResourceManager};
+use crate::world::Direction;
+
+use std::collections::{BTreeSet, HashMap};
+
+/// Size of block textures when no texture could be loaded to take it from.
+const DEFAULT_SIZE: u32 = 16;
+
+/// Every block face texture, as the layers of a single texture array.
+///
+/// Layer 0 holds a placeholder used for textures that are missing or could not be loaded, so
+/// a broken resource pack shows a checkerboard rather than failing to start.
+pub struct BlockTextures {
+    texture: Texture,
+    layers: HashMap<String, u16>,
+    missing: Vec<String>,
+    size: u32,
+}
+
+impl BlockTextures {
+    /// Loads `textures/<name>.png` for every texture name used by `registry`, and for the
+    /// names in `extra`, such as item textures and the cracks of blocks being broken.
+    ///
+    /// Textures must be square. They are scaled to the size of the largest one, so a pack
+    /// overriding only some textures at a higher resolution keeps the others. Taller images
+    /// holding animation frames stacked vertically use their first frame. Files
+    /// that cannot be decoded are reported on stderr; see [`missing`](Self::missing) for the
+    /// full list of textures replaced by the placeholder.
+    pub fn load(resources: &ResourceManager, registry: &BlockRegistry, extra: &[String]) -> Self {
+        let names: BTreeSet<&str> = registry
+            .states()
+            .flat_map(|state| Direction::ALL.map(|d| registry.attributes(state).texture(d)))
+            .chain(extra.iter().map(String::as_str))
+            .collect();
+
+        let mut images = Vec::new();
+        let mut layers = HashMap::new();
+        let mut missing = Vec::new();
+
+        for name in names {
+            let path = format!("textures/{name}.png");
+
+            if resources.resolve(&path).is_none() {
+                missing.push(name.to_string());
+                continue;
+            }
+
+            let image = resources
+                .read(&path)
+                .and_then(|bytes| Image::decode_png(&bytes))
+                .and_then(|image| match image.width > 0 && image.height.is_multiple_of(image.width) {
+                    true => Ok(image.rows(0, image.width)),
+                    false => Err(format!("expected a square, found {}x{}", image.width, image.height)),
+                });
+
+            match image {
+                Ok(image) => {
+                    layers.insert(name.to_string(), (images.len() + 1) as u16);
+                    images.push(image);
+                }
+                Err(e) => {
+                    eprintln!("Block texture {name}: {e}");
+                    missing.push(name.to_string());
+                }
+            }
+        }
+
+        let size = images.iter().map(|image| image.width).max().unwrap_or(DEFAULT_SIZE);
+
+        for image in images.iter_mut().filter(|image| image.width != size) {
+            *image = image.resize_nearest(size, size);
+        }
+
+        let texture = Texture::new(TextureTarget::Texture2DArray);
+
+        texture.storage(size, size, images.len() as u32 + 1, InternalFormat::SRGB8Alpha8);
+        texture.sub_image_layer(0, size, size, &placeholder(size).pixels);
+
+        for (index, image) in images.iter().enumerate() {
+            texture.sub_image_layer(index as u32 + 1, size, size, &image.pixels);
+        }
+
+        texture.generate_mipmap();
+        texture.parameter(ParameterName::MinFilter, ParameterValue::NearestMipmapLinear);
+        texture.parameter(ParameterName::MagFilter, ParameterValue::Nearest);
+        texture.parameter(ParameterName::WrapS, ParameterValue::Repeat);
+        texture.parameter(ParameterName::WrapT, ParameterValue::Repeat);
+
+        Self { texture, layers, missing, size }
+    }
+
+    /// Returns the array layer of the texture called `name`, 0 if it is unknown.
+    pub fn layer(&self, name: &str) -> u16 {
+        self.layers.get(name).copied().unwrap_or(0)
+    }
+
+    /// Returns the names of textures that do not exist or could not be loaded.
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// Returns the width and height of every texture, that of the largest one loaded.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Binds the texture array to texture unit `unit`.
+    pub fn bind(&self, unit: u32) {
+        self.texture.bind_unit(unit);
+    }
+}
+
+/// The magenta and black checkerboard shown in place of missing textures.
+fn placeholder(size: u32) -> Image {
+    let mut image = Image::new(size, size, [0, 0, 0, 255]);
+    let half = (size / 2).max(1);
+
+    for y in 0..size {
+        for x in 0..size {
+            if (x / half + y / half).is_multiple_of(2) {
+                image.set(x, y, [248, 0, 248, 255]);
+            }
+        }
+    }
+
+    image
+}
@@ -0,0 +1,119 @@
+use crate::math::Aabb;
+use crate::opengl::{BufferObject, BufferTarget, BufferUsage, VertexArray, VertexAttribPointer};
+use crate::world::Direction;
+
+/// A vertex of a textured box, see `shaders/boxes.vert`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+struct BoxVertex {
+    position: [f32; 3],
+    uv: [f32; 2],
+    layer: f32,
+    face: f32,
+
+    /// Sky and block light, from 0 to 1.
+    light: [f32; 2],
+}
+
+/// Floats per vertex.
+const VERTEX_SIZE: usize = size_of::<BoxVertex>() / size_of::<f32>();
+
+/// Boxes textured from the block texture array, rebuilt every frame, for what is not part of
+/// a chunk mesh: dropped items and the cracks of blocks being broken.
+///
+/// Drawn with the boxes program (`shaders/boxes.vert` and `shaders/boxes.frag`), both sides
+/// of each face, so boxes can be pushed without caring about winding.
+pub struct TexturedBoxes {
+    vao: VertexArray,
+    vbo: BufferObject,
+    vertices: Vec<BoxVertex>,
+}
+
+impl TexturedBoxes {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        let vao = VertexArray::new();
+        vao.bind();
+
+        let vbo = BufferObject::new(BufferTarget::ArrayBuffer, BufferUsage::StreamDraw);
+        vbo.data::<BoxVertex>(&[]);
+        VertexAttribPointer::new::<f32>(0, 3, false, VERTEX_SIZE, 0);
+        VertexAttribPointer::new::<f32>(1, 2, false, VERTEX_SIZE, 3);
+        VertexAttribPointer::new::<f32>(2, 1, false, VERTEX_SIZE, 5);
+        VertexAttribPointer::new::<f32>(3, 1, false, VERTEX_SIZE, 6);
+        VertexAttribPointer::new::<f32>(4, 2, false, VERTEX_SIZE, 7);
+
+        VertexArray::unbind();
+        Self { vao, vbo, vertices: Vec::new() }
+    }
+
+    /// Removes every box.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    /// Returns whether there are no boxes.
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /// Adds `aabb`, in world coordinates, with the texture array layer of each face indexed
+    /// by [`Direction`] and lit by sky and block light levels from 0 to 15.
+    ///
+    /// Textures span `bounds` on each face as they would a block, upright on the sides, so
+    /// a box smaller than its bounds shows the matching part of the texture.
+    pub fn push(&mut self, aabb: Aabb, bounds: Aabb, layers: [u16; 6], light: (u8, u8)) {
+        let (min, max) = (aabb.min.to_array(), aabb.max.to_array());
+        let (origin, size) = (bounds.min.to_array(), bounds.size().to_array());
+        let light = [light.0 as f32 / 15.0, light.1 as f32 / 15.0];
+
+        for direction in Direction::ALL {
+            let axis = direction.axis();
+            let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+            let plane = if direction.is_positive() { max[axis] } else { min[axis] };
+
+            let corner = |u: f32, v: f32| {
+                let mut position = [0.0; 3];
+                position[axis] = plane;
+                position[b] = u;
+                position[c] = v;
+
+                let t: [f32; 3] = std::array::from_fn(|a| (position[a] - origin[a]) / size[a]);
+
+                let uv = match direction {
+                    Direction::Down | Direction::Up => [t[0], t[2]],
+                    Direction::North => [1.0 - t[0], 1.0 - t[1]],
+                    Direction::South => [t[0], 1.0 - t[1]],
+                    Direction::West => [t[2], 1.0 - t[1]],
+                    Direction::East => [1.0 - t[2], 1.0 - t[1]],
+                };
+
+                BoxVertex { position, uv, layer: layers[direction as usize] as f32, face: direction as usize as f32, light }
+            };
+
+            let quad = [corner(min[b], min[c]), corner(max[b], min[c]), corner(max[b], max[c]), corner(min[b], max[c])];
+            self.vertices.extend([quad[0], quad[1], quad[2], quad[0], quad[2], quad[3]]);
+        }
+    }
+
+    /// Uploads and draws every box. The boxes program must be in use with the block
+    /// textures bound.
+    pub fn draw(&self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        self.vbo.data(&self.vertices);
+        self.vao.bind();
+
+        unsafe {
+            gl::Disable(gl::CULL_FACE);
+            gl::DrawArrays(gl::TRIANGLES, 0, self.vertices.len() as i32);
+            gl::Enable(gl::CULL_FACE);
+        }
+    }
+}
+
+impl Default for TexturedBoxes {
+    fn default() -> Self { Self::new() }
+}
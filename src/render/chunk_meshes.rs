@@ -0,0 +1,85 @@
+use super::{QuadIndexBuffer, SectionBuffers};
+use crate::block::RenderLayer;
+use crate::jobs::ChunkStreamer;
+use crate::mesh::SectionMesh;
+use crate::world::{ChunkPos, SECTION_COUNT, SECTION_SIZE};
+
+use std::collections::HashMap;
+
+/// The GPU buffers of every chunk meshed by a [`ChunkStreamer`].
+///
+/// Meshes are built on worker threads; this is the context thread side, uploading them a few
+/// at a time and freeing the buffers of chunks the streamer unloaded.
+pub struct ChunkMeshes {
+    indices: QuadIndexBuffer,
+    chunks: HashMap<ChunkPos, Vec<SectionBuffers>>,
+}
+
+impl ChunkMeshes {
+    /// Creates an empty set of meshes.
+    pub fn new() -> Self {
+        Self { indices: QuadIndexBuffer::new(), chunks: HashMap::new() }
+    }
+
+    /// Frees the chunks unloaded by `streamer`, then uploads at most `budget` of the meshes
+    /// it finished. Returns the number of chunks uploaded.
+    ///
+    /// Only the sections a mesh holds are replaced, so editing a block uploads one or two
+    /// sections rather than the whole chunk.
+    pub fn sync(&mut self, streamer: &mut ChunkStreamer, budget: usize) -> usize {
+        for pos in streamer.take_unloaded() {
+            self.chunks.remove(&pos);
+        }
+
+        let mut uploaded = 0;
+
+        while uploaded < budget
+            && let Some(mesh) = streamer.take_mesh()
+        {
+            let indices = &mut self.indices;
+            let sections = self
+                .chunks
+                .entry(mesh.pos)
+                .or_insert_with(|| (0..SECTION_COUNT).map(|_| SectionBuffers::upload(&SectionMesh::default(), indices)).collect());
+
+            for (index, section) in &mesh.sections {
+                sections[*index] = SectionBuffers::upload(section, &mut self.indices);
+            }
+
+            uploaded += 1;
+        }
+
+        uploaded
+    }
+
+    /// Draws one render layer of every chunk. The chunk program must be in use;
+    /// `origin_location` is the location of its `sectionOrigin` uniform.
+    pub fn draw(&self, layer: RenderLayer, origin_location: i32) {
+        let size = SECTION_SIZE as f32;
+
+        for (pos, sections) in &self.chunks {
+            for (index, buffers) in sections.iter().enumerate() {
+                if !buffers.has_layer(layer) {
+                    continue;
+                }
+
+                unsafe { gl::Uniform3f(origin_location, pos.x as f32 * size, index as f32 * size, pos.z as f32 * size) };
+                buffers.draw(layer);
+            }
+        }
+    }
+
+    /// Returns the number of chunks uploaded.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns the number of bytes of vertex data held on the GPU.
+    pub fn memory_usage(&self) -> usize {
+        self.chunks.values().flatten().map(SectionBuffers::memory_usage).sum()
+    }
+}
+
+impl Default for ChunkMeshes {
+    fn default() -> Self { Self::new() }
+}
@@ -0,0 +1,15 @@
+mod block_textures;
+mod chunk_meshes;
+mod section_buffers;
+mod settings;
+mod sky;
+mod outline;
+mod boxes;
+
+pub use block_textures::BlockTextures;
+pub use chunk_meshes::ChunkMeshes;
+pub use section_buffers::{QuadIndexBuffer, SectionBuffers};
+pub use settings::RenderSettings;
+pub use sky::{SkyState, SkyUniforms};
+pub use outline::SelectionOutline;
+pub use boxes::TexturedBoxes;
@@ -0,0 +1,80 @@
+use crate::math::{Aabb, Vec3};
+use crate::opengl::{BufferObject, BufferTarget, BufferUsage, VertexArray, VertexAttribPointer};
+
+/// How far the outline stands off the boxes, so its lines are not hidden by the faces they
+/// lie on.
+const OFFSET: f32 = 0.002;
+
+/// The wireframe drawn around the block the player aims at.
+///
+/// Holds the edges of a few boxes as lines, drawn with the outline program
+/// (`shaders/outline.vert` and `shaders/outline.frag`).
+pub struct SelectionOutline {
+    vao: VertexArray,
+    vbo: BufferObject,
+    vertices: usize,
+    boxes: Vec<Aabb>,
+}
+
+impl SelectionOutline {
+    /// Creates an outline around nothing.
+    pub fn new() -> Self {
+        let vao = VertexArray::new();
+        vao.bind();
+
+        let vbo = BufferObject::new(BufferTarget::ArrayBuffer, BufferUsage::DynamicDraw);
+        vbo.data::<Vec3>(&[]);
+        VertexAttribPointer::new::<f32>(0, 3, false, 3, 0);
+
+        VertexArray::unbind();
+        Self { vao, vbo, vertices: 0, boxes: Vec::new() }
+    }
+
+    /// Outlines `boxes`, in world coordinates. Nothing is uploaded if they did not change.
+    pub fn set_boxes(&mut self, boxes: &[Aabb]) {
+        if self.boxes == boxes {
+            return;
+        }
+
+        let vertices: Vec<Vec3> = boxes.iter().flat_map(|b| edges(b.inflate(OFFSET))).collect();
+        self.vbo.data(&vertices);
+        self.vertices = vertices.len();
+        self.boxes = boxes.to_vec();
+    }
+
+    /// Draws the outline. The outline program must be in use.
+    pub fn draw(&self) {
+        if self.vertices == 0 {
+            return;
+        }
+
+        self.vao.bind();
+        unsafe { gl::DrawArrays(gl::LINES, 0, self.vertices as i32) };
+    }
+}
+
+impl Default for SelectionOutline {
+    fn default() -> Self { Self::new() }
+}
+
+/// Returns the twelve edges of `aabb` as pairs of line ends.
+fn edges(aabb: Aabb) -> [Vec3; 24] {
+    let corner = |i: usize| {
+        Vec3::new(
+            if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+            if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+            if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+        )
+    };
+
+    // Corners joined by an edge differ in a single bit of their index.
+    const EDGES: [(usize, usize); 12] = [(0, 1), (2, 3), (4, 5), (6, 7), (0, 2), (1, 3), (4, 6), (5, 7), (0, 4), (1, 5), (2, 6), (3, 7)];
+    let mut lines = [Vec3::ZERO; 24];
+
+    for (i, (a, b)) in EDGES.into_iter().enumerate() {
+        lines[i * 2] = corner(a);
+        lines[i * 2 + 1] = corner(b);
+    }
+
+    lines
+}
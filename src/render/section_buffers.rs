@@ -0,0 +1,133 @@
+use crate::block::RenderLayer;
+use crate::mesh::{BlockVertex, MeshData, SectionMesh};
+use crate::opengl::{BufferObject, BufferTarget, BufferUsage, VertexArray, VertexAttribPointer};
+
+use std::ptr::null;
+
+/// Index buffer shared by every section mesh, repeating the triangulation of a quad.
+///
+/// Meshes only store four vertices per quad; drawing them with this buffer saves both the
+/// memory and the upload time of per-mesh indices.
+pub struct QuadIndexBuffer {
+    buffer: BufferObject,
+    quads: usize,
+}
+
+impl QuadIndexBuffer {
+    /// Creates an empty index buffer.
+    pub fn new() -> Self {
+        Self { buffer: BufferObject::new(BufferTarget::ElementArrayBuffer, BufferUsage::StaticDraw), quads: 0 }
+    }
+
+    /// Grows the buffer so it can draw at least `quads` quads.
+    ///
+    /// The buffer keeps its OpenGL name when it grows, so vertex arrays already referring to
+    /// it stay valid.
+    pub fn ensure(&mut self, quads: usize) {
+        if quads <= self.quads {
+            return;
+        }
+
+        let quads = quads.next_power_of_two().max(1024);
+        let indices: Vec<u32> = (0..quads as u32)
+            .flat_map(|quad| [0, 1, 2, 2, 3, 0].map(|corner| quad * 4 + corner))
+            .collect();
+
+        self.buffer.data(&indices);
+        self.quads = quads;
+    }
+
+    /// Binds the buffer, attaching it to the currently bound vertex array.
+    pub fn bind(&self) {
+        self.buffer.bind();
+    }
+}
+
+impl Default for QuadIndexBuffer {
+    fn default() -> Self { Self::new() }
+}
+
+/// The vertex buffer of one render layer of a section.
+struct LayerBuffers {
+    vao: VertexArray,
+    _vbo: BufferObject,
+    quads: usize,
+}
+
+impl LayerBuffers {
+    fn upload(data: &MeshData, indices: &mut QuadIndexBuffer) -> Option<Self> {
+        if data.is_empty() {
+            return None;
+        }
+
+        indices.ensure(data.quad_count());
+
+        let vao = VertexArray::new();
+        vao.bind();
+
+        let vbo = BufferObject::new(BufferTarget::ArrayBuffer, BufferUsage::StaticDraw);
+        vbo.data(&data.vertices);
+        indices.bind();
+
+        // The words of a vertex are read as one uvec3 and unpacked in the shader.
+        let stride = size_of::<BlockVertex>() / size_of::<u32>();
+        VertexAttribPointer::new_integer::<u32>(0, 3, stride, 0);
+
+        VertexArray::unbind();
+        Some(Self { vao, _vbo: vbo, quads: data.quad_count() })
+    }
+}
+
+/// The geometry of a section, uploaded to the GPU.
+pub struct SectionBuffers {
+    layers: [Option<LayerBuffers>; 3],
+}
+
+impl SectionBuffers {
+    /// Uploads every non-empty layer of `mesh`. Must be called on the thread owning the
+    /// OpenGL context.
+    pub fn upload(mesh: &SectionMesh, indices: &mut QuadIndexBuffer) -> Self {
+        Self {
+            layers: [&mesh.opaque, &mesh.cutout, &mesh.translucent].map(|data| LayerBuffers::upload(data, indices)),
+        }
+    }
+
+    /// Draws one render layer. The chunk program must be in use with the section origin set.
+    pub fn draw(&self, layer: RenderLayer) {
+        let Some(buffers) = Self::slot(layer).and_then(|slot| self.layers[slot].as_ref()) else {
+            return;
+        };
+
+        buffers.vao.bind();
+        unsafe { gl::DrawElements(gl::TRIANGLES, (buffers.quads * 6) as i32, gl::UNSIGNED_INT, null()) };
+    }
+
+    /// Returns whether the layer has anything to draw.
+    pub fn has_layer(&self, layer: RenderLayer) -> bool {
+        Self::slot(layer).is_some_and(|slot| self.layers[slot].is_some())
+    }
+
+    /// Returns whether no layer has anything to draw.
+    pub fn is_empty(&self) -> bool {
+        self.layers.iter().all(Option::is_none)
+    }
+
+    /// Returns the number of quads across all layers.
+    pub fn quad_count(&self) -> usize {
+        self.layers.iter().flatten().map(|layer| layer.quads).sum()
+    }
+
+    /// Returns the number of bytes of vertex data held on the GPU.
+    pub fn memory_usage(&self) -> usize {
+        self.quad_count() * 4 * size_of::<BlockVertex>()
+    }
+
+    fn slot(layer: RenderLayer) -> Option<usize> {
+        match layer {
+            RenderLayer::Invisible => None,
+            RenderLayer::Opaque => Some(0),
+            RenderLayer::Cutout => Some(1),
+            RenderLayer::Translucent => Some(2),
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Rendering preferences that only affect shaders, so changing them needs no remeshing.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderSettings {
+    /// How dark ambient occlusion makes corners, from 0 (off) to 1 (full).
+    pub ao_strength: f32,
+}
+
+impl RenderSettings {
+    /// Returns the AO strength clamped to its valid range, as given to the chunk shader.
+    pub fn ao_strength(&self) -> f32 {
+        self.ao_strength.clamp(0.0, 1.0)
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self { Self { ao_strength: 1.0 } }
+}
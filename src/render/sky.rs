@@ -0,0 +1,186 @@
+use crate::math::Vec3;
+use crate::opengl::{BufferObject, BufferTarget, BufferUsage, Program};
+use crate::world::WorldTime;
+
+/// Color of the sky on a moonless night.
+const NIGHT_SKY: [f32; 3] = [0.01, 0.01, 0.035];
+
+/// Share of sky light left at night, under a new moon and a full moon.
+const NIGHT_LIGHT: [f32; 2] = [0.12, 0.22];
+
+/// Start of the fog as a share of its end.
+const FOG_START: f32 = 0.7;
+
+/// The colors and light of the sky at one moment, worked out from the time of day.
+///
+/// Everything here only changes uniforms, so the world darkens at night and brightens at
+/// dawn without remeshing a single chunk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyState {
+    /// Color of the sky overhead.
+    pub sky_color: [f32; 3],
+
+    /// Color of the fog and the horizon, tinted by sunrises and sunsets the camera faces.
+    pub fog_color: [f32; 3],
+
+    /// Unit vector towards the sun.
+    pub sun_direction: Vec3,
+
+    /// Unit vector towards the moon.
+    pub moon_direction: Vec3,
+
+    /// Factor applied to sky light, 1 at day and a fraction of it at night.
+    pub sky_light: f32,
+
+    /// Phase of the moon, 0 for full moon.
+    pub moon_phase: u32,
+}
+
+impl SkyState {
+    /// Works out the sky `partial` ticks after `time`, for a day sky of `day_sky` and a camera
+    /// looking along `forward`.
+    pub fn new(time: WorldTime, partial: f32, day_sky: [f32; 3], forward: Vec3) -> Self {
+        let daylight = time.daylight(partial);
+        let sun_direction = time.sun_direction(partial);
+        let sky_color = std::array::from_fn(|i| NIGHT_SKY[i] + (day_sky[i] - NIGHT_SKY[i]) * daylight);
+
+        // The horizon is paler than the sky overhead by day.
+        let pale = daylight * 0.9;
+        let mut fog_color: [f32; 3] = std::array::from_fn(|i| sky_color[i] + (pale - sky_color[i]) * 0.25 * daylight);
+
+        if let Some((glow, strength)) = sun_glow(sun_direction) {
+            // Strongest looking towards the sun, fading out looking away.
+            let towards = Vec3::new(sun_direction.x, 0.0, sun_direction.z).normalize_or_zero();
+            let facing = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero().dot(towards).max(0.0);
+            let amount = strength * facing;
+            fog_color = std::array::from_fn(|i| fog_color[i] + (glow[i] - fog_color[i]) * amount);
+        }
+
+        let [new_moon, full_moon] = NIGHT_LIGHT;
+        let night = new_moon + (full_moon - new_moon) * time.moon_fullness();
+
+        Self {
+            sky_color,
+            fog_color,
+            sun_direction,
+            moon_direction: time.moon_direction(partial),
+            sky_light: night + (1.0 - night) * daylight,
+            moon_phase: time.moon_phase() as u32,
+        }
+    }
+}
+
+/// Returns the color of the glow around a rising or setting sun and how strong it is, or
+/// `None` while the sun is well above or below the horizon.
+fn sun_glow(sun_direction: Vec3) -> Option<([f32; 3], f32)> {
+    let height = sun_direction.y;
+
+    if !(-0.4..=0.4).contains(&height) {
+        return None;
+    }
+
+    // From red just below the horizon to orange just above.
+    let shade = height / 0.4 * 0.5 + 0.5;
+    let strength = (1.0 - (1.0 - (shade * std::f32::consts::PI).sin()) * 0.99).powi(2);
+    Some(([shade * 0.3 + 0.7, shade * shade * 0.7 + 0.2, 0.2], strength))
+}
+
+/// Data of the `Sky` uniform block, laid out with the std140 rules.
+///
+/// Shaders declare it as:
+///
+/// ```glsl
+/// layout (std140) uniform Sky {
+///     vec4 skyColor;
+///     vec4 fogColor;
+///     vec4 sunDirection;
+///     vec4 moonDirection;
+///     float skyLight;
+///     float fogStart;
+///     float fogEnd;
+///     float moonPhase;
+/// };
+/// ```
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct SkyBlock {
+    sky_color: [f32; 4],
+    fog_color: [f32; 4],
+    sun_direction: [f32; 4],
+    moon_direction: [f32; 4],
+    sky_light: f32,
+    fog_start: f32,
+    fog_end: f32,
+    moon_phase: f32,
+}
+
+/// A uniform buffer holding the state of the sky and the fog, shared by every shader.
+///
+/// Like [`CameraUniforms`](crate::camera::CameraUniforms), the buffer stays bound to
+/// [`SkyUniforms::BINDING`] and programs are connected to it once with
+/// [`SkyUniforms::attach`].
+pub struct SkyUniforms {
+    buffer: BufferObject,
+}
+
+impl SkyUniforms {
+    /// Name of the uniform block in shaders.
+    pub const BLOCK_NAME: &'static str = "Sky";
+
+    /// Uniform buffer binding point reserved for the sky block.
+    pub const BINDING: u32 = 1;
+
+    /// Creates the uniform buffer, holding a noon sky without fog, and binds it to
+    /// [`SkyUniforms::BINDING`].
+    pub fn new() -> Self {
+        let buffer = BufferObject::new(BufferTarget::UniformBuffer, BufferUsage::DynamicDraw);
+        let block = SkyBlock {
+            sky_color: [1.0; 4],
+            fog_color: [1.0; 4],
+            sun_direction: [0.0, 1.0, 0.0, 0.0],
+            moon_direction: [0.0, -1.0, 0.0, 0.0],
+            sky_light: 1.0,
+            fog_start: 1.0e9,
+            fog_end: 2.0e9,
+            moon_phase: 0.0,
+        };
+
+        buffer.data(std::slice::from_ref(&block));
+        buffer.bind_base(Self::BINDING);
+
+        Self { buffer }
+    }
+
+    /// Connects the `Sky` block of `program` to the shared buffer.
+    ///
+    /// # Returns
+    /// `false` if the program does not use the sky block.
+    pub fn attach(&self, program: &Program) -> bool {
+        program.bind_uniform_block(Self::BLOCK_NAME, Self::BINDING)
+    }
+
+    /// Uploads `sky`, with fog thickening up to `fog_distance` blocks away from the camera.
+    /// Call once per frame before drawing.
+    pub fn update(&self, sky: &SkyState, fog_distance: f32) {
+        let [r, g, b] = sky.sky_color;
+        let [fog_r, fog_g, fog_b] = sky.fog_color;
+        let (sun, moon) = (sky.sun_direction, sky.moon_direction);
+
+        let block = SkyBlock {
+            sky_color: [r, g, b, 1.0],
+            fog_color: [fog_r, fog_g, fog_b, 1.0],
+            sun_direction: [sun.x, sun.y, sun.z, 0.0],
+            moon_direction: [moon.x, moon.y, moon.z, 0.0],
+            sky_light: sky.sky_light,
+            fog_start: fog_distance * FOG_START,
+            fog_end: fog_distance,
+            moon_phase: sky.moon_phase as f32,
+        };
+
+        self.buffer.sub_data(0, std::slice::from_ref(&block));
+    }
+}
+
+impl Default for SkyUniforms {
+    fn default() -> Self { Self::new() }
+}
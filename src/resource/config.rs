@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The user's selection of resource packs, stored as TOML.
+///
+/// Packs are referenced by their file or directory name inside the packs directory.
+/// The first entry has the highest priority: its files override those of every pack
+/// listed after it, and all of them override the base game assets.
+///
+/// # Example
+///
+/// ```toml
+/// enabled = ["faithful", "better_grass.zip"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackConfig {
+    /// Names of the enabled packs, highest priority first.
+    #[serde(default)]
+    pub enabled: Vec<String>,
+}
+
+impl PackConfig {
+    /// Loads the pack configuration from `path`.
+    ///
+    /// A missing file is not an error and yields an empty configuration.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+
+        let source = match std::fs::read_to_string(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+            Ok(source) => source,
+        };
+
+        toml::from_str(&source).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Writes the pack configuration to `path`, creating parent directories as needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let source = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("{}: {e}", parent.display()))?;
+        }
+
+        std::fs::write(path, source).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
@@ -0,0 +1,85 @@
+use std::io::Cursor;
+
+/// An RGBA image with 8 bits per channel, rows stored top to bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates an image of the given size filled with `color`.
+    pub fn new(width: u32, height: u32, color: [u8; 4]) -> Self {
+        Self { width, height, pixels: color.repeat((width * height) as usize) }
+    }
+
+    /// Decodes a PNG file, converting any color type to RGBA.
+    pub fn decode_png(bytes: &[u8]) -> Result<Self, String> {
+        let mut decoder = png::Decoder::new(Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+        let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+        buffer.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buffer,
+            png::ColorType::Rgb => buffer.chunks_exact(3).flat_map(|p| [p[0], p[1], p[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => buffer.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0], p[1]]).collect(),
+            png::ColorType::Grayscale => buffer.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+            png::ColorType::Indexed => return Err(String::from("indexed PNG was not expanded")),
+        };
+
+        Ok(Self { width: info.width, height: info.height, pixels })
+    }
+
+    /// Encodes the image as a PNG file.
+    pub fn encode_png(&self) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(&self.pixels).map_err(|e| e.to_string())?;
+        writer.finish().map_err(|e| e.to_string())?;
+
+        Ok(bytes)
+    }
+
+    /// Returns the pixel at `(x, y)`.
+    pub fn get(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * self.width + x) * 4) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    /// Sets the pixel at `(x, y)`.
+    pub fn set(&mut self, x: u32, y: u32, color: [u8; 4]) {
+        let i = ((y * self.width + x) * 4) as usize;
+        self.pixels[i..i + 4].copy_from_slice(&color);
+    }
+
+    /// Returns the `height` rows starting at `y`, as a new image.
+    pub fn rows(&self, y: u32, height: u32) -> Self {
+        let start = (y * self.width * 4) as usize;
+        let end = start + (height * self.width * 4) as usize;
+        Self { width: self.width, height, pixels: self.pixels[start..end].to_vec() }
+    }
+
+    /// Returns the image scaled to `width` by `height` by repeating or skipping pixels, which
+    /// keeps pixel art sharp.
+    pub fn resize_nearest(&self, width: u32, height: u32) -> Self {
+        let mut resized = Self::new(width, height, [0; 4]);
+
+        for y in 0..height {
+            for x in 0..width {
+                let source = (x as u64 * self.width as u64 / width as u64, y as u64 * self.height as u64 / height as u64);
+                resized.set(x, y, self.get(source.0 as u32, source.1 as u32));
+            }
+        }
+
+        resized
+    }
+}
@@ -0,0 +1,268 @@
+use super::pack::validate_path;
+use super::{PackConfig, ResourcePack};
+
+use std::path::{Path, PathBuf};
+use std::collections::BTreeSet;
+use std::time::SystemTime;
+
+/// Resolves asset paths against a stack of resource packs layered over the base assets.
+///
+/// Lookups walk the enabled packs from highest to lowest priority and fall back to the base
+/// assets, so a pack only needs to ship the files it wants to replace.
+///
+/// Every time the active set of packs changes, [`ResourceManager::generation`] is bumped.
+/// Anything built from resources (shaders, the block atlas, ...) should remember the generation
+/// it was built from and rebuild itself when it no longer matches.
+pub struct ResourceManager {
+    /// The built-in game assets, always consulted last.
+    base: ResourcePack,
+
+    /// The enabled packs, highest priority first.
+    packs: Vec<ResourcePack>,
+
+    /// Directory user packs are looked up in.
+    packs_dir: PathBuf,
+
+    /// Path of the pack configuration file.
+    config_path: PathBuf,
+
+    /// The configuration the current pack stack was built from.
+    config: PackConfig,
+
+    /// Modification time of the configuration file when it was last read.
+    config_modified: Option<SystemTime>,
+
+    /// Incremented every time the active pack stack changes.
+    generation: u64,
+}
+
+impl ResourceManager {
+    /// Creates a resource manager and applies the pack configuration found at `config_path`.
+    ///
+    /// # Arguments
+    /// * `assets` - Directory holding the base game assets.
+    /// * `packs_dir` - Directory containing user resource packs (directories or zip files).
+    /// * `config_path` - TOML file listing the enabled packs, see [`PackConfig`].
+    pub fn new(assets: impl Into<PathBuf>, packs_dir: impl Into<PathBuf>, config_path: impl Into<PathBuf>) -> Result<Self, String> {
+        let mut manager = Self {
+            base: ResourcePack::base(assets),
+            packs: Vec::new(),
+            packs_dir: packs_dir.into(),
+            config_path: config_path.into(),
+            config: PackConfig::default(),
+            config_modified: None,
+            generation: 0,
+        };
+
+        manager.reload_config()?;
+        Ok(manager)
+    }
+
+    /// Returns the generation of the active pack stack.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Returns the enabled packs, highest priority first, excluding the base assets.
+    pub fn active(&self) -> &[ResourcePack] {
+        &self.packs
+    }
+
+    /// Returns the configuration the active pack stack was built from.
+    pub fn config(&self) -> &PackConfig {
+        &self.config
+    }
+
+    /// Lists every pack found in the packs directory, whether enabled or not.
+    ///
+    /// Entries that fail to open are returned as errors so menus can show why.
+    pub fn available(&self) -> Vec<(String, Result<ResourcePack, String>)> {
+        let Ok(entries) = std::fs::read_dir(&self.packs_dir) else { return Vec::new() };
+
+        let mut packs: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .map(|name| {
+                let pack = ResourcePack::open(self.packs_dir.join(&name));
+                (name, pack)
+            })
+            .collect();
+
+        packs.sort_by(|a, b| a.0.cmp(&b.0));
+        packs
+    }
+
+    /// Replaces the active packs with the ones listed in `config`.
+    ///
+    /// The change is all or nothing: if any pack fails to open, the current stack is kept.
+    ///
+    /// # Returns
+    /// * `Ok(true)` if the active set changed and dependent resources must be rebuilt.
+    /// * `Ok(false)` if `config` matches the current stack.
+    /// * `Err(String)` if a pack could not be opened.
+    pub fn apply(&mut self, config: PackConfig) -> Result<bool, String> {
+        if config == self.config {
+            return Ok(false);
+        }
+
+        self.packs = self.open_all(&config)?;
+        self.config = config;
+        self.generation += 1;
+
+        Ok(true)
+    }
+
+    /// Reads the configuration file again and applies it.
+    ///
+    /// # Returns
+    /// Same as [`ResourceManager::apply`].
+    pub fn reload_config(&mut self) -> Result<bool, String> {
+        self.config_modified = modified(&self.config_path);
+        let config = PackConfig::load(&self.config_path)?;
+        self.apply(config)
+    }
+
+    /// Reopens every pack and reads the configuration file again, even if nothing changed.
+    ///
+    /// Picks up edits made inside packs, which are not detected otherwise.
+    pub fn reload(&mut self) -> Result<(), String> {
+        self.config_modified = modified(&self.config_path);
+        let config = PackConfig::load(&self.config_path)?;
+
+        self.packs = self.open_all(&config)?;
+        self.config = config;
+        self.generation += 1;
+
+        Ok(())
+    }
+
+    /// Reloads the configuration file if it was modified since it was last read.
+    ///
+    /// Cheap enough to call once per frame.
+    pub fn poll_config(&mut self) -> Result<bool, String> {
+        if modified(&self.config_path) == self.config_modified {
+            return Ok(false);
+        }
+
+        self.reload_config()
+    }
+
+    /// Enables the packs in `config`, and writes it to the configuration file on success.
+    pub fn save_config(&mut self, config: PackConfig) -> Result<bool, String> {
+        let changed = self.apply(config)?;
+        self.config.save(&self.config_path)?;
+        self.config_modified = modified(&self.config_path);
+        Ok(changed)
+    }
+
+    /// Returns the pack that provides the file at `path`, if any does.
+    pub fn resolve(&self, path: &str) -> Option<&ResourcePack> {
+        self.layers().find(|pack| pack.contains(path))
+    }
+
+    /// Reads the file at `path` from the highest priority pack that provides it.
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, String> {
+        validate_path(path)?;
+
+        self.layers()
+            .find_map(|pack| pack.read(path))
+            .unwrap_or_else(|| Err(format!("resource not found: {path}")))
+    }
+
+    /// Reads the file at `path` as UTF-8 text, see [`ResourceManager::read`].
+    pub fn read_to_string(&self, path: &str) -> Result<String, String> {
+        String::from_utf8(self.read(path)?).map_err(|e| format!("{path}: {e}"))
+    }
+
+    /// Lists every file under `prefix` provided by any layer, without duplicates.
+    ///
+    /// Useful to discover resources that packs may add, such as extra block textures.
+    pub fn list(&self, prefix: &str) -> Vec<String> {
+        let files: BTreeSet<_> = self.layers().flat_map(|pack| pack.list(prefix)).collect();
+        files.into_iter().collect()
+    }
+
+    /// Opens every pack enabled in `config`, failing if any of them cannot be opened.
+    fn open_all(&self, config: &PackConfig) -> Result<Vec<ResourcePack>, String> {
+        config
+            .enabled
+            .iter()
+            .map(|name| ResourcePack::open(self.packs_dir.join(name)))
+            .collect()
+    }
+
+    /// Iterates over all layers, highest priority first, ending with the base assets.
+    fn layers(&self) -> impl Iterator<Item = &ResourcePack> {
+        self.packs.iter().chain(std::iter::once(&self.base))
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resource::pack::tests::{temporary, write_dir, write_zip};
+
+    /// Returns a manager over base assets and packs `high`, `low.zip` and `broken`, with
+    /// `enabled` enabled.
+    fn manager(name: &str, enabled: &[&str]) -> (ResourceManager, PathBuf) {
+        let root = temporary(name);
+        let manifest = ("pack.toml", "name = \"Pack\"");
+
+        write_dir(&root.join("assets"), &[("a.txt", "base a"), ("b.txt", "base b"), ("c.txt", "base c")]);
+        write_dir(&root.join("packs/high"), &[manifest, ("a.txt", "high a")]);
+        write_zip(&root.join("packs/low.zip"), &[manifest, ("a.txt", "low a"), ("b.txt", "low b"), ("d.txt", "low d")]);
+        write_dir(&root.join("packs/broken"), &[("a.txt", "no manifest")]);
+
+        let config = PackConfig { enabled: enabled.iter().map(|name| name.to_string()).collect() };
+        config.save(root.join("packs.toml")).unwrap();
+
+        let manager = ResourceManager::new(root.join("assets"), root.join("packs"), root.join("packs.toml")).unwrap();
+        (manager, root)
+    }
+
+    fn text(manager: &ResourceManager, path: &str) -> String {
+        manager.read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn reads_from_the_highest_pack_providing_a_file() {
+        let (manager, root) = manager("manager-order", &["high", "low.zip"]);
+
+        assert_eq!([text(&manager, "a.txt"), text(&manager, "b.txt"), text(&manager, "c.txt"), text(&manager, "d.txt")], ["high a", "low b", "base c", "low d"]);
+        assert_eq!(manager.resolve("a.txt").unwrap().path(), root.join("packs/high"));
+        assert_eq!(manager.list(""), ["a.txt", "b.txt", "c.txt", "d.txt", "pack.toml"]);
+        assert!(manager.read("e.txt").is_err());
+        assert!(manager.read("../packs.toml").is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn applies_a_new_order_all_or_nothing() {
+        let (mut manager, root) = manager("manager-apply", &["high", "low.zip"]);
+        let generation = manager.generation();
+
+        let reversed = PackConfig { enabled: vec![String::from("low.zip"), String::from("high")] };
+        assert!(manager.apply(reversed.clone()).unwrap());
+        assert!(!manager.apply(reversed).unwrap());
+        assert_eq!(text(&manager, "a.txt"), "low a");
+        assert_eq!(manager.generation(), generation + 1);
+
+        let broken = PackConfig { enabled: vec![String::from("high"), String::from("broken")] };
+        assert!(manager.apply(broken).is_err());
+        assert_eq!(text(&manager, "a.txt"), "low a");
+        assert_eq!(manager.generation(), generation + 1);
+
+        assert!(manager.apply(PackConfig::default()).unwrap());
+        assert_eq!(text(&manager, "a.txt"), "base a");
+
+        let available: Vec<_> = manager.available().into_iter().map(|(name, pack)| (name, pack.is_ok())).collect();
+        assert_eq!(available, [(String::from("broken"), false), (String::from("high"), true), (String::from("low.zip"), true)]);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
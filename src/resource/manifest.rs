@@ -0,0 +1,59 @@
+use super::pack::validate_path;
+
+use serde::Deserialize;
+
+/// The name of the manifest file expected at the root of every resource pack.
+pub const MANIFEST_FILE: &str = "pack.toml";
+
+/// Metadata describing a resource pack, read from its `pack.toml`.
+///
+/// # Example
+///
+/// ```toml
+/// name = "Faithful"
+/// version = "1.2.0"
+/// description = "Classic textures at twice the resolution."
+/// icon = "pack.png"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct PackManifest {
+    /// Human readable name shown in pack selection menus.
+    pub name: String,
+
+    /// Version string of the pack, free-form (e.g. `"1.2.0"`).
+    #[serde(default)]
+    pub version: String,
+
+    /// Short description of the pack contents.
+    #[serde(default)]
+    pub description: String,
+
+    /// Path of the icon image, relative to the pack root and inside it.
+    #[serde(default = "default_icon")]
+    pub icon: String,
+}
+
+fn default_icon() -> String { String::from("pack.png") }
+
+impl PackManifest {
+    /// Parses a manifest from the contents of a `pack.toml` file.
+    ///
+    /// # Returns
+    /// * `Ok(PackManifest)` if the manifest is valid.
+    /// * `Err(String)` describing the parse error otherwise, or why the icon path is invalid.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let manifest: Self = toml::from_str(source).map_err(|e| format!("invalid {MANIFEST_FILE}: {e}"))?;
+        validate_path(&manifest.icon).map_err(|e| format!("invalid {MANIFEST_FILE}: icon: {e}"))?;
+        Ok(manifest)
+    }
+
+    /// Returns the manifest used for the built-in game assets, which have no `pack.toml`.
+    pub fn base() -> Self {
+        Self {
+            name: String::from("Default"),
+            version: String::from(env!("CARGO_PKG_VERSION")),
+            description: String::from("The default look of RustedCraft."),
+            icon: default_icon(),
+        }
+    }
+}
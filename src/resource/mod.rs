@@ -0,0 +1,11 @@
+mod manifest;
+mod manager;
+mod config;
+mod pack;
+mod image;
+
+pub use manifest::PackManifest;
+pub use manager::ResourceManager;
+pub use config::PackConfig;
+pub use pack::ResourcePack;
+pub use image::Image;
@@ -0,0 +1,280 @@
+use super::manifest::{PackManifest, MANIFEST_FILE};
+
+use std::path::{Component, Path, PathBuf};
+use std::collections::HashSet;
+use std::io::{BufReader, Read};
+use std::sync::Mutex;
+use std::fs::File;
+
+use zip::ZipArchive;
+
+/// Where the files of a resource pack are stored.
+enum PackSource {
+    /// A plain directory on disk.
+    Directory(PathBuf),
+
+    /// A zip archive, along with the set of file paths it contains.
+    Zip {
+        archive: Mutex<ZipArchive<BufReader<File>>>,
+        entries: HashSet<String>,
+    },
+}
+
+/// A resource pack: a directory or zip archive whose files override game assets by path.
+///
+/// Paths inside a pack are always relative to the pack root and use `/` as separator,
+/// e.g. `textures/block/stone.png` or `shaders/triangle.vert`.
+pub struct ResourcePack {
+    manifest: PackManifest,
+    source: PackSource,
+    path: PathBuf,
+}
+
+impl ResourcePack {
+    /// Opens the resource pack at `path`, which may be a directory or a `.zip` file.
+    ///
+    /// # Returns
+    /// * `Ok(ResourcePack)` if the pack exists and its `pack.toml` is valid.
+    /// * `Err(String)` describing what went wrong otherwise.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+
+        let source = if path.is_dir() {
+            PackSource::Directory(path.to_path_buf())
+        }
+        else {
+            let file = File::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+            let archive = ZipArchive::new(BufReader::new(file))
+                .map_err(|e| format!("{}: {e}", path.display()))?;
+
+            let entries = archive.file_names().map(String::from).collect();
+            PackSource::Zip { archive: Mutex::new(archive), entries }
+        };
+
+        let mut pack = Self { manifest: PackManifest::base(), source, path: path.to_path_buf() };
+
+        let manifest = pack
+            .read(MANIFEST_FILE)
+            .ok_or_else(|| format!("{}: missing {MANIFEST_FILE}", path.display()))??;
+
+        let manifest = String::from_utf8(manifest)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+
+        pack.manifest = PackManifest::parse(&manifest)
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+
+        Ok(pack)
+    }
+
+    /// Wraps the built-in assets directory as the lowest priority pack.
+    ///
+    /// Unlike user packs, the base assets do not need a `pack.toml`.
+    pub fn base(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let source = PackSource::Directory(path.clone());
+        Self { manifest: PackManifest::base(), source, path }
+    }
+
+    /// Returns the manifest of this pack.
+    pub fn manifest(&self) -> &PackManifest {
+        &self.manifest
+    }
+
+    /// Returns the path of the directory or archive this pack was opened from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns whether this pack contains a file at `path`. Paths that would lead out of the
+    /// pack are never contained.
+    pub fn contains(&self, path: &str) -> bool {
+        if validate_path(path).is_err() {
+            return false;
+        }
+
+        match &self.source {
+            PackSource::Directory(root) => root.join(path).is_file(),
+            PackSource::Zip { entries, .. } => entries.contains(path),
+        }
+    }
+
+    /// Reads the file at `path` from this pack.
+    ///
+    /// # Returns
+    /// * `None` if the pack does not contain the file.
+    /// * `Some(Ok(bytes))` with the file contents.
+    /// * `Some(Err(String))` if the file exists but could not be read, or `path` would lead
+    ///   out of the pack.
+    pub fn read(&self, path: &str) -> Option<Result<Vec<u8>, String>> {
+        if let Err(e) = validate_path(path) {
+            return Some(Err(format!("{}: {e}", self.path.display())));
+        }
+
+        if !self.contains(path) {
+            return None;
+        }
+
+        let result = match &self.source {
+            PackSource::Directory(root) => std::fs::read(root.join(path)).map_err(|e| e.to_string()),
+            PackSource::Zip { archive, .. } => {
+                let mut archive = archive.lock().unwrap();
+
+                archive.by_name(path).map_err(|e| e.to_string()).and_then(|mut file| {
+                    let mut buffer = Vec::with_capacity(file.size() as usize);
+                    file.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
+                    Ok(buffer)
+                })
+            }
+        };
+
+        Some(result.map_err(|e| format!("{}/{path}: {e}", self.path.display())))
+    }
+
+    /// Returns the icon image of this pack, if it has one.
+    pub fn icon(&self) -> Option<Vec<u8>> {
+        self.read(&self.manifest.icon)?.ok()
+    }
+
+    /// Lists every file in this pack whose path starts with `prefix`.
+    pub fn list(&self, prefix: &str) -> Vec<String> {
+        match &self.source {
+            PackSource::Zip { entries, .. } => entries
+                .iter()
+                .filter(|entry| entry.starts_with(prefix) && !entry.ends_with('/'))
+                .cloned()
+                .collect(),
+
+            PackSource::Directory(root) => {
+                let mut files = Vec::new();
+                let mut pending = vec![root.clone()];
+
+                while let Some(dir) = pending.pop() {
+                    let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+
+                    for entry in entries.flatten() {
+                        let path = entry.path();
+
+                        if path.is_dir() {
+                            pending.push(path);
+                            continue;
+                        }
+
+                        let Ok(relative) = path.strip_prefix(root) else { continue };
+                        let relative = relative
+                            .components()
+                            .map(|c| c.as_os_str().to_string_lossy())
+                            .collect::<Vec<_>>()
+                            .join("/");
+
+                        if relative.starts_with(prefix) {
+                            files.push(relative);
+                        }
+                    }
+                }
+
+                files
+            }
+        }
+    }
+}
+
+/// Rejects absolute paths, parent directory components and backslashes, which could lead
+/// out of a pack.
+pub(super) fn validate_path(path: &str) -> Result<(), String> {
+    let escapes = path.contains('\\') || !Path::new(path).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+
+    match escapes {
+        true => Err(format!("invalid resource path: {path}")),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    /// Returns an empty directory of its own in the temporary directory.
+    pub(in crate::resource) fn temporary(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rustedcraft-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    /// Writes each `(path, contents)` file under the directory `root`.
+    pub(in crate::resource) fn write_dir(root: &Path, files: &[(&str, &str)]) {
+        for (path, contents) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+    }
+
+    /// Writes each `(path, contents)` file into a zip archive at `path`.
+    pub(in crate::resource) fn write_zip(path: &Path, files: &[(&str, &str)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+
+        for (name, contents) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+
+        zip.finish().unwrap();
+    }
+
+    const MANIFEST: &str = "name = \"Test\"\nicon = \"art/icon.png\"\n";
+
+    #[test]
+    fn reads_directories_and_zips_alike() {
+        let root = temporary("pack-formats");
+        let files = [("pack.toml", MANIFEST), ("art/icon.png", "icon"), ("textures/block/stone.png", "stone")];
+
+        write_dir(&root.join("directory"), &files);
+        write_zip(&root.join("archive.zip"), &files);
+
+        for name in ["directory", "archive.zip"] {
+            let pack = ResourcePack::open(root.join(name)).unwrap();
+
+            assert_eq!(pack.manifest().name, "Test");
+            assert_eq!(pack.icon().as_deref(), Some(&b"icon"[..]));
+            assert_eq!(pack.read("textures/block/stone.png").unwrap().unwrap(), b"stone");
+            assert!(pack.read("textures/block/dirt.png").is_none());
+            assert_eq!(pack.list("textures/"), ["textures/block/stone.png"]);
+        }
+
+        assert!(ResourcePack::open(root.join("missing")).is_err());
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_paths_leading_out_of_the_pack() {
+        let root = temporary("pack-escape");
+        write_dir(&root, &[("secret.txt", "secret"), ("pack/pack.toml", MANIFEST), ("pack/textures/a.png", "a")]);
+
+        let pack = ResourcePack::open(root.join("pack")).unwrap();
+        let secret = root.join("secret.txt");
+
+        for path in ["../secret.txt", "textures/../../secret.txt", secret.to_str().unwrap(), "textures\\a.png"] {
+            assert!(!pack.contains(path), "{path}");
+            assert!(pack.read(path).unwrap().is_err(), "{path}");
+        }
+
+        assert!(pack.contains("./textures/a.png"));
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn rejects_icons_outside_the_pack() {
+        let root = temporary("pack-icon");
+        write_dir(&root, &[("secret.png", "secret"), ("pack/pack.toml", "name = \"Test\"\nicon = \"../secret.png\"\n")]);
+
+        assert!(ResourcePack::open(root.join("pack")).err().unwrap().contains("icon"));
+        assert!(PackManifest::parse("name = \"Test\"\nicon = \"/etc/passwd\"").is_err());
+        assert_eq!(PackManifest::parse("name = \"Test\"").unwrap().icon, "pack.png");
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
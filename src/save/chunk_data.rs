@@ -0,0 +1,298 @@
+use crate::block::BlockRegistry;
+use crate::world::{BiomeId, BlockState, Chunk, ChunkPos, ChunkSection, SECTION_COUNT, SECTION_SIZE, SECTION_VOLUME};
+
+use std::collections::HashMap;
+
+/// Version of the chunk format written by [`encode_chunk`].
+const CHUNK_VERSION: u8 = 1;
+
+/// Turns the blocks and biomes of `chunk` into bytes for a region file.
+///
+/// Blocks are saved by the full names of their states, so that chunks still load after
+/// blocks are added or removed. Light and heightmaps are left out, as they are rebuilt from
+/// the blocks when the chunk is loaded.
+///
+/// The format is little-endian: the version, the chunk position as two `i32`, the biome of
+/// each column in (Z, X) order, then a `u16` count of state names each written as a `u16`
+/// length and its UTF-8 bytes. Each section from the bottom follows, as a `u16` count of
+/// palette entries, 0 for sections of air only, the index of each entry into the names, then
+/// the palette index of every block packed into as few bits as the palette needs, in the
+/// storage order of [`ChunkSection`], unless the palette has a single entry.
+pub fn encode_chunk(chunk: &Chunk, blocks: &BlockRegistry) -> Vec<u8> {
+    let mut bytes = vec![CHUNK_VERSION];
+    bytes.extend_from_slice(&chunk.pos().x.to_le_bytes());
+    bytes.extend_from_slice(&chunk.pos().z.to_le_bytes());
+
+    for z in 0..SECTION_SIZE {
+        for x in 0..SECTION_SIZE {
+            bytes.push(chunk.biome(x, z).id());
+        }
+    }
+
+    let mut names = Vec::new();
+    let mut name_indices = HashMap::new();
+    let mut sections = Vec::new();
+
+    for section in chunk.sections() {
+        let mut section_bytes = Vec::new();
+
+        if section.is_empty() {
+            section_bytes.extend_from_slice(&0u16.to_le_bytes());
+            sections.push(section_bytes);
+            continue;
+        }
+
+        let mut palette = Vec::new();
+        let mut palette_indices = HashMap::new();
+
+        let indices: Vec<u16> = section
+            .iter()
+            .map(|(_, _, _, state)| {
+                *palette_indices.entry(state).or_insert_with(|| {
+                    palette.push(state);
+                    palette.len() as u16 - 1
+                })
+            })
+            .collect();
+
+        section_bytes.extend_from_slice(&(palette.len() as u16).to_le_bytes());
+
+        for state in palette.iter() {
+            let name = *name_indices.entry(*state).or_insert_with(|| {
+                names.push(blocks.state_name(*state));
+                names.len() as u16 - 1
+            });
+
+            section_bytes.extend_from_slice(&name.to_le_bytes());
+        }
+
+        if palette.len() > 1 {
+            pack_indices(&mut section_bytes, &indices, bits_for(palette.len()));
+        }
+
+        sections.push(section_bytes);
+    }
+
+    bytes.extend_from_slice(&(names.len() as u16).to_le_bytes());
+
+    for name in names {
+        bytes.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(name.as_bytes());
+    }
+
+    bytes.extend(sections.into_iter().flatten());
+    bytes
+}
+
+/// Rebuilds the chunk at `pos` from what [`encode_chunk`] made.
+///
+/// States `blocks` no longer knows become air, and are reported on the standard error once
+/// per chunk. Light and heightmaps are left empty.
+///
+/// # Returns
+/// * `Err(String)` if the data is truncated, of an unknown version or for another chunk.
+pub fn decode_chunk(bytes: &[u8], pos: ChunkPos, blocks: &BlockRegistry) -> Result<Chunk, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+
+    let version = reader.u8()?;
+
+    if version != CHUNK_VERSION {
+        return Err(format!("unknown chunk version {version}"));
+    }
+
+    let saved = ChunkPos::new(reader.i32()?, reader.i32()?);
+
+    if saved != pos {
+        return Err(format!("chunk {}, {} saved as {}, {}", pos.x, pos.z, saved.x, saved.z));
+    }
+
+    let mut chunk = Chunk::new(pos);
+    let biomes = reader.take(SECTION_SIZE * SECTION_SIZE)?;
+
+    for (index, &biome) in biomes.iter().enumerate() {
+        chunk.set_biome(index % SECTION_SIZE, index / SECTION_SIZE, BiomeId::new(biome));
+    }
+
+    let mut unknown = Vec::new();
+    let name_count = reader.u16()?;
+    let mut states = Vec::with_capacity(name_count as usize);
+
+    for _ in 0..name_count {
+        let len = reader.u16()? as usize;
+        let name = std::str::from_utf8(reader.take(len)?).map_err(|e| format!("invalid block name: {e}"))?;
+
+        states.push(blocks.parse_state(name).unwrap_or_else(|_| {
+            unknown.push(name.to_string());
+            BlockState::AIR
+        }));
+    }
+
+    if !unknown.is_empty() {
+        eprintln!("Chunk {}, {} has unknown blocks, replaced with air: {}", pos.x, pos.z, unknown.join(", "));
+    }
+
+    for index in 0..SECTION_COUNT {
+        let palette_len = reader.u16()? as usize;
+
+        if palette_len == 0 {
+            continue;
+        }
+
+        let palette = (0..palette_len)
+            .map(|_| {
+                let name = reader.u16()? as usize;
+                states.get(name).copied().ok_or_else(|| format!("block name {name} out of {}", states.len()))
+            })
+            .collect::<Result<Vec<BlockState>, String>>()?;
+
+        let section = chunk.section_mut(index);
+
+        if palette_len == 1 {
+            section.fill(palette[0]);
+            continue;
+        }
+
+        let bits = bits_for(palette_len);
+        let packed = reader.take((SECTION_VOLUME * bits as usize).div_ceil(8))?;
+
+        for block in 0..SECTION_VOLUME {
+            let entry = unpack_index(packed, block, bits) as usize;
+            let state = *palette.get(entry).ok_or_else(|| format!("palette index {entry} out of {palette_len}"))?;
+            let (x, y, z) = ChunkSection::coords(block);
+            section.set(x, y, z, state);
+        }
+    }
+
+    chunk.compact();
+    Ok(chunk)
+}
+
+/// Returns the number of bits needed for indices into a palette of `len` entries.
+fn bits_for(len: usize) -> u32 {
+    usize::BITS - (len - 1).leading_zeros()
+}
+
+/// Appends `indices` to `bytes`, `bits` each, starting from the lowest bit of each byte.
+fn pack_indices(bytes: &mut Vec<u8>, indices: &[u16], bits: u32) {
+    let start = bytes.len();
+    bytes.resize(start + (indices.len() * bits as usize).div_ceil(8), 0);
+
+    for (i, &index) in indices.iter().enumerate() {
+        for bit in 0..bits {
+            if index & 1 << bit != 0 {
+                let position = i * bits as usize + bit as usize;
+                bytes[start + position / 8] |= 1 << (position % 8);
+            }
+        }
+    }
+}
+
+/// Returns the `index`th value of `bits` bits packed by [`pack_indices`].
+fn unpack_index(packed: &[u8], index: usize, bits: u32) -> u32 {
+    (0..bits).fold(0, |value, bit| {
+        let position = index * bits as usize + bit as usize;
+        value | ((packed[position / 8] >> (position % 8)) as u32 & 1) << bit
+    })
+}
+
+/// Reads the fields of a saved chunk one after the other.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let taken = self.bytes.get(self.pos..self.pos + len).ok_or_else(|| format!("chunk data truncated at byte {}", self.pos))?;
+        self.pos += len;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().expect("two bytes")))
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().expect("four bytes")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockRegistryBuilder;
+
+    const BLOCKS: &str = r#"
+        [stone]
+
+        [dirt]
+
+        [oak_log]
+        properties = { axis = ["x", "y", "z"] }
+    "#;
+
+    fn registry(source: &str) -> BlockRegistry {
+        let mut builder = BlockRegistryBuilder::new();
+        builder.load_str("test", source).unwrap();
+        builder.build().unwrap()
+    }
+
+    fn blocks(chunk: &Chunk) -> Vec<BlockState> {
+        chunk.sections().iter().flat_map(|section| section.iter().map(|(_, _, _, state)| state)).collect()
+    }
+
+    /// A chunk with a full section, mixed ones and biomes.
+    fn sample(registry: &BlockRegistry) -> Chunk {
+        let state = |name| registry.parse_state(name).unwrap();
+        let mut chunk = Chunk::new(ChunkPos::new(-7, 12));
+
+        chunk.fill((0, 0, 0), (15, 15, 15), state("test:stone"));
+
+        for index in 0..SECTION_VOLUME {
+            let (x, y, z) = ChunkSection::coords(index);
+            let name = ["test:dirt", "test:oak_log[axis=x]", "test:oak_log[axis=z]"][(x + y * 3 + z * 5) % 3];
+            chunk.set(x, 16 + y, z, state(name));
+        }
+
+        chunk.set(3, 200, 9, state("test:dirt"));
+        chunk.set_biome(4, 11, BiomeId::new(3));
+        chunk
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let registry = registry(BLOCKS);
+        let chunk = sample(&registry);
+        let decoded = decode_chunk(&encode_chunk(&chunk, &registry), chunk.pos(), &registry).unwrap();
+
+        assert!(blocks(&decoded) == blocks(&chunk));
+        assert_eq!(decoded.biome(4, 11), BiomeId::new(3));
+        assert_eq!(decoded.biome(11, 4), BiomeId::default());
+    }
+
+    #[test]
+    fn turns_unknown_blocks_into_air() {
+        let chunk = sample(&registry(BLOCKS));
+        let bytes = encode_chunk(&chunk, &registry(BLOCKS));
+        let without_dirt = registry("[stone]\n[oak_log]\nproperties = { axis = [\"x\", \"y\", \"z\"] }\n");
+        let decoded = decode_chunk(&bytes, chunk.pos(), &without_dirt).unwrap();
+
+        assert_eq!(decoded.get(3, 200, 9), BlockState::AIR);
+        assert_eq!(decoded.get(0, 0, 0), without_dirt.parse_state("test:stone").unwrap());
+    }
+
+    #[test]
+    fn rejects_other_chunks_and_truncated_data() {
+        let registry = registry(BLOCKS);
+        let chunk = sample(&registry);
+        let bytes = encode_chunk(&chunk, &registry);
+
+        assert!(decode_chunk(&bytes, ChunkPos::new(0, 0), &registry).is_err());
+        assert!(decode_chunk(&bytes[..bytes.len() - 1], chunk.pos(), &registry).is_err());
+        assert!(decode_chunk(&[2], chunk.pos(), &registry).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use super::lz4;
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// How chunks are compressed in region files.
+///
+/// Each chunk records its own compression, so a world can switch between them and still read
+/// the chunks saved before.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Smaller, slower to save.
+    #[default]
+    Zlib,
+
+    /// Larger, several times faster to save and load.
+    Lz4,
+}
+
+impl Compression {
+    /// Returns the number identifying the compression in region files.
+    ///
+    /// Zlib has the id Minecraft gives it. Our LZ4, a single raw block after its
+    /// little-endian `u32` size, cannot be read as Minecraft's lz4-java block stream, so it
+    /// takes an id Minecraft does not use, which runs from 1 to 4 and 127 for its own.
+    pub fn id(self) -> u8 {
+        match self {
+            Self::Zlib => 2,
+            Self::Lz4 => 64,
+        }
+    }
+
+    /// Returns the compression identified by `id` in region files.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            2 => Some(Self::Zlib),
+            64 => Some(Self::Lz4),
+            _ => None,
+        }
+    }
+
+    /// Compresses `data`.
+    pub fn compress(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::with_capacity(data.len() / 4), flate2::Compression::default());
+                encoder.write_all(data).expect("writing to memory cannot fail");
+                encoder.finish().expect("writing to memory cannot fail")
+            }
+            Self::Lz4 => lz4::compress(data),
+        }
+    }
+
+    /// Decompresses what [`compress`](Self::compress) made.
+    ///
+    /// # Returns
+    /// * `Err(String)` if `data` is corrupted.
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Zlib => {
+                let mut output = Vec::with_capacity(data.len() * 4);
+                ZlibDecoder::new(data).read_to_end(&mut output).map_err(|e| format!("corrupted zlib data: {e}"))?;
+                Ok(output)
+            }
+            Self::Lz4 => lz4::decompress(data),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Replaces the file at `path` with `bytes`, so that after a crash it holds either the old
+/// contents or the new ones in full, never a mix.
+///
+/// The bytes go to `<path>.tmp` first, are flushed to the disk, and the file is then renamed
+/// over `path`. Parent directories are created as needed.
+pub fn write_atomic(path: impl AsRef<Path>, bytes: &[u8]) -> Result<(), String> {
+    let path = path.as_ref();
+    let temporary = temporary_path(path);
+    let error = |path: &Path, e: std::io::Error| format!("{}: {e}", path.display());
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| error(parent, e))?;
+    }
+
+    let mut file = File::create(&temporary).map_err(|e| error(&temporary, e))?;
+    file.write_all(bytes).and_then(|_| file.sync_all()).map_err(|e| error(&temporary, e))?;
+    drop(file);
+
+    std::fs::rename(&temporary, path).map_err(|e| error(path, e))?;
+
+    // Makes the rename itself durable. Not every platform can open directories, and the file
+    // is complete either way, so failures are ignored.
+    if let Some(parent) = path.parent()
+        && let Ok(directory) = File::open(parent)
+    {
+        let _ = directory.sync_all();
+    }
+
+    Ok(())
+}
+
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
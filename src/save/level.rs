@@ -0,0 +1,120 @@
+use super::write_atomic;
+use crate::interaction::RANDOM_TICK_SPEED;
+use crate::world::WorldTime;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::Path;
+
+/// Name of the file holding the level data in a world directory.
+pub const LEVEL_FILE: &str = "level.toml";
+
+/// What a world keeps besides its chunks, stored as TOML in its directory.
+///
+/// # Example
+///
+/// ```toml
+/// seed = 104372264566766
+/// spawn = [8, 66, 8]
+/// time = 36200
+///
+/// [game_rules]
+/// do_daylight_cycle = false
+/// random_tick_speed = 3
+///
+/// [[scheduled_ticks]]
+/// pos = [12, 64, -3]
+/// block = "rustedcraft:sand"
+/// delay = 2
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelData {
+    /// Seed the terrain is generated from.
+    #[serde(serialize_with = "serialize_seed", deserialize_with = "deserialize_seed")]
+    pub seed: u64,
+
+    /// Block players appear at, as `[x, y, z]`, or `None` until it is picked on the first
+    /// start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spawn: Option<[i32; 3]>,
+
+    /// Ticks since the world was created, which sets the time of day.
+    #[serde(default)]
+    pub time: WorldTime,
+
+    /// Rules of play chosen for this world.
+    #[serde(default)]
+    pub game_rules: GameRules,
+
+    /// Block ticks waiting to happen when the world was saved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scheduled_ticks: Vec<SavedTick>,
+}
+
+/// Rules of play of a world, which players may change as it goes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameRules {
+    /// Whether the time of day moves on, rather than staying where it is.
+    pub do_daylight_cycle: bool,
+
+    /// Blocks picked for random ticks in each section per game tick, 0 to stop crops growing,
+    /// grass spreading and leaves decaying.
+    pub random_tick_speed: u32,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self { do_daylight_cycle: true, random_tick_speed: RANDOM_TICK_SPEED as u32 }
+    }
+}
+
+/// A block tick scheduled for later, as saved with the level.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SavedTick {
+    /// Position of the block, as `[x, y, z]`.
+    pub pos: [i32; 3],
+
+    /// Full name of the block ticked, which must still be there when the tick comes.
+    pub block: String,
+
+    /// Ticks left until it happens.
+    pub delay: u32,
+}
+
+impl LevelData {
+    /// Creates the data of a new world generated from `seed`, at sunrise of the first day.
+    pub fn new(seed: u64) -> Self {
+        Self { seed, spawn: None, time: WorldTime::default(), game_rules: GameRules::default(), scheduled_ticks: Vec::new() }
+    }
+
+    /// Loads the level data of the world in `directory`, or `None` if the world has none yet.
+    pub fn load(directory: impl AsRef<Path>) -> Result<Option<Self>, String> {
+        let path = directory.as_ref().join(LEVEL_FILE);
+
+        let source = match std::fs::read_to_string(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+            Ok(source) => source,
+        };
+
+        toml::from_str(&source).map(Some).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Writes the level data to the world in `directory`, creating it as needed. The file is
+    /// replaced atomically, so a crash leaves either the old data or the new.
+    pub fn save(&self, directory: impl AsRef<Path>) -> Result<(), String> {
+        let source = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        write_atomic(directory.as_ref().join(LEVEL_FILE), source.as_bytes())
+    }
+}
+
+// TOML integers are signed, so seeds past `i64::MAX` are stored as their two's complement.
+
+fn serialize_seed<S: Serializer>(seed: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(*seed as i64)
+}
+
+fn deserialize_seed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    i64::deserialize(deserializer).map(|seed| seed as u64)
+}
@@ -0,0 +1,243 @@
+//! The LZ4 block format, with the uncompressed size in front as a little-endian `u32`.
+//!
+//! Each sequence starts with a token whose high four bits count the literals copied as they
+//! are and whose low four bits count the bytes of the match after them, minus 4. Counts of
+//! 15 continue in the following bytes, 255 at a time. The match repeats what came `offset`
+//! bytes earlier, stored as two little-endian bytes after the literals. The last sequence
+//! has literals only.
+
+/// Shortest match worth encoding.
+const MIN_MATCH: usize = 4;
+
+/// The last match starts at least this many bytes before the end of the input.
+const MATCH_LIMIT: usize = 12;
+
+/// The last bytes of the input are always literals.
+const END_LITERALS: usize = 5;
+
+/// Farthest a match may reach back.
+const MAX_OFFSET: usize = u16::MAX as usize;
+
+/// Number of bits of the hashes of four bytes looked up to find matches.
+const HASH_BITS: u32 = 12;
+
+/// Most bytes reserved ahead of decompressing, whatever size the input claims.
+const MAX_RESERVED: usize = 1 << 24;
+
+/// Compresses `input`, greedily taking the first match found for every four bytes.
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() / 2 + 16);
+    output.extend_from_slice(&(input.len() as u32).to_le_bytes());
+
+    // Last position of each hash, plus one so that zero means none.
+    let mut table = vec![0u32; 1 << HASH_BITS];
+    let mut anchor = 0;
+    let mut pos = 0;
+
+    if input.len() > MATCH_LIMIT {
+        let match_limit = input.len() - MATCH_LIMIT;
+        let end_limit = input.len() - END_LITERALS;
+
+        while pos < match_limit {
+            let sequence = read_u32(input, pos);
+            let hash = (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize;
+            let candidate = std::mem::replace(&mut table[hash], pos as u32 + 1) as usize;
+
+            if candidate == 0 || pos - (candidate - 1) > MAX_OFFSET || read_u32(input, candidate - 1) != sequence {
+                pos += 1;
+                continue;
+            }
+
+            let candidate = candidate - 1;
+            let mut length = MIN_MATCH;
+
+            while pos + length < end_limit && input[candidate + length] == input[pos + length] {
+                length += 1;
+            }
+
+            write_sequence(&mut output, &input[anchor..pos], Some((pos - candidate, length)));
+            pos += length;
+            anchor = pos;
+        }
+    }
+
+    write_sequence(&mut output, &input[anchor..], None);
+    output
+}
+
+/// Decompresses what [`compress`] made.
+///
+/// # Returns
+/// * `Err(String)` if `input` is truncated, refers back past its start or does not
+///   decompress to the size in front of it.
+pub fn decompress(input: &[u8]) -> Result<Vec<u8>, String> {
+    let size = input.get(..4).ok_or("LZ4 data without a size")?;
+    let size = u32::from_le_bytes(size.try_into().expect("four bytes")) as usize;
+    let mut output = Vec::with_capacity(size.min(MAX_RESERVED));
+    let mut pos = 4;
+
+    loop {
+        let token = *input.get(pos).ok_or("truncated LZ4 data")?;
+        pos += 1;
+
+        let literals = read_length(input, &mut pos, (token >> 4) as usize)?;
+        let literals = input.get(pos..pos + literals).ok_or("truncated LZ4 literals")?;
+        output.extend_from_slice(literals);
+        pos += literals.len();
+
+        if pos == input.len() {
+            break;
+        }
+
+        let offset = input.get(pos..pos + 2).ok_or("truncated LZ4 offset")?;
+        let offset = u16::from_le_bytes([offset[0], offset[1]]) as usize;
+        pos += 2;
+
+        if offset == 0 || offset > output.len() {
+            return Err(format!("LZ4 match {offset} bytes back out of {}", output.len()));
+        }
+
+        let length = read_length(input, &mut pos, (token & 15) as usize)? + MIN_MATCH;
+
+        if output.len() + length > size {
+            return Err(String::from("LZ4 data larger than its size"));
+        }
+
+        // Matches may overlap what they copy, repeating it.
+        let start = output.len() - offset;
+
+        for index in start..start + length {
+            output.push(output[index]);
+        }
+    }
+
+    match output.len() == size {
+        true => Ok(output),
+        false => Err(format!("LZ4 data of {} bytes instead of {size}", output.len())),
+    }
+}
+
+fn read_u32(input: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(input[pos..pos + 4].try_into().expect("four bytes"))
+}
+
+/// Reads the rest of a count of `short`, from a token, when it is 15.
+fn read_length(input: &[u8], pos: &mut usize, short: usize) -> Result<usize, String> {
+    let mut length = short;
+
+    if short == 15 {
+        loop {
+            let byte = *input.get(*pos).ok_or("truncated LZ4 length")?;
+            *pos += 1;
+            length += byte as usize;
+
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+
+    Ok(length)
+}
+
+fn write_sequence(output: &mut Vec<u8>, literals: &[u8], found: Option<(usize, usize)>) {
+    let match_length = found.map_or(0, |(_, length)| length - MIN_MATCH);
+    output.push(((literals.len().min(15) << 4) | match_length.min(15)) as u8);
+
+    if literals.len() >= 15 {
+        write_length(output, literals.len() - 15);
+    }
+
+    output.extend_from_slice(literals);
+
+    if let Some((offset, _)) = found {
+        output.extend_from_slice(&(offset as u16).to_le_bytes());
+
+        if match_length >= 15 {
+            write_length(output, match_length - 15);
+        }
+    }
+}
+
+fn write_length(output: &mut Vec<u8>, mut length: usize) {
+    while length >= 255 {
+        output.push(255);
+        length -= 255;
+    }
+
+    output.push(length as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes that do not repeat, from a linear congruential generator.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 0x1234_5678u32;
+
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    fn round_trip(input: &[u8]) -> Vec<u8> {
+        let compressed = compress(input);
+        assert_eq!(decompress(&compressed).as_deref(), Ok(input));
+        compressed
+    }
+
+    #[test]
+    fn round_trips_empty_input() {
+        round_trip(&[]);
+        round_trip(b"short");
+    }
+
+    #[test]
+    fn round_trips_incompressible_input() {
+        let input = noise(10_000);
+        assert!(round_trip(&input).len() <= input.len() + input.len() / 255 + 16);
+    }
+
+    #[test]
+    fn round_trips_long_matches() {
+        let mut input = noise(300);
+        input.extend(std::iter::repeat_n(7, 100_000));
+        input.extend(noise(300));
+
+        assert!(round_trip(&input).len() < 1_500);
+    }
+
+    #[test]
+    fn round_trips_overlapping_copies() {
+        let input: Vec<u8> = b"abc".iter().copied().cycle().take(5_000).collect();
+        assert!(round_trip(&input).len() < 100);
+    }
+
+    #[test]
+    fn rejects_truncated_input() {
+        let compressed = compress(&noise(1_000).repeat(3));
+
+        for len in [0, 3, 4, 10, compressed.len() / 2, compressed.len() - 1] {
+            assert!(decompress(&compressed[..len]).is_err(), "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn rejects_malicious_input() {
+        // A match before anything was written.
+        assert!(decompress(&[8, 0, 0, 0, 0x04, 1, 0]).is_err());
+
+        // A match with an offset of zero.
+        assert!(decompress(&[8, 0, 0, 0, 0x14, b'a', 0, 0]).is_err());
+
+        // Far more than the size in front.
+        assert!(decompress(&[2, 0, 0, 0, 0x1f, b'a', 1, 0, 255, 255, 255, 10]).is_err());
+
+        // A huge size with hardly any data.
+        assert!(decompress(&[255, 255, 255, 255, 0x10, b'a']).is_err());
+    }
+}
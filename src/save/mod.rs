@@ -0,0 +1,16 @@
+mod chunk_data;
+mod compression;
+mod file;
+mod level;
+mod lz4;
+mod region;
+mod settings;
+mod storage;
+
+pub use chunk_data::{decode_chunk, encode_chunk};
+pub use compression::Compression;
+pub use file::write_atomic;
+pub use level::{GameRules, LevelData, SavedTick, LEVEL_FILE};
+pub use region::{RegionFile, RegionPos, REGION_SIZE};
+pub use settings::SaveSettings;
+pub use storage::{WorldStorage, REGION_DIR};
@@ -0,0 +1,446 @@
+use super::{write_atomic, Compression};
+use crate::world::ChunkPos;
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Width of a region in chunks, along both X and Z.
+pub const REGION_SIZE: i32 = 32;
+
+/// Number of chunks in a region.
+const REGION_CHUNKS: usize = (REGION_SIZE * REGION_SIZE) as usize;
+
+/// Size of the blocks region files are allocated in.
+const SECTOR_SIZE: usize = 4096;
+
+/// Sectors taken by the header: chunk locations, then timestamps.
+const HEADER_SECTORS: usize = 2;
+
+/// Most sectors a chunk can take, as its sector count is stored in one byte.
+const MAX_CHUNK_SECTORS: usize = 255;
+
+/// Bytes in front of the data of each chunk: its length, then its compression.
+const CHUNK_HEADER: usize = 5;
+
+/// Position of a region: the square of [`REGION_SIZE`] by [`REGION_SIZE`] chunks stored in
+/// one region file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionPos {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl RegionPos {
+    /// Returns the region holding the chunk at `pos`.
+    pub fn of(pos: ChunkPos) -> Self {
+        Self { x: pos.x.div_euclid(REGION_SIZE), z: pos.z.div_euclid(REGION_SIZE) }
+    }
+
+    /// Returns the name of the region file, `r.<x>.<z>.rcr`.
+    pub fn file_name(self) -> String {
+        format!("r.{}.{}.rcr", self.x, self.z)
+    }
+}
+
+/// A chunk as stored in a region file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StoredChunk {
+    compression: u8,
+
+    /// Seconds since the Unix epoch when the chunk was saved.
+    timestamp: u32,
+
+    /// Compressed data.
+    data: Vec<u8>,
+}
+
+/// The chunks of one region, as stored in a region file.
+///
+/// The layout follows Minecraft's region files. The file is split into sectors of 4 KiB;
+/// the first holds the location of each chunk as a big-endian `u32` of its first sector
+/// shifted left by 8, plus the number of sectors it takes, and the second holds the time
+/// each chunk was saved, in seconds since the Unix epoch. Chunks are indexed by their local
+/// `x + z * 32`, and absent ones have a location of zero. The data of each chunk starts
+/// with its length in bytes as a big-endian `u32`, counting the compression byte after it,
+/// and is padded to a whole number of sectors.
+///
+/// Regions are read and written whole, with each chunk still compressed; the data of a
+/// single chunk can also be read straight from the file with [`read_chunk`](Self::read_chunk).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegionFile {
+    chunks: Vec<Option<StoredChunk>>,
+
+    /// Index of each chunk left out when the region was read, with why.
+    skipped: Vec<(usize, String)>,
+}
+
+impl RegionFile {
+    /// Creates a region without any chunk.
+    pub fn new() -> Self {
+        Self { chunks: vec![None; REGION_CHUNKS], skipped: Vec::new() }
+    }
+
+    /// Reads the region file at `path`, or returns an empty region if there is none.
+    ///
+    /// Chunks whose location points outside the file are left out, so that the rest of a
+    /// damaged region can still be saved over.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the file cannot be read or is too short for its header.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+
+        match std::fs::read(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(format!("{}: {e}", path.display())),
+            Ok(bytes) => Self::from_bytes(&bytes).map_err(|e| format!("{}: {e}", path.display())),
+        }
+    }
+
+    /// Reads the data of the chunk at `pos` from the region file at `path` and decompresses
+    /// it, without reading the rest of the file. Returns `None` if the file or the chunk does
+    /// not exist.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the file cannot be read or the chunk is corrupted.
+    pub fn read_chunk(path: impl AsRef<Path>, pos: ChunkPos) -> Result<Option<Vec<u8>>, String> {
+        let path = path.as_ref();
+        let error = |e: String| format!("{}: chunk {}, {}: {e}", path.display(), pos.x, pos.z);
+
+        let mut file = match File::open(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+            Ok(file) => file,
+        };
+
+        let index = local_index(pos);
+        let mut location = [0; 4];
+
+        file.seek(SeekFrom::Start(index as u64 * 4))
+            .and_then(|_| file.read_exact(&mut location))
+            .map_err(|e| format!("{}: truncated header: {e}", path.display()))?;
+
+        let location = u32::from_be_bytes(location);
+
+        if location == 0 {
+            return Ok(None);
+        }
+
+        let (start, sectors) = ((location >> 8) as usize * SECTOR_SIZE, (location & 255) as usize);
+        let mut bytes = vec![0; sectors * SECTOR_SIZE];
+
+        file.seek(SeekFrom::Start(start as u64))
+            .and_then(|_| read_up_to(&mut file, &mut bytes))
+            .map_err(|e| error(e.to_string()))
+            .and_then(|read| parse_chunk(&bytes[..read]))
+            .and_then(|(compression, data)| decompress(compression, data))
+            .map(Some)
+            .map_err(error)
+    }
+
+    /// Returns the data of the chunk at `pos` decompressed, or `None` if the region does not
+    /// hold it.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the chunk is corrupted.
+    pub fn get(&self, pos: ChunkPos) -> Result<Option<Vec<u8>>, String> {
+        match &self.chunks[local_index(pos)] {
+            Some(chunk) => decompress(chunk.compression, &chunk.data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the compression byte and the data of the chunk at `pos` as stored, still
+    /// compressed, or `None` if the region does not hold it. This is how chunks compressed
+    /// in ways [`Compression`] does not cover, such as those of Minecraft's own region files,
+    /// are read.
+    pub fn get_raw(&self, pos: ChunkPos) -> Option<(u8, &[u8])> {
+        self.chunks[local_index(pos)].as_ref().map(|chunk| (chunk.compression, chunk.data.as_slice()))
+    }
+
+    /// Iterates over the positions of the chunks held, taking the region to be at `region`.
+    pub fn positions(&self, region: RegionPos) -> impl Iterator<Item = ChunkPos> + '_ {
+        self.chunks.iter().enumerate().filter(|(_, chunk)| chunk.is_some()).map(move |(index, _)| chunk_pos(region, index))
+    }
+
+    /// Iterates over the positions of the chunks left out when the region was read, with
+    /// why, taking the region to be at `region`.
+    pub fn skipped(&self, region: RegionPos) -> impl Iterator<Item = (ChunkPos, &str)> + '_ {
+        self.skipped.iter().map(move |(index, reason)| (chunk_pos(region, *index), reason.as_str()))
+    }
+
+    /// Returns when the chunk at `pos` was saved, in seconds since the Unix epoch, or `None`
+    /// if the region does not hold it.
+    pub fn timestamp(&self, pos: ChunkPos) -> Option<u32> {
+        self.chunks[local_index(pos)].as_ref().map(|chunk| chunk.timestamp)
+    }
+
+    /// Stores `data` as the chunk at `pos`, compressed with `compression` and saved at
+    /// `timestamp`, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the chunk is still too large for a region file once compressed,
+    ///   in which case the region is left as it was.
+    pub fn insert(&mut self, pos: ChunkPos, data: &[u8], compression: Compression, timestamp: u32) -> Result<(), String> {
+        let data = compression.compress(data);
+
+        if sector_count(data.len()) > MAX_CHUNK_SECTORS {
+            return Err(format!("chunk {}, {} takes {} bytes, too large for a region file", pos.x, pos.z, data.len()));
+        }
+
+        self.chunks[local_index(pos)] = Some(StoredChunk { compression: compression.id(), timestamp, data });
+        Ok(())
+    }
+
+    /// Removes the chunk at `pos`, returning whether the region held it.
+    pub fn remove(&mut self, pos: ChunkPos) -> bool {
+        self.chunks[local_index(pos)].take().is_some()
+    }
+
+    /// Returns the number of chunks in the region.
+    pub fn len(&self) -> usize {
+        self.chunks.iter().filter(|chunk| chunk.is_some()).count()
+    }
+
+    /// Returns whether the region holds no chunk.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.iter().all(Option::is_none)
+    }
+
+    /// Writes the region to `path` through [`write_atomic`], so that a crash never leaves a
+    /// half-written region behind.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        write_atomic(path, &self.to_bytes())
+    }
+
+    /// Returns the contents of the region file, with chunks stored one after the other in
+    /// the order of their index.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut header = vec![0; HEADER_SECTORS * SECTOR_SIZE];
+        let mut body = Vec::new();
+
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let Some(chunk) = chunk else {
+                continue;
+            };
+
+            let sector = HEADER_SECTORS + body.len() / SECTOR_SIZE;
+            let sectors = sector_count(chunk.data.len());
+
+            header[index * 4..index * 4 + 4].copy_from_slice(&((sector as u32) << 8 | sectors as u32).to_be_bytes());
+            header[SECTOR_SIZE + index * 4..SECTOR_SIZE + index * 4 + 4].copy_from_slice(&chunk.timestamp.to_be_bytes());
+
+            body.extend_from_slice(&(chunk.data.len() as u32 + 1).to_be_bytes());
+            body.push(chunk.compression);
+            body.extend_from_slice(&chunk.data);
+            body.resize((sector - HEADER_SECTORS + sectors) * SECTOR_SIZE, 0);
+        }
+
+        header.extend_from_slice(&body);
+        header
+    }
+
+    /// Parses the contents of a region file, leaving out chunks pointing outside of it, which
+    /// are listed by [`skipped`](Self::skipped).
+    ///
+    /// # Returns
+    /// * `Err(String)` if `bytes` is too short for the header.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_SECTORS * SECTOR_SIZE {
+            return Err(format!("truncated header of {} bytes", bytes.len()));
+        }
+
+        let word = |offset: usize| u32::from_be_bytes(bytes[offset..offset + 4].try_into().expect("four bytes"));
+        let mut region = Self::new();
+
+        for (index, slot) in region.chunks.iter_mut().enumerate() {
+            let location = word(index * 4);
+
+            if location == 0 {
+                continue;
+            }
+
+            if ((location >> 8) as usize) < HEADER_SECTORS {
+                region.skipped.push((index, format!("location in the header at sector {}", location >> 8)));
+                continue;
+            }
+
+            let start = (location >> 8) as usize * SECTOR_SIZE;
+            let end = (start + (location & 255) as usize * SECTOR_SIZE).min(bytes.len());
+            let sectors = bytes.get(start..end).ok_or_else(|| format!("location at sector {} past the end of the file", location >> 8));
+
+            match sectors.and_then(parse_chunk) {
+                Ok((compression, data)) => *slot = Some(StoredChunk { compression, timestamp: word(SECTOR_SIZE + index * 4), data: data.to_vec() }),
+                Err(e) => region.skipped.push((index, e)),
+            }
+        }
+
+        Ok(region)
+    }
+}
+
+impl Default for RegionFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the index of the chunk at `pos` within its region.
+fn local_index(pos: ChunkPos) -> usize {
+    (pos.x.rem_euclid(REGION_SIZE) + pos.z.rem_euclid(REGION_SIZE) * REGION_SIZE) as usize
+}
+
+/// Returns the position of the chunk with local index `index` in the region at `region`.
+fn chunk_pos(region: RegionPos, index: usize) -> ChunkPos {
+    let (x, z) = (index as i32 % REGION_SIZE, index as i32 / REGION_SIZE);
+    ChunkPos::new(region.x * REGION_SIZE + x, region.z * REGION_SIZE + z)
+}
+
+/// Returns the number of sectors taken by a chunk of `len` compressed bytes.
+fn sector_count(len: usize) -> usize {
+    (CHUNK_HEADER + len).div_ceil(SECTOR_SIZE)
+}
+
+/// Splits the chunk stored at the start of `bytes` into its compression and data.
+fn parse_chunk(bytes: &[u8]) -> Result<(u8, &[u8]), String> {
+    let header = bytes.get(..CHUNK_HEADER).ok_or("truncated chunk header")?;
+    let len = u32::from_be_bytes(header[..4].try_into().expect("four bytes")) as usize;
+
+    if len == 0 {
+        return Err(String::from("chunk without a compression"));
+    }
+
+    let data = bytes.get(CHUNK_HEADER..4 + len).ok_or_else(|| format!("chunk of {len} bytes past its sectors"))?;
+    Ok((header[4], data))
+}
+
+fn decompress(compression: u8, data: &[u8]) -> Result<Vec<u8>, String> {
+    Compression::from_id(compression).ok_or_else(|| format!("unknown compression {compression}"))?.decompress(data)
+}
+
+/// Reads as much of `buffer` as `file` holds, returning the number of bytes read.
+fn read_up_to(file: &mut File, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut read = 0;
+
+    while read < buffer.len() {
+        match file.read(&mut buffer[read..])? {
+            0 => break,
+            n => read += n,
+        }
+    }
+
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bytes that do not compress, from a linear congruential generator.
+    fn noise(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    /// Returns a path of its own in the temporary directory, removed first.
+    fn temporary(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("rustedcraft-{}-{name}.rcr", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn holds_inserted_chunks() {
+        let mut region = RegionFile::new();
+        let (first, second) = (ChunkPos::new(-1, 3), ChunkPos::new(-32, 0));
+
+        region.insert(first, b"first chunk", Compression::Zlib, 10).unwrap();
+        region.insert(second, &noise(5_000, 1), Compression::Lz4, 20).unwrap();
+
+        assert_eq!(region.len(), 2);
+        assert_eq!(region.get(first), Ok(Some(b"first chunk".to_vec())));
+        assert_eq!(region.get(second), Ok(Some(noise(5_000, 1))));
+        assert_eq!(region.timestamp(second), Some(20));
+        assert_eq!(region.get(ChunkPos::new(0, 3)), Ok(None));
+
+        let mut positions: Vec<ChunkPos> = region.positions(RegionPos::of(first)).collect();
+        positions.sort_by_key(|pos| (pos.x, pos.z));
+        assert_eq!(positions, [second, first]);
+
+        assert!(region.remove(first));
+        assert_eq!(region.get(first), Ok(None));
+    }
+
+    #[test]
+    fn reads_what_it_writes() {
+        let path = temporary("reopen");
+        let pos = ChunkPos::new(5, 7);
+        let mut region = RegionFile::new();
+        region.insert(pos, b"saved", Compression::Zlib, 30).unwrap();
+        region.write(&path).unwrap();
+
+        let reopened = RegionFile::read(&path).unwrap();
+        assert_eq!(reopened, region);
+        assert_eq!(RegionFile::read_chunk(&path, pos), Ok(Some(b"saved".to_vec())));
+        assert_eq!(RegionFile::read_chunk(&path, ChunkPos::new(6, 7)), Ok(None));
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(RegionFile::read(&path), Ok(RegionFile::new()));
+    }
+
+    #[test]
+    fn moves_chunks_growing_past_their_sectors() {
+        let path = temporary("grow");
+        let (growing, after) = (ChunkPos::new(0, 0), ChunkPos::new(1, 0));
+
+        let mut region = RegionFile::new();
+        region.insert(growing, b"small", Compression::Zlib, 1).unwrap();
+        region.insert(after, b"next", Compression::Zlib, 1).unwrap();
+        region.write(&path).unwrap();
+
+        let mut region = RegionFile::read(&path).unwrap();
+        region.insert(growing, &noise(20_000, 2), Compression::Zlib, 2).unwrap();
+        region.write(&path).unwrap();
+
+        assert_eq!(RegionFile::read_chunk(&path, growing), Ok(Some(noise(20_000, 2))));
+        assert_eq!(RegionFile::read_chunk(&path, after), Ok(Some(b"next".to_vec())));
+        assert_eq!(RegionFile::read(&path).unwrap(), region);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rejects_chunks_too_large() {
+        let mut region = RegionFile::new();
+        let result = region.insert(ChunkPos::new(0, 0), &noise(MAX_CHUNK_SECTORS * SECTOR_SIZE, 3), Compression::Lz4, 0);
+
+        assert!(result.is_err());
+        assert!(region.is_empty());
+    }
+
+    #[test]
+    fn leaves_out_chunks_pointing_outside_the_file() {
+        let mut region = RegionFile::new();
+        region.insert(ChunkPos::new(0, 0), b"kept", Compression::Zlib, 0).unwrap();
+        region.insert(ChunkPos::new(1, 0), b"lost", Compression::Zlib, 0).unwrap();
+
+        let mut bytes = region.to_bytes();
+        bytes[4..8].copy_from_slice(&(40u32 << 8 | 1).to_be_bytes());
+        bytes[8..12].copy_from_slice(&(1u32 << 8 | 1).to_be_bytes());
+
+        let read = RegionFile::from_bytes(&bytes).unwrap();
+        let region_pos = RegionPos { x: 0, z: 0 };
+        let skipped: Vec<ChunkPos> = read.skipped(region_pos).map(|(pos, _)| pos).collect();
+
+        assert_eq!(read.positions(region_pos).collect::<Vec<_>>(), [ChunkPos::new(0, 0)]);
+        assert_eq!(skipped, [ChunkPos::new(1, 0), ChunkPos::new(2, 0)]);
+        assert!(RegionFile::from_bytes(&bytes[..100]).is_err());
+    }
+}
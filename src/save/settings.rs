@@ -0,0 +1,21 @@
+use super::Compression;
+
+use serde::{Deserialize, Serialize};
+
+/// How and how often the world is saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SaveSettings {
+    /// Compression of chunks saved from now on. Chunks saved before keep theirs.
+    pub compression: Compression,
+
+    /// Seconds between saves of the chunks changed and of the level while playing, 0 to only
+    /// save on exit.
+    pub autosave_interval: u32,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        Self { compression: Compression::Zlib, autosave_interval: 60 }
+    }
+}
@@ -0,0 +1,202 @@
+use super::{decode_chunk, encode_chunk, Compression, LevelData, RegionFile, RegionPos};
+use crate::block::BlockRegistry;
+use crate::world::{Chunk, ChunkPos};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory of the region files within a world directory.
+pub const REGION_DIR: &str = "region";
+
+/// Work for the saving thread.
+enum Message {
+    /// Chunks were added to those waiting to be written.
+    Chunks,
+    Level(LevelData),
+
+    /// Answers once everything sent before is written.
+    Flush(Sender<()>),
+}
+
+/// What the saving thread shares with the rest of the game.
+struct Shared {
+    directory: PathBuf,
+    blocks: Arc<BlockRegistry>,
+    compression: Compression,
+
+    /// Chunks waiting to be written, which loads read before the region files.
+    pending: Mutex<HashMap<ChunkPos, Arc<Chunk>>>,
+}
+
+/// Loads chunks from the region files of a world directory, and saves chunks and level data
+/// to it on a thread of its own.
+///
+/// Saving only takes a reference to the chunk, so it costs the game thread nothing; the
+/// chunk is encoded, compressed and written in the background, and chunks edited meanwhile
+/// are copied on write by their owner. The chunks of a region are written together, and
+/// every file is replaced atomically, so a crash loses at most the changes still waiting,
+/// never the world. Chunks waiting to be written are loaded as saved, so unloading a chunk
+/// and loading it back right away does not lose it.
+///
+/// The storage is meant to be shared behind an [`Arc`] with the threads loading chunks.
+/// Dropping it waits for everything saved to be written.
+pub struct WorldStorage {
+    shared: Arc<Shared>,
+    sender: Option<Sender<Message>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WorldStorage {
+    /// Opens the world in `directory`, whose chunks hold the states of `blocks` and are
+    /// written with `compression`. Nothing is created until something is saved.
+    pub fn new(directory: impl Into<PathBuf>, blocks: Arc<BlockRegistry>, compression: Compression) -> Self {
+        let shared = Arc::new(Shared { directory: directory.into(), blocks, compression, pending: Mutex::new(HashMap::new()) });
+        let (sender, receiver) = channel();
+        let thread_shared = shared.clone();
+
+        let thread = std::thread::Builder::new()
+            .name(String::from("world-saver"))
+            .spawn(move || save_loop(&thread_shared, &receiver))
+            .expect("failed to start the saving thread");
+
+        Self { shared, sender: Some(sender), thread: Some(thread) }
+    }
+
+    /// Returns the world directory.
+    pub fn directory(&self) -> &Path {
+        &self.shared.directory
+    }
+
+    /// Loads the chunk at `pos`, or returns `None` if it was never saved. The chunk has no
+    /// light yet.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the chunk is corrupted or its region file cannot be read.
+    pub fn load(&self, pos: ChunkPos) -> Result<Option<Chunk>, String> {
+        if let Some(chunk) = self.shared.pending.lock().expect("saving thread panicked").get(&pos) {
+            return Ok(Some(Chunk::clone(chunk)));
+        }
+
+        let path = self.shared.region_path(RegionPos::of(pos));
+
+        match RegionFile::read_chunk(&path, pos)? {
+            Some(data) => decode_chunk(&data, pos, &self.shared.blocks).map(Some).map_err(|e| format!("{}: {e}", path.display())),
+            None => Ok(None),
+        }
+    }
+
+    /// Queues `chunk` to be written, replacing any version of it still waiting.
+    pub fn save_chunk(&self, chunk: Arc<Chunk>) {
+        self.shared.pending.lock().expect("saving thread panicked").insert(chunk.pos(), chunk);
+        self.send(Message::Chunks);
+    }
+
+    /// Queues `level` to be written.
+    pub fn save_level(&self, level: LevelData) {
+        self.send(Message::Level(level));
+    }
+
+    /// Waits until everything queued so far is written.
+    pub fn flush(&self) {
+        let (sender, receiver) = channel();
+        self.send(Message::Flush(sender));
+        let _ = receiver.recv();
+    }
+
+    fn send(&self, message: Message) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(message);
+        }
+    }
+}
+
+impl Drop for WorldStorage {
+    fn drop(&mut self) {
+        // Closing the channel makes the thread return once everything is written.
+        self.sender = None;
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Shared {
+    fn region_path(&self, region: RegionPos) -> PathBuf {
+        self.directory.join(REGION_DIR).join(region.file_name())
+    }
+
+    /// Writes every chunk waiting, one region file at a time.
+    fn write_chunks(&self) {
+        let chunks: Vec<Arc<Chunk>> = self.pending.lock().expect("the game thread panicked").values().cloned().collect();
+        let mut regions: HashMap<RegionPos, Vec<Arc<Chunk>>> = HashMap::new();
+
+        for chunk in chunks {
+            regions.entry(RegionPos::of(chunk.pos())).or_default().push(chunk);
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs() as u32);
+
+        for (region, chunks) in regions {
+            let path = self.region_path(region);
+
+            let bytes = match std::fs::read(&path) {
+                Ok(bytes) => Some(bytes),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => {
+                    // The chunks stay waiting for the next write.
+                    eprintln!("Failed to save chunks, {} cannot be read: {e}", path.display());
+                    continue;
+                }
+            };
+
+            // A region too damaged to parse is set aside rather than overwritten.
+            let mut file = bytes.map_or_else(|| Ok(RegionFile::new()), |bytes| RegionFile::from_bytes(&bytes)).unwrap_or_else(|e| {
+                let corrupted = path.with_extension("rcr.corrupted");
+                eprintln!("Region file corrupted, moved to {}: {e}", corrupted.display());
+                let _ = std::fs::rename(&path, &corrupted);
+                RegionFile::new()
+            });
+
+            for chunk in chunks.iter() {
+                if let Err(e) = file.insert(chunk.pos(), &encode_chunk(chunk, &self.blocks), self.compression, timestamp) {
+                    eprintln!("Failed to save a chunk: {e}");
+                }
+            }
+
+            if let Err(e) = file.write(&path) {
+                eprintln!("Failed to save chunks: {e}");
+                continue;
+            }
+
+            // Chunks saved again meanwhile stay for the next write.
+            let mut pending = self.pending.lock().expect("the game thread panicked");
+
+            for chunk in chunks {
+                if pending.get(&chunk.pos()).is_some_and(|waiting| Arc::ptr_eq(waiting, &chunk)) {
+                    pending.remove(&chunk.pos());
+                }
+            }
+        }
+    }
+}
+
+fn save_loop(shared: &Shared, receiver: &Receiver<Message>) {
+    while let Ok(message) = receiver.recv() {
+        match message {
+            Message::Chunks => shared.write_chunks(),
+            Message::Level(level) => {
+                if let Err(e) = level.save(&shared.directory) {
+                    eprintln!("Failed to save the world: {e}");
+                }
+            }
+            Message::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::camera::CameraSettings;
+use crate::jobs::ChunkSettings;
+use crate::mesh::MeshSettings;
+use crate::render::RenderSettings;
+use crate::save::SaveSettings;
+use crate::window::WindowSettings;
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// User preferences persisted between runs, stored as TOML.
+///
+/// Every field has a default, so missing sections or keys are filled in and the file only
+/// needs to contain what the user changed.
+///
+/// # Example
+///
+/// ```toml
+/// [window]
+/// mode = "borderless"
+/// vsync = "adaptive"
+/// msaa_samples = 4
+///
+/// [window.context]
+/// major = 4
+/// minor = 5
+///
+/// [camera]
+/// fov = 90.0
+/// invert_y = true
+///
+/// [meshing]
+/// translucent = "greedy"
+/// smooth_lighting = true
+///
+/// [render]
+/// ao_strength = 0.5
+///
+/// [chunks]
+/// view_distance = 12
+///
+/// [save]
+/// compression = "lz4"
+/// autosave_interval = 300
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Window and display preferences.
+    pub window: WindowSettings,
+
+    /// Camera and mouse look preferences.
+    pub camera: CameraSettings,
+
+    /// Chunk meshing strategy of each render layer.
+    pub meshing: MeshSettings,
+
+    /// Shading preferences of the world renderer.
+    pub render: RenderSettings,
+
+    /// View distance and chunk building preferences.
+    pub chunks: ChunkSettings,
+
+    /// Chunk compression and autosave preferences.
+    pub save: SaveSettings,
+}
+
+impl Settings {
+    /// Loads the settings from `path`, using the defaults if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+
+        let source = match std::fs::read_to_string(path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("{}: {e}", path.display())),
+            Ok(source) => source,
+        };
+
+        toml::from_str(&source).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Writes the settings to `path`, creating parent directories as needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let source = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("{}: {e}", parent.display()))?;
+        }
+
+        std::fs::write(path, source).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
@@ -0,0 +1,247 @@
+use super::{list_monitors, DisplayMode, GlProfile, VsyncMode, WindowSettings};
+
+use glfw::{Context, Glfw, GlfwReceiver, OpenGlProfileHint, PWindow, SwapInterval};
+use glfw::{WindowEvent, WindowHint, WindowMode};
+
+/// The game window and its OpenGL context, configured from [`WindowSettings`].
+///
+/// Besides creating the window, this keeps the OpenGL viewport in sync with the framebuffer
+/// size, remembers the windowed position and size to restore after leaving fullscreen, and
+/// writes display changes back into its settings so they can be saved.
+pub struct GameWindow {
+    window: PWindow,
+    events: GlfwReceiver<(f64, WindowEvent)>,
+    settings: WindowSettings,
+
+    /// Position and size of the window the last time it was windowed.
+    windowed_rect: (i32, i32, u32, u32),
+
+    /// Size of the framebuffer in pixels.
+    framebuffer_size: (u32, u32),
+
+    /// Set when the framebuffer size changed and render targets must be resized.
+    resized: bool,
+}
+
+impl GameWindow {
+    /// Creates the window, makes its context current and loads the OpenGL functions.
+    ///
+    /// # Returns
+    /// * `Ok(GameWindow)` on success.
+    /// * `Err(String)` explaining the failure, typically an OpenGL version or profile that the
+    ///   graphics driver does not support.
+    pub fn create(glfw: &mut Glfw, settings: WindowSettings, title: &str) -> Result<Self, String> {
+        let context = settings.context;
+
+        let profile = match context.profile {
+            GlProfile::Core => OpenGlProfileHint::Core,
+            GlProfile::Compatibility => OpenGlProfileHint::Compat,
+            GlProfile::Any => OpenGlProfileHint::Any,
+        };
+
+        glfw.default_window_hints();
+        glfw.window_hint(WindowHint::ContextVersion(context.major, context.minor));
+        glfw.window_hint(WindowHint::OpenGlProfile(profile));
+        glfw.window_hint(WindowHint::OpenGlDebugContext(context.debug));
+        glfw.window_hint(WindowHint::OpenGlForwardCompat(cfg!(target_os = "macos")));
+        glfw.window_hint(WindowHint::Resizable(settings.resizable));
+        glfw.window_hint(WindowHint::Samples(Some(settings.msaa_samples)));
+        glfw.window_hint(WindowHint::ScaleToMonitor(settings.hidpi));
+        glfw.window_hint(WindowHint::CocoaRetinaFramebuffer(settings.hidpi));
+
+        let (mut window, events) = glfw
+            .create_window(settings.width, settings.height, title, WindowMode::Windowed)
+            .ok_or_else(|| {
+                format!(
+                    "Failed to create a window with an OpenGL {}.{} {:?} context. \
+                     Your graphics driver may not support this version; try another one \
+                     in the [window.context] section of the settings file.",
+                    context.major, context.minor, context.profile,
+                )
+            })?;
+
+        window.make_current();
+        gl::load_with(|s| window.get_proc_address(s));
+
+        let version = window.get_context_version();
+        let (major, minor) = (version.major as u32, version.minor as u32);
+
+        if (major, minor) < (context.major, context.minor) {
+            return Err(format!(
+                "OpenGL {}.{} was requested but the driver created a {major}.{minor} context",
+                context.major, context.minor,
+            ));
+        }
+
+        window.set_framebuffer_size_polling(true);
+        window.set_content_scale_polling(true);
+        window.set_size_polling(true);
+        window.set_pos_polling(true);
+
+        let (x, y) = window.get_pos();
+        let (width, height) = window.get_size();
+        let (fb_width, fb_height) = window.get_framebuffer_size();
+
+        let mut game_window = Self {
+            window,
+            events,
+            settings,
+            windowed_rect: (x, y, width.max(1) as u32, height.max(1) as u32),
+            framebuffer_size: (fb_width.max(0) as u32, fb_height.max(0) as u32),
+            resized: true,
+        };
+
+        if settings.msaa_samples > 0 {
+            unsafe { gl::Enable(gl::MULTISAMPLE) };
+        }
+
+        game_window.set_vsync(settings.vsync);
+        game_window.set_display_mode(settings.mode);
+        game_window.update_viewport();
+
+        Ok(game_window)
+    }
+
+    /// Returns the underlying GLFW window.
+    pub fn window(&self) -> &PWindow {
+        &self.window
+    }
+
+    /// Returns the underlying GLFW window mutably.
+    pub fn window_mut(&mut self) -> &mut PWindow {
+        &mut self.window
+    }
+
+    /// Returns the receiver of window events.
+    pub fn events(&self) -> &GlfwReceiver<(f64, WindowEvent)> {
+        &self.events
+    }
+
+    /// Returns the current settings, including changes made while running.
+    pub fn settings(&self) -> &WindowSettings {
+        &self.settings
+    }
+
+    /// Returns the size of the framebuffer in pixels.
+    pub fn framebuffer_size(&self) -> (u32, u32) {
+        self.framebuffer_size
+    }
+
+    /// Returns the content scale of the window, e.g. `(2.0, 2.0)` on a high density display.
+    pub fn content_scale(&self) -> (f32, f32) {
+        self.window.get_content_scale()
+    }
+
+    /// Returns the new framebuffer size if it changed since the last call.
+    ///
+    /// Render targets sized after the framebuffer should be recreated when this returns
+    /// `Some`. The OpenGL viewport is already updated.
+    pub fn take_resize(&mut self) -> Option<(u32, u32)> {
+        std::mem::take(&mut self.resized).then_some(self.framebuffer_size)
+    }
+
+    /// Updates the window state from an event. Call for every event received.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        let windowed = self.settings.mode == DisplayMode::Windowed;
+
+        match *event {
+            WindowEvent::FramebufferSize(width, height) => {
+                self.framebuffer_size = (width.max(0) as u32, height.max(0) as u32);
+                self.resized = true;
+                self.update_viewport();
+            }
+
+            WindowEvent::Size(width, height) if windowed && width > 0 && height > 0 => {
+                self.windowed_rect.2 = width as u32;
+                self.windowed_rect.3 = height as u32;
+                self.settings.width = width as u32;
+                self.settings.height = height as u32;
+            }
+
+            WindowEvent::Pos(x, y) if windowed => {
+                self.windowed_rect.0 = x;
+                self.windowed_rect.1 = y;
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Switches between windowed mode and the configured fullscreen mode.
+    pub fn toggle_fullscreen(&mut self) {
+        match self.settings.mode {
+            DisplayMode::Windowed => self.set_display_mode(self.settings.fullscreen_mode),
+            _ => self.set_display_mode(DisplayMode::Windowed),
+        }
+    }
+
+    /// Changes how the window occupies the screen.
+    ///
+    /// Fullscreen modes use the monitor selected in the settings, falling back to the primary
+    /// monitor if it is no longer connected.
+    pub fn set_display_mode(&mut self, mode: DisplayMode) {
+        if self.settings.mode == DisplayMode::Windowed && mode != DisplayMode::Windowed {
+            let (x, y) = self.window.get_pos();
+            let (width, height) = self.window.get_size();
+            self.windowed_rect = (x, y, width.max(1) as u32, height.max(1) as u32);
+        }
+
+        self.settings.mode = mode;
+
+        if mode == DisplayMode::Windowed {
+            let (x, y, width, height) = self.windowed_rect;
+            self.window.set_monitor(WindowMode::Windowed, x, y, width, height, None);
+            return;
+        }
+
+        let mut glfw = self.window.glfw.clone();
+        let monitors = list_monitors(&mut glfw);
+
+        let Some(info) = self.settings.monitor.and_then(|i| monitors.get(i)).or(monitors.first()) else {
+            self.settings.mode = DisplayMode::Windowed;
+            return;
+        };
+
+        let current = info.current;
+        let wanted = self.settings.video_mode;
+
+        let video_mode = match mode {
+            DisplayMode::Fullscreen => wanted.and_then(|wanted| info.closest_mode(wanted)).or(current),
+            _ => current,
+        };
+
+        let Some(video_mode) = video_mode else { return };
+        let index = info.index;
+        let window = &mut self.window;
+
+        glfw.with_connected_monitors(|_, monitors| {
+            let Some(monitor) = monitors.get(index) else { return };
+
+            window.set_monitor(
+                WindowMode::FullScreen(monitor),
+                0,
+                0,
+                video_mode.width,
+                video_mode.height,
+                Some(video_mode.refresh_rate),
+            );
+        });
+    }
+
+    /// Changes vertical synchronization. The context must be current.
+    pub fn set_vsync(&mut self, vsync: VsyncMode) {
+        let interval = match vsync {
+            VsyncMode::Off => SwapInterval::None,
+            VsyncMode::On => SwapInterval::Sync(1),
+            VsyncMode::Adaptive => SwapInterval::Adaptive,
+        };
+
+        self.settings.vsync = vsync;
+        self.window.glfw.set_swap_interval(interval);
+    }
+
+    fn update_viewport(&self) {
+        let (width, height) = self.framebuffer_size;
+        unsafe { gl::Viewport(0, 0, width as i32, height as i32) };
+    }
+}
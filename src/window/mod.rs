@@ -0,0 +1,7 @@
+mod game_window;
+mod settings;
+mod monitor;
+
+pub use settings::{WindowSettings, DisplayMode, VsyncMode, VideoMode, GlProfile, ContextSettings};
+pub use monitor::{MonitorInfo, list_monitors};
+pub use game_window::GameWindow;
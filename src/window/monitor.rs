@@ -0,0 +1,53 @@
+use super::VideoMode;
+use glfw::{Glfw, Monitor, VidMode};
+
+/// Information about a connected monitor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    /// Position of the monitor in the list of connected monitors.
+    pub index: usize,
+
+    /// Human readable name of the monitor.
+    pub name: String,
+
+    /// The video mode the monitor currently runs at.
+    pub current: Option<VideoMode>,
+
+    /// Every video mode the monitor supports.
+    pub modes: Vec<VideoMode>,
+
+    /// Ratio between the monitor's DPI and the platform's default DPI.
+    pub content_scale: (f32, f32),
+}
+
+impl MonitorInfo {
+    fn new(index: usize, monitor: &Monitor) -> Self {
+        Self {
+            index,
+            name: monitor.get_name().unwrap_or_else(|| format!("Monitor {}", index + 1)),
+            current: monitor.get_video_mode().map(video_mode),
+            modes: monitor.get_video_modes().into_iter().map(video_mode).collect(),
+            content_scale: monitor.get_content_scale(),
+        }
+    }
+
+    /// Returns the supported mode closest to `wanted`, preferring an exact resolution and then
+    /// the nearest refresh rate.
+    pub fn closest_mode(&self, wanted: VideoMode) -> Option<VideoMode> {
+        self.modes.iter().copied().min_by_key(|mode| {
+            let size = mode.width.abs_diff(wanted.width) + mode.height.abs_diff(wanted.height);
+            (size, mode.refresh_rate.abs_diff(wanted.refresh_rate))
+        })
+    }
+}
+
+/// Lists the connected monitors, the primary one first.
+pub fn list_monitors(glfw: &mut Glfw) -> Vec<MonitorInfo> {
+    glfw.with_connected_monitors(|_, monitors| {
+        monitors.iter().enumerate().map(|(index, monitor)| MonitorInfo::new(index, monitor)).collect()
+    })
+}
+
+fn video_mode(mode: VidMode) -> VideoMode {
+    VideoMode { width: mode.width, height: mode.height, refresh_rate: mode.refresh_rate }
+}
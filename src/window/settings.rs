@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+/// How the window occupies the screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayMode {
+    /// A regular decorated window.
+    #[default]
+    Windowed,
+
+    /// A window covering the whole monitor at its current video mode, without a mode switch.
+    Borderless,
+
+    /// Exclusive fullscreen, switching the monitor to the selected video mode.
+    Fullscreen,
+}
+
+/// When buffer swaps wait for the monitor's vertical blank.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VsyncMode {
+    /// Swap immediately, possibly tearing.
+    Off,
+
+    /// Wait for every vertical blank.
+    #[default]
+    On,
+
+    /// Wait for vertical blanks unless the frame is late, where supported.
+    Adaptive,
+}
+
+/// OpenGL context profile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlProfile {
+    #[default]
+    Core,
+    Compatibility,
+    Any,
+}
+
+/// A monitor resolution and refresh rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoMode {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_rate: u32,
+}
+
+/// The OpenGL context requested when creating the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextSettings {
+    /// Major version of the context.
+    pub major: u32,
+
+    /// Minor version of the context.
+    pub minor: u32,
+
+    /// Context profile.
+    pub profile: GlProfile,
+
+    /// Whether to create a debug context, which reports errors in more detail.
+    pub debug: bool,
+}
+
+impl Default for ContextSettings {
+    fn default() -> Self {
+        Self { major: 3, minor: 3, profile: GlProfile::Core, debug: false }
+    }
+}
+
+/// Window and display preferences.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// Width of the window in windowed mode, in screen coordinates.
+    pub width: u32,
+
+    /// Height of the window in windowed mode, in screen coordinates.
+    pub height: u32,
+
+    /// Whether the user can resize the window.
+    pub resizable: bool,
+
+    /// How the window occupies the screen.
+    pub mode: DisplayMode,
+
+    /// Mode used when toggling fullscreen from windowed mode.
+    pub fullscreen_mode: DisplayMode,
+
+    /// Index of the monitor used for fullscreen, or `None` for the primary monitor.
+    pub monitor: Option<usize>,
+
+    /// Video mode for exclusive fullscreen, or `None` for the monitor's current mode.
+    pub video_mode: Option<VideoMode>,
+
+    /// Vertical synchronization.
+    pub vsync: VsyncMode,
+
+    /// Multisample anti-aliasing samples per pixel, `0` to disable.
+    pub msaa_samples: u32,
+
+    /// Whether to scale the window with the monitor's content scale and use a full
+    /// resolution framebuffer on high density displays.
+    pub hidpi: bool,
+
+    /// The OpenGL context to create.
+    pub context: ContextSettings,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 854,
+            height: 480,
+            resizable: true,
+            mode: DisplayMode::Windowed,
+            fullscreen_mode: DisplayMode::Borderless,
+            monitor: None,
+            video_mode: None,
+            vsync: VsyncMode::On,
+            msaa_samples: 0,
+            hidpi: true,
+            context: ContextSettings::default(),
+        }
+    }
+}
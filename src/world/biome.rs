@@ -0,0 +1,23 @@
+/// Numeric identifier of a biome, such as plains or desert.
+///
+/// Identifiers follow the order biomes were loaded in, so like block states they are only
+/// meaningful together with the registry that assigned them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BiomeId(u8);
+
+impl BiomeId {
+    /// Creates a biome identifier from its number.
+    pub const fn new(id: u8) -> Self {
+        Self(id)
+    }
+
+    /// Returns the number of the identifier.
+    pub const fn id(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the identifier as an index into per-biome tables.
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
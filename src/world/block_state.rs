@@ -0,0 +1,26 @@
+/// Numeric identifier of a block state, such as "oak stairs facing north, bottom half".
+///
+/// Identifiers are dense: every state of every registered block gets its own number, starting
+/// from zero for air. They are only meaningful together with the registry that assigned them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockState(u16);
+
+impl BlockState {
+    /// The empty block. Always identifier zero.
+    pub const AIR: Self = Self(0);
+
+    /// Creates a block state from its numeric identifier.
+    pub const fn new(id: u16) -> Self {
+        Self(id)
+    }
+
+    /// Returns the numeric identifier.
+    pub const fn id(self) -> u16 {
+        self.0
+    }
+
+    /// Returns whether this is air.
+    pub const fn is_air(self) -> bool {
+        self.0 == 0
+    }
+}
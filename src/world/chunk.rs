@@ -0,0 +1,310 @@
+use super::{BiomeId, BlockState, ChunkPos, ChunkSection, Heightmap, LightArray, MAX_LIGHT, SECTION_SIZE};
+
+/// Number of sections stacked in a chunk column.
+pub const SECTION_COUNT: usize = 16;
+
+/// Height of a chunk column in blocks.
+pub const CHUNK_HEIGHT: usize = SECTION_COUNT * SECTION_SIZE;
+
+/// A 16x256x16 column of blocks, made of 16 stacked [`ChunkSection`]s.
+///
+/// Coordinates passed to a chunk are local: X and Z in `0..16`, Y in `0..256`. Biomes are
+/// stored per column, the same from the bottom of the world to the top.
+///
+/// Each block also has a sky light and a block light level, kept with a [`Heightmap`] of the
+/// columns. Changing blocks leaves them as they are; the light engine in
+/// [`crate::light`] keeps them up to date.
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pos: ChunkPos,
+    sections: Box<[ChunkSection; SECTION_COUNT]>,
+    biomes: Box<[BiomeId; SECTION_SIZE * SECTION_SIZE]>,
+    sky_light: Box<[LightArray; SECTION_COUNT]>,
+    block_light: Box<[LightArray; SECTION_COUNT]>,
+    heightmap: Heightmap,
+}
+
+impl Chunk {
+    /// Creates a chunk filled with air in full sky light, in the biome with identifier zero.
+    pub fn new(pos: ChunkPos) -> Self {
+        Self {
+            pos,
+            sections: Box::new(std::array::from_fn(|_| ChunkSection::new())),
+            biomes: Box::new([BiomeId::default(); SECTION_SIZE * SECTION_SIZE]),
+            sky_light: Box::new(std::array::from_fn(|_| LightArray::new(MAX_LIGHT))),
+            block_light: Box::new(std::array::from_fn(|_| LightArray::new(0))),
+            heightmap: Heightmap::new(),
+        }
+    }
+
+    /// Returns the position of the chunk.
+    pub fn pos(&self) -> ChunkPos {
+        self.pos
+    }
+
+    /// Returns the block state at local coordinates.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn get(&self, x: usize, y: usize, z: usize) -> BlockState {
+        self.sections[y / SECTION_SIZE].get(x, y % SECTION_SIZE, z)
+    }
+
+    /// Sets the block state at local coordinates, returning the previous one.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn set(&mut self, x: usize, y: usize, z: usize, state: BlockState) -> BlockState {
+        self.sections[y / SECTION_SIZE].set(x, y % SECTION_SIZE, z, state)
+    }
+
+    /// Returns the biome of the column at local `x` and `z`.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn biome(&self, x: usize, z: usize) -> BiomeId {
+        assert!(x < SECTION_SIZE && z < SECTION_SIZE, "column {x}, {z} outside of the chunk");
+        self.biomes[z * SECTION_SIZE + x]
+    }
+
+    /// Sets the biome of the column at local `x` and `z`.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn set_biome(&mut self, x: usize, z: usize, biome: BiomeId) {
+        assert!(x < SECTION_SIZE && z < SECTION_SIZE, "column {x}, {z} outside of the chunk");
+        self.biomes[z * SECTION_SIZE + x] = biome;
+    }
+
+    /// Returns the sky light level at local coordinates.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn sky_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.sky_light[y / SECTION_SIZE].get(x, y % SECTION_SIZE, z)
+    }
+
+    /// Sets the sky light level at local coordinates, returning the previous one.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn set_sky_light(&mut self, x: usize, y: usize, z: usize, level: u8) -> u8 {
+        self.sky_light[y / SECTION_SIZE].set(x, y % SECTION_SIZE, z, level)
+    }
+
+    /// Returns the block light level at local coordinates.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn block_light(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.block_light[y / SECTION_SIZE].get(x, y % SECTION_SIZE, z)
+    }
+
+    /// Sets the block light level at local coordinates, returning the previous one.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn set_block_light(&mut self, x: usize, y: usize, z: usize, level: u8) -> u8 {
+        self.block_light[y / SECTION_SIZE].set(x, y % SECTION_SIZE, z, level)
+    }
+
+    /// Returns the sky light of section `index`, counted from the bottom.
+    pub fn sky_light_section(&self, index: usize) -> &LightArray {
+        &self.sky_light[index]
+    }
+
+    /// Returns the sky light of section `index` mutably, counted from the bottom.
+    pub fn sky_light_section_mut(&mut self, index: usize) -> &mut LightArray {
+        &mut self.sky_light[index]
+    }
+
+    /// Returns the block light of section `index`, counted from the bottom.
+    pub fn block_light_section(&self, index: usize) -> &LightArray {
+        &self.block_light[index]
+    }
+
+    /// Returns the block light of section `index` mutably, counted from the bottom.
+    pub fn block_light_section_mut(&mut self, index: usize) -> &mut LightArray {
+        &mut self.block_light[index]
+    }
+
+    /// Returns the heights from which sky light falls freely in each column.
+    pub fn heightmap(&self) -> &Heightmap {
+        &self.heightmap
+    }
+
+    /// Returns the heightmap mutably.
+    pub fn heightmap_mut(&mut self) -> &mut Heightmap {
+        &mut self.heightmap
+    }
+
+    /// Sets every block in the box from `min` to `max` (both inclusive) to `state`.
+    ///
+    /// Sections entirely covered by the box are replaced in one step, without touching their
+    /// individual blocks.
+    ///
+    /// # Panics
+    /// Panics if the box is not inside the chunk.
+    pub fn fill(&mut self, min: (usize, usize, usize), max: (usize, usize, usize), state: BlockState) {
+        let (min_x, min_y, min_z) = min;
+        let (max_x, max_y, max_z) = max;
+
+        assert!(max_x < SECTION_SIZE && max_y < CHUNK_HEIGHT && max_z < SECTION_SIZE, "fill box outside of the chunk");
+
+        if min_x > max_x || min_y > max_y || min_z > max_z {
+            return;
+        }
+
+        let full_layer = min_x == 0 && min_z == 0 && max_x == SECTION_SIZE - 1 && max_z == SECTION_SIZE - 1;
+
+        for section_y in min_y / SECTION_SIZE..=max_y / SECTION_SIZE {
+            let base = section_y * SECTION_SIZE;
+            let from = min_y.max(base) - base;
+            let to = max_y.min(base + SECTION_SIZE - 1) - base;
+            let section = &mut self.sections[section_y];
+
+            if full_layer && from == 0 && to == SECTION_SIZE - 1 {
+                section.fill(state);
+                continue;
+            }
+
+            for y in from..=to {
+                for z in min_z..=max_z {
+                    for x in min_x..=max_x {
+                        section.set(x, y, z, state);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets every block of the chunk to `state`.
+    pub fn fill_all(&mut self, state: BlockState) {
+        for section in self.sections.iter_mut() {
+            section.fill(state);
+        }
+    }
+
+    /// Rebuilds every section and light array in its smallest form, typically once
+    /// generation or loading is done.
+    pub fn compact(&mut self) {
+        for section in self.sections.iter_mut() {
+            section.compact();
+        }
+
+        for light in self.sky_light.iter_mut().chain(self.block_light.iter_mut()) {
+            light.compact();
+        }
+    }
+
+    /// Returns the section at `index`, counted from the bottom.
+    pub fn section(&self, index: usize) -> &ChunkSection {
+        &self.sections[index]
+    }
+
+    /// Returns the section at `index` mutably, counted from the bottom.
+    pub fn section_mut(&mut self, index: usize) -> &mut ChunkSection {
+        &mut self.sections[index]
+    }
+
+    /// Returns all sections, from bottom to top.
+    pub fn sections(&self) -> &[ChunkSection] {
+        &self.sections[..]
+    }
+
+    /// Returns whether the chunk only contains air.
+    pub fn is_empty(&self) -> bool {
+        self.sections.iter().all(ChunkSection::is_empty)
+    }
+
+    /// Iterates over every block as `(x, y, z, state)` with local coordinates, from bottom to
+    /// top. Empty sections are skipped.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, usize, BlockState)> + '_ {
+        self.sections
+            .iter()
+            .enumerate()
+            .filter(|(_, section)| !section.is_empty())
+            .flat_map(|(index, section)| {
+                let base = index * SECTION_SIZE;
+                section.iter().map(move |(x, y, z, state)| (x, base + y, z, state))
+            })
+    }
+
+    /// Returns a hash of every block, independent of how blocks are stored and stable across
+    /// platforms and versions, for checking that chunks are generated the same every time.
+    pub fn content_hash(&self) -> u64 {
+        // FNV-1a over the state ids of all blocks, empty sections included.
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+
+        for section in self.sections.iter() {
+            for (_, _, _, state) in section.iter() {
+                for byte in state.id().to_le_bytes() {
+                    hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+                }
+            }
+        }
+
+        hash
+    }
+
+    /// Returns the number of bytes used by the chunk, including heap allocations.
+    pub fn memory_usage(&self) -> usize {
+        let light = self.sky_light.iter().chain(self.block_light.iter()).map(LightArray::memory_usage).sum::<usize>();
+        let sections = self.sections.iter().map(ChunkSection::memory_usage).sum::<usize>();
+        size_of::<Self>() + size_of_val(&*self.biomes) + self.heightmap.heap_size() + light + sections
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STONE: BlockState = BlockState::new(1);
+
+    #[test]
+    fn fills_part_of_sections() {
+        let mut chunk = Chunk::new(ChunkPos::new(0, 0));
+        chunk.fill((2, 10, 3), (5, 40, 7), STONE);
+
+        for (x, y, z, state) in chunk.iter() {
+            let inside = (2..=5).contains(&x) && (10..=40).contains(&y) && (3..=7).contains(&z);
+            assert_eq!(state == STONE, inside, "{x}, {y}, {z}");
+        }
+
+        assert_eq!(chunk.iter().filter(|(_, _, _, state)| *state == STONE).count(), 4 * 31 * 5);
+        assert!(chunk.sections()[3..].iter().all(ChunkSection::is_empty));
+    }
+
+    #[test]
+    fn fills_whole_sections_at_once() {
+        let mut chunk = Chunk::new(ChunkPos::new(0, 0));
+        chunk.set(3, 20, 3, BlockState::new(2));
+        chunk.fill((0, 12, 0), (15, 47, 15), STONE);
+
+        // Sections covered entirely hold a single value, those cut by the box a palette.
+        assert_eq!(chunk.section(0).blocks().bits(), 1);
+        assert_eq!(chunk.section(1).blocks().bits(), 0);
+        assert_eq!(chunk.section(2).blocks().bits(), 0);
+        assert!(chunk.section(3).is_empty());
+
+        assert_eq!(chunk.get(3, 20, 3), STONE);
+        assert_eq!(chunk.get(0, 11, 0), BlockState::AIR);
+        assert_eq!(chunk.get(15, 12, 15), STONE);
+        assert_eq!(chunk.section(0).non_air_count(), 4 * 256);
+
+        chunk.fill_all(BlockState::AIR);
+        assert!(chunk.is_empty());
+
+        // Empty boxes change nothing.
+        chunk.fill((5, 5, 5), (4, 5, 5), STONE);
+        assert!(chunk.is_empty());
+    }
+}
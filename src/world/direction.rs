@@ -0,0 +1,99 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// One of the six faces of a block.
+///
+/// North is towards -Z and east towards +X, so a camera with a yaw of zero faces north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    /// Every direction, in the order of their discriminants.
+    pub const ALL: [Self; 6] = [Self::Down, Self::Up, Self::North, Self::South, Self::West, Self::East];
+
+    /// The four directions parallel to the ground.
+    pub const HORIZONTAL: [Self; 4] = [Self::North, Self::South, Self::West, Self::East];
+
+    /// Returns the unit offset pointing in this direction.
+    pub const fn offset(self) -> (i32, i32, i32) {
+        match self {
+            Self::Down => (0, -1, 0),
+            Self::Up => (0, 1, 0),
+            Self::North => (0, 0, -1),
+            Self::South => (0, 0, 1),
+            Self::West => (-1, 0, 0),
+            Self::East => (1, 0, 0),
+        }
+    }
+
+    /// Returns the direction pointing the other way.
+    pub const fn opposite(self) -> Self {
+        match self {
+            Self::Down => Self::Up,
+            Self::Up => Self::Down,
+            Self::North => Self::South,
+            Self::South => Self::North,
+            Self::West => Self::East,
+            Self::East => Self::West,
+        }
+    }
+
+    /// Returns the direction a quarter turn clockwise around the Y axis, seen from above. Up
+    /// and down are left unchanged.
+    pub const fn rotate_clockwise(self) -> Self {
+        match self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+            vertical => vertical,
+        }
+    }
+
+    /// Returns the axis this direction lies on: 0 for X, 1 for Y, 2 for Z.
+    pub const fn axis(self) -> usize {
+        match self {
+            Self::West | Self::East => 0,
+            Self::Down | Self::Up => 1,
+            Self::North | Self::South => 2,
+        }
+    }
+
+    /// Returns whether this direction points towards positive coordinates.
+    pub const fn is_positive(self) -> bool {
+        matches!(self, Self::Up | Self::South | Self::East)
+    }
+
+    /// Returns the lowercase name used in data files, such as `"north"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Down => "down",
+            Self::Up => "up",
+            Self::North => "north",
+            Self::South => "south",
+            Self::West => "west",
+            Self::East => "east",
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL.into_iter().find(|d| d.name() == s).ok_or_else(|| format!("unknown direction: {s}"))
+    }
+}
@@ -0,0 +1,53 @@
+use super::{BlockState, Chunk, ChunkPos, CHUNK_HEIGHT, SECTION_SIZE};
+
+/// Produces the initial contents of chunks.
+///
+/// Generators run on worker threads, several chunks at once, so they must be shareable and
+/// give the same chunk for the same position whatever the order chunks are asked in.
+pub trait ChunkGenerator: Send + Sync {
+    /// Generates the chunk at `pos`.
+    fn generate(&self, pos: ChunkPos) -> Chunk;
+}
+
+/// A generator stacking the same layers of blocks everywhere.
+#[derive(Debug, Clone, Default)]
+pub struct FlatGenerator {
+    layers: Vec<(BlockState, usize)>,
+}
+
+impl FlatGenerator {
+    /// Creates a generator with no layers, producing empty chunks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `thickness` blocks of `state` above the layers added so far.
+    pub fn layer(mut self, state: BlockState, thickness: usize) -> Self {
+        self.layers.push((state, thickness));
+        self
+    }
+
+    /// Returns the height of the first air block above the layers.
+    pub fn height(&self) -> usize {
+        self.layers.iter().map(|&(_, thickness)| thickness).sum::<usize>().min(CHUNK_HEIGHT)
+    }
+}
+
+impl ChunkGenerator for FlatGenerator {
+    fn generate(&self, pos: ChunkPos) -> Chunk {
+        let mut chunk = Chunk::new(pos);
+        let mut y = 0;
+
+        for &(state, thickness) in &self.layers {
+            let top = (y + thickness).min(CHUNK_HEIGHT);
+
+            if top > y {
+                chunk.fill((0, y, 0), (SECTION_SIZE - 1, top - 1, SECTION_SIZE - 1), state);
+            }
+
+            y = top;
+        }
+
+        chunk
+    }
+}
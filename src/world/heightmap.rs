@@ -0,0 +1,58 @@
+use super::{CHUNK_HEIGHT, SECTION_SIZE};
+
+/// For every column of a chunk, the height from which sky light falls freely: one above the
+/// highest block that dims light, or zero if the column lets it through all the way down.
+///
+/// Blocks at and above this height are in full sky light, which is how lighting seeds it
+/// without walking every column from the top of the world.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap {
+    heights: Box<[u16; SECTION_SIZE * SECTION_SIZE]>,
+}
+
+impl Heightmap {
+    /// Creates a heightmap of a chunk with nothing dimming light.
+    pub fn new() -> Self {
+        Self { heights: Box::new([0; SECTION_SIZE * SECTION_SIZE]) }
+    }
+
+    /// Returns the height of the column at local `x` and `z`, in `0..=256`.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk.
+    #[inline]
+    pub fn get(&self, x: usize, z: usize) -> usize {
+        assert!(x < SECTION_SIZE && z < SECTION_SIZE, "column {x}, {z} outside of the chunk");
+        self.heights[z * SECTION_SIZE + x] as usize
+    }
+
+    /// Sets the height of the column at local `x` and `z`.
+    ///
+    /// # Panics
+    /// Panics if the coordinates are outside of the chunk or the height above the world.
+    #[inline]
+    pub fn set(&mut self, x: usize, z: usize, height: usize) {
+        assert!(x < SECTION_SIZE && z < SECTION_SIZE, "column {x}, {z} outside of the chunk");
+        assert!(height <= CHUNK_HEIGHT, "height {height} above the world");
+        self.heights[z * SECTION_SIZE + x] = height as u16;
+    }
+
+    /// Returns the lowest height of all columns.
+    pub fn min(&self) -> usize {
+        self.heights.iter().copied().min().unwrap_or(0) as usize
+    }
+
+    /// Returns the highest height of all columns.
+    pub fn max(&self) -> usize {
+        self.heights.iter().copied().max().unwrap_or(0) as usize
+    }
+
+    /// Returns the number of heap bytes used by the heightmap.
+    pub fn heap_size(&self) -> usize {
+        size_of_val(&*self.heights)
+    }
+}
+
+impl Default for Heightmap {
+    fn default() -> Self { Self::new() }
+}
@@ -0,0 +1,96 @@
+use super::{ChunkSection, SECTION_VOLUME};
+
+/// Highest light level.
+pub const MAX_LIGHT: u8 = 15;
+
+/// The light levels of a section, four bits per block.
+///
+/// Most sections are either in full daylight or in complete darkness, so a uniform array keeps
+/// a single level and allocates its 2 KiB of nibbles only once a block differs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightArray {
+    /// Two levels per byte, the block with the even index in the low nibble.
+    nibbles: Option<Box<[u8; SECTION_VOLUME / 2]>>,
+
+    /// The level of every block while `nibbles` is not allocated.
+    uniform: u8,
+}
+
+impl LightArray {
+    /// Creates an array with every block at `level`.
+    pub fn new(level: u8) -> Self {
+        Self { nibbles: None, uniform: level.min(MAX_LIGHT) }
+    }
+
+    /// Returns the level of the block at local coordinates.
+    #[inline]
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.get_index(ChunkSection::index(x, y, z))
+    }
+
+    /// Returns the level of the block at `index`, in [`ChunkSection::index`] order.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> u8 {
+        match &self.nibbles {
+            Some(nibbles) => (nibbles[index / 2] >> ((index & 1) * 4)) & 15,
+            None => self.uniform,
+        }
+    }
+
+    /// Sets the level of the block at local coordinates, returning the previous one.
+    #[inline]
+    pub fn set(&mut self, x: usize, y: usize, z: usize, level: u8) -> u8 {
+        self.set_index(ChunkSection::index(x, y, z), level)
+    }
+
+    /// Sets the level of the block at `index`, in [`ChunkSection::index`] order, returning
+    /// the previous one.
+    pub fn set_index(&mut self, index: usize, level: u8) -> u8 {
+        let level = level.min(MAX_LIGHT);
+
+        let nibbles = match &mut self.nibbles {
+            Some(nibbles) => nibbles,
+            None if level == self.uniform => return level,
+            None => self.nibbles.insert(Box::new([self.uniform * 0x11; SECTION_VOLUME / 2])),
+        };
+
+        let shift = (index & 1) * 4;
+        let old = (nibbles[index / 2] >> shift) & 15;
+        nibbles[index / 2] = (nibbles[index / 2] & !(15 << shift)) | (level << shift);
+        old
+    }
+
+    /// Sets every block to `level`, freeing the nibbles.
+    pub fn fill(&mut self, level: u8) {
+        *self = Self::new(level);
+    }
+
+    /// Returns the level of every block if they all have the same.
+    pub fn uniform(&self) -> Option<u8> {
+        match &self.nibbles {
+            Some(nibbles) => {
+                let first = nibbles[0];
+                (first >> 4 == first & 15 && nibbles.iter().all(|&byte| byte == first)).then_some(first & 15)
+            }
+            None => Some(self.uniform),
+        }
+    }
+
+    /// Frees the nibbles if every block has the same level.
+    pub fn compact(&mut self) {
+        if self.nibbles.is_some()
+            && let Some(level) = self.uniform()
+        {
+            self.fill(level);
+        }
+    }
+
+    /// Returns the number of bytes used by the array, including heap allocations.
+    pub fn memory_usage(&self) -> usize {
+        size_of::<Self>() + self.nibbles.as_ref().map_or(0, |nibbles| size_of_val(&**nibbles))
+    }
+}
+
+impl Default for LightArray {
+    fn default() -> Self { Self::new(0) }
+}
@@ -0,0 +1,25 @@
+mod packed_array;
+mod paletted;
+mod block_state;
+mod biome;
+mod section;
+mod light;
+mod heightmap;
+mod chunk;
+mod pos;
+mod direction;
+mod generator;
+mod time;
+
+pub use packed_array::PackedArray;
+pub use paletted::PalettedContainer;
+pub use block_state::BlockState;
+pub use biome::BiomeId;
+pub use section::{ChunkSection, SECTION_SIZE, SECTION_VOLUME};
+pub use light::{LightArray, MAX_LIGHT};
+pub use heightmap::Heightmap;
+pub use chunk::{Chunk, SECTION_COUNT, CHUNK_HEIGHT};
+pub use pos::{BlockPos, ChunkPos};
+pub use direction::Direction;
+pub use generator::{ChunkGenerator, FlatGenerator};
+pub use time::{WorldTime, MOON_PHASES, TICKS_PER_DAY};
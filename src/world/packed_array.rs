@@ -0,0 +1,142 @@
+/// A fixed-length array of unsigned integers packed into 64-bit words.
+///
+/// Each value takes `bits` bits. Values never straddle two words: each word holds
+/// `64 / bits` values and any leftover high bits are unused, which keeps reads to a single
+/// shift and mask.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedArray {
+    bits: u8,
+    len: usize,
+    data: Box<[u64]>,
+}
+
+impl PackedArray {
+    /// Creates an array of `len` zeroes, each stored on `bits` bits.
+    ///
+    /// # Panics
+    /// Panics if `bits` is not in `1..=32`.
+    pub fn new(bits: u8, len: usize) -> Self {
+        assert!((1..=32).contains(&bits), "packed values must use 1 to 32 bits");
+
+        let per_word = 64 / bits as usize;
+        let words = len.div_ceil(per_word);
+
+        Self { bits, len, data: vec![0; words].into_boxed_slice() }
+    }
+
+    /// Returns the number of bits used by each value.
+    pub fn bits(&self) -> u8 {
+        self.bits
+    }
+
+    /// Returns the number of values in the array.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the array holds no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the largest value that fits in the array.
+    pub fn max_value(&self) -> u32 {
+        (u64::MAX >> (64 - self.bits)) as u32
+    }
+
+    /// Returns the value at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> u32 {
+        assert!(index < self.len, "index {index} out of bounds for length {}", self.len);
+
+        let (word, shift) = self.locate(index);
+        ((self.data[word] >> shift) & self.mask()) as u32
+    }
+
+    /// Sets the value at `index`, returning the previous one.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds or `value` does not fit in the array's bits.
+    #[inline]
+    pub fn set(&mut self, index: usize, value: u32) -> u32 {
+        assert!(index < self.len, "index {index} out of bounds for length {}", self.len);
+        assert!(value <= self.max_value(), "{value} does not fit in {} bits", self.bits);
+
+        let (word, shift) = self.locate(index);
+        let mask = self.mask();
+        let old = (self.data[word] >> shift) & mask;
+
+        self.data[word] = (self.data[word] & !(mask << shift)) | ((value as u64) << shift);
+        old as u32
+    }
+
+    /// Iterates over every value in order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        let per_word = 64 / self.bits as usize;
+        let mask = self.mask();
+
+        self.data
+            .iter()
+            .flat_map(move |&word| (0..per_word).map(move |i| ((word >> (i * self.bits as usize)) & mask) as u32))
+            .take(self.len)
+    }
+
+    /// Returns the packed words backing the array.
+    pub fn words(&self) -> &[u64] {
+        &self.data
+    }
+
+    /// Returns the number of heap bytes used by the array.
+    pub fn heap_size(&self) -> usize {
+        self.data.len() * size_of::<u64>()
+    }
+
+    #[inline]
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.bits)
+    }
+
+    #[inline]
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let per_word = 64 / self.bits as usize;
+        (index / per_word, (index % per_word) * self.bits as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_values_of_every_width() {
+        for bits in 1..=32u8 {
+            let mut array = PackedArray::new(bits, 1000);
+            let max = array.max_value();
+            let value = |index: usize| (index as u64 * 2_654_435_761 % (max as u64 + 1)) as u32;
+
+            assert_eq!(max as u64, (1u64 << bits) - 1);
+            assert_eq!(array.words().len(), 1000usize.div_ceil(64 / bits as usize));
+
+            for index in 0..1000 {
+                assert_eq!(array.set(index, value(index)), 0);
+            }
+
+            for index in 0..1000 {
+                assert_eq!(array.get(index), value(index), "{bits} bits at {index}");
+            }
+
+            assert!(array.iter().eq((0..1000).map(value)));
+            assert_eq!(array.set(999, max), value(999));
+            assert_eq!(array.get(998), value(998));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn rejects_values_too_wide() {
+        PackedArray::new(4, 16).set(0, 16);
+    }
+}
@@ -0,0 +1,538 @@
+use super::PackedArray;
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Palettes longer than this get a hash map to find existing entries quickly.
+const LOOKUP_THRESHOLD: usize = 32;
+
+/// Widest palette index. Past this, values are stored directly, which is smaller than a wide
+/// index plus a long palette.
+const MAX_INDIRECT_BITS: u8 = 8;
+
+/// Storage backing a [`PalettedContainer`].
+#[derive(Debug, Clone)]
+enum Storage<T> {
+    /// Every entry holds the same value; nothing else is allocated.
+    Single(T),
+
+    /// Entries are indices into a palette of the distinct values present.
+    Indirect {
+        /// Distinct values, indexed by the packed data. Unused slots are listed in `free`.
+        palette: Vec<T>,
+
+        /// Number of entries referencing each palette slot.
+        counts: Vec<u16>,
+
+        /// Palette slots no longer referenced, to be reused before growing.
+        free: Vec<u16>,
+
+        /// Reverse index of `palette`, only built for long palettes.
+        lookup: Option<HashMap<T, u16>>,
+
+        /// Palette index of every entry.
+        data: PackedArray,
+    },
+
+    /// Every entry holds its value in full, for containers with too many distinct values.
+    Direct(Box<[T]>),
+}
+
+/// A fixed-length array compressed with a palette.
+///
+/// Instead of storing every value in full, the container keeps a palette of the distinct
+/// values present and stores each entry as an index into it, using only as many bits as the
+/// palette size requires. A uniform container stores a single value and no array at all.
+///
+/// The bit width grows when new distinct values no longer fit the palette, and shrinks again
+/// once enough values have disappeared, so memory follows the variety of the data. Past 256
+/// distinct values the palette is dropped and values are stored in full; call
+/// [`compact`](Self::compact) to go back to a palette once the variety has dropped again.
+#[derive(Debug, Clone)]
+pub struct PalettedContainer<T> {
+    storage: Storage<T>,
+    len: usize,
+}
+
+impl<T: Copy + Eq + Hash> PalettedContainer<T> {
+    /// Creates a container of `len` entries all set to `value`.
+    ///
+    /// # Panics
+    /// Panics if `len` is zero or larger than `u16::MAX`.
+    pub fn new(len: usize, value: T) -> Self {
+        assert!(len > 0 && len <= u16::MAX as usize, "container length must be in 1..=65535");
+        Self { storage: Storage::Single(value), len }
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always false: containers have at least one entry.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the number of bits used per entry, zero for a uniform container.
+    pub fn bits(&self) -> u8 {
+        match &self.storage {
+            Storage::Single(_) => 0,
+            Storage::Indirect { data, .. } => data.bits(),
+            Storage::Direct(_) => (size_of::<T>() * 8) as u8,
+        }
+    }
+
+    /// Returns the number of distinct values present.
+    pub fn unique_count(&self) -> usize {
+        match &self.storage {
+            Storage::Single(_) => 1,
+            Storage::Indirect { palette, free, .. } => palette.len() - free.len(),
+            Storage::Direct(values) => values.iter().collect::<HashSet<_>>().len(),
+        }
+    }
+
+    /// Returns the value at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> T {
+        assert!(index < self.len, "index {index} out of bounds for length {}", self.len);
+
+        match &self.storage {
+            Storage::Single(value) => *value,
+            Storage::Indirect { palette, data, .. } => palette[data.get(index) as usize],
+            Storage::Direct(values) => values[index],
+        }
+    }
+
+    /// Sets the value at `index`, returning the previous one.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, value: T) -> T {
+        assert!(index < self.len, "index {index} out of bounds for length {}", self.len);
+
+        match &mut self.storage {
+            Storage::Single(current) if *current == value => return value,
+            Storage::Single(current) => {
+                let current = *current;
+                self.storage = Storage::Indirect {
+                    palette: vec![current],
+                    counts: vec![self.len as u16],
+                    free: Vec::new(),
+                    lookup: None,
+                    data: PackedArray::new(1, self.len),
+                };
+            }
+            Storage::Indirect { .. } => {}
+            Storage::Direct(values) => return std::mem::replace(&mut values[index], value),
+        }
+
+        let Some(slot) = self.slot_for(value) else {
+            self.storage = Storage::Direct(self.iter().collect());
+            return self.set(index, value);
+        };
+        let Storage::Indirect { palette, counts, free, lookup, data } = &mut self.storage else {
+            unreachable!("storage was made indirect above")
+        };
+
+        let old_slot = data.set(index, slot as u32) as u16;
+        let old = palette[old_slot as usize];
+
+        counts[slot as usize] += 1;
+        counts[old_slot as usize] -= 1;
+
+        if counts[old_slot as usize] == 0 {
+            free.push(old_slot);
+
+            if let Some(lookup) = lookup {
+                lookup.remove(&old);
+            }
+
+            self.shrink_if_sparse();
+        }
+
+        old
+    }
+
+    /// Sets every entry to `value`, releasing the packed storage.
+    pub fn fill(&mut self, value: T) {
+        self.storage = Storage::Single(value);
+    }
+
+    /// Rebuilds the storage in its smallest form for the values currently present.
+    ///
+    /// Containers shrink on their own as values disappear, but only by large steps and never
+    /// out of direct storage. Compacting is worth doing once bulk edits are over, for example
+    /// after generating or loading a chunk.
+    pub fn compact(&mut self) {
+        let unique: HashSet<T> = self.iter().collect();
+
+        if unique.len() == 1 {
+            self.storage = Storage::Single(self.get(0));
+            return;
+        }
+
+        let bits = bits_for(unique.len());
+
+        match &self.storage {
+            Storage::Indirect { .. } => self.repack(bits),
+            Storage::Direct(values) if bits <= MAX_INDIRECT_BITS => {
+                let palette: Vec<T> = unique.into_iter().collect();
+                let slots: HashMap<T, u16> = palette.iter().enumerate().map(|(i, v)| (*v, i as u16)).collect();
+                let mut counts = vec![0u16; palette.len()];
+                let mut data = PackedArray::new(bits, self.len);
+
+                for (index, value) in values.iter().enumerate() {
+                    let slot = slots[value];
+                    counts[slot as usize] += 1;
+                    data.set(index, slot as u32);
+                }
+
+                let lookup = (palette.len() > LOOKUP_THRESHOLD).then_some(slots);
+                self.storage = Storage::Indirect { palette, counts, free: Vec::new(), lookup, data };
+            }
+            _ => {}
+        }
+    }
+
+    /// Iterates over every value in order.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        let (single, indirect, direct) = match &self.storage {
+            Storage::Single(value) => (Some(std::iter::repeat_n(*value, self.len)), None, None),
+            Storage::Indirect { palette, data, .. } => {
+                (None, Some(data.iter().map(move |slot| palette[slot as usize])), None)
+            }
+            Storage::Direct(values) => (None, None, Some(values.iter().copied())),
+        };
+
+        single.into_iter().flatten().chain(indirect.into_iter().flatten()).chain(direct.into_iter().flatten())
+    }
+
+    /// Iterates over the distinct values present, in no particular order.
+    pub fn palette(&self) -> impl Iterator<Item = T> + '_ {
+        let (single, indirect, direct) = match &self.storage {
+            Storage::Single(value) => (Some(*value), None, None),
+            Storage::Indirect { palette, counts, .. } => {
+                let used = palette.iter().zip(counts).filter(|(_, count)| **count > 0);
+                (None, Some(used.map(|(value, _)| *value)), None)
+            }
+            Storage::Direct(values) => (None, None, Some(values.iter().copied().collect::<HashSet<_>>())),
+        };
+
+        single.into_iter().chain(indirect.into_iter().flatten()).chain(direct.into_iter().flatten())
+    }
+
+    /// Returns how many entries hold `value`.
+    pub fn count(&self, value: T) -> usize {
+        match &self.storage {
+            Storage::Single(single) => if *single == value { self.len } else { 0 },
+            Storage::Indirect { palette, counts, .. } => palette
+                .iter()
+                .zip(counts)
+                .filter(|(entry, _)| **entry == value)
+                .map(|(_, count)| *count as usize)
+                .sum(),
+            Storage::Direct(values) => values.iter().filter(|entry| **entry == value).count(),
+        }
+    }
+
+    /// Returns the number of bytes used by the container, including heap allocations.
+    pub fn memory_usage(&self) -> usize {
+        let heap = match &self.storage {
+            Storage::Single(_) => 0,
+            Storage::Indirect { palette, counts, free, lookup, data } => {
+                palette.capacity() * size_of::<T>()
+                    + counts.capacity() * size_of::<u16>()
+                    + free.capacity() * size_of::<u16>()
+                    + lookup.as_ref().map_or(0, |l| l.capacity() * (size_of::<T>() + size_of::<u16>() + 1))
+                    + data.heap_size()
+            }
+            Storage::Direct(values) => values.len() * size_of::<T>(),
+        };
+
+        size_of::<Self>() + heap
+    }
+
+    /// Returns the palette slot holding `value`, adding it and growing the array as needed.
+    ///
+    /// Returns `None` if the palette is full and would grow past [`MAX_INDIRECT_BITS`].
+    fn slot_for(&mut self, value: T) -> Option<u16> {
+        if let Some(slot) = self.find(value) {
+            return Some(slot);
+        }
+
+        // Grow before inserting. Growing only happens with no free slot left, in which case
+        // every slot is in use and repacking keeps slot numbers unchanged.
+        if let Storage::Indirect { palette, free, data, .. } = &self.storage
+            && free.is_empty()
+            && palette.len() as u32 > data.max_value()
+        {
+            let bits = data.bits() + 1;
+
+            if bits > MAX_INDIRECT_BITS {
+                return None;
+            }
+
+            self.repack(bits);
+        }
+
+        let Storage::Indirect { palette, counts, free, lookup, .. } = &mut self.storage else {
+            unreachable!("only called on indirect storage")
+        };
+
+        let slot = match free.pop() {
+            Some(slot) => {
+                palette[slot as usize] = value;
+                slot
+            }
+            None => {
+                palette.push(value);
+                counts.push(0);
+                (palette.len() - 1) as u16
+            }
+        };
+
+        match lookup {
+            Some(lookup) => _ = lookup.insert(value, slot),
+            None if palette.len() > LOOKUP_THRESHOLD => {
+                let live = (0..palette.len()).filter(|&i| counts[i] > 0 || i == slot as usize);
+                *lookup = Some(live.map(|i| (palette[i], i as u16)).collect());
+            }
+            None => {}
+        }
+
+        Some(slot)
+    }
+
+    /// Returns the palette slot currently holding `value`, if any.
+    fn find(&self, value: T) -> Option<u16> {
+        let Storage::Indirect { palette, counts, lookup, .. } = &self.storage else {
+            return None;
+        };
+
+        match lookup {
+            Some(lookup) => lookup.get(&value).copied(),
+            None => palette
+                .iter()
+                .zip(counts)
+                .position(|(entry, count)| *entry == value && *count > 0)
+                .map(|slot| slot as u16),
+        }
+    }
+
+    /// Compacts the palette and lowers the bit width once few distinct values remain.
+    ///
+    /// Shrinking waits until two bits can be saved, so a palette hovering around a power of
+    /// two does not repack the array on every change.
+    fn shrink_if_sparse(&mut self) {
+        let unique = self.unique_count();
+
+        if unique == 1 {
+            let value = self.palette().next().expect("one value is present");
+            self.storage = Storage::Single(value);
+            return;
+        }
+
+        let needed = bits_for(unique);
+
+        if needed + 2 <= self.bits() {
+            self.repack(needed);
+        }
+    }
+
+    /// Rebuilds the packed array with `bits` bits per entry, dropping unused palette slots.
+    fn repack(&mut self, bits: u8) {
+        let Storage::Indirect { palette, counts, free, lookup, data } = &mut self.storage else {
+            return;
+        };
+
+        let mut remap = vec![0u16; palette.len()];
+        let mut new_palette = Vec::with_capacity(palette.len() - free.len());
+        let mut new_counts = Vec::with_capacity(new_palette.capacity());
+
+        for (slot, (&value, &count)) in palette.iter().zip(counts.iter()).enumerate() {
+            if count > 0 {
+                remap[slot] = new_palette.len() as u16;
+                new_palette.push(value);
+                new_counts.push(count);
+            }
+        }
+
+        let mut new_data = PackedArray::new(bits, self.len);
+
+        for (index, slot) in data.iter().enumerate() {
+            new_data.set(index, remap[slot as usize] as u32);
+        }
+
+        *lookup = (new_palette.len() > LOOKUP_THRESHOLD)
+            .then(|| new_palette.iter().enumerate().map(|(i, v)| (*v, i as u16)).collect());
+
+        *palette = new_palette;
+        *counts = new_counts;
+        *data = new_data;
+        free.clear();
+    }
+}
+
+impl<T: Copy + Eq + Hash + Default> Default for PalettedContainer<T> {
+    fn default() -> Self {
+        Self::new(4096, T::default())
+    }
+}
+
+/// Returns the number of bits needed to index `count` palette entries, at least one.
+fn bits_for(count: usize) -> u8 {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()).max(1) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEN: usize = 4096;
+
+    /// Returns a container whose entry `i` holds `i` for every `i` below `unique`, and zero
+    /// everywhere else.
+    fn with_values(unique: u16) -> PalettedContainer<u16> {
+        let mut container = PalettedContainer::new(LEN, 0);
+
+        for value in 1..unique {
+            container.set(value as usize, value);
+        }
+
+        container
+    }
+
+    fn assert_values(container: &PalettedContainer<u16>, expected: &[u16]) {
+        assert_eq!(container.iter().collect::<Vec<_>>(), expected);
+
+        for (index, &value) in expected.iter().enumerate() {
+            assert_eq!(container.get(index), value);
+        }
+    }
+
+    #[test]
+    fn grows_from_single_through_every_width_to_direct() {
+        let mut container = PalettedContainer::new(LEN, 0u16);
+        let mut expected = vec![0u16; LEN];
+        assert_eq!(container.bits(), 0);
+
+        for value in 1..=256u16 {
+            container.set(value as usize, value);
+            expected[value as usize] = value;
+
+            assert_eq!(container.unique_count(), value as usize + 1);
+            let expected_bits = match value {
+                ..=255 => bits_for(value as usize + 1),
+                _ => 16,
+            };
+
+            assert_eq!(container.bits(), expected_bits);
+        }
+
+        // The 257th distinct value does not fit in 8 bits.
+        assert!(matches!(container.storage, Storage::Direct(_)));
+        assert_eq!(container.bits(), 16);
+        assert_values(&container, &expected);
+    }
+
+    #[test]
+    fn shrinks_as_values_disappear() {
+        let mut container = with_values(64);
+        let mut expected: Vec<u16> = (0..LEN).map(|i| if i < 64 { i as u16 } else { 0 }).collect();
+        assert_eq!(container.bits(), 6);
+
+        for value in (1..64u16).rev() {
+            container.set(value as usize, 0);
+            expected[value as usize] = 0;
+
+            let unique = value as usize;
+            assert_eq!(container.unique_count(), unique);
+
+            // Each shrink waits until two bits can be saved.
+            match unique {
+                17..=64 => assert_eq!(container.bits(), 6, "{unique} values"),
+                5..=16 => assert_eq!(container.bits(), 4, "{unique} values"),
+                2..=4 => assert_eq!(container.bits(), 2, "{unique} values"),
+                _ => assert_eq!(container.bits(), 0),
+            }
+
+            assert_values(&container, &expected);
+        }
+
+        assert!(matches!(container.storage, Storage::Single(0)));
+    }
+
+    #[test]
+    fn compacts_back_to_a_palette() {
+        let mut container = with_values(300);
+        assert!(matches!(container.storage, Storage::Direct(_)));
+
+        for index in 3..300 {
+            container.set(index, 7);
+        }
+
+        // Direct storage never shrinks on its own.
+        assert_eq!(container.bits(), 16);
+
+        let expected: Vec<u16> = container.iter().collect();
+        container.compact();
+
+        assert_eq!((container.bits(), container.unique_count()), (2, 4));
+        assert_values(&container, &expected);
+
+        // Indirect storage keeping free slots is repacked to the width its values need.
+        let mut container = with_values(16);
+
+        for index in 2..16 {
+            container.set(index, 1);
+        }
+
+        // Two values left would fit in one bit, but shrinking stopped at two bits.
+        let expected: Vec<u16> = container.iter().collect();
+        assert_eq!(container.bits(), 2);
+
+        container.compact();
+        assert_eq!(container.bits(), 1);
+        assert_values(&container, &expected);
+
+        container.fill(5);
+        container.compact();
+        assert!(matches!(container.storage, Storage::Single(5)));
+    }
+
+    #[test]
+    fn reuses_free_slots_of_long_palettes() {
+        let mut container = with_values(LOOKUP_THRESHOLD as u16 + 8);
+
+        let Storage::Indirect { palette, lookup, .. } = &container.storage else {
+            panic!("expected a palette");
+        };
+
+        let palette_len = palette.len();
+        assert!(lookup.as_ref().is_some_and(|lookup| lookup.len() == palette_len));
+
+        container.set(5, 0);
+        container.set(6, 1000);
+        container.set(7, 1000);
+
+        let Storage::Indirect { palette, free, lookup, .. } = &container.storage else {
+            panic!("expected a palette");
+        };
+
+        // The slot freed by 5 went to 1000, and those of 6 and 7 wait for the next values.
+        let lookup = lookup.as_ref().expect("a lookup for a long palette");
+        assert_eq!(palette.len(), palette_len);
+        assert_eq!(free, &[6, 7]);
+        assert_eq!(palette[5], 1000);
+        assert_eq!(lookup.get(&1000), Some(&5));
+        assert!(!lookup.contains_key(&5) && !lookup.contains_key(&6) && !lookup.contains_key(&7));
+
+        assert_eq!(container.count(1000), 2);
+        assert_eq!(container.count(0), LEN - palette_len + 2);
+        assert_eq!((container.get(5), container.get(6), container.get(7)), (0, 1000, 1000));
+    }
+}
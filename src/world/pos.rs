@@ -0,0 +1,89 @@
+use super::{Direction, CHUNK_HEIGHT, SECTION_SIZE};
+use std::ops::{Add, Sub};
+
+/// Position of a block in the world, in block coordinates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl BlockPos {
+    /// Creates a block position from its coordinates.
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Returns the position of the chunk column containing this block.
+    pub fn chunk(self) -> ChunkPos {
+        ChunkPos::new(self.x.div_euclid(SECTION_SIZE as i32), self.z.div_euclid(SECTION_SIZE as i32))
+    }
+
+    /// Returns the coordinates of this block inside its chunk, or `None` if it is above or
+    /// below the world.
+    pub fn local(self) -> Option<(usize, usize, usize)> {
+        if self.y < 0 || self.y >= CHUNK_HEIGHT as i32 {
+            return None;
+        }
+
+        let size = SECTION_SIZE as i32;
+        Some((self.x.rem_euclid(size) as usize, self.y as usize, self.z.rem_euclid(size) as usize))
+    }
+
+    /// Returns the position offset by the given amounts.
+    pub const fn offset(self, dx: i32, dy: i32, dz: i32) -> Self {
+        Self::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    /// Returns the neighboring position in `direction`.
+    pub const fn step(self, direction: Direction) -> Self {
+        let (dx, dy, dz) = direction.offset();
+        self.offset(dx, dy, dz)
+    }
+
+    /// Returns the six positions sharing a face with this one, in [`Direction::ALL`] order.
+    pub fn neighbors(self) -> [Self; 6] {
+        Direction::ALL.map(|direction| self.step(direction))
+    }
+}
+
+impl Add for BlockPos {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self { Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z) }
+}
+
+impl Sub for BlockPos {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self { Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z) }
+}
+
+/// Position of a chunk column, in chunk coordinates (block coordinates divided by 16).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl ChunkPos {
+    /// Creates a chunk position from its coordinates.
+    pub const fn new(x: i32, z: i32) -> Self {
+        Self { x, z }
+    }
+
+    /// Returns the world position of the block at the given local coordinates.
+    pub fn block(self, x: usize, y: usize, z: usize) -> BlockPos {
+        let size = SECTION_SIZE as i32;
+        BlockPos::new(self.x * size + x as i32, y as i32, self.z * size + z as i32)
+    }
+
+    /// Returns the chunk position offset by the given amounts.
+    pub const fn offset(self, dx: i32, dz: i32) -> Self {
+        Self::new(self.x + dx, self.z + dz)
+    }
+
+    /// Returns the Chebyshev distance to `other`, the usual measure for view distances.
+    pub fn distance(self, other: Self) -> i32 {
+        (self.x - other.x).abs().max((self.z - other.z).abs())
+    }
+}
@@ -0,0 +1,106 @@
+use super::{BlockState, PalettedContainer};
+
+/// Width, height and depth of a chunk section in blocks.
+pub const SECTION_SIZE: usize = 16;
+
+/// Number of blocks in a chunk section.
+pub const SECTION_VOLUME: usize = SECTION_SIZE * SECTION_SIZE * SECTION_SIZE;
+
+/// A 16x16x16 cube of blocks, the unit of storage and meshing.
+///
+/// Block states are kept in a [`PalettedContainer`], so a section made of a single block
+/// (all air, or solid stone) costs a few bytes.
+#[derive(Debug, Clone)]
+pub struct ChunkSection {
+    blocks: PalettedContainer<BlockState>,
+
+    /// Number of blocks other than air, to skip empty sections quickly.
+    non_air: u16,
+}
+
+impl ChunkSection {
+    /// Creates a section filled with air.
+    pub fn new() -> Self {
+        Self::filled(BlockState::AIR)
+    }
+
+    /// Creates a section filled with `state`.
+    pub fn filled(state: BlockState) -> Self {
+        let non_air = if state.is_air() { 0 } else { SECTION_VOLUME as u16 };
+        Self { blocks: PalettedContainer::new(SECTION_VOLUME, state), non_air }
+    }
+
+    /// Returns the index of the block at local coordinates in the storage order (Y, Z, X).
+    #[inline]
+    pub fn index(x: usize, y: usize, z: usize) -> usize {
+        debug_assert!(x < SECTION_SIZE && y < SECTION_SIZE && z < SECTION_SIZE);
+        (y * SECTION_SIZE + z) * SECTION_SIZE + x
+    }
+
+    /// Returns the local coordinates of the block stored at `index`.
+    #[inline]
+    pub fn coords(index: usize) -> (usize, usize, usize) {
+        (index % SECTION_SIZE, index / (SECTION_SIZE * SECTION_SIZE), (index / SECTION_SIZE) % SECTION_SIZE)
+    }
+
+    /// Returns the block state at local coordinates.
+    #[inline]
+    pub fn get(&self, x: usize, y: usize, z: usize) -> BlockState {
+        self.blocks.get(Self::index(x, y, z))
+    }
+
+    /// Sets the block state at local coordinates, returning the previous one.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, state: BlockState) -> BlockState {
+        let old = self.blocks.set(Self::index(x, y, z), state);
+
+        match (old.is_air(), state.is_air()) {
+            (true, false) => self.non_air += 1,
+            (false, true) => self.non_air -= 1,
+            _ => {}
+        }
+
+        old
+    }
+
+    /// Sets every block of the section to `state`.
+    pub fn fill(&mut self, state: BlockState) {
+        *self = Self::filled(state);
+    }
+
+    /// Rebuilds the block storage in its smallest form, see [`PalettedContainer::compact`].
+    pub fn compact(&mut self) {
+        self.blocks.compact();
+    }
+
+    /// Returns whether the section only contains air.
+    pub fn is_empty(&self) -> bool {
+        self.non_air == 0
+    }
+
+    /// Returns the number of blocks other than air.
+    pub fn non_air_count(&self) -> usize {
+        self.non_air as usize
+    }
+
+    /// Iterates over every block as `(x, y, z, state)`, in storage order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, usize, BlockState)> + '_ {
+        self.blocks.iter().enumerate().map(|(index, state)| {
+            let (x, y, z) = Self::coords(index);
+            (x, y, z, state)
+        })
+    }
+
+    /// Returns the underlying paletted storage.
+    pub fn blocks(&self) -> &PalettedContainer<BlockState> {
+        &self.blocks
+    }
+
+    /// Returns the number of bytes used by the section, including heap allocations.
+    pub fn memory_usage(&self) -> usize {
+        self.blocks.memory_usage() + size_of::<u16>()
+    }
+}
+
+impl Default for ChunkSection {
+    fn default() -> Self { Self::new() }
+}
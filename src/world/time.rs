@@ -0,0 +1,114 @@
+use crate::math::Vec3;
+
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+
+/// Length of a day and night in game ticks, twenty minutes at 20 ticks per second.
+pub const TICKS_PER_DAY: u64 = 24_000;
+
+/// Number of moon phases, going from full moon to new moon and back, one per night.
+pub const MOON_PHASES: u64 = 8;
+
+/// Named times of day, as accepted by [`WorldTime::parse_time_of_day`].
+const NAMED_TIMES: [(&str, u64); 6] = [("sunrise", 23_000), ("day", 1_000), ("noon", 6_000), ("sunset", 12_000), ("night", 13_000), ("midnight", 18_000)];
+
+/// The time of a world, in game ticks since it was created.
+///
+/// A time of day of 0 is when the sun rises in the east, 6000 noon, 12000 sunset in the west
+/// and 18000 midnight. The sun and moon follow a circle overhead, moving slightly faster
+/// around noon and midnight than at dawn and dusk, so days and nights linger a little.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WorldTime {
+    ticks: u64,
+}
+
+impl WorldTime {
+    /// Creates a time from the number of ticks since the world was created.
+    pub const fn new(ticks: u64) -> Self {
+        Self { ticks }
+    }
+
+    /// Returns the number of ticks since the world was created.
+    pub const fn ticks(self) -> u64 {
+        self.ticks
+    }
+
+    /// Advances the time by one tick.
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    /// Advances the time by `ticks`.
+    pub fn add(&mut self, ticks: u64) {
+        self.ticks = self.ticks.saturating_add(ticks);
+    }
+
+    /// Returns the number of days elapsed.
+    pub const fn day(self) -> u64 {
+        self.ticks / TICKS_PER_DAY
+    }
+
+    /// Returns the ticks elapsed since the last sunrise, in `0..24000`.
+    pub const fn time_of_day(self) -> u64 {
+        self.ticks % TICKS_PER_DAY
+    }
+
+    /// Moves to `time_of_day` of the current day, in ticks since sunrise.
+    pub fn set_time_of_day(&mut self, time_of_day: u64) {
+        self.ticks = self.day() * TICKS_PER_DAY + time_of_day % TICKS_PER_DAY;
+    }
+
+    /// Returns the phase of the moon, 0 for a full moon and 4 for a new moon.
+    pub const fn moon_phase(self) -> u64 {
+        self.day() % MOON_PHASES
+    }
+
+    /// Returns how much of the moon is lit, from 0 at new moon to 1 at full moon.
+    pub fn moon_fullness(self) -> f32 {
+        let phase = self.moon_phase() as f32;
+        (phase - MOON_PHASES as f32 / 2.0).abs() / (MOON_PHASES as f32 / 2.0)
+    }
+
+    /// Returns the position of the sun on its circle as a fraction of a turn, 0 at noon and
+    /// 0.5 at midnight, `partial` ticks (in `0..1`) after this time.
+    pub fn celestial_angle(self, partial: f32) -> f32 {
+        let fraction = (self.time_of_day() as f32 + partial) / TICKS_PER_DAY as f32 - 0.25;
+        let fraction = fraction.rem_euclid(1.0);
+
+        // Ease around sunrise and sunset, blended a third of the way with linear motion.
+        let eased = 1.0 - ((fraction * std::f32::consts::PI).cos() + 1.0) / 2.0;
+        fraction + (eased - fraction) / 3.0
+    }
+
+    /// Returns the unit vector pointing towards the sun, rising in the east (+X) and setting
+    /// in the west.
+    pub fn sun_direction(self, partial: f32) -> Vec3 {
+        let angle = self.celestial_angle(partial) * TAU;
+        Vec3::new(-angle.sin(), angle.cos(), 0.0)
+    }
+
+    /// Returns the unit vector pointing towards the moon, always opposite the sun.
+    pub fn moon_direction(self, partial: f32) -> Vec3 {
+        let sun = self.sun_direction(partial);
+        Vec3::new(-sun.x, -sun.y, -sun.z)
+    }
+
+    /// Returns how much of the day light is out, from 0 through the night to 1 through the
+    /// day, crossing 0.5 at sunrise and sunset.
+    pub fn daylight(self, partial: f32) -> f32 {
+        let height = (self.celestial_angle(partial) * TAU).cos();
+        (height * 2.0 + 0.5).clamp(0.0, 1.0)
+    }
+
+    /// Parses a time of day given by name ("sunrise", "day", "noon", "sunset", "night" or
+    /// "midnight") into ticks since sunrise.
+    pub fn parse_time_of_day(name: &str) -> Option<u64> {
+        NAMED_TIMES.iter().find(|(time, _)| time.eq_ignore_ascii_case(name)).map(|&(_, ticks)| ticks)
+    }
+
+    /// Returns the names [`parse_time_of_day`](Self::parse_time_of_day) accepts.
+    pub fn time_of_day_names() -> impl Iterator<Item = &'static str> {
+        NAMED_TIMES.iter().map(|&(name, _)| name)
+    }
+}
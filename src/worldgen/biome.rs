@@ -0,0 +1,369 @@
+use super::noise::{Fbm, Noise, Simplex};
+use super::random::derive_seed;
+use super::{TerrainColumn, SEA_LEVEL};
+use crate::block::{BlockRegistry, DEFAULT_NAMESPACE};
+use crate::mesh::{BiomeTints, TintColors};
+use crate::resource::ResourceManager;
+use crate::world::{BiomeId, BlockState};
+
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Directory of `assets/` holding biome data files, one per namespace.
+const BIOMES_DIR: &str = "biomes/";
+
+/// Height above which the air gets colder, and by how much per block.
+const COOLING_START: i32 = SEA_LEVEL + 24;
+const COOLING_PER_BLOCK: f64 = 0.004;
+
+/// The climate of a column, from which its biome is chosen. Values are roughly within -1 to 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Climate {
+    /// From frozen (-1) to scorching (1), colder at high altitudes.
+    pub temperature: f64,
+
+    /// From arid (-1) to wet (1).
+    pub humidity: f64,
+
+    /// See [`TerrainColumn::continentalness`].
+    pub continentalness: f64,
+
+    /// See [`TerrainColumn::erosion`].
+    pub erosion: f64,
+}
+
+/// The climate a biome is found in, as `[min, max]` ranges. Parameters left out match
+/// anything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClimateRanges {
+    pub temperature: Option<[f64; 2]>,
+    pub humidity: Option<[f64; 2]>,
+    pub continentalness: Option<[f64; 2]>,
+    pub erosion: Option<[f64; 2]>,
+}
+
+impl ClimateRanges {
+    /// Returns how far `climate` is from the ranges, as the distance outside of them and the
+    /// distance to their centers. Both are zero only for a climate at the very center.
+    fn distance(&self, climate: &Climate) -> (f64, f64) {
+        let parameters = [
+            (self.temperature, climate.temperature),
+            (self.humidity, climate.humidity),
+            (self.continentalness, climate.continentalness),
+            (self.erosion, climate.erosion),
+        ];
+
+        let (mut outside, mut center) = (0.0, 0.0);
+
+        for ([min, max], value) in parameters.into_iter().filter_map(|(range, value)| Some((range?, value))) {
+            let beyond = (min - value).max(value - max).max(0.0);
+            let from_center = value - (min + max) / 2.0;
+            outside += beyond * beyond;
+            center += from_center * from_center;
+        }
+
+        (outside, center)
+    }
+}
+
+/// How densely plants grow in a biome.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Vegetation {
+    /// Average number of trees per chunk.
+    pub trees: f64,
+
+    /// Names of the tree features growing in the biome, each tree picking one at random.
+    /// Listing a name more than once makes it more common.
+    pub tree_kinds: Vec<String>,
+
+    /// Chance of each grass block to grow tall grass.
+    pub grass: f64,
+
+    /// Chance of each grass block to grow a flower.
+    pub flowers: f64,
+}
+
+/// The colors of a biome, as RGB.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BiomeColors {
+    #[serde(deserialize_with = "hex_color")]
+    pub grass: [u8; 3],
+    #[serde(deserialize_with = "hex_color")]
+    pub foliage: [u8; 3],
+    #[serde(deserialize_with = "hex_color")]
+    pub water: [u8; 3],
+    #[serde(deserialize_with = "hex_color")]
+    pub sky: [u8; 3],
+
+    /// Color of the biome on maps, see [`biome_map`](super::biome_map).
+    #[serde(deserialize_with = "hex_color")]
+    pub map: [u8; 3],
+}
+
+/// A biome as written in data files.
+///
+/// ```toml
+/// [plains]
+/// climate = { temperature = [-0.1, 0.35], humidity = [-1.0, 0.0] }
+/// surface = "grass_block"
+/// filler = "dirt"
+/// underwater = "sand"
+/// height_scale = 0.7
+/// vegetation = { trees = 0.1, tree_kinds = ["oak"], grass = 0.3, flowers = 0.02 }
+/// colors = { grass = "#91bd59", foliage = "#77ab2f", water = "#3f76e4", sky = "#78a7ff", map = "#8db360" }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BiomeDefinition {
+    #[serde(default)]
+    pub climate: ClimateRanges,
+
+    /// Top block of dry land.
+    pub surface: String,
+
+    /// Blocks between the surface and stone.
+    pub filler: String,
+
+    /// Top block and filler under water.
+    pub underwater: String,
+
+    /// Blocks added to the height of the terrain.
+    #[serde(default)]
+    pub height_offset: f64,
+
+    /// Factor applied to the height of mountains and the depth of valleys.
+    #[serde(default = "one")]
+    pub height_scale: f64,
+
+    #[serde(default)]
+    pub vegetation: Vegetation,
+
+    pub colors: BiomeColors,
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+/// Parses a color written as `#rrggbb`.
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 3], D::Error> {
+    let text = String::deserialize(deserializer)?;
+
+    parse_color(&text).ok_or_else(|| serde::de::Error::custom(format!("invalid color {text}, expected #rrggbb")))
+}
+
+fn parse_color(text: &str) -> Option<[u8; 3]> {
+    let hex = text.strip_prefix('#').filter(|hex| hex.len() == 6 && hex.is_ascii())?;
+    let channel = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+
+    Some([channel(0)?, channel(1)?, channel(2)?])
+}
+
+/// A biome, with its blocks resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Biome {
+    pub name: String,
+    pub climate: ClimateRanges,
+    pub surface: BlockState,
+    pub filler: BlockState,
+    pub underwater: BlockState,
+    pub height_offset: f64,
+    pub height_scale: f64,
+    pub vegetation: Vegetation,
+    pub colors: BiomeColors,
+}
+
+/// Every biome known to the game.
+///
+/// Biomes are loaded from data files like blocks; see `assets/biomes/rustedcraft.toml` for the
+/// format. Identifiers follow the loading order, up to 256 biomes.
+#[derive(Debug, Clone, Default)]
+pub struct BiomeRegistry {
+    biomes: Vec<Biome>,
+    names: HashMap<String, BiomeId>,
+}
+
+impl BiomeRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the biomes of every `biomes/<namespace>.toml` file provided by `resources`, in
+    /// file name order, taking their blocks from `blocks`.
+    ///
+    /// # Returns
+    /// * `Err(String)` if a file is invalid or there are no biomes at all.
+    pub fn load(resources: &ResourceManager, blocks: &BlockRegistry) -> Result<Self, String> {
+        let mut registry = Self::new();
+
+        for path in resources.list(BIOMES_DIR) {
+            let Some(namespace) = path.strip_prefix(BIOMES_DIR).and_then(|file| file.strip_suffix(".toml")) else {
+                continue;
+            };
+
+            if namespace.contains('/') {
+                continue;
+            }
+
+            let source = resources.read_to_string(&path)?;
+            registry.load_str(namespace, &source, blocks).map_err(|e| format!("{path}: {e}"))?;
+        }
+
+        match registry.biomes.is_empty() {
+            true => Err(String::from("no biomes defined")),
+            false => Ok(registry),
+        }
+    }
+
+    /// Registers every biome of a data file whose biomes belong to `namespace`. Each top-level
+    /// table is a biome named after its key, see [`BiomeDefinition`].
+    pub fn load_str(&mut self, namespace: &str, source: &str, blocks: &BlockRegistry) -> Result<(), String> {
+        let table: toml::Table = toml::from_str(source).map_err(|e| e.to_string())?;
+
+        for (path, value) in table {
+            let name = format!("{namespace}:{path}");
+            let definition = value.try_into().map_err(|e| format!("{name}: {e}"))?;
+            self.register(&name, &definition, blocks).map_err(|e| format!("{name}: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a biome, taking its blocks from `blocks`.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the name is taken, a block is unknown or there are already 256
+    ///   biomes.
+    pub fn register(&mut self, name: &str, definition: &BiomeDefinition, blocks: &BlockRegistry) -> Result<BiomeId, String> {
+        if self.names.contains_key(name) {
+            return Err(format!("biome {name} is registered twice"));
+        }
+
+        let id = u8::try_from(self.biomes.len()).map(BiomeId::new).map_err(|_| String::from("too many biomes"))?;
+
+        self.biomes.push(Biome {
+            name: name.to_string(),
+            climate: definition.climate,
+            surface: blocks.parse_state(&definition.surface)?,
+            filler: blocks.parse_state(&definition.filler)?,
+            underwater: blocks.parse_state(&definition.underwater)?,
+            height_offset: definition.height_offset,
+            height_scale: definition.height_scale,
+            vegetation: definition.vegetation.clone(),
+            colors: definition.colors,
+        });
+
+        self.names.insert(name.to_string(), id);
+        Ok(id)
+    }
+
+    /// Returns the number of biomes.
+    pub fn len(&self) -> usize {
+        self.biomes.len()
+    }
+
+    /// Returns whether no biome is registered.
+    pub fn is_empty(&self) -> bool {
+        self.biomes.is_empty()
+    }
+
+    /// Returns the biome with the given identifier.
+    ///
+    /// # Panics
+    /// Panics if `id` was not assigned by this registry.
+    #[inline]
+    pub fn get(&self, id: BiomeId) -> &Biome {
+        &self.biomes[id.index()]
+    }
+
+    /// Returns the identifier of the biome called `name`. The namespace may be left out for
+    /// built-in biomes.
+    pub fn id(&self, name: &str) -> Option<BiomeId> {
+        match name.contains(':') {
+            true => self.names.get(name).copied(),
+            false => self.names.get(&format!("{DEFAULT_NAMESPACE}:{name}")).copied(),
+        }
+    }
+
+    /// Iterates over every biome with its identifier.
+    pub fn iter(&self) -> impl Iterator<Item = (BiomeId, &Biome)> {
+        self.biomes.iter().enumerate().map(|(index, biome)| (BiomeId::new(index as u8), biome))
+    }
+
+    /// Returns the biome whose climate ranges are closest to `climate`.
+    ///
+    /// Biomes whose ranges all contain the climate come first, and among those the one whose
+    /// ranges are centered closest to it, so overlapping biomes split the space between them.
+    pub fn select(&self, climate: &Climate) -> BiomeId {
+        let mut best = (BiomeId::default(), (f64::INFINITY, f64::INFINITY));
+
+        for (id, biome) in self.iter() {
+            let distance = biome.climate.distance(climate);
+
+            if distance < best.1 {
+                best = (id, distance);
+            }
+        }
+
+        best.0
+    }
+
+    /// Returns the tint colors of every biome, for meshing.
+    pub fn tints(&self) -> BiomeTints {
+        BiomeTints::new(
+            self.biomes
+                .iter()
+                .map(|biome| TintColors { grass: biome.colors.grass, foliage: biome.colors.foliage, water: biome.colors.water })
+                .collect(),
+        )
+    }
+}
+
+/// Picks the biome of each column from its climate.
+///
+/// Continentalness and erosion come from the terrain; temperature and humidity are noises of
+/// their own, at an even larger scale, with the air cooling down high in the mountains.
+pub struct BiomeSource {
+    registry: Arc<BiomeRegistry>,
+    temperature: Fbm<Simplex>,
+    humidity: Fbm<Simplex>,
+}
+
+impl BiomeSource {
+    /// Creates the biome source of the world with seed `seed`, choosing among `registry`.
+    pub fn new(seed: u64, registry: Arc<BiomeRegistry>) -> Self {
+        Self {
+            registry,
+            temperature: Fbm::new(derive_seed(seed, "temperature"), 4, 1.0 / 1400.0, Simplex::new),
+            humidity: Fbm::new(derive_seed(seed, "humidity"), 4, 1.0 / 1100.0, Simplex::new),
+        }
+    }
+
+    /// Returns the biomes chosen from.
+    pub fn registry(&self) -> &Arc<BiomeRegistry> {
+        &self.registry
+    }
+
+    /// Returns the climate of the column at world `x` and `z`, whose terrain is `column`.
+    pub fn climate(&self, x: i32, z: i32, column: &TerrainColumn) -> Climate {
+        let (x, z) = (x as f64, z as f64);
+        let cooling = (column.height - COOLING_START).max(0) as f64 * COOLING_PER_BLOCK;
+
+        Climate {
+            temperature: self.temperature.sample2(x, z) * 2.0 - cooling,
+            humidity: self.humidity.sample2(x, z) * 2.0,
+            continentalness: column.continentalness,
+            erosion: column.erosion,
+        }
+    }
+
+    /// Returns the biome of the column at world `x` and `z`, whose terrain is `column`.
+    pub fn biome(&self, x: i32, z: i32, column: &TerrainColumn) -> BiomeId {
+        self.registry.select(&self.climate(x, z, column))
+    }
+}
@@ -0,0 +1,255 @@
+use super::random::{derive_seed, hash3, WorldRandom};
+use super::CaveSettings;
+use crate::math::sin_cos;
+use crate::world::{ChunkPos, CHUNK_HEIGHT, SECTION_SIZE};
+
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+/// Distance in chunks from which a tunnel or ravine can reach a chunk.
+///
+/// Walks are at most `MAX_LENGTH` steps of one block, so they never reach further.
+const RANGE: i32 = 8;
+const MAX_LENGTH: u32 = (RANGE as u32 - 1) * SECTION_SIZE as u32;
+
+/// A tunnel or ravine being dug, one step at a time.
+#[derive(Debug, Clone)]
+struct Walk {
+    position: [f64; 3],
+    yaw: f64,
+    pitch: f64,
+
+    /// Radius added at the middle of the walk, on top of a base radius of 1.5.
+    width: f64,
+
+    /// Height of the cross section over its width: 1 for tunnels, more for ravines.
+    stretch: f64,
+
+    step: u32,
+    length: u32,
+    seed: u64,
+    kind: WalkKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WalkKind {
+    /// A tunnel that may split into two.
+    Tunnel,
+
+    /// A tunnel split from another, which does not split again.
+    Branch,
+
+    /// A tall, narrow crack with ragged walls.
+    Ravine,
+}
+
+/// Digs tunnels and ravines by random walks, the way carvers of the original game do.
+///
+/// Every walk starts in some chunk with a random generator seeded by that chunk alone, and
+/// keeps drawing from it whichever chunk is being generated. Generating a chunk replays the
+/// walks of every chunk in range and only digs the blocks inside it, so a tunnel continues
+/// seamlessly into its neighbors whatever order chunks are generated in.
+pub struct Carvers {
+    tunnel_seed: u64,
+    ravine_seed: u64,
+    tunnel_chance: f64,
+    ravine_chance: f64,
+}
+
+impl Carvers {
+    /// Creates the carvers of the world with seed `seed`.
+    pub fn new(seed: u64, settings: &CaveSettings) -> Self {
+        Self {
+            tunnel_seed: derive_seed(seed, "tunnels"),
+            ravine_seed: derive_seed(seed, "ravines"),
+            tunnel_chance: settings.tunnel_chance,
+            ravine_chance: settings.ravine_chance,
+        }
+    }
+
+    /// Calls `dig` with the local coordinates of every block of the chunk at `pos` that
+    /// tunnels and ravines go through. Blocks where walks cross are given more than once.
+    pub fn carve(&self, pos: ChunkPos, mut dig: impl FnMut(usize, usize, usize)) {
+        let mut walks = Vec::new();
+
+        for dz in -RANGE..=RANGE {
+            for dx in -RANGE..=RANGE {
+                let source = pos.offset(dx, dz);
+                self.start_tunnels(source, &mut walks);
+                self.start_ravine(source, &mut walks);
+
+                while let Some(walk) = walks.pop() {
+                    run(walk, pos, &mut walks, &mut dig);
+                }
+            }
+        }
+    }
+
+    fn start_tunnels(&self, source: ChunkPos, walks: &mut Vec<Walk>) {
+        let mut random = WorldRandom::for_chunk(self.tunnel_seed, source, 0);
+
+        if !random.chance(self.tunnel_chance) {
+            return;
+        }
+
+        for _ in 0..random.next_range(1, 4) {
+            let position = start_position(&mut random, source, 8, 120);
+
+            // Now and then a tunnel is much wider, making a small cavern.
+            let width = random.next_f64() * 2.0 + random.next_f64();
+            let width = if random.chance(0.1) { width * 2.5 } else { width };
+
+            walks.push(Walk {
+                position,
+                yaw: random.next_f64() * TAU,
+                pitch: (random.next_f64() - 0.5) * 0.25,
+                width,
+                stretch: 1.0,
+                step: 0,
+                length: random.next_range(MAX_LENGTH as i32 * 3 / 4, MAX_LENGTH as i32) as u32,
+                seed: random.next_u64(),
+                kind: WalkKind::Tunnel,
+            });
+        }
+    }
+
+    fn start_ravine(&self, source: ChunkPos, walks: &mut Vec<Walk>) {
+        let mut random = WorldRandom::for_chunk(self.ravine_seed, source, 0);
+
+        if !random.chance(self.ravine_chance) {
+            return;
+        }
+
+        walks.push(Walk {
+            position: start_position(&mut random, source, 20, 68),
+            yaw: random.next_f64() * TAU,
+            pitch: (random.next_f64() - 0.5) * 0.25,
+            width: (random.next_f64() * 2.0 + random.next_f64()) * 2.0,
+            stretch: 3.0,
+            step: 0,
+            length: random.next_range(MAX_LENGTH as i32 * 3 / 4, MAX_LENGTH as i32) as u32,
+            seed: random.next_u64(),
+            kind: WalkKind::Ravine,
+        });
+    }
+}
+
+/// Returns a random block center in the chunk at `source`, between heights `min` and `max`.
+fn start_position(random: &mut WorldRandom, source: ChunkPos, min: i32, max: i32) -> [f64; 3] {
+    let origin = source.block(0, 0, 0);
+    let x = origin.x + random.next_below(SECTION_SIZE as u32) as i32;
+    let y = random.next_range(min, max);
+    let z = origin.z + random.next_below(SECTION_SIZE as u32) as i32;
+
+    [x as f64 + 0.5, y as f64 + 0.5, z as f64 + 0.5]
+}
+
+/// Walks `walk` to its end, digging the blocks of `target` along the way, and queues the
+/// branches it splits into in `walks`.
+fn run(mut walk: Walk, target: ChunkPos, walks: &mut Vec<Walk>, dig: &mut impl FnMut(usize, usize, usize)) {
+    let mut random = WorldRandom::new(walk.seed);
+    let (mut yaw_change, mut pitch_change) = (0.0, 0.0);
+
+    let split_at = match walk.kind {
+        WalkKind::Tunnel if walk.width > 1.0 => random.next_range(walk.length as i32 / 4, walk.length as i32 * 3 / 4) as u32,
+        _ => u32::MAX,
+    };
+
+    let origin = target.block(0, 0, 0);
+    let center = [origin.x as f64 + 8.0, origin.z as f64 + 8.0];
+
+    while walk.step < walk.length {
+        let progress = walk.step as f64 / walk.length as f64;
+        let radius = 1.5 + sin_cos(progress * PI).0 * walk.width;
+
+        let (yaw_sin, yaw_cos) = sin_cos(walk.yaw);
+        let (pitch_sin, pitch_cos) = sin_cos(walk.pitch);
+        walk.position[0] += yaw_cos * pitch_cos;
+        walk.position[1] += pitch_sin;
+        walk.position[2] += yaw_sin * pitch_cos;
+
+        // Ravines keep their slope longer, tunnels level out quickly.
+        walk.pitch *= if walk.kind == WalkKind::Ravine { 0.7 } else { 0.8 };
+        walk.pitch += pitch_change * 0.1;
+        walk.yaw += yaw_change * 0.1;
+        pitch_change = pitch_change * 0.9 + (random.next_f64() - random.next_f64()) * random.next_f64() * 2.0;
+        yaw_change = yaw_change * 0.75 + (random.next_f64() - random.next_f64()) * random.next_f64() * 4.0;
+
+        walk.step += 1;
+
+        if walk.step == split_at {
+            for side in [-FRAC_PI_2, FRAC_PI_2] {
+                walks.push(Walk {
+                    yaw: walk.yaw + side,
+                    pitch: walk.pitch / 3.0,
+                    width: random.next_f64() * 0.5 + 0.5,
+                    seed: random.next_u64(),
+                    kind: WalkKind::Branch,
+                    ..walk.clone()
+                });
+            }
+
+            return;
+        }
+
+        // Skipping a few steps roughens the walls. Every random number of the step has been
+        // drawn by now, so what follows may depend on the target chunk.
+        if random.chance(0.25) {
+            continue;
+        }
+
+        let [dx, dz] = [walk.position[0] - center[0], walk.position[2] - center[1]];
+        let reach = (walk.length - walk.step) as f64 + walk.width + 16.0;
+
+        if dx * dx + dz * dz > reach * reach {
+            return;
+        }
+
+        if dx.abs() <= 8.0 + radius * 2.0 && dz.abs() <= 8.0 + radius * 2.0 {
+            dig_ellipsoid(&walk, radius, origin.x, origin.z, dig);
+        }
+    }
+}
+
+/// Digs the blocks of the chunk starting at world `min_x` and `min_z` inside the cross section
+/// of `walk` at its current position.
+fn dig_ellipsoid(walk: &Walk, radius: f64, min_x: i32, min_z: i32, dig: &mut impl FnMut(usize, usize, usize)) {
+    let [px, py, pz] = walk.position;
+    let vertical = radius * walk.stretch;
+    let size = SECTION_SIZE as i32;
+
+    let range = |center: f64, radius: f64, min: i32, max: i32| {
+        let from = ((center - radius).floor() as i32).max(min);
+        let to = ((center + radius).ceil() as i32).min(max);
+        from..to
+    };
+
+    for y in range(py, vertical, 1, CHUNK_HEIGHT as i32 - 1) {
+        let ny = (y as f64 + 0.5 - py) / vertical;
+
+        // Tunnels get flat floors, like the original ones.
+        if walk.kind != WalkKind::Ravine && ny <= -0.7 {
+            continue;
+        }
+
+        // Ravine walls move in and out every few blocks.
+        let ragged = match walk.kind {
+            WalkKind::Ravine => {
+                let bump = (hash3(walk.seed, 0, (y / 3) as i64, 0) >> 11) as f64 / (1u64 << 53) as f64;
+                1.0 + bump * bump
+            }
+            _ => 1.0,
+        };
+
+        for z in range(pz, radius, min_z, min_z + size) {
+            let nz = (z as f64 + 0.5 - pz) / radius;
+
+            for x in range(px, radius, min_x, min_x + size) {
+                let nx = (x as f64 + 0.5 - px) / radius;
+
+                if (nx * nx + nz * nz) * ragged + ny * ny < 1.0 {
+                    dig((x - min_x) as usize, y as usize, (z - min_z) as usize);
+                }
+            }
+        }
+    }
+}
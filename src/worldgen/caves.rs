@@ -0,0 +1,234 @@
+use super::noise::{Fbm, Noise, OpenSimplex2};
+use super::random::{derive_seed, hash3};
+use super::SEA_LEVEL;
+use crate::world::{ChunkPos, SECTION_SIZE};
+
+use serde::{Deserialize, Serialize};
+
+/// Options of cave generation, part of how a world is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaveSettings {
+    /// Hollows out large caverns ("cheese" caves) and long winding tubes ("spaghetti" caves)
+    /// from 3D noise.
+    pub noise_caves: bool,
+
+    /// Chance of each chunk to start a system of tunnels.
+    pub tunnel_chance: f64,
+
+    /// Chance of each chunk to start a ravine.
+    pub ravine_chance: f64,
+
+    /// Fills parts of caves with water, each pocket at its own level.
+    pub aquifers: bool,
+
+    /// Highest level of open caves filled with lava.
+    pub lava_level: i32,
+}
+
+impl Default for CaveSettings {
+    fn default() -> Self {
+        Self { noise_caves: true, tunnel_chance: 0.14, ravine_chance: 0.02, aquifers: true, lava_level: 10 }
+    }
+}
+
+/// What an opening dug by caves is filled with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CaveFill {
+    Air,
+    Water,
+    Lava,
+}
+
+/// Spacing of the grid cave noises are sampled on, horizontally and vertically. Blocks in
+/// between are interpolated, which is much cheaper and barely shows at this scale.
+const GRID_XZ: usize = 4;
+const GRID_Y: usize = 8;
+const GRID_WIDTH: usize = SECTION_SIZE / GRID_XZ + 1;
+
+/// Cheese caves open where their noise is above this.
+const CHEESE_THRESHOLD: f64 = 0.32;
+
+/// Spaghetti caves open where both of their noises are within this of zero.
+const SPAGHETTI_RADIUS: f64 = 0.075;
+
+/// Depth below the surface over which cheese caves close up, so they rarely break through.
+const CHEESE_ROOF: f64 = 14.0;
+
+/// Caves hollowed out of 3D noise.
+///
+/// Cheese caves are the pockets where one noise is high: large caverns with pillars, kept
+/// flatter than they are wide. Spaghetti caves follow the lines where two other noises are
+/// both close to zero, giving long tubes that sometimes reach the surface as cave mouths.
+pub struct NoiseCaves {
+    cheese: Fbm<OpenSimplex2>,
+    spaghetti: [Fbm<OpenSimplex2>; 2],
+}
+
+impl NoiseCaves {
+    /// Creates the caves of the world with seed `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            cheese: Fbm::new(derive_seed(seed, "cheese_caves"), 3, 1.0 / 72.0, OpenSimplex2::new),
+            spaghetti: [
+                Fbm::new(derive_seed(seed, "spaghetti_caves_a"), 2, 1.0 / 56.0, OpenSimplex2::new),
+                Fbm::new(derive_seed(seed, "spaghetti_caves_b"), 2, 1.0 / 56.0, OpenSimplex2::new),
+            ],
+        }
+    }
+
+    /// Samples the noises for the chunk at `pos`, from the bottom of the world up to `top`.
+    pub fn chunk(&self, pos: ChunkPos, top: usize) -> NoiseCaveChunk {
+        let layers = top / GRID_Y + 2;
+        let origin = pos.block(0, 0, 0);
+        let mut samples = Vec::with_capacity(GRID_WIDTH * GRID_WIDTH * layers);
+
+        for layer in 0..layers {
+            for z in 0..GRID_WIDTH {
+                for x in 0..GRID_WIDTH {
+                    let world_x = (origin.x + (x * GRID_XZ) as i32) as f64;
+                    let world_y = (layer * GRID_Y) as f64;
+                    let world_z = (origin.z + (z * GRID_XZ) as i32) as f64;
+
+                    // Stretching y out flattens caverns into wide chambers.
+                    samples.push([
+                        self.cheese.sample3(world_x, world_y * 1.6, world_z),
+                        self.spaghetti[0].sample3(world_x, world_y, world_z),
+                        self.spaghetti[1].sample3(world_x, world_y, world_z),
+                    ]);
+                }
+            }
+        }
+
+        NoiseCaveChunk { samples, layers }
+    }
+}
+
+/// The cave noises of one chunk, see [`NoiseCaves::chunk`].
+pub struct NoiseCaveChunk {
+    samples: Vec<[f64; 3]>,
+    layers: usize,
+}
+
+impl NoiseCaveChunk {
+    /// Returns whether the block at local coordinates, `depth` blocks below the surface of its
+    /// column, is hollowed out.
+    pub fn is_cave(&self, x: usize, y: usize, z: usize, depth: i32) -> bool {
+        let [cheese, a, b] = self.sample(x, y, z);
+        let roof = (CHEESE_ROOF - depth as f64).max(0.0) * 0.05;
+
+        cheese > CHEESE_THRESHOLD + roof || (depth > 0 && a * a + b * b < SPAGHETTI_RADIUS * SPAGHETTI_RADIUS)
+    }
+
+    /// Interpolates the noises at local coordinates between the grid points around them.
+    fn sample(&self, x: usize, y: usize, z: usize) -> [f64; 3] {
+        let (gx, gy, gz) = (x / GRID_XZ, (y / GRID_Y).min(self.layers - 2), z / GRID_XZ);
+        let (tx, ty, tz) = (
+            (x % GRID_XZ) as f64 / GRID_XZ as f64,
+            ((y - gy * GRID_Y) as f64 / GRID_Y as f64).min(1.0),
+            (z % GRID_XZ) as f64 / GRID_XZ as f64,
+        );
+
+        let at = |dx: usize, dy: usize, dz: usize| self.samples[((gy + dy) * GRID_WIDTH + gz + dz) * GRID_WIDTH + gx + dx];
+        let lerp = |a: [f64; 3], b: [f64; 3], t: f64| std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t);
+
+        let layer = |dy: usize| {
+            let near = lerp(at(0, dy, 0), at(1, dy, 0), tx);
+            let far = lerp(at(0, dy, 1), at(1, dy, 1), tx);
+            lerp(near, far, tz)
+        };
+
+        lerp(layer(0), layer(1), ty)
+    }
+}
+
+/// Size of aquifer cells, in blocks.
+const CELL_XZ: i32 = 16;
+const CELL_Y: i32 = 12;
+
+/// Chance of each aquifer cell to hold water, out of 256.
+const WET_CELLS: u64 = 72;
+
+/// Thickness of the rock left standing between aquifers at different levels.
+const BARRIER: f64 = 2.5;
+
+/// Decides what fills the openings dug by caves.
+///
+/// Openings below the lava level are filled with lava. Above it, space is divided into cells
+/// around jittered points, some of which hold water up to a level of their own, so caves get
+/// flooded pockets rather than one water table. Where two neighboring cells have different
+/// levels, a thin wall of rock is left standing between them so water never ends in a
+/// vertical face.
+pub struct Aquifer {
+    seed: u64,
+    enabled: bool,
+    lava_level: i32,
+}
+
+impl Aquifer {
+    /// Creates the aquifers of the world with seed `seed`.
+    pub fn new(seed: u64, settings: &CaveSettings) -> Self {
+        Self { seed: derive_seed(seed, "aquifers"), enabled: settings.aquifers, lava_level: settings.lava_level }
+    }
+
+    /// Returns what fills an opening at world `x`, `y` and `z`, or `None` when the block must
+    /// stay solid to hold back the water of a neighboring cell.
+    pub fn fill_at(&self, x: i32, y: i32, z: i32) -> Option<CaveFill> {
+        if y <= self.lava_level {
+            return Some(CaveFill::Lava);
+        }
+
+        if !self.enabled {
+            return Some(CaveFill::Air);
+        }
+
+        // The two cell points closest to the block, as squared distance and water level.
+        let mut nearest = [(i64::MAX, None), (i64::MAX, None)];
+        let (cell_x, cell_y, cell_z) = (x.div_euclid(CELL_XZ), y.div_euclid(CELL_Y), z.div_euclid(CELL_XZ));
+
+        for cy in cell_y - 1..=cell_y + 1 {
+            for cz in cell_z - 1..=cell_z + 1 {
+                for cx in cell_x - 1..=cell_x + 1 {
+                    let (point, level) = self.cell(cx, cy, cz);
+                    let [dx, dy, dz] = [point[0] - x, point[1] - y, point[2] - z].map(|d| d as i64);
+                    let distance = dx * dx + dy * dy + dz * dz;
+
+                    if distance < nearest[0].0 {
+                        nearest = [(distance, level), nearest[0]];
+                    }
+                    else if distance < nearest[1].0 {
+                        nearest[1] = (distance, level);
+                    }
+                }
+            }
+        }
+
+        let [(first, level), (second, other)] = nearest;
+        let flooded = |level: Option<i32>| level.is_some_and(|level| y <= level);
+
+        if level != other && (flooded(level) || flooded(other)) && (second as f64).sqrt() - (first as f64).sqrt() < BARRIER {
+            return None;
+        }
+
+        match flooded(level) {
+            true => Some(CaveFill::Water),
+            false => Some(CaveFill::Air),
+        }
+    }
+
+    /// Returns the jittered point of a cell and its water level, if it holds water.
+    fn cell(&self, x: i32, y: i32, z: i32) -> ([i32; 3], Option<i32>) {
+        let hash = hash3(self.seed, x as i64, y as i64, z as i64);
+        let point = [
+            x * CELL_XZ + (hash & 15) as i32,
+            y * CELL_Y + ((hash >> 4) % CELL_Y as u64) as i32,
+            z * CELL_XZ + ((hash >> 12) & 15) as i32,
+        ];
+
+        // Cells reaching the sea are left dry, so that caves do not fill up above the land.
+        let wet = (hash >> 16) & 0xff < WET_CELLS && (y + 1) * CELL_Y <= SEA_LEVEL;
+        let level = wet.then(|| (y * CELL_Y + ((hash >> 24) % (CELL_Y as u64 + 4)) as i32).min(SEA_LEVEL));
+
+        (point, level)
+    }
+}
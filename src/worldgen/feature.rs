@@ -0,0 +1,399 @@
+use super::pending::{FeatureWrites, Replace};
+use super::WorldRandom;
+use crate::block::BlockRegistry;
+use crate::resource::ResourceManager;
+use crate::world::{BlockPos, BlockState, ChunkPos, Direction, CHUNK_HEIGHT, SECTION_SIZE};
+
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Directory of `assets/` holding feature data files, one per namespace.
+const FEATURES_DIR: &str = "features/";
+
+/// Largest ore vein, so that veins never reach beyond the neighbors of their chunk.
+pub const MAX_VEIN_SIZE: u32 = SECTION_SIZE as u32;
+
+/// How heights are spread between the bounds of a [`HeightRange`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeightDistribution {
+    /// Every height is as likely.
+    #[default]
+    Uniform,
+
+    /// Heights halfway between the bounds are the most likely, fading out towards them.
+    Triangle,
+}
+
+/// A range of heights features are placed at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeightRange {
+    pub min: i32,
+    pub max: i32,
+
+    #[serde(default)]
+    pub distribution: HeightDistribution,
+}
+
+impl HeightRange {
+    /// Returns a random height within the range.
+    pub fn sample(&self, random: &mut WorldRandom) -> i32 {
+        match self.distribution {
+            HeightDistribution::Uniform => random.next_range(self.min, self.max),
+            HeightDistribution::Triangle => (random.next_range(self.min, self.max) + random.next_range(self.min, self.max)) / 2,
+        }
+    }
+}
+
+/// The shape of a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TreeShape {
+    /// A round crown with ragged corners.
+    Oak,
+
+    /// A narrower crown without corners.
+    Birch,
+
+    /// A cone of leaves in tiers, down most of the trunk.
+    Spruce,
+
+    /// A tall trunk with a wide crown, and bushes of leaves sprouting along it.
+    Jungle,
+}
+
+/// A feature as written in data files.
+///
+/// ```toml
+/// [iron_ore]
+/// type = "ore"
+/// block = "iron_ore"
+/// size = 9
+/// count = 12
+/// height = { min = 5, max = 72, distribution = "triangle" }
+///
+/// [oak]
+/// type = "tree"
+/// shape = "oak"
+/// log = "oak_log"
+/// leaves = "oak_leaves"
+/// height = [4, 6]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum FeatureDefinition {
+    Ore(OreDefinition),
+    Tree(TreeDefinition),
+}
+
+/// Veins of a block grown through stone, see [`FeatureDefinition`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OreDefinition {
+    pub block: String,
+
+    /// Blocks the veins grow through.
+    #[serde(default = "stone")]
+    pub replaces: Vec<String>,
+
+    /// Blocks per vein, at most [`MAX_VEIN_SIZE`].
+    pub size: u32,
+
+    /// Veins per chunk. Fractions give a chance of one more.
+    pub count: f64,
+
+    pub height: HeightRange,
+}
+
+/// A tree, see [`FeatureDefinition`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TreeDefinition {
+    pub shape: TreeShape,
+    pub log: String,
+    pub leaves: String,
+
+    /// Smallest and largest height of the trunk.
+    pub height: [u32; 2],
+
+    /// Blocks the tree grows on.
+    #[serde(default = "soil")]
+    pub soil: Vec<String>,
+}
+
+fn stone() -> Vec<String> {
+    vec![String::from("stone")]
+}
+
+fn soil() -> Vec<String> {
+    vec![String::from("grass_block"), String::from("dirt")]
+}
+
+/// Veins of a block, with their blocks resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ore {
+    pub name: String,
+    pub block: BlockState,
+    pub replaces: Vec<BlockState>,
+    pub size: u32,
+    pub count: f64,
+    pub height: HeightRange,
+}
+
+impl Ore {
+    /// Grows the veins of one chunk at `pos`, made as ore number `index` of its registry.
+    ///
+    /// Each vein starts from a random block and grows by adding a block next to one it already
+    /// has, which gives compact clumps rather than lines.
+    pub fn place(&self, random: &mut WorldRandom, pos: ChunkPos, index: u16, writes: &mut FeatureWrites) {
+        let count = self.count as u32 + random.chance(self.count.fract()) as u32;
+        let mut vein = Vec::with_capacity(self.size as usize);
+
+        for _ in 0..count {
+            let x = random.next_below(SECTION_SIZE as u32) as usize;
+            let z = random.next_below(SECTION_SIZE as u32) as usize;
+            let start = pos.block(x, 0, z).offset(0, self.height.sample(random), 0);
+
+            vein.clear();
+            vein.push(start);
+
+            for _ in 1..self.size {
+                let from = vein[random.next_below(vein.len() as u32) as usize];
+                vein.push(from.step(Direction::ALL[random.next_below(6) as usize]));
+            }
+
+            for &block in &vein {
+                writes.set(block, self.block, Replace::Ore(index));
+            }
+        }
+    }
+}
+
+/// A tree, with its blocks resolved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tree {
+    pub name: String,
+    pub shape: TreeShape,
+    pub log: BlockState,
+    pub leaves: BlockState,
+    pub height: [u32; 2],
+    pub soil: Vec<BlockState>,
+}
+
+impl Tree {
+    /// Returns a random height for the trunk of a tree about to grow.
+    pub fn trunk_height(&self, random: &mut WorldRandom) -> i32 {
+        random.next_range(self.height[0] as i32, self.height[1] as i32)
+    }
+
+    /// Grows the tree with a trunk of `height` blocks on the block at `ground`, through air,
+    /// leaves and plants.
+    pub fn grow(&self, random: &mut WorldRandom, ground: BlockPos, height: i32, writes: &mut FeatureWrites) {
+        let top = ground.y + height + 1;
+        let mut leaves = |x: i32, y: i32, z: i32| writes.set(BlockPos::new(ground.x + x, y, ground.z + z), self.leaves, Replace::Air);
+
+        match self.shape {
+            TreeShape::Oak | TreeShape::Birch | TreeShape::Jungle => {
+                let wider = (self.shape == TreeShape::Jungle) as i32;
+
+                // Like the original game: two wide layers, then two narrow ones, with some
+                // corners left out. Birches lose all of them.
+                for y in top - 3..=top {
+                    let radius = 1 - (y - top) / 2 + wider;
+
+                    for (x, z) in square(radius) {
+                        let corner = x.abs() == radius && z.abs() == radius;
+
+                        if corner && (y == top || self.shape == TreeShape::Birch || random.chance(0.5)) {
+                            continue;
+                        }
+
+                        leaves(x, y, z);
+                    }
+                }
+
+                if self.shape == TreeShape::Jungle {
+                    for _ in 0..random.next_range(1, 3) {
+                        let y = ground.y + random.next_range(height / 2, (height - 3).max(height / 2));
+                        let (dx, _, dz) = Direction::HORIZONTAL[random.next_below(4) as usize].offset();
+
+                        for (x, z) in square(1) {
+                            leaves(dx + x, y, dz + z);
+                        }
+
+                        leaves(dx, y + 1, dz);
+                    }
+                }
+            }
+            TreeShape::Spruce => {
+                let bare = random.next_range(1, 2);
+                let widest = if height >= 8 { 3 } else { 2 };
+
+                // Tiers widen down the trunk, each stepping back in a little before the next.
+                for (tier, y) in (ground.y + 1 + bare..=top).rev().enumerate() {
+                    let tier = tier as i32;
+                    let radius = ((tier + 1) / 2).min(widest) - (tier > 0 && tier % 3 == 0) as i32;
+
+                    for (x, z) in square(radius) {
+                        if radius == 0 || x.abs() != radius || z.abs() != radius {
+                            leaves(x, y, z);
+                        }
+                    }
+                }
+            }
+        }
+
+        for y in ground.y + 1..=ground.y + height {
+            writes.set(BlockPos::new(ground.x, y, ground.z), self.log, Replace::Soft);
+        }
+    }
+}
+
+/// Iterates over the offsets of a square of `radius` blocks around a center.
+fn square(radius: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-radius..=radius).flat_map(move |z| (-radius..=radius).map(move |x| (x, z)))
+}
+
+/// Every feature known to the game: ores placed in every chunk, and trees placed by biomes.
+///
+/// Features are loaded from data files like blocks and biomes; see
+/// `assets/features/rustedcraft.toml` for the format.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureRegistry {
+    ores: Vec<Ore>,
+    trees: Vec<Tree>,
+    names: HashSet<String>,
+}
+
+impl FeatureRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the features of every `features/<namespace>.toml` file provided by `resources`,
+    /// in file name order, taking their blocks from `blocks`.
+    ///
+    /// # Returns
+    /// * `Err(String)` if a file is invalid.
+    pub fn load(resources: &ResourceManager, blocks: &BlockRegistry) -> Result<Self, String> {
+        let mut registry = Self::new();
+
+        for path in resources.list(FEATURES_DIR) {
+            let Some(namespace) = path.strip_prefix(FEATURES_DIR).and_then(|file| file.strip_suffix(".toml")) else {
+                continue;
+            };
+
+            if namespace.contains('/') {
+                continue;
+            }
+
+            let source = resources.read_to_string(&path)?;
+            registry.load_str(namespace, &source, blocks).map_err(|e| format!("{path}: {e}"))?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Registers every feature of a data file whose features belong to `namespace`. Each
+    /// top-level table is a feature named after its key, see [`FeatureDefinition`].
+    pub fn load_str(&mut self, namespace: &str, source: &str, blocks: &BlockRegistry) -> Result<(), String> {
+        let table: toml::Table = toml::from_str(source).map_err(|e| e.to_string())?;
+
+        for (path, value) in table {
+            let name = format!("{namespace}:{path}");
+            let definition = value.try_into().map_err(|e| format!("{name}: {e}"))?;
+            self.register(&name, &definition, blocks).map_err(|e| format!("{name}: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a feature, taking its blocks from `blocks`.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the name is taken, a block is unknown or a size or height is out of
+    ///   bounds.
+    pub fn register(&mut self, name: &str, definition: &FeatureDefinition, blocks: &BlockRegistry) -> Result<(), String> {
+        if self.names.contains(name) {
+            return Err(format!("feature {name} is registered twice"));
+        }
+
+        let states = |names: &[String]| names.iter().map(|name| blocks.parse_state(name)).collect::<Result<Vec<_>, _>>();
+
+        match definition {
+            FeatureDefinition::Ore(ore) => {
+                let height = ore.height;
+
+                if ore.size == 0 || ore.size > MAX_VEIN_SIZE {
+                    return Err(format!("vein size {} is not within 1 and {MAX_VEIN_SIZE}", ore.size));
+                }
+
+                if height.min > height.max || height.min < 0 || height.max >= CHUNK_HEIGHT as i32 {
+                    return Err(format!("invalid height range {} to {}", height.min, height.max));
+                }
+
+                if ore.count.is_nan() || ore.count < 0.0 {
+                    return Err(format!("invalid vein count {}", ore.count));
+                }
+
+                if u16::try_from(self.ores.len()).is_err() {
+                    return Err(String::from("too many ores"));
+                }
+
+                self.ores.push(Ore {
+                    name: name.to_string(),
+                    block: blocks.parse_state(&ore.block)?,
+                    replaces: states(&ore.replaces)?,
+                    size: ore.size,
+                    count: ore.count,
+                    height,
+                });
+            }
+            FeatureDefinition::Tree(tree) => {
+                if tree.height[0] == 0 || tree.height[0] > tree.height[1] || tree.height[1] > 32 {
+                    return Err(format!("invalid trunk height {} to {}", tree.height[0], tree.height[1]));
+                }
+
+                self.trees.push(Tree {
+                    name: name.to_string(),
+                    shape: tree.shape,
+                    log: blocks.parse_state(&tree.log)?,
+                    leaves: blocks.parse_state(&tree.leaves)?,
+                    height: tree.height,
+                    soil: states(&tree.soil)?,
+                });
+            }
+        }
+
+        self.names.insert(name.to_string());
+        Ok(())
+    }
+
+    /// Returns every ore, in registration order.
+    pub fn ores(&self) -> &[Ore] {
+        &self.ores
+    }
+
+    /// Returns every tree, in registration order.
+    pub fn trees(&self) -> &[Tree] {
+        &self.trees
+    }
+
+    /// Returns the index of the tree called `name`. The namespace may be left out for
+    /// built-in trees.
+    pub fn tree(&self, name: &str) -> Option<usize> {
+        let name = match name.contains(':') {
+            true => name.to_string(),
+            false => format!("{}:{name}", crate::block::DEFAULT_NAMESPACE),
+        };
+
+        self.trees.iter().position(|tree| tree.name == name)
+    }
+
+    /// Returns whether no feature is registered.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -0,0 +1,76 @@
+use super::{TerrainGenerator, SEA_LEVEL};
+use crate::resource::Image;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// What a map made by [`biome_map`] shows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapLayer {
+    /// The map color of each biome, shaded by the slope of the terrain.
+    #[default]
+    Biomes,
+
+    /// Terrain height, from black at the bottom of the world to white at the top, with
+    /// everything under water tinted blue.
+    Height,
+
+    /// Temperature, from blue (cold) to red (hot).
+    Temperature,
+
+    /// Humidity, from yellow (arid) to blue (wet).
+    Humidity,
+}
+
+/// Draws a map of the world made by `generator`, without generating any chunk.
+///
+/// The map covers `width` by `height` pixels from world `x` and `z`, north up, each pixel
+/// sampling one column every `scale` blocks. It is meant for tuning biomes and terrain
+/// without launching the game.
+pub fn biome_map(generator: &TerrainGenerator, layer: MapLayer, x: i32, z: i32, width: u32, height: u32, scale: u32) -> Image {
+    let mut image = Image::new(width, height, [0, 0, 0, 255]);
+    let scale = scale.max(1) as i32;
+    let source = generator.biome_source();
+    let climate = |x, z| source.climate(x, z, &generator.shape().column(x, z));
+    let mut heights = HashMap::new();
+
+    for pixel_z in 0..height {
+        for pixel_x in 0..width {
+            let world_x = x + pixel_x as i32 * scale;
+            let world_z = z + pixel_z as i32 * scale;
+
+            let [r, g, b] = match layer {
+                MapLayer::Biomes => {
+                    let biome = source.registry().get(generator.biome_at(world_x, world_z));
+
+                    // Lit from the north west, like hill shading on paper maps.
+                    let slope = generator.height_with(world_x, world_z, &mut heights) - generator.height_with(world_x - scale, world_z - scale, &mut heights);
+                    let shade = (1.0 + slope as f64 * 0.08 / scale as f64).clamp(0.6, 1.3);
+                    biome.colors.map.map(|c| (c as f64 * shade).min(255.0) as u8)
+                }
+                MapLayer::Height => {
+                    let column = generator.height_with(world_x, world_z, &mut heights);
+                    let level = column.clamp(0, 255) as u8;
+
+                    match column < SEA_LEVEL {
+                        true => [level / 3, level / 2, level],
+                        false => [level; 3],
+                    }
+                }
+                MapLayer::Temperature => gradient(climate(world_x, world_z).temperature, [40, 80, 220], [220, 50, 30]),
+                MapLayer::Humidity => gradient(climate(world_x, world_z).humidity, [220, 190, 80], [30, 90, 220]),
+            };
+
+            image.set(pixel_x, pixel_z, [r, g, b, 255]);
+        }
+    }
+
+    image
+}
+
+/// Returns the color between `low` at -1 and `high` at 1 for `value`.
+fn gradient(value: f64, low: [u8; 3], high: [u8; 3]) -> [u8; 3] {
+    let t = ((value + 1.0) / 2.0).clamp(0.0, 1.0);
+    std::array::from_fn(|i| (low[i] as f64 + (high[i] as f64 - low[i] as f64) * t).round() as u8)
+}
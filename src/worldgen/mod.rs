@@ -0,0 +1,22 @@
+pub mod noise;
+mod random;
+mod biome;
+mod spline;
+mod terrain;
+mod caves;
+mod carver;
+mod pending;
+mod feature;
+mod structure;
+mod map;
+
+pub use random::{derive_seed, hash3, mix, WorldRandom};
+pub use spline::Spline;
+pub use biome::{Biome, BiomeColors, BiomeDefinition, BiomeRegistry, BiomeSource, Climate, ClimateRanges, Vegetation};
+pub use caves::{Aquifer, CaveFill, CaveSettings, NoiseCaveChunk, NoiseCaves};
+pub use carver::Carvers;
+pub use pending::{FeatureWrites, PendingWrite, PendingWrites, Replace};
+pub use feature::{FeatureDefinition, FeatureRegistry, HeightDistribution, HeightRange, Ore, OreDefinition, Tree, TreeDefinition, TreeShape, MAX_VEIN_SIZE};
+pub use structure::{ConnectorDefinition, PieceDefinition, Structure, StructureDefinition, StructurePlacer, StructureRegistry};
+pub use terrain::{TerrainColumn, TerrainGenerator, TerrainShape, SEA_LEVEL};
+pub use map::{biome_map, MapLayer};
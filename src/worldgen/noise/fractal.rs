@@ -0,0 +1,82 @@
+use super::Noise;
+use crate::worldgen::random::mix;
+
+/// Fractal Brownian motion: several octaves of a noise summed, each at a higher frequency and
+/// a lower amplitude than the one before.
+///
+/// Every octave has its own seed, so their lattices never line up. The sum is divided by the
+/// total amplitude, keeping the result in the range of the base noise.
+#[derive(Debug, Clone)]
+pub struct Fbm<N> {
+    octaves: Vec<N>,
+    frequency: f64,
+    lacunarity: f64,
+    persistence: f64,
+    normalizer: f64,
+}
+
+impl<N: Noise> Fbm<N> {
+    /// Creates `octaves` octaves of the noise built by `noise` from a seed, starting at
+    /// `frequency` samples per block. Each octave doubles the frequency and halves the
+    /// amplitude of the previous one.
+    pub fn new(seed: u64, octaves: usize, frequency: f64, noise: impl Fn(u64) -> N) -> Self {
+        let octaves: Vec<N> = (0..octaves as u64).map(|octave| noise(mix(seed.wrapping_add(octave)))).collect();
+        let mut fbm = Self { octaves, frequency, lacunarity: 2.0, persistence: 0.5, normalizer: 1.0 };
+        fbm.normalize();
+        fbm
+    }
+
+    /// Sets how much the frequency grows from one octave to the next.
+    pub fn lacunarity(mut self, lacunarity: f64) -> Self {
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    /// Sets how much the amplitude shrinks from one octave to the next.
+    pub fn persistence(mut self, persistence: f64) -> Self {
+        self.persistence = persistence;
+        self.normalize();
+        self
+    }
+
+    /// Returns the number of octaves.
+    pub fn octaves(&self) -> usize {
+        self.octaves.len()
+    }
+
+    fn normalize(&mut self) {
+        let total: f64 = (0..self.octaves.len()).scan(1.0, |amplitude, _| {
+            let current = *amplitude;
+            *amplitude *= self.persistence;
+            Some(current)
+        }).sum();
+
+        self.normalizer = if total > 0.0 { 1.0 / total } else { 0.0 };
+    }
+}
+
+impl<N: Noise> Noise for Fbm<N> {
+    fn sample2(&self, x: f64, y: f64) -> f64 {
+        let (mut frequency, mut amplitude, mut sum) = (self.frequency, 1.0, 0.0);
+
+        for octave in &self.octaves {
+            sum += octave.sample2(x * frequency, y * frequency) * amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+
+        sum * self.normalizer
+    }
+
+    fn sample3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (mut frequency, mut amplitude, mut sum) = (self.frequency, 1.0, 0.0);
+
+        for octave in &self.octaves {
+            sum += octave.sample3(x * frequency, y * frequency, z * frequency) * amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+
+        sum * self.normalizer
+    }
+}
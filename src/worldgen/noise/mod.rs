@@ -0,0 +1,50 @@
+mod perlin;
+mod simplex;
+mod open_simplex;
+mod fractal;
+mod warp;
+
+pub use perlin::Perlin;
+pub use simplex::Simplex;
+pub use open_simplex::OpenSimplex2;
+pub use fractal::Fbm;
+pub use warp::DomainWarp;
+
+use super::WorldRandom;
+
+/// Smooth, seeded pseudo-random noise, roughly in `[-1, 1]`.
+///
+/// Implementations only use basic floating point arithmetic, never platform math functions
+/// such as `sin`, so they give bit for bit the same values everywhere.
+pub trait Noise: Send + Sync {
+    /// Samples the noise on a plane; heightmaps pass the world x and z.
+    fn sample2(&self, x: f64, y: f64) -> f64;
+
+    /// Samples the noise in space.
+    fn sample3(&self, x: f64, y: f64, z: f64) -> f64;
+}
+
+/// Returns the numbers 0 to 255 shuffled by `seed`, repeated twice so that looking up
+/// `table[table[x] + y]` never needs wrapping.
+fn permutation(seed: u64) -> [u8; 512] {
+    let mut random = WorldRandom::new(seed);
+    let mut table = [0u8; 512];
+
+    for (i, value) in table.iter_mut().take(256).enumerate() {
+        *value = i as u8;
+    }
+
+    for i in (1..256).rev() {
+        let j = random.next_below(i as u32 + 1) as usize;
+        table.swap(i, j);
+    }
+
+    table.copy_within(0..256, 256);
+    table
+}
+
+/// Returns `t` eased so the first and second derivatives are zero at 0 and 1.
+#[inline]
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
@@ -0,0 +1,231 @@
+use super::Noise;
+
+const PRIME_X: i64 = 0x5205_402b_9270_c86f;
+const PRIME_Y: i64 = 0x598c_d327_0038_17b5;
+const PRIME_Z: i64 = 0x5bcc_226e_9fa0_bacb;
+const HASH_MULTIPLIER: i64 = 0x53a3_f72d_eec5_46f5;
+
+/// Flips the seed between the two interleaved lattices of the 3D noise.
+const SEED_FLIP_3D: i64 = -0x52d5_47b2_e96e_d629;
+
+const SKEW_2D: f64 = 0.366_025_403_784_438_6;
+const UNSKEW_2D: f64 = -0.211_324_865_405_187_1;
+
+/// Squared radius of the contribution of each lattice point.
+const RADIUS_2D: f64 = 0.5;
+const RADIUS_3D: f64 = 0.6;
+
+/// Scale bringing the output to about `[-1, 1]`, measured over many samples.
+const NORMALIZER_2D: f64 = 99.2;
+const NORMALIZER_3D: f64 = 32.7;
+
+/// Unit vector of the first quadrant used to build the 32 gradients of the 2D noise, at
+/// angles `(i + 0.5) * 360° / 32`.
+const QUADRANT: [f64; 8] = [
+    0.995_184_726_672_196_9,
+    0.956_940_335_732_208_8,
+    0.881_921_264_348_355,
+    0.773_010_453_362_737,
+    0.634_393_284_163_645_5,
+    0.471_396_736_825_997_8,
+    0.290_284_677_254_462_33,
+    0.098_017_140_329_560_6,
+];
+
+const GRADIENTS_2D: [[f64; 2]; 32] = gradients_2d();
+
+/// Gradients of the 3D noise, the twelve edges of a cube with four of them repeated.
+const GRADIENTS_3D: [[f64; 3]; 16] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+    [1.0, 1.0, 0.0],
+    [0.0, -1.0, 1.0],
+    [-1.0, 1.0, 0.0],
+    [0.0, -1.0, -1.0],
+];
+
+const fn gradients_2d() -> [[f64; 2]; 32] {
+    let mut gradients = [[0.0; 2]; 32];
+    let mut j = 0;
+
+    while j < 8 {
+        let (c, s) = (QUADRANT[j], QUADRANT[7 - j]);
+        gradients[j] = [c, s];
+        gradients[j + 8] = [-s, c];
+        gradients[j + 16] = [-c, -s];
+        gradients[j + 24] = [s, -c];
+        j += 1;
+    }
+
+    gradients
+}
+
+/// OpenSimplex2 noise, after K.jpg's reference implementation.
+///
+/// The 2D noise runs on a triangular lattice like simplex noise but hashes lattice points
+/// without a permutation table, so it has no period. The 3D noise uses two interleaved cubic
+/// lattices (a body centered cubic one) seen along the main diagonal, which hides the grid
+/// better than simplex noise does.
+#[derive(Debug, Clone)]
+pub struct OpenSimplex2 {
+    seed: i64,
+}
+
+impl OpenSimplex2 {
+    /// Creates the noise for `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { seed: seed as i64 }
+    }
+
+    #[inline]
+    fn gradient2(seed: i64, x: i64, y: i64, dx: f64, dy: f64) -> f64 {
+        let hash = (seed ^ x ^ y).wrapping_mul(HASH_MULTIPLIER);
+        let gradient = &GRADIENTS_2D[(hash as u64 >> 59) as usize];
+        gradient[0] * dx + gradient[1] * dy
+    }
+
+    #[inline]
+    fn gradient3(seed: i64, x: i64, y: i64, z: i64, dx: f64, dy: f64, dz: f64) -> f64 {
+        let hash = ((seed ^ x) ^ (y ^ z)).wrapping_mul(HASH_MULTIPLIER);
+        let gradient = &GRADIENTS_3D[(hash as u64 >> 60) as usize];
+        gradient[0] * dx + gradient[1] * dy + gradient[2] * dz
+    }
+}
+
+impl Noise for OpenSimplex2 {
+    fn sample2(&self, x: f64, y: f64) -> f64 {
+        let skew = SKEW_2D * (x + y);
+        let (xs, ys) = (x + skew, y + skew);
+
+        let (xsb, ysb) = (xs.floor(), ys.floor());
+        let (xi, yi) = (xs - xsb, ys - ysb);
+        let xsbp = (xsb as i64).wrapping_mul(PRIME_X);
+        let ysbp = (ysb as i64).wrapping_mul(PRIME_Y);
+
+        let t = (xi + yi) * UNSKEW_2D;
+        let (dx0, dy0) = (xi + t, yi + t);
+        let mut value = 0.0;
+
+        let a0 = RADIUS_2D - dx0 * dx0 - dy0 * dy0;
+
+        if a0 > 0.0 {
+            value += (a0 * a0) * (a0 * a0) * Self::gradient2(self.seed, xsbp, ysbp, dx0, dy0);
+        }
+
+        // The opposite corner, with its falloff derived from the first one.
+        let diagonal = 1.0 + 2.0 * UNSKEW_2D;
+        let a1 = (2.0 * diagonal * (1.0 / UNSKEW_2D + 2.0)) * t + (-2.0 * diagonal * diagonal + a0);
+
+        if a1 > 0.0 {
+            let (dx1, dy1) = (dx0 - diagonal, dy0 - diagonal);
+            let (x1, y1) = (xsbp.wrapping_add(PRIME_X), ysbp.wrapping_add(PRIME_Y));
+            value += (a1 * a1) * (a1 * a1) * Self::gradient2(self.seed, x1, y1, dx1, dy1);
+        }
+
+        // The third corner, depending on which half of the skewed square the point is in.
+        let (dx2, dy2, x2, y2) = match dy0 > dx0 {
+            true => (dx0 - UNSKEW_2D, dy0 - (UNSKEW_2D + 1.0), xsbp, ysbp.wrapping_add(PRIME_Y)),
+            false => (dx0 - (UNSKEW_2D + 1.0), dy0 - UNSKEW_2D, xsbp.wrapping_add(PRIME_X), ysbp),
+        };
+
+        let a2 = RADIUS_2D - dx2 * dx2 - dy2 * dy2;
+
+        if a2 > 0.0 {
+            value += (a2 * a2) * (a2 * a2) * Self::gradient2(self.seed, x2, y2, dx2, dy2);
+        }
+
+        value * NORMALIZER_2D
+    }
+
+    fn sample3(&self, x: f64, y: f64, z: f64) -> f64 {
+        // Rotate so the main diagonal of the lattice points up, away from the sampled axes.
+        let r = (2.0 / 3.0) * (x + y + z);
+        let (xr, yr, zr) = (r - x, r - y, r - z);
+
+        let (xrb, yrb, zrb) = (xr.round(), yr.round(), zr.round());
+        let (mut xri, mut yri, mut zri) = (xr - xrb, yr - yrb, zr - zrb);
+
+        // Towards the nearest other lattice point on each axis, and the distance to it.
+        let mut x_sign: i64 = if xri >= 0.0 { -1 } else { 1 };
+        let mut y_sign: i64 = if yri >= 0.0 { -1 } else { 1 };
+        let mut z_sign: i64 = if zri >= 0.0 { -1 } else { 1 };
+        let (mut ax0, mut ay0, mut az0) = (xri.abs(), yri.abs(), zri.abs());
+
+        let mut xrbp = (xrb as i64).wrapping_mul(PRIME_X);
+        let mut yrbp = (yrb as i64).wrapping_mul(PRIME_Y);
+        let mut zrbp = (zrb as i64).wrapping_mul(PRIME_Z);
+
+        let mut seed = self.seed;
+        let mut value = 0.0;
+        let mut a = (RADIUS_3D - xri * xri) - (yri * yri + zri * zri);
+
+        for lattice in 0..2 {
+            if a > 0.0 {
+                value += (a * a) * (a * a) * Self::gradient3(seed, xrbp, yrbp, zrbp, xri, yri, zri);
+            }
+
+            // The closest neighbor along the axis the point is furthest from the center on.
+            if ax0 >= ay0 && ax0 >= az0 {
+                let b = a + ax0 + ax0 - 1.0;
+
+                if b > 0.0 {
+                    let xp = xrbp.wrapping_sub(x_sign.wrapping_mul(PRIME_X));
+                    value += (b * b) * (b * b) * Self::gradient3(seed, xp, yrbp, zrbp, xri + x_sign as f64, yri, zri);
+                }
+            }
+            else if ay0 > ax0 && ay0 >= az0 {
+                let b = a + ay0 + ay0 - 1.0;
+
+                if b > 0.0 {
+                    let yp = yrbp.wrapping_sub(y_sign.wrapping_mul(PRIME_Y));
+                    value += (b * b) * (b * b) * Self::gradient3(seed, xrbp, yp, zrbp, xri, yri + y_sign as f64, zri);
+                }
+            }
+            else {
+                let b = a + az0 + az0 - 1.0;
+
+                if b > 0.0 {
+                    let zp = zrbp.wrapping_sub(z_sign.wrapping_mul(PRIME_Z));
+                    value += (b * b) * (b * b) * Self::gradient3(seed, xrbp, yrbp, zp, xri, yri, zri + z_sign as f64);
+                }
+            }
+
+            if lattice == 1 {
+                break;
+            }
+
+            // Move to the second lattice, offset by half a cell on every axis.
+            ax0 = 0.5 - ax0;
+            ay0 = 0.5 - ay0;
+            az0 = 0.5 - az0;
+
+            xri = x_sign as f64 * ax0;
+            yri = y_sign as f64 * ay0;
+            zri = z_sign as f64 * az0;
+
+            a += (0.75 - ax0) - (ay0 + az0);
+
+            xrbp = xrbp.wrapping_add((x_sign >> 1) & PRIME_X);
+            yrbp = yrbp.wrapping_add((y_sign >> 1) & PRIME_Y);
+            zrbp = zrbp.wrapping_add((z_sign >> 1) & PRIME_Z);
+
+            x_sign = -x_sign;
+            y_sign = -y_sign;
+            z_sign = -z_sign;
+
+            seed ^= SEED_FLIP_3D;
+        }
+
+        value * NORMALIZER_3D
+    }
+}
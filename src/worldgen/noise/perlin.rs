@@ -0,0 +1,93 @@
+use super::{fade, permutation, Noise};
+use crate::worldgen::WorldRandom;
+
+/// Improved Perlin noise, with a seeded permutation and origin.
+///
+/// Perlin noise is zero on every integer coordinate; the seed also moves the lattice by a
+/// random offset so that the world origin is not special.
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    table: [u8; 512],
+    offset: [f64; 3],
+}
+
+impl Perlin {
+    /// Creates the noise for `seed`.
+    pub fn new(seed: u64) -> Self {
+        let mut random = WorldRandom::new(seed);
+        let offset = [0; 3].map(|_| random.next_f64() * 256.0);
+        Self { table: permutation(random.next_u64()), offset }
+    }
+
+    #[inline]
+    fn hash(&self, x: i32, y: i32, z: i32) -> u8 {
+        let table = &self.table;
+        let x = table[(x & 255) as usize] as usize;
+        let y = table[x + (y & 255) as usize] as usize;
+        table[y + (z & 255) as usize]
+    }
+}
+
+impl Noise for Perlin {
+    fn sample2(&self, x: f64, y: f64) -> f64 {
+        let (x, y) = (x + self.offset[0], y + self.offset[1]);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (ix, iy) = (x0 as i32, y0 as i32);
+        let (u, v) = (fade(fx), fade(fy));
+
+        let corner = |dx: i32, dy: i32| grad2(self.hash(ix + dx, iy + dy, 0), fx - dx as f64, fy - dy as f64);
+
+        lerp(v, lerp(u, corner(0, 0), corner(1, 0)), lerp(u, corner(0, 1), corner(1, 1)))
+    }
+
+    fn sample3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = (x + self.offset[0], y + self.offset[1], z + self.offset[2]);
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (fx, fy, fz) = (x - x0, y - y0, z - z0);
+        let (ix, iy, iz) = (x0 as i32, y0 as i32, z0 as i32);
+        let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            grad3(self.hash(ix + dx, iy + dy, iz + dz), fx - dx as f64, fy - dy as f64, fz - dz as f64)
+        };
+
+        let near = lerp(v, lerp(u, corner(0, 0, 0), corner(1, 0, 0)), lerp(u, corner(0, 1, 0), corner(1, 1, 0)));
+        let far = lerp(v, lerp(u, corner(0, 0, 1), corner(1, 0, 1)), lerp(u, corner(0, 1, 1), corner(1, 1, 1)));
+        lerp(w, near, far)
+    }
+}
+
+#[inline]
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product of `(x, y)` with one of eight gradients picked by `hash`.
+#[inline]
+fn grad2(hash: u8, x: f64, y: f64) -> f64 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+/// Dot product of `(x, y, z)` with one of the twelve cube edge gradients picked by `hash`.
+#[inline]
+fn grad3(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let hash = hash & 15;
+    let u = if hash < 8 { x } else { y };
+    let v = match hash {
+        0..4 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+
+    (if hash & 1 == 0 { u } else { -u }) + (if hash & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,114 @@
+use super::{permutation, Noise};
+
+/// Gradients of simplex noise, the twelve edges of a cube.
+const GRADIENTS: [[f64; 3]; 12] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+];
+
+/// `(sqrt(3) - 1) / 2`, skewing the plane onto a grid of squares split in two triangles.
+const SKEW_2D: f64 = 0.366_025_403_784_438_6;
+
+/// `(3 - sqrt(3)) / 6`, going back from the skewed grid.
+const UNSKEW_2D: f64 = 0.211_324_865_405_187_1;
+
+const SKEW_3D: f64 = 1.0 / 3.0;
+const UNSKEW_3D: f64 = 1.0 / 6.0;
+
+/// Classic simplex noise, after Stefan Gustavson's reference implementation.
+///
+/// Cheaper than Perlin noise in 3D and without its axis aligned artifacts.
+#[derive(Debug, Clone)]
+pub struct Simplex {
+    table: [u8; 512],
+}
+
+impl Simplex {
+    /// Creates the noise for `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self { table: permutation(seed) }
+    }
+
+    #[inline]
+    fn gradient(&self, x: i32, y: i32, z: i32) -> &[f64; 3] {
+        let table = &self.table;
+        let hash = table[(x & 255) as usize + table[(y & 255) as usize + table[(z & 255) as usize] as usize] as usize];
+        &GRADIENTS[hash as usize % 12]
+    }
+}
+
+impl Noise for Simplex {
+    fn sample2(&self, x: f64, y: f64) -> f64 {
+        let skew = (x + y) * SKEW_2D;
+        let (i, j) = ((x + skew).floor(), (y + skew).floor());
+        let unskew = (i + j) * UNSKEW_2D;
+        let (x0, y0) = (x - (i - unskew), y - (j - unskew));
+
+        // The triangle containing the point: lower right or upper left half of the square.
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let (i, j) = (i as i32, j as i32);
+
+        let corners = [
+            (0, 0, x0, y0),
+            (i1, j1, x0 - i1 as f64 + UNSKEW_2D, y0 - j1 as f64 + UNSKEW_2D),
+            (1, 1, x0 - 1.0 + 2.0 * UNSKEW_2D, y0 - 1.0 + 2.0 * UNSKEW_2D),
+        ];
+
+        let mut value = 0.0;
+
+        for (di, dj, dx, dy) in corners {
+            let t = 0.5 - dx * dx - dy * dy;
+
+            if t > 0.0 {
+                let gradient = self.gradient(i + di, j + dj, 0);
+                value += t * t * t * t * (gradient[0] * dx + gradient[1] * dy);
+            }
+        }
+
+        70.0 * value
+    }
+
+    fn sample3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let skew = (x + y + z) * SKEW_3D;
+        let (i, j, k) = ((x + skew).floor(), (y + skew).floor(), (z + skew).floor());
+        let unskew = (i + j + k) * UNSKEW_3D;
+        let (x0, y0, z0) = (x - (i - unskew), y - (j - unskew), z - (k - unskew));
+
+        // The tetrahedron containing the point, from the order of its coordinates.
+        let (second, third) = match (x0 >= y0, y0 >= z0, x0 >= z0) {
+            (true, true, _) => ([1, 0, 0], [1, 1, 0]),
+            (true, false, true) => ([1, 0, 0], [1, 0, 1]),
+            (true, false, false) => ([0, 0, 1], [1, 0, 1]),
+            (false, false, _) => ([0, 0, 1], [0, 1, 1]),
+            (false, true, false) => ([0, 1, 0], [0, 1, 1]),
+            (false, true, true) => ([0, 1, 0], [1, 1, 0]),
+        };
+
+        let (i, j, k) = (i as i32, j as i32, k as i32);
+        let mut value = 0.0;
+
+        for (corner, offset) in [([0, 0, 0], 0.0), (second, UNSKEW_3D), (third, 2.0 * UNSKEW_3D), ([1, 1, 1], 3.0 * UNSKEW_3D)] {
+            let dx = x0 - corner[0] as f64 + offset;
+            let dy = y0 - corner[1] as f64 + offset;
+            let dz = z0 - corner[2] as f64 + offset;
+            let t = 0.6 - dx * dx - dy * dy - dz * dz;
+
+            if t > 0.0 {
+                let gradient = self.gradient(i + corner[0], j + corner[1], k + corner[2]);
+                value += t * t * t * t * (gradient[0] * dx + gradient[1] * dy + gradient[2] * dz);
+            }
+        }
+
+        32.0 * value
+    }
+}
@@ -0,0 +1,36 @@
+use super::Noise;
+
+/// A noise sampled at coordinates displaced by other noises, bending its features into
+/// swirls and ridges that look less regular than plain fractal noise.
+///
+/// One offset noise is used per axis; 2D sampling only reads the first two.
+#[derive(Debug, Clone)]
+pub struct DomainWarp<N, W> {
+    noise: N,
+    offsets: [W; 3],
+
+    /// Largest displacement, in the units of the sampled coordinates.
+    strength: f64,
+}
+
+impl<N: Noise, W: Noise> DomainWarp<N, W> {
+    /// Warps `noise` by up to `strength` along each axis, with one offset noise per axis.
+    pub fn new(noise: N, offsets: [W; 3], strength: f64) -> Self {
+        Self { noise, offsets, strength }
+    }
+}
+
+impl<N: Noise, W: Noise> Noise for DomainWarp<N, W> {
+    fn sample2(&self, x: f64, y: f64) -> f64 {
+        let dx = self.offsets[0].sample2(x, y) * self.strength;
+        let dy = self.offsets[1].sample2(x, y) * self.strength;
+        self.noise.sample2(x + dx, y + dy)
+    }
+
+    fn sample3(&self, x: f64, y: f64, z: f64) -> f64 {
+        let dx = self.offsets[0].sample3(x, y, z) * self.strength;
+        let dy = self.offsets[1].sample3(x, y, z) * self.strength;
+        let dz = self.offsets[2].sample3(x, y, z) * self.strength;
+        self.noise.sample3(x + dx, y + dy, z + dz)
+    }
+}
@@ -0,0 +1,219 @@
+use crate::world::{BlockPos, BlockState, Chunk, ChunkPos, CHUNK_HEIGHT};
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Which blocks a write made by a feature may replace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replace {
+    /// Only air, for leaves and plants.
+    Air,
+
+    /// Air and blocks giving way to growing trees, such as leaves and plants.
+    Soft,
+
+    /// The blocks the ore with this index in the feature registry grows through.
+    Ore(u16),
+
+    /// Anything.
+    Any,
+}
+
+/// A block written by a feature, in local coordinates of the chunk it lands in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PendingWrite {
+    pub x: u8,
+    pub y: u8,
+    pub z: u8,
+    pub state: BlockState,
+    pub replace: Replace,
+}
+
+/// The blocks written by the features starting in one chunk, grouped by the chunk they land
+/// in: the chunk itself or one of its eight neighbors.
+#[derive(Debug, Clone)]
+pub struct FeatureWrites {
+    source: ChunkPos,
+    targets: [Vec<PendingWrite>; 9],
+}
+
+impl FeatureWrites {
+    /// Creates an empty set of writes for the features starting in the chunk at `source`.
+    pub fn new(source: ChunkPos) -> Self {
+        Self { source, targets: Default::default() }
+    }
+
+    /// Returns the chunk the features start in.
+    pub fn source(&self) -> ChunkPos {
+        self.source
+    }
+
+    /// Writes `state` at world `pos` where it may replace the block already there. Blocks
+    /// beyond the neighbors of the source chunk, or above or below the world, are dropped.
+    pub fn set(&mut self, pos: BlockPos, state: BlockState, replace: Replace) {
+        let Some((x, y, z)) = pos.local() else {
+            return;
+        };
+
+        if let Some(index) = target_index(self.source, pos.chunk()) {
+            self.targets[index].push(PendingWrite { x: x as u8, y: y as u8, z: z as u8, state, replace });
+        }
+    }
+
+    /// Returns the writes landing in the chunk at `target`, in the order they were made.
+    pub fn landing_in(&self, target: ChunkPos) -> &[PendingWrite] {
+        target_index(self.source, target).map_or(&[], |index| &self.targets[index])
+    }
+}
+
+/// Returns the index of `target` among the chunks the features of `source` may write to.
+fn target_index(source: ChunkPos, target: ChunkPos) -> Option<usize> {
+    let (dx, dz) = (target.x - source.x, target.z - source.z);
+    (dx.abs() <= 1 && dz.abs() <= 1).then_some(((dz + 1) * 3 + dx + 1) as usize)
+}
+
+/// The features of one source chunk, decorated once and shared by the chunks they land in.
+#[derive(Debug, Default)]
+struct Decorated {
+    writes: OnceLock<FeatureWrites>,
+
+    /// Terrain of the source chunk, kept from decorating it until the chunk itself is
+    /// generated so it is not generated twice.
+    terrain: Mutex<Option<Chunk>>,
+}
+
+#[derive(Debug)]
+struct Source {
+    decorated: Arc<Decorated>,
+
+    /// One bit per target chunk already generated, see [`target_index`].
+    generated: u16,
+    last_used: u64,
+}
+
+/// Blocks written by features, held until the chunks they land in are generated.
+///
+/// Features are placed from the chunk they start in, reading only the terrain of that chunk,
+/// and may spill into its neighbors, such as a tree growing at the edge of a chunk. Generating
+/// a chunk decorates each of its neighbors in turn (or reuses their writes, if another chunk
+/// already needed them) and collects what they write into it, always in the same order. Every
+/// chunk thus gets the same blocks whatever order chunks are generated in, and none waits for
+/// its neighbors to be finished.
+///
+/// The writes of a source are dropped once all nine chunks they may land in are generated, or
+/// when too many sources are held; they are decorated again if needed later.
+#[derive(Debug)]
+pub struct PendingWrites {
+    sources: Mutex<(HashMap<ChunkPos, Source>, u64)>,
+    capacity: usize,
+}
+
+impl PendingWrites {
+    /// Creates an empty store holding the writes of at most `capacity` source chunks.
+    pub fn new(capacity: usize) -> Self {
+        Self { sources: Mutex::new((HashMap::new(), 0)), capacity: capacity.max(9) }
+    }
+
+    /// Returns the terrain of the chunk at `target` and every block written into it by
+    /// features, in the order they must be applied.
+    ///
+    /// Chunks are made by `terrain` and the writes of their features by `decorate`, both
+    /// called only when not already held.
+    pub fn collect(
+        &self,
+        target: ChunkPos,
+        terrain: impl Fn(ChunkPos) -> Chunk,
+        decorate: impl Fn(&Chunk) -> FeatureWrites,
+    ) -> (Chunk, Vec<PendingWrite>) {
+        let mut own = None;
+        let mut writes = Vec::new();
+
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                let source = target.offset(dx, dz);
+                let decorated = self.source(source);
+
+                let features = decorated.writes.get_or_init(|| {
+                    let chunk = terrain(source);
+                    let features = decorate(&chunk);
+                    *decorated.terrain.lock().unwrap() = Some(chunk);
+                    features
+                });
+
+                writes.extend_from_slice(features.landing_in(target));
+
+                if source == target {
+                    own = decorated.terrain.lock().unwrap().take();
+                }
+            }
+        }
+
+        self.generated(target);
+        (own.unwrap_or_else(|| terrain(target)), writes)
+    }
+
+    /// Returns the number of source chunks whose writes are held.
+    pub fn len(&self) -> usize {
+        self.sources.lock().unwrap().0.len()
+    }
+
+    /// Returns whether no writes are held.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every held write.
+    pub fn clear(&self) {
+        self.sources.lock().unwrap().0.clear();
+    }
+
+    fn source(&self, pos: ChunkPos) -> Arc<Decorated> {
+        let mut guard = self.sources.lock().unwrap();
+        let (sources, clock) = &mut *guard;
+        *clock += 1;
+
+        if !sources.contains_key(&pos) && sources.len() >= self.capacity {
+            // The least recently used is most likely left behind by the player.
+            if let Some(oldest) = sources.iter().min_by_key(|(_, source)| source.last_used).map(|(&pos, _)| pos) {
+                sources.remove(&oldest);
+            }
+        }
+
+        let source = sources.entry(pos).or_insert_with(|| Source { decorated: Arc::default(), generated: 0, last_used: 0 });
+        source.last_used = *clock;
+        source.decorated.clone()
+    }
+
+    /// Records that `target` is generated, dropping the sources it was the last target of.
+    fn generated(&self, target: ChunkPos) {
+        let mut guard = self.sources.lock().unwrap();
+
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                let pos = target.offset(dx, dz);
+
+                let Some(source) = guard.0.get_mut(&pos) else {
+                    continue;
+                };
+
+                source.generated |= 1 << target_index(pos, target).unwrap();
+
+                if source.generated == 0x1ff {
+                    guard.0.remove(&pos);
+                }
+            }
+        }
+    }
+}
+
+/// Applies `writes` to `chunk`, each where `replaces` allows it to replace the block there.
+pub fn apply(chunk: &mut Chunk, writes: &[PendingWrite], mut replaces: impl FnMut(Replace, BlockState) -> bool) {
+    for write in writes {
+        let (x, y, z) = (write.x as usize, write.y as usize, write.z as usize);
+        debug_assert!(y < CHUNK_HEIGHT);
+
+        if replaces(write.replace, chunk.get(x, y, z)) {
+            chunk.set(x, y, z, write.state);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crate::world::ChunkPos;
+
+/// A small, fast random number generator (SplitMix64) with a fixed algorithm.
+///
+/// World generation must give the same world for the same seed on every platform and every
+/// version, so it never relies on the standard library's randomness or hashing, whose
+/// algorithms may change.
+#[derive(Debug, Clone)]
+pub struct WorldRandom {
+    state: u64,
+}
+
+impl WorldRandom {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator for the chunk at `pos`, independent of any other chunk or salt.
+    pub fn for_chunk(seed: u64, pos: ChunkPos, salt: u64) -> Self {
+        Self::new(hash3(seed ^ salt, pos.x as i64, 0, pos.z as i64))
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        mix(self.state)
+    }
+
+    /// Returns the next 32 random bits.
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Returns a number in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a number in `[0, bound)`, or 0 when `bound` is 0.
+    pub fn next_below(&mut self, bound: u32) -> u32 {
+        ((self.next_u32() as u64 * bound as u64) >> 32) as u32
+    }
+
+    /// Returns a number in `[min, max]`.
+    pub fn next_range(&mut self, min: i32, max: i32) -> i32 {
+        debug_assert!(min <= max);
+        min + self.next_below((max - min) as u32 + 1) as i32
+    }
+
+    /// Returns `true` with probability `chance`.
+    pub fn chance(&mut self, chance: f64) -> bool {
+        self.next_f64() < chance
+    }
+}
+
+/// Scrambles the bits of `value` (the SplitMix64 finalizer).
+pub fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// Hashes a position together with a seed.
+pub fn hash3(seed: u64, x: i64, y: i64, z: i64) -> u64 {
+    let mut hash = mix(seed);
+    hash = mix(hash ^ (x as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    hash = mix(hash ^ (y as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f));
+    mix(hash ^ (z as u64).wrapping_mul(0x1656_67b1_9e37_79f9))
+}
+
+/// Derives an independent seed for one use of the world seed, such as one noise layer.
+pub fn derive_seed(seed: u64, name: &str) -> u64 {
+    // FNV-1a, so the derived seeds never change between builds.
+    let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3));
+    mix(seed ^ mix(hash))
+}
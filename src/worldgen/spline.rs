@@ -0,0 +1,38 @@
+/// A piecewise linear curve through control points, flat before the first point and after
+/// the last.
+///
+/// Terrain maps noise values to heights with these, which keeps the shape of the world easy
+/// to tune: each point reads as "at this noise value, this height".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spline {
+    points: Vec<(f64, f64)>,
+}
+
+impl Spline {
+    /// Creates a curve through `points`, given as `(input, output)` pairs.
+    ///
+    /// # Panics
+    /// Panics if there are no points or their inputs are not increasing.
+    pub fn new(points: &[(f64, f64)]) -> Self {
+        assert!(!points.is_empty(), "a spline needs at least one point");
+        assert!(points.windows(2).all(|pair| pair[0].0 < pair[1].0), "spline inputs must be increasing");
+        Self { points: points.to_vec() }
+    }
+
+    /// Returns the value of the curve at `x`.
+    pub fn sample(&self, x: f64) -> f64 {
+        let index = self.points.partition_point(|&(input, _)| input <= x);
+
+        if index == 0 {
+            return self.points[0].1;
+        }
+
+        if index == self.points.len() {
+            return self.points[index - 1].1;
+        }
+
+        let (x0, y0) = self.points[index - 1];
+        let (x1, y1) = self.points[index];
+        y0 + (x - x0) / (x1 - x0) * (y1 - y0)
+    }
+}
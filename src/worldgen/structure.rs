@@ -0,0 +1,688 @@
+use super::random::derive_seed;
+use super::{BiomeRegistry, TerrainGenerator, WorldRandom, SEA_LEVEL};
+use crate::block::{BlockRegistry, DEFAULT_NAMESPACE};
+use crate::resource::ResourceManager;
+use crate::world::{BiomeId, BlockPos, BlockState, Chunk, ChunkPos, Direction, CHUNK_HEIGHT, SECTION_SIZE};
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+/// Directory of `assets/` holding structure data files, one per namespace.
+const STRUCTURES_DIR: &str = "structures/";
+
+/// Deepest a foundation reaches under a piece.
+const MAX_FOUNDATION: i32 = 12;
+
+/// Pieces tried at each connector before leaving it unused.
+const ATTEMPTS: u32 = 6;
+
+/// Assembled structures kept for the chunks they span.
+const LAYOUT_CACHE: usize = 256;
+
+/// A structure as written in data files.
+///
+/// ```toml
+/// [desert_well]
+/// biomes = ["desert"]
+/// spacing = 16
+/// separation = 4
+/// start = "well"
+///
+/// [desert_well.pieces.well]
+/// palette = { s = "sandstone", w = "water" }
+/// layers = [
+///     ["sssss", "sswss", "swwws", "sswss", "sssss"],
+///     ["s...s", ".....", ".....", ".....", "s...s"],
+///     ["sssss", "sssss", "sssss", "sssss", "sssss"],
+/// ]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StructureDefinition {
+    /// Biomes the structure may start in.
+    pub biomes: Vec<String>,
+
+    /// Size of the square regions of chunks the structure starts at most once in.
+    pub spacing: u32,
+
+    /// Chunks left between the start and the next region, so starts are never too close.
+    pub separation: u32,
+
+    /// Piece the structure is assembled from.
+    pub start: String,
+
+    /// Most pieces between the start and any other piece.
+    #[serde(default = "default_depth")]
+    pub depth: u32,
+
+    /// Farthest any piece reaches from the center of the start chunk, in blocks.
+    #[serde(default = "default_radius")]
+    pub radius: u32,
+
+    /// Block filling the gap between pieces and lower ground.
+    #[serde(default)]
+    pub foundation: Option<String>,
+
+    /// Lists of pieces connectors pick from. Listing a piece more than once makes it more
+    /// common.
+    #[serde(default)]
+    pub pools: BTreeMap<String, Vec<String>>,
+
+    pub pieces: BTreeMap<String, PieceDefinition>,
+}
+
+fn default_depth() -> u32 {
+    4
+}
+
+fn default_radius() -> u32 {
+    48
+}
+
+/// A piece of a structure, see [`StructureDefinition`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PieceDefinition {
+    /// Block of each character of the layers.
+    #[serde(default)]
+    pub palette: BTreeMap<String, String>,
+
+    /// Horizontal slices of the piece from the bottom up, each a list of rows from north to
+    /// south, with one character per block from west to east. Spaces are left untouched and
+    /// `.` is air.
+    pub layers: Vec<Vec<String>>,
+
+    #[serde(default)]
+    pub connectors: Vec<ConnectorDefinition>,
+
+    /// Bends the piece column by column to lie on the ground, like a path, instead of placing
+    /// it level at the height of the ground at its center.
+    #[serde(default)]
+    pub follow_terrain: bool,
+}
+
+/// A point of the bottom layer of a piece where another piece may be attached.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectorDefinition {
+    /// Column of the connector, from the north west corner.
+    pub at: [u32; 2],
+
+    /// Side of the piece the connector is on.
+    pub facing: String,
+
+    /// Pool the attached piece comes from. Connectors without one only receive pieces.
+    #[serde(default)]
+    pub pool: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Connector {
+    x: i32,
+    z: i32,
+    facing: Direction,
+    pool: Option<usize>,
+}
+
+/// The blocks of a piece in one orientation.
+#[derive(Debug, Clone)]
+struct Template {
+    size: [i32; 3],
+    blocks: Vec<Option<BlockState>>,
+    connectors: Vec<Connector>,
+}
+
+impl Template {
+    fn get(&self, x: i32, y: i32, z: i32) -> Option<BlockState> {
+        let [size_x, _, size_z] = self.size;
+        self.blocks[((y * size_z + z) * size_x + x) as usize]
+    }
+
+    /// Returns the template turned a quarter clockwise, seen from above, with the facing and
+    /// axis of its blocks turned along.
+    fn rotated(&self, registry: &BlockRegistry) -> Self {
+        let [size_x, size_y, size_z] = self.size;
+        let mut blocks = vec![None; self.blocks.len()];
+
+        for y in 0..size_y {
+            for z in 0..size_z {
+                for x in 0..size_x {
+                    let (new_x, new_z) = (size_z - 1 - z, x);
+                    blocks[((y * size_x + new_z) * size_z + new_x) as usize] = self.get(x, y, z).map(|state| rotate_state(registry, state));
+                }
+            }
+        }
+
+        let connectors = self
+            .connectors
+            .iter()
+            .map(|c| Connector { x: size_z - 1 - c.z, z: c.x, facing: c.facing.rotate_clockwise(), pool: c.pool })
+            .collect();
+
+        Self { size: [size_z, size_y, size_x], blocks, connectors }
+    }
+}
+
+/// Returns `state` turned a quarter clockwise, seen from above.
+fn rotate_state(registry: &BlockRegistry, state: BlockState) -> BlockState {
+    if let Some(facing) = registry.value(state, "facing").and_then(|facing| facing.parse::<Direction>().ok()) {
+        return registry.with_value(state, "facing", facing.rotate_clockwise().name()).unwrap_or(state);
+    }
+
+    match registry.value(state, "axis") {
+        Some("x") => registry.with_value(state, "axis", "z").unwrap_or(state),
+        Some("z") => registry.with_value(state, "axis", "x").unwrap_or(state),
+        _ => state,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Piece {
+    rotations: [Template; 4],
+    follow_terrain: bool,
+}
+
+/// A structure, with its blocks, biomes and pieces resolved.
+#[derive(Debug, Clone)]
+pub struct Structure {
+    pub name: String,
+    pub biomes: Vec<BiomeId>,
+    pub spacing: i32,
+    pub separation: i32,
+    pub depth: u32,
+    pub radius: i32,
+    pub foundation: Option<BlockState>,
+    start: usize,
+    pieces: Vec<Piece>,
+    pools: Vec<Vec<usize>>,
+}
+
+/// Every structure known to the game.
+///
+/// Structures are loaded from data files like blocks and biomes; see
+/// `assets/structures/rustedcraft.toml` for the format.
+#[derive(Debug, Clone, Default)]
+pub struct StructureRegistry {
+    structures: Vec<Structure>,
+}
+
+impl StructureRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the structures of every `structures/<namespace>.toml` file provided by
+    /// `resources`, in file name order, taking their blocks from `blocks` and biomes from
+    /// `biomes`.
+    ///
+    /// # Returns
+    /// * `Err(String)` if a file is invalid.
+    pub fn load(resources: &ResourceManager, blocks: &BlockRegistry, biomes: &BiomeRegistry) -> Result<Self, String> {
+        let mut registry = Self::new();
+
+        for path in resources.list(STRUCTURES_DIR) {
+            let Some(namespace) = path.strip_prefix(STRUCTURES_DIR).and_then(|file| file.strip_suffix(".toml")) else {
+                continue;
+            };
+
+            if namespace.contains('/') {
+                continue;
+            }
+
+            let source = resources.read_to_string(&path)?;
+            registry.load_str(namespace, &source, blocks, biomes).map_err(|e| format!("{path}: {e}"))?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Registers every structure of a data file whose structures belong to `namespace`. Each
+    /// top-level table is a structure named after its key, see [`StructureDefinition`].
+    pub fn load_str(&mut self, namespace: &str, source: &str, blocks: &BlockRegistry, biomes: &BiomeRegistry) -> Result<(), String> {
+        let table: toml::Table = toml::from_str(source).map_err(|e| e.to_string())?;
+
+        for (path, value) in table {
+            let name = format!("{namespace}:{path}");
+            let definition = value.try_into().map_err(|e| format!("{name}: {e}"))?;
+            self.register(&name, &definition, blocks, biomes).map_err(|e| format!("{name}: {e}"))?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds a structure, taking its blocks from `blocks` and biomes from `biomes`.
+    ///
+    /// # Returns
+    /// * `Err(String)` if the name is taken, a block, biome, piece or pool is unknown, or a
+    ///   piece is malformed.
+    pub fn register(&mut self, name: &str, definition: &StructureDefinition, blocks: &BlockRegistry, biomes: &BiomeRegistry) -> Result<(), String> {
+        if self.structures.iter().any(|structure| structure.name == name) {
+            return Err(format!("structure {name} is registered twice"));
+        }
+
+        if definition.spacing == 0 || definition.separation >= definition.spacing {
+            return Err(format!("spacing {} must be larger than separation {}", definition.spacing, definition.separation));
+        }
+
+        let piece_names: Vec<&String> = definition.pieces.keys().collect();
+        let piece_index = |piece: &str| piece_names.iter().position(|name| *name == piece).ok_or_else(|| format!("unknown piece {piece}"));
+        let pool_names: Vec<&String> = definition.pools.keys().collect();
+
+        let pools = definition
+            .pools
+            .values()
+            .map(|pool| pool.iter().map(|piece| piece_index(piece)).collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let pieces = definition
+            .pieces
+            .iter()
+            .map(|(piece_name, piece)| build_piece(piece, blocks, &pool_names).map_err(|e| format!("piece {piece_name}: {e}")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let biomes = definition
+            .biomes
+            .iter()
+            .map(|biome| biomes.id(biome).ok_or_else(|| format!("unknown biome {biome}")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.structures.push(Structure {
+            name: name.to_string(),
+            biomes,
+            spacing: definition.spacing as i32,
+            separation: definition.separation as i32,
+            depth: definition.depth,
+            radius: definition.radius as i32,
+            foundation: definition.foundation.as_deref().map(|block| blocks.parse_state(block)).transpose()?,
+            start: piece_index(&definition.start)?,
+            pieces,
+            pools,
+        });
+
+        Ok(())
+    }
+
+    /// Returns every structure, in registration order.
+    pub fn structures(&self) -> &[Structure] {
+        &self.structures
+    }
+
+    /// Returns the index of the structure called `name`. The namespace may be left out for
+    /// built-in structures.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        let name = match name.contains(':') {
+            true => name.to_string(),
+            false => format!("{DEFAULT_NAMESPACE}:{name}"),
+        };
+
+        self.structures.iter().position(|structure| structure.name == name)
+    }
+
+    /// Returns whether no structure is registered.
+    pub fn is_empty(&self) -> bool {
+        self.structures.is_empty()
+    }
+}
+
+fn build_piece(definition: &PieceDefinition, blocks: &BlockRegistry, pools: &[&String]) -> Result<Piece, String> {
+    let mut palette = HashMap::from([(' ', None), ('.', Some(BlockState::AIR))]);
+
+    for (key, block) in &definition.palette {
+        let mut chars = key.chars();
+
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return Err(format!("palette key {key:?} is not a single character"));
+        };
+
+        if palette.insert(c, Some(blocks.parse_state(block)?)).is_some() {
+            return Err(format!("palette key {key:?} is reserved"));
+        }
+    }
+
+    let rows = definition.layers.first().map_or(0, Vec::len);
+    let width = definition.layers.first().and_then(|layer| layer.first()).map_or(0, |row| row.chars().count());
+
+    if rows == 0 || width == 0 {
+        return Err(String::from("piece is empty"));
+    }
+
+    let mut cells = Vec::with_capacity(definition.layers.len() * rows * width);
+
+    for layer in &definition.layers {
+        if layer.len() != rows {
+            return Err(format!("layers have {} and {rows} rows", layer.len()));
+        }
+
+        for row in layer {
+            if row.chars().count() != width {
+                return Err(format!("row {row:?} is not {width} blocks wide"));
+            }
+
+            for c in row.chars() {
+                cells.push(*palette.get(&c).ok_or_else(|| format!("{c:?} is not in the palette"))?);
+            }
+        }
+    }
+
+    let connectors = definition
+        .connectors
+        .iter()
+        .map(|connector| {
+            let facing: Direction = connector.facing.parse()?;
+            let [x, z] = connector.at;
+
+            if facing.axis() == 1 || x as usize >= width || z as usize >= rows {
+                return Err(format!("invalid connector at {x}, {z} facing {facing}"));
+            }
+
+            let pool = match &connector.pool {
+                Some(pool) => Some(pools.iter().position(|name| *name == pool).ok_or_else(|| format!("unknown pool {pool}"))?),
+                None => None,
+            };
+
+            Ok(Connector { x: x as i32, z: z as i32, facing, pool })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let first = Template { size: [width as i32, definition.layers.len() as i32, rows as i32], blocks: cells, connectors };
+    let second = first.rotated(blocks);
+    let third = second.rotated(blocks);
+    let fourth = third.rotated(blocks);
+
+    Ok(Piece { rotations: [first, second, third, fourth], follow_terrain: definition.follow_terrain })
+}
+
+/// A piece placed in the world: its north west corner, and for level pieces the height of its
+/// bottom layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PlacedPiece {
+    piece: usize,
+    rotation: usize,
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+/// Columns covered by a piece, as west, north, east and south bounds, the last two excluded.
+type Footprint = [i32; 4];
+
+/// A structure assembled from its pieces.
+#[derive(Debug, Clone)]
+struct Layout {
+    pieces: Vec<PlacedPiece>,
+    footprints: Vec<Footprint>,
+}
+
+/// Assembled structures by structure index and start chunk, `None` where they do not
+/// generate.
+type Layouts = HashMap<(usize, ChunkPos), Option<Arc<Layout>>>;
+
+/// Places structures in the world.
+///
+/// The world is divided into square regions of `spacing` chunks, and each structure may start
+/// once per region, in a chunk chosen from the seed. There, it is assembled from its pieces,
+/// attached one to another at their connectors, choosing among the pieces of each pool at
+/// random, in whatever orientation fits without overlapping the others. Assembling only
+/// depends on the seed and on the heights and biomes of the terrain, so every chunk a
+/// structure spans assembles the same one and places the part of it inside itself.
+pub struct StructurePlacer {
+    seed: u64,
+    registry: Arc<StructureRegistry>,
+    layouts: Mutex<Layouts>,
+}
+
+impl StructurePlacer {
+    /// Creates the placer of the world with seed `seed`, placing the structures of `registry`.
+    pub fn new(seed: u64, registry: Arc<StructureRegistry>) -> Self {
+        Self { seed, registry, layouts: Mutex::new(HashMap::new()) }
+    }
+
+    /// Returns the structures placed.
+    pub fn registry(&self) -> &Arc<StructureRegistry> {
+        &self.registry
+    }
+
+    /// Returns the chunk where the structure with index `index` may start in the region at
+    /// `region_x` and `region_z`.
+    pub fn start(&self, index: usize, region_x: i32, region_z: i32) -> ChunkPos {
+        let structure = &self.registry.structures[index];
+        let seed = derive_seed(self.seed, &structure.name);
+        let mut random = WorldRandom::for_chunk(seed, ChunkPos::new(region_x, region_z), 0);
+        let range = (structure.spacing - structure.separation) as u32;
+
+        ChunkPos::new(
+            region_x * structure.spacing + random.next_below(range) as i32,
+            region_z * structure.spacing + random.next_below(range) as i32,
+        )
+    }
+
+    /// Returns the structures generated with their start within `radius` chunks of `center`,
+    /// as their index and the center of their start chunk, nearest first.
+    pub fn starts_near(&self, generator: &TerrainGenerator, center: ChunkPos, radius: i32) -> Vec<(usize, BlockPos)> {
+        let mut starts = Vec::new();
+
+        for (index, _) in self.registry.structures.iter().enumerate() {
+            self.for_each_start(index, center, radius, |start| {
+                if self.layout(index, start, generator).is_some() {
+                    starts.push((index, start));
+                }
+            });
+        }
+
+        starts.sort_by_key(|&(_, start)| start.distance(center));
+        starts.into_iter().map(|(index, start)| (index, start.block(8, 0, 8))).collect()
+    }
+
+    /// Returns the columns covered by structure pieces within `margin` blocks of the chunk at
+    /// `pos`, so that features can keep clear of them.
+    pub(super) fn footprints(&self, generator: &TerrainGenerator, pos: ChunkPos, margin: i32) -> Vec<Footprint> {
+        let (min, max) = (pos.block(0, 0, 0), pos.block(SECTION_SIZE, 0, SECTION_SIZE));
+        let mut footprints = Vec::new();
+
+        self.for_each_layout(generator, pos, margin, |_, layout| {
+            let near = |f: &&Footprint| f[0] < max.x + margin && f[2] > min.x - margin && f[1] < max.z + margin && f[3] > min.z - margin;
+            footprints.extend(layout.footprints.iter().filter(near));
+        });
+
+        footprints
+    }
+
+    /// Places the parts of structures inside `chunk`. Foundations are built down through blocks
+    /// for which `is_open` returns `true`.
+    pub(super) fn place(&self, chunk: &mut Chunk, generator: &TerrainGenerator, is_open: impl Fn(BlockState) -> bool) {
+        let pos = chunk.pos();
+        let origin = pos.block(0, 0, 0);
+        let size = SECTION_SIZE as i32;
+        let mut heights = HashMap::new();
+
+        self.for_each_layout(generator, pos, 0, |index, layout| {
+            let structure = &self.registry.structures[index];
+
+            for placed in &layout.pieces {
+                let piece = &structure.pieces[placed.piece];
+                let template = &piece.rotations[placed.rotation];
+                let [size_x, size_y, size_z] = template.size;
+
+                for z in placed.z.max(origin.z)..(placed.z + size_z).min(origin.z + size) {
+                    for x in placed.x.max(origin.x)..(placed.x + size_x).min(origin.x + size) {
+                        let ground = match piece.follow_terrain {
+                            true => generator.height_with(x, z, &mut heights),
+                            false => placed.y,
+                        };
+
+                        let (local_x, local_z) = ((x - origin.x) as usize, (z - origin.z) as usize);
+
+                        for y in 0..size_y {
+                            let world_y = ground + y;
+
+                            if let Some(state) = template.get(x - placed.x, y, z - placed.z)
+                                && (0..CHUNK_HEIGHT as i32).contains(&world_y)
+                            {
+                                chunk.set(local_x, world_y as usize, local_z, state);
+                            }
+                        }
+
+                        let Some(foundation) = structure.foundation else {
+                            continue;
+                        };
+
+                        if template.get(x - placed.x, 0, z - placed.z).is_none_or(BlockState::is_air) {
+                            continue;
+                        }
+
+                        for y in ((ground - MAX_FOUNDATION).max(0)..ground.min(CHUNK_HEIGHT as i32)).rev() {
+                            if !is_open(chunk.get(local_x, y as usize, local_z)) {
+                                break;
+                            }
+
+                            chunk.set(local_x, y as usize, local_z, foundation);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Calls `f` with the start chunk of every region of the structure with index `index`
+    /// within `radius` chunks of `center`.
+    fn for_each_start(&self, index: usize, center: ChunkPos, radius: i32, mut f: impl FnMut(ChunkPos)) {
+        let spacing = self.registry.structures[index].spacing;
+
+        for region_z in (center.z - radius).div_euclid(spacing)..=(center.z + radius).div_euclid(spacing) {
+            for region_x in (center.x - radius).div_euclid(spacing)..=(center.x + radius).div_euclid(spacing) {
+                let start = self.start(index, region_x, region_z);
+
+                if start.distance(center) <= radius {
+                    f(start);
+                }
+            }
+        }
+    }
+
+    /// Calls `f` with every assembled structure that may reach within `margin` blocks of the
+    /// chunk at `pos`.
+    fn for_each_layout(&self, generator: &TerrainGenerator, pos: ChunkPos, margin: i32, mut f: impl FnMut(usize, &Layout)) {
+        for (index, structure) in self.registry.structures.iter().enumerate() {
+            let reach = (structure.radius + margin) / SECTION_SIZE as i32 + 1;
+
+            self.for_each_start(index, pos, reach, |start| {
+                if let Some(layout) = self.layout(index, start, generator) {
+                    f(index, &layout);
+                }
+            });
+        }
+    }
+
+    /// Returns the structure with index `index` assembled at `start`, or `None` if it does
+    /// not generate there.
+    fn layout(&self, index: usize, start: ChunkPos, generator: &TerrainGenerator) -> Option<Arc<Layout>> {
+        if let Some(layout) = self.layouts.lock().unwrap().get(&(index, start)) {
+            return layout.clone();
+        }
+
+        let layout = self.assemble(&self.registry.structures[index], start, generator).map(Arc::new);
+        let mut layouts = self.layouts.lock().unwrap();
+
+        if layouts.len() >= LAYOUT_CACHE {
+            layouts.clear();
+        }
+
+        layouts.insert((index, start), layout.clone());
+        layout
+    }
+
+    fn assemble(&self, structure: &Structure, start: ChunkPos, generator: &TerrainGenerator) -> Option<Layout> {
+        let mut random = WorldRandom::for_chunk(derive_seed(self.seed, &structure.name), start, 1);
+        let center = start.block(8, 0, 8);
+        let mut heights = HashMap::new();
+
+        if !structure.biomes.contains(&generator.biome_at(center.x, center.z)) {
+            return None;
+        }
+
+        // Level pieces sit at the height of the ground at their center, and none is built in
+        // water.
+        let mut fit = |piece: usize, rotation: usize, x: i32, z: i32| {
+            let [size_x, _, size_z] = structure.pieces[piece].rotations[rotation].size;
+            let y = generator.height_with(x + size_x / 2, z + size_z / 2, &mut heights);
+            (y >= SEA_LEVEL).then_some(PlacedPiece { piece, rotation, x, y, z })
+        };
+
+        let rotation = random.next_below(4) as usize;
+        let [size_x, _, size_z] = structure.pieces[structure.start].rotations[rotation].size;
+        let first = fit(structure.start, rotation, center.x - size_x / 2, center.z - size_z / 2)?;
+
+        let mut layout = Layout { pieces: Vec::new(), footprints: Vec::new() };
+        let mut open = VecDeque::new();
+        add_piece(structure, &mut layout, &mut open, first, None, 0);
+
+        while let Some((connector, depth)) = open.pop_front() {
+            let Connector { x, z, facing, pool: Some(pool) } = connector else {
+                continue;
+            };
+
+            let pool = &structure.pools[pool];
+
+            if depth > structure.depth || pool.is_empty() {
+                continue;
+            }
+
+            let (dx, _, dz) = facing.offset();
+            let (target_x, target_z) = (x + dx, z + dz);
+
+            for _ in 0..ATTEMPTS {
+                let piece = pool[random.next_below(pool.len() as u32) as usize];
+
+                // Any orientation with a connector facing back towards this one fits.
+                let choices: Vec<(usize, Connector)> = (0..4)
+                    .flat_map(|rotation| structure.pieces[piece].rotations[rotation].connectors.iter().map(move |&c| (rotation, c)))
+                    .filter(|(_, c)| c.facing == facing.opposite())
+                    .collect();
+
+                if choices.is_empty() {
+                    continue;
+                }
+
+                let (rotation, other) = choices[random.next_below(choices.len() as u32) as usize];
+                let (piece_x, piece_z) = (target_x - other.x, target_z - other.z);
+                let [size_x, _, size_z] = structure.pieces[piece].rotations[rotation].size;
+                let footprint = [piece_x, piece_z, piece_x + size_x, piece_z + size_z];
+
+                let overlaps = layout.footprints.iter().any(|f| f[0] < footprint[2] && footprint[0] < f[2] && f[1] < footprint[3] && footprint[1] < f[3]);
+                let outside = footprint[0] < center.x - structure.radius
+                    || footprint[1] < center.z - structure.radius
+                    || footprint[2] > center.x + structure.radius
+                    || footprint[3] > center.z + structure.radius;
+
+                if overlaps || outside {
+                    continue;
+                }
+
+                let Some(placed) = fit(piece, rotation, piece_x, piece_z) else {
+                    continue;
+                };
+
+                add_piece(structure, &mut layout, &mut open, placed, Some(other), depth);
+                break;
+            }
+        }
+
+        Some(layout)
+    }
+}
+
+/// Adds a piece `depth` pieces away from the start to `layout`, and its connectors, in world
+/// coordinates, to `open`, but for the one it is attached by.
+fn add_piece(structure: &Structure, layout: &mut Layout, open: &mut VecDeque<(Connector, u32)>, placed: PlacedPiece, attached: Option<Connector>, depth: u32) {
+    let template = &structure.pieces[placed.piece].rotations[placed.rotation];
+    let [size_x, _, size_z] = template.size;
+
+    layout.pieces.push(placed);
+    layout.footprints.push([placed.x, placed.z, placed.x + size_x, placed.z + size_z]);
+
+    for &connector in template.connectors.iter().filter(|&&c| Some(c) != attached) {
+        open.push_back((Connector { x: placed.x + connector.x, z: placed.z + connector.z, ..connector }, depth + 1));
+    }
+}